@@ -0,0 +1,50 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+
+use crate::handlers::backup::{handle_backup_create, handle_backup_restore};
+
+/// Save the config, installed binaries and default versions to an archive, and restore them,
+/// e.g. before an experiment or to move to another machine.
+#[derive(Debug, Args)]
+pub struct Command {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Write a backup archive. It is compressed with zstd if the file ends in `.zst`, with gzip
+    /// if it ends in `.gz` or `.tgz`.
+    Create {
+        /// Archive to write, e.g. `backup.tar.zst`
+        file: PathBuf,
+
+        /// Also save the installed binaries, so that restoring does not download them again
+        #[arg(long)]
+        binaries: bool,
+    },
+    /// Replace the config, installed binaries and default versions with those of a backup
+    Restore {
+        /// Archive written by `suiup backup create`
+        file: PathBuf,
+    },
+}
+
+impl Command {
+    pub fn exec(&self, yes: bool) -> Result<()> {
+        match &self.command {
+            Commands::Create { file, binaries } => handle_backup_create(file, *binaries),
+            Commands::Restore { file } => handle_backup_restore(file, yes),
+        }
+    }
+
+    /// Whether this replaces the installed binaries
+    pub fn is_restore(&self) -> bool {
+        matches!(self.command, Commands::Restore { .. })
+    }
+}
@@ -0,0 +1,26 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::handlers::block::handle_block;
+
+/// Block a version on this machine, so that it cannot be installed or switched to until it is
+/// unblocked. Without a version, lists the blocked versions.
+#[derive(Args, Debug)]
+pub struct Command {
+    /// Version to block, on one network (e.g. 'sui@testnet-1.40.0') or all of them
+    /// (e.g. 'sui@1.40.0')
+    spec: Option<String>,
+
+    /// Why the version is blocked, shown when it is refused
+    #[arg(long, requires = "spec")]
+    reason: Option<String>,
+}
+
+impl Command {
+    pub fn exec(&self) -> Result<()> {
+        handle_block(self.spec.as_deref(), self.reason.clone())
+    }
+}
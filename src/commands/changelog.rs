@@ -0,0 +1,28 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::handlers::changelog::handle_changelog;
+
+use super::BinaryName;
+
+/// Show the release notes of every release in a range, e.g. to review an upgrade.
+#[derive(Args, Debug)]
+pub struct Command {
+    /// Binary whose releases to show
+    #[arg(value_enum)]
+    binary: BinaryName,
+
+    /// Releases after `from` up to and including `to`, e.g. 'testnet-1.39.3..testnet-1.41.0'.
+    /// Leave out `from` to start at the oldest release, or `to` to end at the latest one.
+    /// The network defaults to testnet.
+    range: String,
+}
+
+impl Command {
+    pub async fn exec(&self, github_token: &Option<String>) -> Result<()> {
+        handle_changelog(&self.binary, &self.range, github_token.clone()).await
+    }
+}
@@ -19,15 +19,39 @@ pub struct Command {
     /// Show what would be removed without actually removing anything
     #[clap(long, short = 'n')]
     dry_run: bool,
+
+    /// Also remove the archives of installed versions, which are otherwise kept to reinstall
+    /// or restore them without downloading them again
+    #[clap(long, conflicts_with_all = ["orphans", "interactive"])]
+    force: bool,
+
+    /// Also uninstall the versions that were not used (run, or switched to) for this many days.
+    /// Default versions and binaries installed before suiup tracked their usage are kept.
+    #[clap(long, value_name = "DAYS", conflicts_with_all = ["orphans", "interactive"])]
+    unused: Option<u32>,
+
+    /// Find binaries in the binaries folder that suiup does not know about (e.g. left over by an
+    /// interrupted install), and register them again or remove them
+    #[clap(long, conflicts_with_all = ["all", "days"])]
+    orphans: bool,
+
+    /// Choose the release archives to remove from a list showing their size and age
+    #[clap(long, short = 'i', conflicts_with_all = ["all", "days", "orphans"])]
+    interactive: bool,
 }
 
 impl Command {
-    pub async fn exec(&self, github_token: &Option<String>) -> Result<()> {
+    pub async fn exec(&self, github_token: &Option<String>, yes: bool) -> Result<()> {
         handle_cmd(
             ComponentCommands::Cleanup {
                 all: self.all,
                 days: self.days,
                 dry_run: self.dry_run,
+                force: self.force,
+                unused: self.unused,
+                orphans: self.orphans,
+                interactive: self.interactive,
+                yes,
             },
             github_token.to_owned(),
         )
@@ -1,15 +1,11 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::collections::BTreeMap;
-
 use anyhow::Result;
 use clap::Args;
+use colored::Colorize;
 
-use crate::{
-    paths::default_file_path,
-    types::{Binaries, Version},
-};
+use crate::{state, types::Binaries};
 
 use crate::commands::print_table;
 
@@ -19,12 +15,10 @@ pub struct Command;
 
 impl Command {
     pub fn exec(&self) -> Result<()> {
-        let default = std::fs::read_to_string(default_file_path()?)?;
-        let default: BTreeMap<String, (String, Version, bool)> = serde_json::from_str(&default)?;
-        let binaries = Binaries::from(default);
+        let binaries = Binaries::from(state::default_versions()?);
 
-        println!("\x1b[1mDefault binaries:\x1b[0m");
-        print_table(&binaries.binaries);
+        println!("{}", "Default binaries:".bold());
+        print_table(&binaries.binaries, false);
         Ok(())
     }
 }
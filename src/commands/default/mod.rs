@@ -28,4 +28,9 @@ impl Command {
             Commands::Set(cmd) => cmd.exec(),
         }
     }
+
+    /// Whether this changes the default version
+    pub fn is_set(&self) -> bool {
+        matches!(self.command, Commands::Set(_))
+    }
 }
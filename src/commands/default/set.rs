@@ -3,17 +3,23 @@
 
 use anyhow::{anyhow, bail, Result};
 use clap::Args;
+use clap_complete::ArgValueCompleter;
 use tracing::{debug, info};
 
+use crate::status;
 use crate::{
     commands::{parse_component_with_version, BinaryName, CommandMetadata},
-    handlers::{installed_binaries_grouped_by_network, update_default_version_file},
+    handlers::{
+        block::ensure_not_blocked,
+        completions::installed_specs,
+        enable::{is_disabled, report_disabled},
+        installed_binaries_grouped_by_network, replace_binary, update_default_version_file,
+        version::ensure_minimum_version,
+    },
     paths::{binaries_dir, get_default_bin_dir},
+    types::default_binary_name,
 };
 
-#[cfg(not(windows))]
-use std::os::unix::fs::PermissionsExt;
-
 /// Set the default Sui CLI version.
 #[derive(Args, Debug)]
 pub struct Command {
@@ -21,6 +27,7 @@ pub struct Command {
     /// e.g. 'sui@testnet-1.39.3', 'sui@testnet' --
     /// this will use an installed binary
     /// that has the highest testnet version)
+    #[arg(add = ArgValueCompleter::new(installed_specs))]
     name: String,
 
     /// Whether to set the debug version of the binary as default (only available for sui).
@@ -31,6 +38,10 @@ pub struct Command {
     /// Use `suiup show` to find all installed binaries
     #[arg(long, value_name = "branch", default_missing_value = "main", num_args = 0..=1)]
     nightly: Option<String>,
+
+    /// Set the version even if it is older than the minimum version set in the config file
+    #[arg(long)]
+    allow_below_minimum: bool,
 }
 
 impl Command {
@@ -39,17 +50,22 @@ impl Command {
             name,
             debug,
             nightly,
+            allow_below_minimum,
         } = self;
 
         if name.is_empty() && nightly.is_none() {
             bail!("Invalid number of arguments. Version is required: 'sui@testnet-1.39.3', 'sui@testnet' -- this will use an installed binary that has the highest testnet version. \n For `mvr` only pass the version: `mvr@0.0.5`")
         }
 
+        let metadata = parse_component_with_version(name)?;
+        let (nightly, debug) = metadata.with_flags(nightly.as_deref(), *debug)?;
+        let debug = &debug;
         let CommandMetadata {
             name,
             network,
             version,
-        } = parse_component_with_version(name)?;
+            ..
+        } = metadata;
 
         let network = if name == BinaryName::Mvr {
             if let Some(ref nightly) = nightly {
@@ -60,7 +76,7 @@ impl Command {
         } else if let Some(ref nightly) = nightly {
             nightly
         } else {
-            &network
+            network.as_str()
         };
 
         // a map of network --> to BinaryVersion
@@ -86,8 +102,8 @@ impl Command {
         } else {
             binaries
                 .iter()
-                .filter(|b| b.binary_name == name.to_string())
-                .max_by(|a, b| a.version.cmp(&b.version))
+                .filter(|b| b.binary_name == name.to_string() && b.debug == *debug)
+                .max_by_key(|b| b.release_version())
                 .map(|b| b.version.clone())
                 .ok_or_else(|| anyhow!("No version found for {name} in {network}"))?
         };
@@ -98,21 +114,19 @@ impl Command {
         binaries
         .iter()
         .find(|b| {
-            b.binary_name == name.to_string() && b.version == version && b.network_release == network
+            b.binary_name == name.to_string() && b.version == version && b.network_release == network && b.debug == *debug
         })
         .ok_or_else(|| {
-            anyhow!("Binary {binary_version} from {network} release not found. Use `suiup show` to see installed binaries.")
+            let build = if *debug { " debug build" } else { "" };
+            anyhow!("Binary {binary_version}{build} from {network} release not found. Use `suiup show` to see installed binaries.")
         })?;
 
+        ensure_not_blocked(name.to_str(), network, &version)?;
+        ensure_minimum_version(name.to_str(), &version, *allow_below_minimum)?;
+
         // copy files to default-bin
         let mut dst = get_default_bin_dir();
-        let name = if *debug {
-            format!("{}-debug", name)
-        } else {
-            format!("{}", name)
-        };
-
-        dst.push(&name);
+        dst.push(default_binary_name(name.to_str(), *debug));
 
         #[cfg(target_os = "windows")]
         dst.set_extension("exe");
@@ -143,25 +157,10 @@ impl Command {
                 .expect("Expected binary filename as string")
         ));
 
-        #[cfg(not(target_os = "windows"))]
-        {
-            if dst.exists() {
-                std::fs::remove_file(&dst)?;
-            }
-
-            std::fs::copy(&src, &dst)?;
-
-            #[cfg(unix)]
-            {
-                let mut perms = std::fs::metadata(&dst)?.permissions();
-                perms.set_mode(0o755);
-                std::fs::set_permissions(&dst, perms)?;
-            }
-        }
-
-        #[cfg(target_os = "windows")]
-        {
-            std::fs::copy(&src, &dst)?;
+        if is_disabled(name.to_str()) {
+            report_disabled(name.to_str());
+        } else {
+            replace_binary(&src, &dst)?;
         }
 
         update_default_version_file(
@@ -171,7 +170,7 @@ impl Command {
             *debug,
         )?;
 
-        println!("Default binary updated successfully");
+        status!("Default binary updated successfully");
         Ok(())
     }
 }
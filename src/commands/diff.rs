@@ -0,0 +1,24 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::handlers::diff::handle_diff;
+
+/// Compare two installed binaries: size, checksum, `--version` output, protocol version and
+/// provenance.
+#[derive(Args, Debug)]
+pub struct Command {
+    /// First binary, e.g. 'sui@testnet-1.40.1'
+    a: String,
+
+    /// Second binary, e.g. 'sui@testnet-1.41.0'
+    b: String,
+}
+
+impl Command {
+    pub async fn exec(&self, github_token: &Option<String>) -> Result<()> {
+        handle_diff(&self.a, &self.b, github_token.clone()).await
+    }
+}
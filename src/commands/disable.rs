@@ -0,0 +1,24 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::handlers::enable::handle_disable;
+
+use super::BinaryName;
+
+/// Take a binary out of the default bin folder without uninstalling it, e.g. to use another
+/// installation of it for a while, until `suiup enable`.
+#[derive(Args, Debug)]
+pub struct Command {
+    /// Binary to disable (e.g. 'sui')
+    #[arg(value_enum)]
+    name: BinaryName,
+}
+
+impl Command {
+    pub fn exec(&self) -> Result<()> {
+        handle_disable(&self.name)
+    }
+}
@@ -8,11 +8,15 @@ use crate::component::ComponentManager;
 
 /// Run diagnostic checks on the environment.
 #[derive(Args, Debug)]
-pub struct Command {}
+pub struct Command {
+    /// Print each check with its id, status, details and a command fixing it, as JSON
+    #[arg(long)]
+    json: bool,
+}
 
 impl Command {
     pub async fn exec(&self, github_token: &Option<String>) -> Result<()> {
         let component_manager = ComponentManager::new(github_token.clone());
-        component_manager.run_doctor_checks().await
+        component_manager.run_doctor_checks(self.json).await
     }
 }
@@ -0,0 +1,23 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::handlers::enable::handle_enable;
+
+use super::BinaryName;
+
+/// Put a binary disabled with `suiup disable` back in the default bin folder.
+#[derive(Args, Debug)]
+pub struct Command {
+    /// Binary to enable (e.g. 'sui')
+    #[arg(value_enum)]
+    name: BinaryName,
+}
+
+impl Command {
+    pub fn exec(&self) -> Result<()> {
+        handle_enable(&self.name)
+    }
+}
@@ -0,0 +1,32 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::handlers::env::handle_env;
+
+/// Show the settings suiup runs with, after applying flags, environment variables and the
+/// config file, and the paths it uses.
+#[derive(Args, Debug)]
+pub struct Command {
+    /// Print the settings as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+impl Command {
+    pub fn exec(
+        &self,
+        yes: bool,
+        disable_update_warnings: bool,
+        github_token: &Option<String>,
+    ) -> Result<()> {
+        handle_env(
+            self.json,
+            yes,
+            disable_update_warnings,
+            github_token.is_some(),
+        )
+    }
+}
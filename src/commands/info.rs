@@ -0,0 +1,21 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::handlers::info::handle_info;
+
+/// Show details about an installed binary, including where it came from.
+#[derive(Args, Debug)]
+pub struct Command {
+    /// Binary to inspect, optionally narrowed down by release and version
+    /// e.g. 'sui', 'sui@testnet', 'sui@testnet-1.39.3', 'mvr@main'
+    binary_spec: String,
+}
+
+impl Command {
+    pub async fn exec(&self, github_token: &Option<String>) -> Result<()> {
+        handle_info(&self.binary_spec, github_token.clone()).await
+    }
+}
@@ -0,0 +1,29 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::handlers::init::{handle_init, Shell};
+
+/// Add the default binaries directory to the PATH in your shell startup files.
+#[derive(Args, Debug)]
+pub struct Command {
+    /// Shells to set up. Defaults to the current shell and those with an existing startup file.
+    #[arg(long, value_enum, value_delimiter = ',')]
+    shell: Vec<Shell>,
+
+    /// Remove the PATH setup added by `suiup init`
+    #[arg(long)]
+    undo: bool,
+
+    /// Show which files would change without writing them
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl Command {
+    pub fn exec(&self) -> Result<()> {
+        handle_init(&self.shell, self.undo, self.dry_run)
+    }
+}
@@ -1,19 +1,37 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::Result;
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
 use clap::Args;
 
+use crate::config::NightlyBuildConfig;
 use crate::handle_commands::handle_cmd;
+use crate::handlers::expand_group;
+use crate::handlers::install::{install_from_url, InstallOptions};
+use crate::handlers::install_plan::handle_install_dry_run;
+use crate::handlers::integrity::parse_sha256;
+use crate::handlers::manifest::install_manifest;
+use crate::handlers::wizard::install_wizard;
+use crate::platform::Target;
 
-use super::ComponentCommands;
+use super::{parse_component_with_version, ComponentCommands};
 
 /// Install a binary.
 #[derive(Args, Debug)]
 pub struct Command {
     /// Binary to install with optional version
-    /// (e.g. 'sui', 'sui@1.40.1', 'sui@testnet', 'sui@testnet-1.39.3')
-    component: String,
+    /// (e.g. 'sui', 'sui@1.40.1', 'sui@testnet', 'sui@testnet-1.39.3'), or a group of binaries
+    /// (e.g. 'walrus-suite@testnet', see `suiup list groups`).
+    /// Without it, suiup asks for the binary, network and version to install.
+    #[arg(conflicts_with = "manifest")]
+    component: Option<String>,
+
+    /// Install the binaries listed in a TOML manifest (e.g. suiup.toml) that are not installed
+    /// yet, and report what was installed, skipped or failed
+    #[arg(long, value_name = "file", conflicts_with_all = ["nightly", "debug", "if_missing"])]
+    manifest: Option<PathBuf>,
 
     /// Install from a branch in release mode (use --debug for debug mode).
     /// If none provided, main is used. Note that this requires Rust & cargo to be installed.
@@ -26,19 +44,139 @@ pub struct Command {
     #[arg(long)]
     debug: bool,
 
-    /// Accept defaults without prompting
-    #[arg(short, long)]
-    yes: bool,
+    /// Extra arguments passed to `cargo install` when building with `--nightly`
+    /// (e.g. '--features tracing'). Replaces the `cargo_args` of the config file.
+    #[arg(
+        long,
+        value_name = "args",
+        requires = "nightly",
+        allow_hyphen_values = true
+    )]
+    cargo_args: Option<String>,
+
+    /// `RUSTFLAGS` of the `--nightly` build (e.g. '-C target-cpu=native').
+    /// Replaces the `rustflags` of the config file.
+    #[arg(
+        long,
+        value_name = "flags",
+        requires = "nightly",
+        allow_hyphen_values = true
+    )]
+    rustflags: Option<String>,
+
+    /// Cargo profile of the `--nightly` build instead of `release`.
+    /// Replaces the `profile` of the config file.
+    #[arg(long, value_name = "name", requires = "nightly")]
+    profile: Option<String>,
+
+    /// Build the `--nightly` binary in a Docker container of a pinned Rust image, so that Rust
+    /// does not need to be installed (Linux only)
+    #[arg(long, requires = "nightly")]
+    in_docker: bool,
+
+    /// Install again even if this version is already installed: download the release again
+    /// instead of using the cached archive, extract it and overwrite the installed binary.
+    /// Use it to recover from a corrupted installation.
+    #[arg(long)]
+    force: bool,
+
+    /// Install the version even if it is older than the minimum version set in the config file
+    #[arg(long)]
+    allow_below_minimum: bool,
+
+    /// Do nothing if this version is already installed, without checking for releases.
+    /// Without a version, any installed version of the network counts.
+    #[arg(long, conflicts_with = "force")]
+    if_missing: bool,
+
+    /// Install the release built for this platform instead of the detected one
+    /// (e.g. 'linux-aarch64', 'windows-x86_64', 'macos-arm64')
+    #[arg(long, value_name = "os-arch", conflicts_with = "nightly")]
+    target: Option<Target>,
+
+    /// Resolve the release and show the asset URL, download size, target paths and whether
+    /// the archive is cached, without downloading or writing anything
+    #[arg(long, conflicts_with_all = ["manifest", "nightly"])]
+    dry_run: bool,
+
+    /// Install the archive (`.tgz`/`.tar.gz`) or binary at this URL instead of a release, e.g. a
+    /// hotfix build. The version is taken from the spec or else from the file name of the URL.
+    #[arg(
+        long,
+        value_name = "url",
+        requires_all = ["component", "sha256"],
+        conflicts_with_all = ["manifest", "nightly", "debug", "target", "dry_run"]
+    )]
+    from_url: Option<String>,
+
+    /// SHA-256 digest of the file at `--from-url`, checked before it is installed
+    #[arg(long, value_name = "hex", requires = "from_url", value_parser = parse_sha256)]
+    sha256: Option<String>,
 }
 
 impl Command {
-    pub async fn exec(&self, github_token: &Option<String>) -> Result<()> {
+    pub async fn exec(&self, github_token: &Option<String>, yes: bool) -> Result<()> {
+        let options = InstallOptions {
+            yes,
+            force: self.force,
+            allow_below_minimum: self.allow_below_minimum,
+            target: self.target,
+            nightly_build: NightlyBuildConfig {
+                cargo_args: self.cargo_args.clone(),
+                rustflags: self.rustflags.clone(),
+                profile: self.profile.clone(),
+                in_docker: self.in_docker.then_some(true),
+                docker_image: None,
+            },
+            ..Default::default()
+        };
+        if let (Some(url), Some(sha256)) = (&self.from_url, &self.sha256) {
+            let component = self.component.as_deref().unwrap_or_default();
+            let metadata = parse_component_with_version(component)?;
+            if metadata.nightly.is_some() || metadata.debug {
+                bail!("`--from-url` does not support nightly or debug builds");
+            }
+            return install_from_url(metadata, url, sha256, &options).await;
+        }
+        if let Some(manifest) = &self.manifest {
+            return install_manifest(manifest, github_token.to_owned(), &options).await;
+        }
+        let (component, options) = match &self.component {
+            Some(component) => (component.to_owned(), options),
+            // The wizard asks for confirmation before installing
+            None => {
+                match install_wizard(self.nightly.is_some(), yes, github_token.to_owned()).await? {
+                    Some(component) => (
+                        component,
+                        InstallOptions {
+                            yes: true,
+                            ..options
+                        },
+                    ),
+                    None => return Ok(()),
+                }
+            }
+        };
+        if self.dry_run {
+            // A group is resolved member by member, as when installing it
+            let specs = expand_group(&component).unwrap_or_else(|| vec![component]);
+            for spec in specs {
+                let metadata = parse_component_with_version(&spec)?;
+                let (nightly, debug) = metadata.with_flags(None, self.debug)?;
+                if nightly.is_some() {
+                    bail!("`--dry-run` does not support nightly builds");
+                }
+                handle_install_dry_run(metadata, debug, github_token.to_owned(), &options).await?;
+            }
+            return Ok(());
+        }
         handle_cmd(
             ComponentCommands::Add {
-                component: self.component.to_owned(),
+                component,
                 nightly: self.nightly.to_owned(),
                 debug: self.debug.to_owned(),
-                yes: self.yes.to_owned(),
+                if_missing: self.if_missing,
+                options,
             },
             github_token.to_owned(),
         )
@@ -1,19 +1,73 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::Result;
-use clap::Args;
+use anyhow::{bail, Result};
+use clap::{Args, ValueEnum};
 
 use crate::handle_commands::handle_cmd;
+use crate::handlers::branches::handle_list_branches;
+use crate::handlers::nightly::handle_list_nightly;
+use crate::handlers::plugin::handle_list_plugins;
 
-use super::ComponentCommands;
+use super::{BinaryName, ComponentCommands};
 
 /// List available binaries to install.
 #[derive(Args, Debug)]
-pub struct Command;
+pub struct Command {
+    /// What to list: the binaries (default), the groups of binaries that can be installed
+    /// together, the branches a binary can be installed from with `--nightly`, the installed
+    /// nightly builds, or the plugins adding commands to suiup
+    #[arg(value_enum)]
+    kind: Option<ListKind>,
+
+    /// Binary whose repository branches are listed (with `branches`)
+    #[arg(value_enum, required_if_eq("kind", "branches"))]
+    binary: Option<BinaryName>,
+
+    /// Only list the branches whose name contains this text (e.g. 'release')
+    #[arg(long, value_name = "text", requires = "binary")]
+    filter: Option<String>,
+
+    /// Number of recent commits shown for each branch
+    #[arg(long, value_name = "n", default_value_t = 1, requires = "binary")]
+    commits: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ListKind {
+    Binaries,
+    Groups,
+    Branches,
+    Nightly,
+    Plugins,
+}
 
 impl Command {
     pub async fn exec(&self, github_token: &Option<String>) -> Result<()> {
-        handle_cmd(ComponentCommands::List, github_token.to_owned()).await
+        if let Some(binary) = &self.binary {
+            if self.kind != Some(ListKind::Branches) {
+                bail!("A binary can only be given to `suiup list branches`");
+            }
+            return handle_list_branches(
+                binary,
+                self.filter.as_deref(),
+                self.commits.max(1),
+                github_token.to_owned(),
+            )
+            .await;
+        }
+        if self.kind == Some(ListKind::Nightly) {
+            return handle_list_nightly();
+        }
+        if self.kind == Some(ListKind::Plugins) {
+            return handle_list_plugins();
+        }
+        handle_cmd(
+            ComponentCommands::List {
+                groups: self.kind == Some(ListKind::Groups),
+            },
+            github_token.to_owned(),
+        )
+        .await
     }
 }
@@ -0,0 +1,58 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+
+use crate::handlers::localnet::{
+    handle_localnet_start, handle_localnet_status, handle_localnet_stop,
+};
+
+/// Run a local Sui network in the background for developing and testing dapps.
+#[derive(Debug, Args)]
+pub struct Command {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Start the local network with a faucet, installing the sui version first if it is
+    /// missing and generating the genesis on the first start. The network keeps its state
+    /// between restarts.
+    Start {
+        /// Sui version to run (e.g. 'testnet', 'testnet-1.40.1', '1.40.1'). Defaults to the
+        /// default sui binary, or the latest installed one.
+        #[arg(long)]
+        version: Option<String>,
+
+        /// Delete the state of the local network and generate a new genesis
+        #[arg(long)]
+        force_regenesis: bool,
+    },
+    /// Stop the local network
+    Stop,
+    /// Show whether the local network is running, with its version and endpoints
+    Status,
+}
+
+impl Command {
+    pub async fn exec(&self, github_token: &Option<String>, yes: bool) -> Result<()> {
+        match &self.command {
+            Commands::Start {
+                version,
+                force_regenesis,
+            } => {
+                handle_localnet_start(
+                    version.as_deref(),
+                    *force_regenesis,
+                    yes,
+                    github_token.clone(),
+                )
+                .await
+            }
+            Commands::Stop => handle_localnet_stop().await,
+            Commands::Status => handle_localnet_status(),
+        }
+    }
+}
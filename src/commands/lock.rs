@@ -0,0 +1,28 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::handlers::lock::handle_lock;
+
+/// Write a lock file with the exact versions of the default binaries and the checksums of their
+/// release assets for every platform, to reproduce them elsewhere with `suiup install --manifest`.
+#[derive(Args, Debug)]
+pub struct Command {
+    /// Lock every installed version instead of only the default ones
+    #[arg(long)]
+    all: bool,
+
+    /// File to write the lock to
+    #[arg(long, short = 'o', default_value = "suiup.lock")]
+    output: PathBuf,
+}
+
+impl Command {
+    pub async fn exec(&self, github_token: &Option<String>) -> Result<()> {
+        handle_lock(self.all, &self.output, github_token.clone()).await
+    }
+}
@@ -0,0 +1,41 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+
+use crate::handlers::mirror::handle_mirror_check;
+use crate::types::Network;
+
+/// Inspect the servers binaries are downloaded from.
+#[derive(Debug, Args)]
+pub struct Command {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Probe GitHub releases and the download sources of the config file for the current
+    /// platform's asset: latency, whether they have the latest release, size and last
+    /// modification date. Tells which one each binary is downloaded from.
+    Check {
+        /// Network release to check
+        #[arg(long, value_enum, default_value_t = Network::Testnet)]
+        network: Network,
+
+        /// Version to look for on the download sources, instead of the latest release
+        #[arg(long)]
+        version: Option<String>,
+    },
+}
+
+impl Command {
+    pub async fn exec(&self, github_token: &Option<String>) -> Result<()> {
+        match &self.command {
+            Commands::Check { network, version } => {
+                handle_mirror_check(network.as_str(), version.clone(), github_token.clone()).await
+            }
+        }
+    }
+}
@@ -1,24 +1,71 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+mod backup;
+mod block;
+mod changelog;
 mod cleanup;
 mod default;
+mod diff;
+mod disable;
 mod doctor;
+mod enable;
+mod env;
+mod info;
+mod init;
 mod install;
 mod list;
+mod localnet;
+mod lock;
+mod mirror;
+mod prompt;
 mod remove;
+mod run;
+mod sbom;
 mod self_;
+mod shell;
 mod show;
+mod status;
 mod switch;
+mod try_;
+mod unblock;
 mod update;
+mod upgrade;
 mod which;
 
-use crate::{handlers::self_::check_for_updates, types::BinaryVersion};
+use crate::{
+    config::{self, load_config},
+    handlers::{
+        available_components, cleanup_stale_binaries, component_groups, install::InstallOptions,
+        plugin::handle_plugin, self_::check_for_updates, update_notice::notify_binary_updates,
+    },
+    http, output, paths, state,
+    suggest::did_you_mean,
+    timings,
+    types::{BinaryVersion, Network, Repo},
+};
+
+use chrono::{DateTime, Utc};
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::time::Instant;
 
 use anyhow::{anyhow, bail, Result};
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::builder::FalseyValueParser;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use comfy_table::Table;
 pub const TABLE_FORMAT: &str = "  ── ══      ──    ";
+/// [`TABLE_FORMAT`] in ASCII, for `--plain`
+pub const PLAIN_TABLE_FORMAT: &str = "  -- ==      --    ";
+
+/// Preset of the tables printed by the commands
+pub fn table_format() -> &'static str {
+    if output::is_plain() {
+        PLAIN_TABLE_FORMAT
+    } else {
+        TABLE_FORMAT
+    }
+}
 #[derive(Parser)]
 #[command(arg_required_else_help = true, disable_help_subcommand = true)]
 #[command(version, about)]
@@ -30,49 +77,377 @@ pub struct Command {
     #[arg(long, env = "GITHUB_TOKEN", global = true)]
     pub github_token: Option<String>,
 
-    /// Disable update warnings for suiup itself.
+    /// Disable update warnings for suiup itself and the notices of newer releases of the
+    /// installed binaries.
     #[arg(long, env = "SUIUP_DISABLE_UPDATE_WARNINGS", global = true)]
     pub disable_update_warnings: bool,
+
+    /// Manage the system-wide installation (/usr/local, or %ProgramData%\suiup on Windows)
+    /// shared by all users, instead of the user's own. Modifying it requires administrator
+    /// privileges.
+    #[arg(long, env = "SUIUP_SYSTEM", global = true)]
+    pub system: bool,
+
+    /// Keep the config, state, caches and binaries in a `suiup-portable` folder next to the
+    /// suiup executable, e.g. to run suiup from a USB stick or a project checkout
+    #[arg(long, env = "SUIUP_PORTABLE", global = true, conflicts_with = "system")]
+    pub portable: bool,
+
+    /// Accept defaults without prompting, e.g. setting an installed version as the default one.
+    /// Use it in scripts and CI so that suiup never waits for input.
+    #[arg(
+        short,
+        long,
+        visible_alias = "non-interactive",
+        env = "SUIUP_NONINTERACTIVE",
+        value_parser = FalseyValueParser::new(),
+        global = true
+    )]
+    pub yes: bool,
+
+    /// Ask before changing the defaults and on network failures, even if `assume_yes` is set in
+    /// the config file or `SUIUP_NONINTERACTIVE` in the environment
+    #[arg(long, global = true)]
+    pub ask: bool,
+
+    /// Do not access the network. Installs use the release archives in the cache, and commands
+    /// that need the network fail, telling what they would download.
+    #[arg(
+        long,
+        env = "SUIUP_OFFLINE",
+        value_parser = FalseyValueParser::new(),
+        global = true
+    )]
+    pub offline: bool,
+
+    /// Before downloading, check that the GitHub API, the GitHub downloads and the mirrors set in
+    /// `sources` can be reached, and fail early telling why one cannot (DNS, TLS, proxy, HTTP
+    /// status) instead of half way through
+    #[arg(
+        long,
+        env = "SUIUP_CHECK_NETWORK",
+        value_parser = FalseyValueParser::new(),
+        global = true
+    )]
+    pub check_network: bool,
+
+    /// Read the settings from this file (JSON, or TOML if it ends in `.toml`) instead of
+    /// `config.json` in the suiup config directory
+    #[arg(long, value_name = "file", env = "SUIUP_CONFIG", global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Limit the bandwidth of downloads, in bytes per second with an optional K, M or G suffix,
+    /// e.g. `5M`
+    #[arg(long, value_name = "rate", value_parser = http::parse_rate, env = "SUIUP_LIMIT_RATE", global = true)]
+    pub limit_rate: Option<u64>,
+
+    /// Print the underlying errors (e.g. the OS or network error) along with the error message
+    #[arg(
+        short,
+        long,
+        env = "SUIUP_VERBOSE",
+        value_parser = FalseyValueParser::new(),
+        global = true
+    )]
+    pub verbose: bool,
+
+    /// Only print errors, warnings and the data asked for, e.g. the output of `list` or `which`,
+    /// without progress bars or status messages. For scripts and Makefiles.
+    #[arg(
+        short,
+        long,
+        env = "SUIUP_QUIET",
+        value_parser = FalseyValueParser::new(),
+        global = true
+    )]
+    pub quiet: bool,
+
+    /// How to print errors. `json` prints `{code, category, message, suggestion}` on stderr,
+    /// for tools that run suiup.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = ErrorFormat::Human,
+        env = "SUIUP_ERROR_FORMAT",
+        global = true
+    )]
+    pub error_format: ErrorFormat,
+
+    /// How to report progress: `bar` shows progress bars and spinners, `json` prints one JSON
+    /// object per progress event on stderr instead, for programs wrapping suiup
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value_t = ProgressFormat::Bar,
+        env = "SUIUP_PROGRESS"
+    )]
+    pub progress: ProgressFormat,
+
+    /// Print on stderr how long each phase of the command took (resolving releases,
+    /// downloading, verifying, extracting, registering, cleaning up) and the total time
+    #[arg(
+        long,
+        env = "SUIUP_TIMINGS",
+        value_parser = FalseyValueParser::new(),
+        global = true
+    )]
+    pub timings: bool,
+
+    /// Print ASCII only, without emoji, box drawing or colors, e.g. for screen readers and CI
+    /// logs. Also used when `TERM` is `dumb`.
+    #[arg(
+        long,
+        env = "SUIUP_PLAIN",
+        value_parser = FalseyValueParser::new(),
+        global = true
+    )]
+    pub plain: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProgressFormat {
+    Bar,
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorFormat {
+    Human,
+    Json,
+}
+
+impl ErrorFormat {
+    /// Finds the format requested on the command line or in the environment, for errors that
+    /// happen before the arguments could be parsed
+    pub fn from_raw_args(args: &[OsString]) -> ErrorFormat {
+        let mut args = args.iter().filter_map(|arg| arg.to_str());
+        while let Some(arg) = args.next() {
+            let value = match arg.strip_prefix("--error-format") {
+                Some("") => args.next(),
+                Some(value) => value.strip_prefix('='),
+                None => continue,
+            };
+            if let Some(format) = value.and_then(|v| ErrorFormat::from_str(v, true).ok()) {
+                return format;
+            }
+        }
+        std::env::var("SUIUP_ERROR_FORMAT")
+            .ok()
+            .and_then(|v| ErrorFormat::from_str(&v, true).ok())
+            .unwrap_or(ErrorFormat::Human)
+    }
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
+    Backup(backup::Command),
+    Block(block::Command),
+    Changelog(changelog::Command),
     Default(default::Command),
+    Diff(diff::Command),
+    Disable(disable::Command),
     Doctor(doctor::Command),
+    Enable(enable::Command),
+    Env(env::Command),
+    Info(info::Command),
+    Init(init::Command),
+    #[command(visible_alias = "i")]
     Install(install::Command),
+    #[command(visible_alias = "rm")]
     Remove(remove::Command),
+    Run(run::Command),
+    Sbom(sbom::Command),
+    #[command(visible_alias = "ls")]
     List(list::Command),
+    Localnet(localnet::Command),
+    Lock(lock::Command),
+    Mirror(mirror::Command),
+    Prompt(prompt::Command),
 
     #[command(name = "self")]
     Self_(self_::Command),
 
+    Shell(shell::Command),
     Show(show::Command),
+    Status(status::Command),
+    #[command(visible_alias = "sw")]
     Switch(switch::Command),
+    Try(try_::Command),
+    Unblock(unblock::Command),
+    #[command(visible_alias = "up")]
     Update(update::Command),
+    Upgrade(upgrade::Command),
     Which(which::Command),
     Cleanup(cleanup::Command),
+
+    /// Any other command runs the `suiup-<name>` executable found on the PATH
+    #[command(external_subcommand)]
+    External(Vec<OsString>),
+}
+
+/// Rewrites the rustup-style shorthand `suiup +<release> <binary> [args]` into
+/// `suiup run <binary>@<release> -- [args]`, e.g. `suiup +testnet-1.40.1 sui --version`.
+/// Other arguments are returned unchanged.
+pub fn expand_release_shorthand(args: Vec<OsString>) -> Vec<OsString> {
+    let release = match args.get(1).and_then(|arg| arg.to_str()) {
+        Some(arg) if arg.len() > 1 && arg.starts_with('+') => arg[1..].to_string(),
+        _ => return args,
+    };
+    let mut rest = args.into_iter();
+    let mut expanded = vec![rest.next().unwrap_or_default(), "run".into()];
+    rest.next();
+    // Without a binary, `run` reports it missing
+    if let Some(mut spec) = rest.next() {
+        spec.push(format!("@{release}"));
+        expanded.push(spec);
+        expanded.push("--".into());
+        expanded.extend(rest);
+    }
+    expanded
 }
 
 impl Command {
     pub async fn exec(&self) -> Result<()> {
+        // Run on every prompt, so without the update check and the config file
+        match &self.command {
+            Commands::Prompt(cmd) => return cmd.exec(),
+            Commands::Status(cmd) if cmd.is_fast() => return cmd.exec(&self.github_token).await,
+            _ => {}
+        }
+        let start = Instant::now();
+        output::set_quiet(self.quiet);
+        output::set_plain(self.plain || std::env::var("TERM").is_ok_and(|term| term == "dumb"));
+        timings::set_enabled(self.timings);
+        output::set_json_progress(self.progress == ProgressFormat::Json);
+        http::set_offline(self.offline);
+        http::set_limit_rate(self.limit_rate.unwrap_or(0));
+        // A broken config file is reported by the commands that read it
+        let yes = !self.ask && (self.yes || load_config().is_ok_and(|config| config.assume_yes));
+        http::set_interactive(!yes);
+        if http::is_interactive() {
+            config::offer_repair()?;
+        }
+
         // Check for updates before executing any command (except self update to avoid recursion)
-        if !matches!(self.command, Commands::Self_(_)) && !self.disable_update_warnings {
+        if !matches!(self.command, Commands::Self_(_))
+            && !self.disable_update_warnings
+            && !self.offline
+        {
             check_for_updates();
         }
 
-        match &self.command {
+        // Binaries replaced while running on Windows can only be removed once they exited
+        cleanup_stale_binaries();
+
+        if paths::is_system_mode() && self.command.modifies_installation() {
+            paths::ensure_system_root_writable()?;
+        }
+        if self.check_network && !self.offline && self.command.downloads() {
+            http::check_reachability(self.github_token.as_deref()).await?;
+        }
+
+        let result = match &self.command {
+            Commands::Backup(cmd) => cmd.exec(yes),
+            Commands::Block(cmd) => cmd.exec(),
+            Commands::Changelog(cmd) => cmd.exec(&self.github_token).await,
             Commands::Default(cmd) => cmd.exec(),
+            Commands::Diff(cmd) => cmd.exec(&self.github_token).await,
+            Commands::Disable(cmd) => cmd.exec(),
             Commands::Doctor(cmd) => cmd.exec(&self.github_token).await,
-            Commands::Install(cmd) => cmd.exec(&self.github_token).await,
+            Commands::Enable(cmd) => cmd.exec(),
+            Commands::Env(cmd) => cmd.exec(yes, self.disable_update_warnings, &self.github_token),
+            Commands::Info(cmd) => cmd.exec(&self.github_token).await,
+            Commands::Init(cmd) => cmd.exec(),
+            Commands::Install(cmd) => cmd.exec(&self.github_token, yes).await,
             Commands::Remove(cmd) => cmd.exec(&self.github_token).await,
+            Commands::Run(cmd) => cmd.exec(),
+            Commands::Sbom(cmd) => cmd.exec(),
             Commands::List(cmd) => cmd.exec(&self.github_token).await,
+            Commands::Localnet(cmd) => cmd.exec(&self.github_token, yes).await,
+            Commands::Lock(cmd) => cmd.exec(&self.github_token).await,
+            Commands::Mirror(cmd) => cmd.exec(&self.github_token).await,
+            Commands::Prompt(_) => unreachable!("`suiup prompt` is run before the setup above"),
             Commands::Self_(cmd) => cmd.exec().await,
+            Commands::Shell(cmd) => cmd.exec(),
             Commands::Show(cmd) => cmd.exec(),
+            Commands::Status(cmd) => cmd.exec(&self.github_token).await,
             Commands::Switch(cmd) => cmd.exec(),
-            Commands::Update(cmd) => cmd.exec(&self.github_token).await,
+            Commands::Try(cmd) => cmd.exec(&self.github_token).await,
+            Commands::Unblock(cmd) => cmd.exec(),
+            Commands::Update(cmd) => cmd.exec(&self.github_token, yes).await,
+            Commands::Upgrade(cmd) => cmd.exec(&self.github_token).await,
             Commands::Which(cmd) => cmd.exec(),
-            Commands::Cleanup(cmd) => cmd.exec(&self.github_token).await,
+            Commands::Cleanup(cmd) => cmd.exec(&self.github_token, yes).await,
+            Commands::External(args) => handle_plugin(args, &command_names()),
+        };
+
+        // Persist state changes, even if the command failed half way through
+        let flushed = state::flush();
+        if result.is_ok()
+            && self.command.shows_update_notice()
+            && !self.disable_update_warnings
+            && !self.offline
+            && !output::is_quiet()
+        {
+            notify_binary_updates(self.github_token.clone()).await;
         }
+        timings::report(start.elapsed());
+        result.and(flushed)
+    }
+}
+
+/// Names and aliases of the commands of suiup
+fn command_names() -> Vec<String> {
+    Command::command()
+        .get_subcommands()
+        .flat_map(|cmd| std::iter::once(cmd.get_name()).chain(cmd.get_all_aliases()))
+        .map(str::to_string)
+        .collect()
+}
+
+impl Commands {
+    /// Whether the command installs, removes or switches binaries
+    fn modifies_installation(&self) -> bool {
+        match self {
+            Commands::Backup(cmd) => cmd.is_restore(),
+            Commands::Default(cmd) => cmd.is_set(),
+            Commands::Disable(_)
+            | Commands::Enable(_)
+            | Commands::Install(_)
+            | Commands::Remove(_)
+            | Commands::Switch(_)
+            | Commands::Update(_)
+            | Commands::Upgrade(_)
+            | Commands::Cleanup(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the command downloads releases, worth checking the network for beforehand
+    fn downloads(&self) -> bool {
+        matches!(
+            self,
+            Commands::Install(_)
+                | Commands::Localnet(_)
+                | Commands::Mirror(_)
+                | Commands::Try(_)
+                | Commands::Update(_)
+                | Commands::Upgrade(_)
+        )
+    }
+
+    /// Whether the command is followed by a notice of the newer releases of the installed
+    /// binaries
+    fn shows_update_notice(&self) -> bool {
+        matches!(
+            self,
+            Commands::Default(_)
+                | Commands::Install(_)
+                | Commands::List(_)
+                | Commands::Show(_)
+                | Commands::Switch(_)
+        )
     }
 }
 
@@ -81,7 +456,11 @@ pub enum ComponentCommands {
     #[command(about = "Run diagnostic checks on the environment")]
     Doctor,
     #[command(about = "List available binaries to install")]
-    List,
+    List {
+        /// List the groups of binaries instead
+        #[arg(long)]
+        groups: bool,
+    },
     #[command(about = "Add a binary")]
     Add {
         #[arg(
@@ -103,8 +482,11 @@ pub enum ComponentCommands {
             help = "Install from a branch in release mode. If none provided, main is used. Note that this requires Rust & cargo to be installed."
         )]
         nightly: Option<String>,
-        #[arg(short, long, help = "Accept defaults without prompting")]
-        yes: bool,
+        #[arg(long, help = "Skip binaries that are already installed")]
+        if_missing: bool,
+        /// Set by the commands installing binaries, not on the command line
+        #[arg(skip)]
+        options: InstallOptions,
     },
     #[command(
         about = "Remove one. By default, the binary from each release will be removed. Use --version to specify which exact version to remove"
@@ -125,16 +507,40 @@ pub enum ComponentCommands {
         /// Show what would be removed without actually removing anything
         #[arg(long, short = 'n')]
         dry_run: bool,
+        /// Also remove the archives of installed versions
+        #[arg(long, conflicts_with_all = ["orphans", "interactive"])]
+        force: bool,
+        /// Also uninstall the versions that were not used for this many days
+        #[arg(long, value_name = "DAYS", conflicts_with_all = ["orphans", "interactive"])]
+        unused: Option<u32>,
+        /// Find binaries that are not registered as installed, and register or remove them
+        #[arg(long, conflicts_with_all = ["all", "days"])]
+        orphans: bool,
+        /// Choose the cache files to remove from a list
+        #[arg(long, short = 'i', conflicts_with_all = ["all", "days", "orphans"])]
+        interactive: bool,
+        #[arg(short, long, help = "Accept defaults without prompting")]
+        yes: bool,
     },
 }
 
 #[derive(Clone, Debug, PartialEq, Hash, Eq, ValueEnum)]
 #[value(rename_all = "lowercase")]
 pub enum BinaryName {
+    #[value(name = "move-analyzer")]
+    MoveAnalyzer,
     #[value(name = "mvr")]
     Mvr,
     #[value(name = "sui")]
     Sui,
+    #[value(name = "sui-bridge")]
+    SuiBridge,
+    #[value(name = "sui-faucet")]
+    SuiFaucet,
+    #[value(name = "sui-node")]
+    SuiNode,
+    #[value(name = "sui-tool")]
+    SuiTool,
     #[value(name = "walrus")]
     Walrus,
     #[value(name = "site-builder")]
@@ -144,24 +550,64 @@ pub enum BinaryName {
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct CommandMetadata {
     pub name: BinaryName,
-    pub network: String,
+    pub network: Network,
     pub version: Option<String>,
+    /// Debug build, from a `+debug` qualifier
+    pub debug: bool,
+    /// Branch to build from, from a `nightly` or `nightly:<branch>` release
+    pub nightly: Option<String>,
+}
+
+impl CommandMetadata {
+    /// The branch to build from and whether to install a debug build, combining the spec with
+    /// the `--nightly` and `--debug` flags
+    pub fn with_flags(&self, nightly: Option<&str>, debug: bool) -> Result<(Option<String>, bool)> {
+        let nightly = match (self.nightly.as_deref(), nightly) {
+            (Some(spec), Some(flag)) if spec != flag => bail!(
+                "The spec builds from `{spec}` and `--nightly` from `{flag}`, keep only one of them"
+            ),
+            (spec, flag) => spec.or(flag).map(str::to_string),
+        };
+        Ok((nightly, self.debug || debug))
+    }
 }
 
 impl BinaryName {
-    pub fn repo_url(&self) -> &str {
+    pub fn repo_url(&self) -> Result<String> {
+        Ok(format!(
+            "{}/{}",
+            http::github_url()?,
+            self.repo().repository()?
+        ))
+    }
+
+    /// Repository whose releases the binary is downloaded from
+    pub fn repo(&self) -> Repo {
         match self {
-            BinaryName::Mvr => "https://github.com/MystenLabs/mvr",
-            BinaryName::Walrus => "https://github.com/MystenLabs/walrus",
-            BinaryName::WalrusSites => "https://github.com/MystenLabs/walrus-sites",
-            _ => "https://github.com/MystenLabs/sui",
+            BinaryName::Mvr => Repo::Mvr,
+            BinaryName::Walrus => Repo::Walrus,
+            BinaryName::WalrusSites => Repo::WalrusSites,
+            _ => Repo::Sui,
+        }
+    }
+
+    /// Network used when a spec does not name one. Walrus Sites is mostly used on mainnet.
+    pub fn default_network(&self) -> Network {
+        match self {
+            BinaryName::WalrusSites => Network::Mainnet,
+            _ => Network::Testnet,
         }
     }
 
     pub fn to_str(&self) -> &str {
         match self {
+            BinaryName::MoveAnalyzer => "move-analyzer",
             BinaryName::Mvr => "mvr",
             BinaryName::Sui => "sui",
+            BinaryName::SuiBridge => "sui-bridge",
+            BinaryName::SuiFaucet => "sui-faucet",
+            BinaryName::SuiNode => "sui-node",
+            BinaryName::SuiTool => "sui-tool",
             BinaryName::Walrus => "walrus",
             BinaryName::WalrusSites => "site-builder",
         }
@@ -171,8 +617,13 @@ impl BinaryName {
 impl std::fmt::Display for BinaryName {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            BinaryName::MoveAnalyzer => write!(f, "move-analyzer"),
             BinaryName::Mvr => write!(f, "mvr"),
             BinaryName::Sui => write!(f, "sui"),
+            BinaryName::SuiBridge => write!(f, "sui-bridge"),
+            BinaryName::SuiFaucet => write!(f, "sui-faucet"),
+            BinaryName::SuiNode => write!(f, "sui-node"),
+            BinaryName::SuiTool => write!(f, "sui-tool"),
             BinaryName::Walrus => write!(f, "walrus"),
             BinaryName::WalrusSites => write!(f, "site-builder"),
         }
@@ -185,88 +636,145 @@ impl std::str::FromStr for BinaryName {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
             "sui" => Ok(BinaryName::Sui),
+            "sui-bridge" => Ok(BinaryName::SuiBridge),
+            "sui-faucet" => Ok(BinaryName::SuiFaucet),
+            "sui-node" => Ok(BinaryName::SuiNode),
+            "sui-tool" => Ok(BinaryName::SuiTool),
+            "move-analyzer" => Ok(BinaryName::MoveAnalyzer),
             "mvr" => Ok(BinaryName::Mvr),
             "walrus" => Ok(BinaryName::Walrus),
             "site-builder" => Ok(BinaryName::WalrusSites),
-            _ => Err(format!("Unknown binary: {}", s)),
+            _ => Err(format!(
+                "Unknown binary: {s}.{}",
+                did_you_mean(s, available_components().iter().copied())
+            )),
         }
     }
 }
 
+pub(crate) fn invalid_binary_name(name: &str) -> anyhow::Error {
+    anyhow!(
+        "Invalid binary name: {name}.{} Use `suiup list` to find available binaries to install.",
+        did_you_mean(
+            name,
+            available_components()
+                .iter()
+                .copied()
+                .chain(component_groups().iter().map(|g| g.name))
+        )
+    )
+}
+
+/// Separators between a binary name and its release: `sui@testnet`, `sui==testnet`,
+/// `sui=testnet` or `sui testnet`
+const SPEC_SEPARATORS: [&str; 4] = ["@", "==", "=", " "];
+
+/// Parses a component spec:
+///
+/// ```text
+/// spec    = name [separator release] ["+" build]
+/// release = "nightly" [":" branch] | network ["-" version] | version
+/// version = "latest" | [v]major.minor.patch
+/// build   = "debug" | "release"
+/// ```
+///
+/// e.g. `sui`, `sui@testnet-1.39.3+debug`, `sui@nightly:main`, `walrus@mainnet-latest` or
+/// `mvr@0.0.8`. Without a network, the default network of the binary is used.
 pub fn parse_component_with_version(s: &str) -> Result<CommandMetadata, anyhow::Error> {
-    let split_char = if s.contains("@") {
-        "@"
-    } else if s.contains("==") {
-        "=="
-    } else if s.contains("=") {
-        "="
-    } else {
-        // TODO this is a hack because we don't have a better way to split
-        " "
+    let (rest, debug) = match s.rsplit_once('+') {
+        Some((rest, "debug")) => (rest, true),
+        Some((rest, "release")) => (rest, false),
+        Some((_, build)) => bail!("Unknown build type `{build}` in {s}, use `debug` or `release`"),
+        None => (s, false),
     };
-
-    let parts: Vec<&str> = s.split(split_char).collect();
-
-    match parts.len() {
-        1 => {
-            let component = BinaryName::from_str(parts[0], true)
-                .map_err(|_| anyhow!("Invalid binary name: {}. Use `suiup list` to find available binaries to install.", parts[0]))?;
-            let (network, version) = parse_version_spec(None)?;
-            let component_metadata = CommandMetadata {
-                name: component,
-                network,
-                version,
-            };
-            Ok(component_metadata)
-        }
-        2 => {
-            let component = BinaryName::from_str(parts[0], true)
-                .map_err(|_| anyhow!("Invalid binary name: {}. Use `suiup list` to find available binaries to install.", parts[0]))?;
-            let (network, version) = parse_version_spec(Some(parts[1].to_string()))?;
-            let component_metadata = CommandMetadata {
-                name: component,
-                network,
-                version,
-            };
-            Ok(component_metadata)
-        }
-        _ => bail!("Invalid format. Use 'binary' or 'binary version'".to_string()),
+    let (name, release) = match SPEC_SEPARATORS
+        .iter()
+        .find_map(|separator| rest.split_once(separator))
+    {
+        Some((name, release)) => (name, Some(release)),
+        None => (rest, None),
+    };
+    if release.is_some_and(|release| {
+        release.is_empty()
+            || SPEC_SEPARATORS
+                .iter()
+                .any(|separator| release.contains(separator))
+    }) {
+        bail!("Invalid format. Use 'binary' or 'binary version'".to_string());
     }
-}
+    let component = BinaryName::from_str(name, true).map_err(|_| invalid_binary_name(name))?;
+    let default_network = component.default_network();
 
-pub fn parse_version_spec(spec: Option<String>) -> Result<(String, Option<String>)> {
-    match spec {
-        None => Ok(("testnet".to_string(), None)),
-        Some(spec) => {
-            if spec.starts_with("testnet-")
-                || spec.starts_with("devnet-")
-                || spec.starts_with("mainnet-")
-            {
-                let parts: Vec<&str> = spec.splitn(2, '-').collect();
-                Ok((parts[0].to_string(), Some(parts[1].to_string())))
-            } else if spec == "testnet" || spec == "devnet" || spec == "mainnet" {
-                Ok((spec, None))
-            } else {
-                // Assume it's a version for testnet
-                Ok(("testnet".to_string(), Some(spec)))
+    let (network, version, nightly) = match release {
+        None => (default_network, None, None),
+        Some("nightly") => (default_network, None, Some("main".to_string())),
+        Some(release) => match release.strip_prefix("nightly:") {
+            Some("") => bail!("Missing the branch after `nightly:` in {s}"),
+            Some(branch) => (default_network, None, Some(branch.to_string())),
+            None => {
+                let (network, version) =
+                    parse_version_spec_or(Some(release.to_string()), default_network)?;
+                if version.as_deref() == Some("") {
+                    bail!("Missing the version after `{network}-` in {s}");
+                }
+                (network, version.filter(|v| v != "latest"), None)
             }
-        }
+        },
+    };
+    Ok(CommandMetadata {
+        name: component,
+        network,
+        version,
+        debug,
+        nightly,
+    })
+}
+
+pub fn parse_version_spec(spec: Option<String>) -> Result<(Network, Option<String>)> {
+    parse_version_spec_or(spec, Network::Testnet)
+}
+
+/// Parses a version spec like [`parse_version_spec`], using `default_network` when the spec
+/// does not name a network. A spec starting with a word names a network, e.g. `testnet-1.40.1`
+/// or `mainnet`, and fails if it is not one.
+pub fn parse_version_spec_or(
+    spec: Option<String>,
+    default_network: Network,
+) -> Result<(Network, Option<String>)> {
+    let Some(spec) = spec else {
+        return Ok((default_network, None));
+    };
+    let (network, version) = match spec.split_once('-') {
+        Some((network, version)) => (network, Some(version)),
+        None => (spec.as_str(), None),
+    };
+    if network != "latest" && network.chars().all(|c| c.is_ascii_alphabetic()) {
+        Ok((network.parse()?, version.map(str::to_string)))
+    } else {
+        // Assume it's a version for the default network
+        Ok((default_network, Some(spec)))
     }
 }
 
-pub fn print_table(binaries: &Vec<BinaryVersion>) {
-    let mut binaries_vec = binaries.clone();
+/// Prints the binaries as a table. `with_last_used` adds a column with the time each binary was
+/// last run through `suiup run`, `shell` or `localnet`, or switched to.
+pub fn print_table(binaries: &[BinaryVersion], with_last_used: bool) {
+    let mut binaries_vec = binaries.to_vec();
     // sort by Binary column
     binaries_vec.sort_by_key(|b| b.binary_name.clone());
+    let mut header = vec!["Binary", "Release/Branch", "Version", "Debug"];
+    if with_last_used {
+        header.push("Last Used");
+    }
     let mut table = Table::new();
     table
-        .load_preset(TABLE_FORMAT)
-        .set_header(vec!["Binary", "Release/Branch", "Version", "Debug"])
+        .load_preset(table_format())
+        .set_header(header)
         .add_rows(
             binaries_vec
                 .into_iter()
                 .map(|binary| {
-                    vec![
+                    let mut row = vec![
                         binary.binary_name,
                         binary.network_release,
                         binary.version,
@@ -275,13 +783,29 @@ pub fn print_table(binaries: &Vec<BinaryVersion>) {
                         } else {
                             "No".to_string()
                         },
-                    ]
+                    ];
+                    if with_last_used {
+                        row.push(format_last_used(binary.last_used, Utc::now()));
+                    }
+                    row
                 })
                 .collect::<Vec<Vec<String>>>(),
         );
     println!("{table}");
 }
 
+/// Formats when a binary was last used relative to `now`, e.g. "today" or "95 days ago".
+pub fn format_last_used(last_used: Option<DateTime<Utc>>, now: DateTime<Utc>) -> String {
+    let Some(last_used) = last_used else {
+        return "-".to_string();
+    };
+    match (now - last_used).num_days() {
+        ..=0 => "today".to_string(),
+        1 => "1 day ago".to_string(),
+        days => format!("{days} days ago"),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use clap::CommandFactory;
@@ -290,4 +814,19 @@ mod tests {
     fn verify_command() {
         super::Command::command().debug_assert();
     }
+
+    #[test]
+    fn test_format_last_used() {
+        let now = chrono::Utc::now();
+        assert_eq!(super::format_last_used(None, now), "-");
+        assert_eq!(super::format_last_used(Some(now), now), "today");
+        assert_eq!(
+            super::format_last_used(Some(now - chrono::Duration::days(1)), now),
+            "1 day ago"
+        );
+        assert_eq!(
+            super::format_last_used(Some(now - chrono::Duration::days(95)), now),
+            "95 days ago"
+        );
+    }
 }
@@ -0,0 +1,22 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::handlers::prompt::handle_prompt;
+
+/// Print the versions in use in the current folder in a compact form, e.g.
+/// `sui:testnet-1.40.1`, to show in a shell prompt (PS1, starship). Only local files are read,
+/// and nothing is printed when no version is in use.
+#[derive(Args, Debug)]
+pub struct Command {
+    /// Only show these binaries, e.g. 'sui'
+    binaries: Vec<String>,
+}
+
+impl Command {
+    pub fn exec(&self) -> Result<()> {
+        handle_prompt(&self.binaries)
+    }
+}
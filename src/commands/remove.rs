@@ -3,15 +3,17 @@
 
 use anyhow::Result;
 use clap::Args;
+use clap_complete::ArgValueCompleter;
 
 use crate::handle_commands::handle_cmd;
+use crate::handlers::completions::installed_names;
 
 use super::{BinaryName, ComponentCommands};
 
 /// Remove one or more binaries.
 #[derive(Args, Debug)]
 pub struct Command {
-    #[arg(value_enum)]
+    #[arg(value_enum, add = ArgValueCompleter::new(installed_names))]
     binary: BinaryName,
 }
 
@@ -0,0 +1,29 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use clap::Args;
+use clap_complete::ArgValueCompleter;
+
+use crate::handlers::completions::installed_specs;
+use crate::handlers::run::handle_run;
+
+/// Run an installed binary without making it the default. The time it was last run is
+/// recorded and shown by `suiup show`.
+#[derive(Args, Debug)]
+pub struct Command {
+    /// Binary to run, optionally narrowed down by release and version
+    /// e.g. 'sui', 'sui@testnet', 'sui@testnet-1.39.3', 'mvr@main'
+    #[arg(add = ArgValueCompleter::new(installed_specs))]
+    binary_spec: String,
+
+    /// Arguments passed to the binary. Use `--` before arguments starting with a dash
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    args: Vec<String>,
+}
+
+impl Command {
+    pub fn exec(&self) -> Result<()> {
+        handle_run(&self.binary_spec, &self.args)
+    }
+}
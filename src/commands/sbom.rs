@@ -0,0 +1,27 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::handlers::sbom::{handle_sbom, SbomFormat};
+
+/// Generate a software bill of materials (SBOM) of the installed binaries.
+#[derive(Args, Debug)]
+pub struct Command {
+    /// SBOM format to generate
+    #[arg(long, value_enum, default_value_t = SbomFormat::Cyclonedx)]
+    format: SbomFormat,
+
+    /// Write the SBOM to this file instead of stdout
+    #[arg(long, short = 'o')]
+    output: Option<PathBuf>,
+}
+
+impl Command {
+    pub fn exec(&self) -> Result<()> {
+        handle_sbom(self.format, self.output.as_deref())
+    }
+}
@@ -0,0 +1,29 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::handlers::shell::handle_shell;
+
+/// Start a shell that uses other installed versions instead of the default binaries, until it
+/// exits. Without binaries, the versions of the manifest (`suiup.toml` in the current folder
+/// by default) are used.
+#[derive(Args, Debug)]
+pub struct Command {
+    /// Installed binaries to use, e.g. 'sui@testnet-1.40.1', 'walrus@testnet'
+    #[arg(conflicts_with = "manifest")]
+    binaries: Vec<String>,
+
+    /// Use the versions listed in this manifest, see `suiup install --manifest`
+    #[arg(long, value_name = "file")]
+    manifest: Option<PathBuf>,
+}
+
+impl Command {
+    pub fn exec(&self) -> Result<()> {
+        handle_shell(&self.binaries, self.manifest.as_deref())
+    }
+}
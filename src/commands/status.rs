@@ -0,0 +1,32 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::handlers::status::handle_status;
+
+/// Show the versions in use in the current folder, whether they are the default versions or
+/// come from `suiup shell` or `suiup.toml`, and the pending updates.
+#[derive(Args, Debug)]
+pub struct Command {
+    /// Print the status as JSON
+    #[arg(long)]
+    json: bool,
+
+    /// Only read local files, for prompts and editors: pending updates are found in the release
+    /// lists cached by earlier commands
+    #[arg(long)]
+    fast: bool,
+}
+
+impl Command {
+    pub async fn exec(&self, github_token: &Option<String>) -> Result<()> {
+        handle_status(self.json, self.fast, github_token.clone()).await
+    }
+
+    /// Whether only local files are read
+    pub fn is_fast(&self) -> bool {
+        self.fast
+    }
+}
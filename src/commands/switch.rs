@@ -3,20 +3,38 @@
 
 use anyhow::Result;
 use clap::Args;
+use clap_complete::ArgValueCompleter;
 
+use crate::handlers::completions::installed_specs;
 use crate::handlers::switch::handle_switch;
 
 /// Switch to a different version of an installed binary.
 #[derive(Args, Debug)]
 pub struct Command {
     /// Binary and network/release to switch to
-    /// e.g. 'sui@testnet', 'mvr@main', 'walrus@testnet'
+    /// e.g. 'sui@testnet', 'mvr@main', 'walrus@testnet', or 'sui-debug@testnet' for the debug build
     /// This will use the latest installed version for that network/release
+    /// A version selects it, e.g. 'sui@testnet-1.39.3', or 'sui@1.39.3' on the network it is
+    /// installed for
+    #[arg(add = ArgValueCompleter::new(installed_specs))]
     binary_spec: String,
+
+    /// Switch even if the version is older than the minimum version set in the config file
+    #[arg(long)]
+    allow_below_minimum: bool,
+
+    /// Switch to the kept nightly build of this commit (or a prefix of it) instead of the
+    /// latest one of the branch, see `suiup list nightly`
+    #[arg(long, value_name = "commit")]
+    commit: Option<String>,
 }
 
 impl Command {
     pub fn exec(&self) -> Result<()> {
-        handle_switch(&self.binary_spec)
+        handle_switch(
+            &self.binary_spec,
+            self.allow_below_minimum,
+            self.commit.as_deref(),
+        )
     }
 }
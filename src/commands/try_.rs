@@ -0,0 +1,26 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::handlers::try_::handle_try;
+
+/// Run a version without installing it: it is installed into a temporary sandbox, run and
+/// deleted. The installed binaries and default versions are left untouched, and cached release
+/// archives are reused.
+#[derive(Args, Debug)]
+pub struct Command {
+    /// Binary and version to try, e.g. 'sui@devnet-1.41.0', 'sui@testnet', 'walrus@testnet'
+    binary_spec: String,
+
+    /// Arguments passed to the binary. Use `--` before arguments starting with a dash
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    args: Vec<String>,
+}
+
+impl Command {
+    pub async fn exec(&self, github_token: &Option<String>) -> Result<()> {
+        handle_try(&self.binary_spec, &self.args, github_token.clone()).await
+    }
+}
@@ -0,0 +1,20 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::handlers::block::handle_unblock;
+
+/// Allow a version blocked with `suiup block` again.
+#[derive(Args, Debug)]
+pub struct Command {
+    /// Blocked version, as passed to `suiup block` (e.g. 'sui@testnet-1.40.0')
+    spec: String,
+}
+
+impl Command {
+    pub fn exec(&self) -> Result<()> {
+        handle_unblock(&self.spec)
+    }
+}
@@ -4,27 +4,33 @@
 use anyhow::Result;
 use clap::Args;
 
-use crate::handlers::update::handle_update;
+use crate::handlers::update::{handle_update, handle_update_all};
+
+use super::BinaryName;
 
 /// Update binary.
 #[derive(Args, Debug)]
 pub struct Command {
     /// Binary to update (e.g. 'sui', 'mvr', 'walrus'). By default, it will update the default
-    /// binary version. For updating a specific release, use the `sui@testnet` form.
-    name: String,
+    /// binary version. For updating a specific release, use the `sui@testnet` form, and
+    /// `sui@nightly` to rebuild the nightly builds whose branch has new commits.
+    #[arg(required_unless_present = "all", conflicts_with = "all")]
+    name: Option<String>,
+
+    /// Update every installed binary, except those of `update_exclude` in the config file
+    #[arg(long)]
+    all: bool,
 
-    /// Accept defaults without prompting
-    #[arg(short, long)]
-    yes: bool,
+    /// Binary to skip with `--all`, can be repeated
+    #[arg(long, value_enum, conflicts_with = "name")]
+    except: Vec<BinaryName>,
 }
 
 impl Command {
-    pub async fn exec(&self, github_token: &Option<String>) -> Result<()> {
-        handle_update(
-            self.name.to_owned(),
-            self.yes.to_owned(),
-            github_token.to_owned(),
-        )
-        .await
+    pub async fn exec(&self, github_token: &Option<String>, yes: bool) -> Result<()> {
+        match &self.name {
+            Some(name) => handle_update(name.to_owned(), yes, github_token.to_owned()).await,
+            None => handle_update_all(&self.except, yes, github_token.to_owned()).await,
+        }
     }
 }
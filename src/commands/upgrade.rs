@@ -0,0 +1,28 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use clap::Args;
+
+use crate::handlers::upgrade::handle_upgrade;
+
+use super::BinaryName;
+
+/// Install the latest release of the network of the default version and make it the default, in
+/// one step.
+#[derive(Args, Debug)]
+pub struct Command {
+    /// Binary to upgrade (e.g. 'sui', 'walrus')
+    #[arg(value_enum)]
+    name: BinaryName,
+
+    /// Only install the latest release, keeping the current default version
+    #[arg(long)]
+    no_switch: bool,
+}
+
+impl Command {
+    pub async fn exec(&self, github_token: &Option<String>) -> Result<()> {
+        handle_upgrade(&self.name, self.no_switch, github_token.to_owned()).await
+    }
+}
@@ -6,12 +6,18 @@ use clap::Args;
 
 use crate::handlers::which::handle_which;
 
-/// Show the path where default binaries are installed.
+use super::BinaryName;
+
+/// Show the path where default binaries are installed, or the path of one of them.
 #[derive(Args, Debug)]
-pub struct Command;
+pub struct Command {
+    /// Binary whose default version's path is shown (e.g. 'sui')
+    #[arg(value_enum)]
+    name: Option<BinaryName>,
+}
 
 impl Command {
     pub fn exec(&self) -> Result<()> {
-        handle_which()
+        handle_which(self.name.as_ref())
     }
 }
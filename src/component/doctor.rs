@@ -1,97 +1,210 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::config::{config_file, load_config};
+use crate::handlers::available_components;
+use crate::handlers::conflicts::{find_conflicts, InstallSource};
+use crate::handlers::info::installed_path;
+use crate::handlers::integrity::verify_all;
+use crate::http;
+use crate::output;
 use crate::paths::{
     default_file_path, get_default_bin_dir, get_suiup_data_dir, installed_binaries_file,
 };
+use crate::state;
 use crate::types::InstalledBinaries;
 use anyhow::Result;
 use colored::Colorize;
+use serde::Serialize;
 use std::env;
+use std::path::Path;
 use std::process::Command;
 
-pub async fn run_doctor_checks() -> Result<()> {
-    println!("\n{}", "Suiup Environment Doctor".bold());
-    println!("{}", "------------------------");
-
-    let mut warnings = 0;
-    let mut errors = 0;
-
-    let mut check = |message: &str, result: Result<String, String>| match result {
-        Ok(info) if info.is_empty() => println!("[{}] {}", "✓".green(), message),
-        Ok(info) => println!("[{}] {} {}", "✓".green(), message, info.dimmed()),
-        Err(e) => {
-            if e.starts_with("WARN:") {
-                warnings += 1;
-                println!(
-                    "[{}] {}",
-                    "!".yellow(),
-                    e.strip_prefix("WARN:").unwrap_or(&e).trim()
-                );
-            } else {
-                errors += 1;
-                println!(
-                    "[{}] {}",
-                    "✗".red(),
-                    e.strip_prefix("ERROR:").unwrap_or(&e).trim()
-                );
-            }
+/// Installs Rust with rustup, for the checks of the tools of `--nightly` builds
+const RUSTUP_INSTALL: &str =
+    "curl --proto '=https' --tlsv1.2 -sSf https://sh.rustup.rs | sh -s -- -y";
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// Result of a check, printed as a line or as JSON with `doctor --json`
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Check {
+    /// Stable identifier of the check, e.g. `bin_dir_in_path`
+    pub id: &'static str,
+    pub status: CheckStatus,
+    /// What is checked
+    pub name: String,
+    pub details: String,
+    /// Command fixing the problem without prompting, for provisioning scripts
+    pub fix_command: Option<String>,
+}
+
+impl Check {
+    fn ok(id: &'static str, name: &str, details: impl Into<String>) -> Self {
+        Check {
+            id,
+            status: CheckStatus::Ok,
+            name: name.to_string(),
+            details: details.into(),
+            fix_command: None,
+        }
+    }
+
+    fn warning(id: &'static str, name: &str, details: impl Into<String>) -> Self {
+        Check {
+            status: CheckStatus::Warning,
+            ..Check::ok(id, name, details)
+        }
+    }
+
+    fn error(id: &'static str, name: &str, details: impl Into<String>) -> Self {
+        Check {
+            status: CheckStatus::Error,
+            ..Check::ok(id, name, details)
         }
+    }
+
+    fn fix(self, command: impl Into<String>) -> Self {
+        Check {
+            fix_command: Some(command.into()),
+            ..self
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct DoctorReport {
+    checks: Vec<Check>,
+    errors: usize,
+    warnings: usize,
+}
+
+pub async fn run_doctor_checks(json: bool) -> Result<()> {
+    // Status messages would break the JSON
+    if json {
+        output::set_quiet(true);
+    }
+    let mut checks = vec![check_suiup_data_dir()];
+    check_path_variables(&mut checks);
+    check_conflicting_installs(&mut checks);
+    check_config_files(&mut checks);
+    check_integrity(&mut checks);
+    check_dependencies(&mut checks);
+    checks.push(check_network_connectivity().await);
+
+    let count = |status| checks.iter().filter(|c| c.status == status).count();
+    let report = DoctorReport {
+        errors: count(CheckStatus::Error),
+        warnings: count(CheckStatus::Warning),
+        checks,
     };
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&report);
+    }
+    Ok(())
+}
 
-    check("suiup data directory exists", check_suiup_data_dir());
-    check_path_variables(&mut check);
-    check_config_files(&mut check);
-    check_dependencies(&mut check);
-    check_network_connectivity(&mut check).await;
+fn print_report(report: &DoctorReport) {
+    println!("\n{}", "Suiup Environment Doctor".bold());
+    println!("------------------------");
+
+    for check in &report.checks {
+        match check.status {
+            CheckStatus::Ok if check.details.is_empty() => {
+                println!("[{}] {}", output::symbol("✓", "ok").green(), check.name)
+            }
+            CheckStatus::Ok => println!(
+                "[{}] {} {}",
+                output::symbol("✓", "ok").green(),
+                check.name,
+                check.details.dimmed()
+            ),
+            CheckStatus::Warning => println!("[{}] {}", "!".yellow(), check.details),
+            CheckStatus::Error => {
+                println!("[{}] {}", output::symbol("✗", "x").red(), check.details)
+            }
+        }
+    }
 
     println!("\n{}", "Checkup complete.".bold());
-    if errors > 0 {
+    if report.errors > 0 {
+        println!(
+            "{}",
+            format!(
+                "Found {} error(s) and {} warning(s).",
+                report.errors, report.warnings
+            )
+            .red()
+        );
+    } else if report.warnings > 0 {
         println!(
             "{}",
-            format!("Found {} error(s) and {} warning(s).", errors, warnings).red()
+            format!("Found {} warning(s).", report.warnings).yellow()
         );
-    } else if warnings > 0 {
-        println!("{}", format!("Found {} warning(s).", warnings).yellow());
     } else {
         println!("{}", "Your environment looks good!".green());
     }
+}
 
-    Ok(())
+/// `path` quoted for the shell of the platform
+fn quote(path: &Path) -> String {
+    if cfg!(windows) {
+        format!("\"{}\"", path.display())
+    } else {
+        format!("'{}'", path.display().to_string().replace('\'', r"'\''"))
+    }
 }
 
-fn check_suiup_data_dir() -> Result<String, String> {
+fn check_suiup_data_dir() -> Check {
+    let name = "suiup data directory exists";
     let path = get_suiup_data_dir();
     if path.exists() && path.is_dir() {
-        Ok(format!("at {}", path.display()))
+        Check::ok("data_dir", name, format!("at {}", path.display()))
     } else {
-        Err(format!(
-            "ERROR: suiup data directory not found at {}",
-            path.display()
-        ))
+        let mkdir = if cfg!(windows) { "mkdir" } else { "mkdir -p" };
+        Check::error(
+            "data_dir",
+            name,
+            format!("suiup data directory not found at {}", path.display()),
+        )
+        .fix(format!("{mkdir} {}", quote(&path)))
     }
 }
 
-fn check_path_variables(check: &mut impl FnMut(&str, Result<String, String>)) {
+fn check_path_variables(checks: &mut Vec<Check>) {
     let default_bin_dir = get_default_bin_dir();
-    check(
+    checks.push(Check::ok(
+        "bin_dir",
         "Default binary directory",
-        Ok(format!("is {}", default_bin_dir.display())),
-    );
+        format!("is {}", default_bin_dir.display()),
+    ));
 
     match env::var("PATH") {
         Ok(path_var) => {
             let paths: Vec<_> = env::split_paths(&path_var).collect();
             if !paths.contains(&default_bin_dir) {
-                check(
-                    "Default binary directory in PATH",
-                    Err(
-                        "WARN: Not found in PATH. Binaries managed by suiup may not be accessible."
-                            .to_string(),
-                    ),
+                checks.push(
+                    Check::warning(
+                        "bin_dir_in_path",
+                        "Default binary directory in PATH",
+                        "Not found in PATH. Binaries managed by suiup may not be accessible.",
+                    )
+                    .fix("suiup init"),
                 );
             } else {
-                check("Default binary directory in PATH", Ok("".to_string()));
+                checks.push(Check::ok(
+                    "bin_dir_in_path",
+                    "Default binary directory in PATH",
+                    "",
+                ));
 
                 // Check PATH order
                 let cargo_bin_dir = dirs::home_dir().map(|p| p.join(".cargo/bin"));
@@ -101,9 +214,9 @@ fn check_path_variables(check: &mut impl FnMut(&str, Result<String, String>)) {
                         let cargo_pos = paths.iter().position(|p| p == &cargo_bin);
                         if let (Some(s_pos), Some(c_pos)) = (suiup_pos, cargo_pos) {
                             if s_pos > c_pos {
-                                check("PATH order", Err(format!("WARN: Default binary directory ({}) is after cargo's binary directory ({}). This may cause conflicts if you have also installed sui via `cargo install`.", default_bin_dir.display(), cargo_bin.display())));
+                                checks.push(Check::warning("path_order", "PATH order", format!("Default binary directory ({}) is after cargo's binary directory ({}). This may cause conflicts if you have also installed sui via `cargo install`.", default_bin_dir.display(), cargo_bin.display())).fix("suiup init"));
                             } else {
-                                check("PATH order", Ok("is correct".to_string()));
+                                checks.push(Check::ok("path_order", "PATH order", "is correct"));
                             }
                         }
                     }
@@ -111,127 +224,184 @@ fn check_path_variables(check: &mut impl FnMut(&str, Result<String, String>)) {
             }
         }
         Err(_) => {
-            check(
+            checks.push(Check::error(
+                "path",
                 "PATH variable",
-                Err("ERROR: Could not read PATH environment variable.".to_string()),
-            );
+                "Could not read PATH environment variable.",
+            ));
         }
     }
 }
 
-fn check_config_files(check: &mut impl FnMut(&str, Result<String, String>)) {
-    let installed_path = installed_binaries_file();
-    match installed_path {
-        Ok(path) => {
-            if !path.exists() {
-                check(
-                    "Installed binaries config",
-                    Err(format!("WARN: File not found at {}", path.display())),
-                );
-            } else {
-                match InstalledBinaries::read_from_file() {
-                    Ok(_) => check("Installed binaries config", Ok("is valid".to_string())),
-                    Err(e) => check(
-                        "Installed binaries config",
-                        Err(format!("ERROR: Failed to parse: {}", e)),
-                    ),
-                }
-            }
-        }
-        Err(e) => check(
-            "Installed binaries config",
-            Err(format!("ERROR: Could not get path: {}", e)),
-        ),
+fn check_conflicting_installs(checks: &mut Vec<Check>) {
+    let conflicts = find_conflicts(available_components());
+    if conflicts.is_empty() {
+        checks.push(Check::ok("conflicts", "No conflicting installations", ""));
+    }
+    for conflict in conflicts {
+        let check = Check::warning(
+            "conflicts",
+            "Conflicting installation",
+            conflict.to_string(),
+        );
+        checks.push(match conflict.source {
+            InstallSource::Cargo => check.fix(format!("cargo uninstall {}", conflict.binary)),
+            InstallSource::Homebrew => check.fix(format!("brew uninstall {}", conflict.binary)),
+            InstallSource::SystemPackage | InstallSource::Unknown => check,
+        });
     }
+}
 
-    let default_path = default_file_path();
-    match default_path {
-        Ok(path) => {
-            if !path.exists() {
-                check(
-                    "Default version config",
-                    Err(format!("WARN: File not found at {}", path.display())),
-                );
-            } else {
-                match std::fs::read_to_string(&path) {
-                    Ok(content) => {
-                        let result: Result<serde_json::Value, _> = serde_json::from_str(&content);
-                        if result.is_ok() {
-                            check("Default version config", Ok("is valid".to_string()));
-                        } else {
-                            check(
-                                "Default version config",
-                                Err("ERROR: Failed to parse as valid JSON.".to_string()),
-                            );
-                        }
-                    }
-                    Err(e) => check(
-                        "Default version config",
-                        Err(format!("ERROR: Failed to read: {}", e)),
-                    ),
+fn check_config_files(checks: &mut Vec<Check>) {
+    let name = "Installed binaries config";
+    checks.push(match installed_binaries_file() {
+        Ok(path) if !path.exists() => Check::warning(
+            "installed_binaries",
+            name,
+            format!("File not found at {}", path.display()),
+        ),
+        Ok(_) => match InstalledBinaries::read_from_file() {
+            Ok(_) => Check::ok("installed_binaries", name, "is valid"),
+            Err(e) => Check::error(
+                "installed_binaries",
+                name,
+                format!("Failed to parse: {}", e),
+            ),
+        },
+        Err(e) => Check::error(
+            "installed_binaries",
+            name,
+            format!("Could not get path: {}", e),
+        ),
+    });
+
+    let name = "Default version config";
+    checks.push(match default_file_path() {
+        Ok(path) if !path.exists() => Check::warning(
+            "default_versions",
+            name,
+            format!("File not found at {}", path.display()),
+        ),
+        Ok(path) => match std::fs::read_to_string(&path) {
+            Ok(content) => {
+                let result: Result<serde_json::Value, _> = serde_json::from_str(&content);
+                if result.is_ok() {
+                    Check::ok("default_versions", name, "is valid")
+                } else {
+                    Check::error("default_versions", name, "Failed to parse as valid JSON.")
                 }
             }
-        }
-        Err(e) => check(
-            "Default version config",
-            Err(format!("ERROR: Could not get path: {}", e)),
+            Err(e) => Check::error("default_versions", name, format!("Failed to read: {}", e)),
+        },
+        Err(e) => Check::error(
+            "default_versions",
+            name,
+            format!("Could not get path: {}", e),
         ),
+    });
+
+    if config_file().exists() {
+        checks.push(match load_config() {
+            Ok(_) => Check::ok("config", "suiup config", "is valid"),
+            Err(e) => Check::error("config", "suiup config", e.to_string()),
+        });
     }
 }
 
-fn check_dependencies(check: &mut impl FnMut(&str, Result<String, String>)) {
-    // Check for rustc
-    match Command::new("rustc").arg("--version").output() {
-        Ok(output) if output.status.success() => {
-            let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            check("rustc", Ok(version));
+/// Rehashes the cached archives and installed binaries, to find those modified or corrupted
+/// since they were downloaded or installed
+fn check_integrity(checks: &mut Vec<Check>) {
+    match verify_all() {
+        Ok((checked, changed)) if changed.is_empty() => checks.push(Check::ok(
+            "integrity",
+            "Cached archives and installed binaries are intact",
+            format!("({checked} files checked)"),
+        )),
+        Ok((_, changed)) => {
+            for path in changed {
+                let check = Check::error(
+                    "integrity",
+                    "Integrity",
+                    format!(
+                        "{} changed since it was downloaded or installed. Install it again with `suiup install --force`.",
+                        path.display()
+                    ),
+                );
+                checks.push(match reinstall_command(&path) {
+                    Some(command) => check.fix(command),
+                    // A cached archive is downloaded again by the next install
+                    None if cfg!(windows) => check.fix(format!("del {}", quote(&path))),
+                    None => check.fix(format!("rm {}", quote(&path))),
+                });
+            }
         }
-        _ => check(
-            "rustc",
-            Err("WARN: Not found. Required for --nightly builds.".to_string()),
-        ),
+        Err(e) => checks.push(Check::warning(
+            "integrity",
+            "Integrity",
+            format!("Cannot check the installed binaries: {e}"),
+        )),
     }
+}
 
-    // Check for cargo
-    match Command::new("cargo").arg("--version").output() {
-        Ok(output) if output.status.success() => {
-            let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            check("cargo", Ok(version));
-        }
-        _ => check(
-            "cargo",
-            Err("WARN: Not found. Required for --nightly builds.".to_string()),
-        ),
+/// `suiup install --force` command installing the binary at `path` again, `None` if it is not an
+/// installed binary
+fn reinstall_command(path: &Path) -> Option<String> {
+    let installed = state::installed_binaries().ok()?;
+    let binary = installed
+        .binaries()
+        .iter()
+        .find(|b| installed_path(b, false) == path)?;
+    let name = &binary.binary_name;
+    let mut command = if binary.version == "nightly" {
+        format!("suiup install {name} --nightly {}", binary.network_release)
+    } else {
+        format!(
+            "suiup install {name}@{}-{}",
+            binary.network_release, binary.version
+        )
+    };
+    if binary.debug {
+        command.push_str(" --debug");
     }
+    command.push_str(" --force --yes");
+    Some(command)
+}
 
-    // Check for git
-    match Command::new("git").arg("--version").output() {
-        Ok(output) if output.status.success() => {
-            let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            check("git", Ok(version));
+fn check_dependencies(checks: &mut Vec<Check>) {
+    for tool in ["rustc", "cargo", "git"] {
+        match Command::new(tool).arg("--version").output() {
+            Ok(output) if output.status.success() => {
+                let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                checks.push(Check::ok(tool, tool, version));
+            }
+            _ => {
+                let check = Check::warning(tool, tool, "Not found. Required for --nightly builds.");
+                // rustup installs both rustc and cargo, git comes from the system packages
+                checks.push(if tool != "git" && !cfg!(windows) {
+                    check.fix(RUSTUP_INSTALL)
+                } else {
+                    check
+                });
+            }
         }
-        _ => check(
-            "git",
-            Err("WARN: Not found. Required for --nightly builds.".to_string()),
-        ),
     }
 }
 
-async fn check_network_connectivity(check: &mut impl FnMut(&str, Result<String, String>)) {
-    let client = reqwest::Client::new();
-
-    match client
-        .get("https://api.github.com")
-        .header("User-Agent", "suiup")
-        .send()
-        .await
-    {
-        Ok(resp) if resp.status().is_success() => {
-            check("GitHub API connectivity", Ok("".to_string()))
-        }
-        _ => check(
-            "GitHub API connectivity",
-            Err("ERROR: Cannot connect to GitHub API. Downloads will fail.".to_string()),
+async fn check_network_connectivity() -> Check {
+    let name = "GitHub API connectivity";
+    if http::is_offline() {
+        return Check::warning("github_api", name, "Not checked in offline mode.");
+    }
+    let request = match http::github_api_url().and_then(|url| http::get(&url, None)) {
+        Ok(request) => request,
+        Err(e) => return Check::error("github_api", name, e.to_string()),
+    };
+    match request.send().await {
+        Ok(resp) if resp.status().is_success() => Check::ok("github_api", name, ""),
+        _ => Check::error(
+            "github_api",
+            name,
+            "Cannot connect to GitHub API. Downloads will fail.",
         ),
     }
 }
@@ -242,6 +412,28 @@ mod tests {
     use std::fs;
     use tempfile::TempDir;
 
+    #[test]
+    fn test_check_json() {
+        let check = Check::warning(
+            "bin_dir_in_path",
+            "Default binary directory in PATH",
+            "Not found in PATH.",
+        )
+        .fix("suiup init");
+        assert_eq!(
+            serde_json::to_value(&check).unwrap(),
+            serde_json::json!({
+                "id": "bin_dir_in_path",
+                "status": "warning",
+                "name": "Default binary directory in PATH",
+                "details": "Not found in PATH.",
+                "fix_command": "suiup init",
+            })
+        );
+        #[cfg(not(windows))]
+        assert_eq!(quote(Path::new("/tmp/it's")), r"'/tmp/it'\''s'");
+    }
+
     #[test]
     fn test_check_suiup_data_dir_exists() {
         let temp_dir = TempDir::new().unwrap();
@@ -262,8 +454,9 @@ mod tests {
         }
 
         let result = check_suiup_data_dir();
-        assert!(result.is_ok());
-        assert!(result.unwrap().contains("at"));
+        assert_eq!(result.status, CheckStatus::Ok);
+        assert!(result.details.contains("at"));
+        assert_eq!(result.fix_command, None);
 
         // Restore original env var
         #[cfg(windows)]
@@ -306,10 +499,12 @@ mod tests {
         println!("Testing path: {}", path.display());
         println!("Path exists: {}", path.exists());
         let result = check_suiup_data_dir();
-        assert!(result.is_err());
+        assert_eq!(result.status, CheckStatus::Error);
+        assert!(result.details.contains("suiup data directory not found"));
         assert!(result
-            .unwrap_err()
-            .contains("suiup data directory not found"));
+            .fix_command
+            .unwrap()
+            .contains(&*path.to_string_lossy()));
 
         // Restore original env var
         #[cfg(windows)]
@@ -4,21 +4,36 @@
 use anyhow::{anyhow, Result};
 use std::fs::create_dir_all;
 
-use crate::commands::BinaryName;
-use crate::handlers::install::{install_from_nightly, install_from_release, install_standalone};
+use crate::commands::{BinaryName, CommandMetadata};
+use crate::config::load_config;
+use crate::handlers::block::ensure_not_blocked;
+use crate::handlers::deprecation::refresh_deprecations;
+use crate::handlers::download::cached_release_archive;
+use crate::handlers::install::{
+    install_from_nightly, install_from_release, install_from_source, install_standalone,
+    InstallOptions,
+};
+use crate::handlers::release::latest_version;
+use crate::handlers::version::{ensure_minimum_version, extract_version_from_release};
+use crate::http;
 use crate::paths::{binaries_dir, get_default_bin_dir};
-use crate::types::{Repo, Version};
+use crate::platform::{current_target, Target};
+use crate::types::Repo;
 
 /// Install a component with the given parameters
 pub async fn install_component(
-    name: BinaryName,
-    network: String,
-    version: Option<Version>,
+    metadata: CommandMetadata,
     nightly: Option<String>,
     debug: bool,
-    yes: bool,
     github_token: Option<String>,
+    options: &InstallOptions,
 ) -> Result<()> {
+    let CommandMetadata {
+        name,
+        network,
+        version,
+        ..
+    } = metadata;
     // Ensure installation directories exist
     let default_bin_dir = get_default_bin_dir();
     create_dir_all(&default_bin_dir)?;
@@ -36,45 +51,63 @@ pub async fn install_component(
         ));
     }
 
+    // Installs warn about deprecated versions, keep the list of them up to date
+    refresh_deprecations().await;
+
+    // mvr releases are not per network
+    let network = if name == BinaryName::Mvr {
+        "standalone"
+    } else {
+        network.as_str()
+    };
+    if let (None, Some(version)) = (&nightly, &version) {
+        ensure_not_blocked(name.to_str(), network, version)?;
+        ensure_minimum_version(name.to_str(), version, options.allow_below_minimum)?;
+    }
+
+    // A download source in the config file replaces the GitHub releases
+    if nightly.is_none() {
+        let config = load_config()?;
+        if let Some(source) = config.sources.get(name.to_str()) {
+            let asset_pattern = config.asset_pattern(name.repo());
+            return install_from_source(&name, network, version, source, asset_pattern, options)
+                .await;
+        }
+    }
+
+    // The latest release is resolved up front, so it is checked before it is downloaded
+    let version = match (&nightly, version) {
+        (None, None) => {
+            let version =
+                resolve_latest(&name, network, github_token.clone(), options.target).await?;
+            ensure_not_blocked(name.to_str(), network, &version)?;
+            ensure_minimum_version(name.to_str(), &version, options.allow_below_minimum)?;
+            Some(version)
+        }
+        (_, version) => version,
+    };
+
     match (&name, &nightly) {
         (BinaryName::Walrus, nightly) => {
-            create_dir_all(installed_bins_dir.join(network.clone()))?;
+            create_dir_all(installed_bins_dir.join(network))?;
             if let Some(branch) = nightly {
-                install_from_nightly(&name, branch, debug, yes).await?;
+                install_from_nightly(&name, branch, debug, options).await?;
             } else {
-                install_from_release(
-                    name.to_string().as_str(),
-                    &network,
-                    version,
-                    debug,
-                    yes,
-                    Repo::Walrus,
-                    github_token,
-                )
-                .await?;
+                install_from_release(&name, network, version, debug, github_token, options).await?;
             }
         }
         (BinaryName::WalrusSites, nightly) => {
-            create_dir_all(installed_bins_dir.join("mainnet"))?;
+            create_dir_all(installed_bins_dir.join(network))?;
             if let Some(branch) = nightly {
-                install_from_nightly(&name, branch, debug, yes).await?;
+                install_from_nightly(&name, branch, debug, options).await?;
             } else {
-                install_from_release(
-                    name.to_string().as_str(),
-                    "mainnet",
-                    version,
-                    debug,
-                    yes,
-                    Repo::WalrusSites,
-                    github_token,
-                )
-                .await?;
+                install_from_release(&name, network, version, debug, github_token, options).await?;
             }
         }
         (BinaryName::Mvr, nightly) => {
             create_dir_all(installed_bins_dir.join("standalone"))?;
             if let Some(branch) = nightly {
-                install_from_nightly(&name, branch, debug, yes).await?;
+                install_from_nightly(&name, branch, debug, options).await?;
             } else {
                 install_standalone(
                     version,
@@ -84,27 +117,33 @@ pub async fn install_component(
                             return Err(anyhow!("Invalid binary name for standalone installation"))
                         }
                     },
-                    yes,
+                    options,
                 )
                 .await?;
             }
         }
         (_, Some(branch)) => {
-            install_from_nightly(&name, branch, debug, yes).await?;
+            install_from_nightly(&name, branch, debug, options).await?;
         }
         _ => {
-            install_from_release(
-                name.to_string().as_str(),
-                &network,
-                version,
-                debug,
-                yes,
-                Repo::Sui,
-                github_token,
-            )
-            .await?;
+            install_from_release(&name, network, version, debug, github_token, options).await?;
         }
     }
 
     Ok(())
 }
+
+/// The version installing the latest release of `name` resolves to. Offline, the newest
+/// downloaded archive.
+async fn resolve_latest(
+    name: &BinaryName,
+    network: &str,
+    github_token: Option<String>,
+    target: Option<Target>,
+) -> Result<String> {
+    if http::is_offline() && *name != BinaryName::Mvr {
+        let asset = cached_release_archive(&name.repo(), network, None, &current_target(target)?)?;
+        return extract_version_from_release(&asset.name);
+    }
+    latest_version(name.repo(), network, github_token).await
+}
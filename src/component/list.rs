@@ -1,21 +1,52 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::commands::TABLE_FORMAT;
+use crate::commands::table_format;
+use crate::handlers::component_groups;
+use crate::state::disabled_binaries;
 use anyhow::Result;
 use comfy_table::*;
 
 /// List all available components
 pub async fn list_components() -> Result<()> {
     let components = crate::handlers::available_components();
+    let disabled = disabled_binaries()?;
     let mut table = Table::new();
     table
-        .load_preset(TABLE_FORMAT)
+        .load_preset(table_format())
         .set_header(vec![Cell::new("Available Binaries to Install")])
         .add_rows(
             components
                 .iter()
-                .map(|component| vec![Cell::new(component)])
+                .map(|component| {
+                    if disabled.iter().any(|b| b == component) {
+                        vec![Cell::new(format!("{component} (disabled)"))]
+                    } else {
+                        vec![Cell::new(component)]
+                    }
+                })
+                .collect::<Vec<Vec<Cell>>>(),
+        );
+    println!("{table}");
+    println!(
+        "Use `suiup list groups` to see the groups of binaries that can be installed together."
+    );
+    if !disabled.is_empty() {
+        println!("Disabled binaries are not in the default bin folder, `suiup enable <binary>` puts them back.");
+    }
+    Ok(())
+}
+
+/// List the groups of binaries that can be installed together
+pub fn list_groups() -> Result<()> {
+    let mut table = Table::new();
+    table
+        .load_preset(table_format())
+        .set_header(vec![Cell::new("Group"), Cell::new("Binaries")])
+        .add_rows(
+            component_groups()
+                .iter()
+                .map(|group| vec![Cell::new(group.name), Cell::new(group.members.join(", "))])
                 .collect::<Vec<Vec<Cell>>>(),
         );
     println!("{table}");
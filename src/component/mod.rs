@@ -11,6 +11,11 @@ use anyhow::Result;
 use crate::commands::{
     parse_component_with_version, BinaryName, CommandMetadata, ComponentCommands,
 };
+use crate::handlers::expand_group;
+use crate::handlers::install::{find_installed_version, InstallOptions};
+use crate::http::Skipped;
+use crate::status;
+use crate::timings;
 
 /// ComponentManager handles all component-related operations
 pub struct ComponentManager {
@@ -26,28 +31,73 @@ impl ComponentManager {
     /// Handle component commands
     pub async fn handle_command(&self, cmd: ComponentCommands) -> Result<()> {
         match cmd {
-            ComponentCommands::Doctor => self.run_doctor_checks().await,
-            ComponentCommands::List => self.list_components().await,
+            ComponentCommands::Doctor => self.run_doctor_checks(false).await,
+            ComponentCommands::List { groups } => self.list_components(groups).await,
             ComponentCommands::Add {
                 component,
                 nightly,
                 debug,
-                yes,
+                if_missing,
+                options,
             } => {
-                let command_metadata = parse_component_with_version(&component)?;
-                self.install_component(command_metadata, nightly, debug, yes)
-                    .await
+                // A group installs each of its members the same way
+                let specs = expand_group(&component).unwrap_or_else(|| vec![component]);
+                for spec in specs {
+                    let command_metadata = parse_component_with_version(&spec)?;
+                    let (nightly, debug) =
+                        command_metadata.with_flags(nightly.as_deref(), debug)?;
+                    if if_missing {
+                        if let Some(installed) =
+                            find_installed_version(&command_metadata, nightly.as_deref(), debug)?
+                        {
+                            status!(
+                                "[{}] {installed} is already installed, skipping.",
+                                installed.network_release
+                            );
+                            continue;
+                        }
+                    }
+                    let result = self
+                        .install_component(command_metadata, nightly, debug, &options)
+                        .await;
+                    match result {
+                        // The other members of the group are still installed
+                        Err(e) if e.is::<Skipped>() => status!("{e}"),
+                        result => result?,
+                    }
+                }
+                Ok(())
             }
             ComponentCommands::Remove { binary } => self.remove_component(binary).await,
-            ComponentCommands::Cleanup { all, days, dry_run } => {
-                self.handle_cleanup(all, days, dry_run).await
+            ComponentCommands::Cleanup {
+                all,
+                days,
+                dry_run,
+                force,
+                unused,
+                orphans,
+                interactive,
+                yes,
+            } => {
+                let _timer = timings::phase("cleanup");
+                if orphans {
+                    crate::handlers::cleanup::handle_orphans(dry_run, yes)
+                } else if interactive {
+                    crate::handlers::cleanup::handle_interactive_cleanup(dry_run, yes)
+                } else {
+                    self.handle_cleanup(all, days, dry_run, force, unused).await
+                }
             }
         }
     }
 
     /// List all available components
-    async fn list_components(&self) -> Result<()> {
-        list::list_components().await
+    async fn list_components(&self, groups: bool) -> Result<()> {
+        if groups {
+            list::list_groups()
+        } else {
+            list::list_components().await
+        }
     }
 
     /// Install a component
@@ -56,21 +106,14 @@ impl ComponentManager {
         command_metadata: CommandMetadata,
         nightly: Option<String>,
         debug: bool,
-        yes: bool,
+        options: &InstallOptions,
     ) -> Result<()> {
-        let CommandMetadata {
-            name,
-            network,
-            version,
-        } = command_metadata;
         install::install_component(
-            name,
-            network,
-            version,
+            command_metadata,
             nightly,
             debug,
-            yes,
             self.github_token.clone(),
+            options,
         )
         .await
     }
@@ -81,12 +124,19 @@ impl ComponentManager {
     }
 
     /// Run diagnostic checks on the environment
-    pub async fn run_doctor_checks(&self) -> Result<()> {
-        doctor::run_doctor_checks().await
+    pub async fn run_doctor_checks(&self, json: bool) -> Result<()> {
+        doctor::run_doctor_checks(json).await
     }
 
     /// Handle cleanup operations
-    async fn handle_cleanup(&self, all: bool, days: u32, dry_run: bool) -> Result<()> {
-        crate::handlers::cleanup::handle_cleanup(all, days, dry_run).await
+    async fn handle_cleanup(
+        &self,
+        all: bool,
+        days: u32,
+        dry_run: bool,
+        force: bool,
+        unused: Option<u32>,
+    ) -> Result<()> {
+        crate::handlers::cleanup::handle_cleanup(all, days, dry_run, force, unused).await
     }
 }
@@ -0,0 +1,842 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+
+use anyhow::{anyhow, bail, Error};
+use clap::ValueEnum;
+use dialoguer::Confirm;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::commands::BinaryName;
+use crate::credentials;
+use crate::http;
+use crate::output;
+use crate::paths::get_config_file;
+use crate::platform::{check_asset_pattern, Os, Target};
+use crate::state::write_file_atomically;
+use crate::status;
+use crate::suggest::did_you_mean;
+use crate::types::Repo;
+
+/// Name of the config file in the suiup config directory
+pub const CONFIG_FILE: &str = "config.json";
+
+lazy_static! {
+    /// Config file selected with `--config`, used instead of the one in the config directory
+    static ref CONFIG_OVERRIDE: RwLock<Option<PathBuf>> = RwLock::new(None);
+    /// The config file loaded by [`load_config`] and its path, read once per command
+    static ref LOADED_CONFIG: Mutex<Option<(PathBuf, SuiupConfig)>> = Mutex::new(None);
+}
+
+/// User settings, read from `config.json` in the suiup config directory. All keys are optional.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct SuiupConfig {
+    pub tls: TlsConfig,
+    /// Where to download binaries from instead of their GitHub releases, keyed by binary name,
+    /// e.g. an internal fork hosted on GitLab, S3 or an artifact server
+    pub sources: BTreeMap<String, SourceConfig>,
+    /// How many downloads may run at the same time (4 if not set)
+    pub download_concurrency: Option<usize>,
+    /// Oldest version of each binary that can be installed or switched to without
+    /// `--allow-below-minimum`, keyed by binary name, e.g. `"sui": "1.39"`
+    pub minimum_versions: BTreeMap<String, String>,
+    /// Names of the release assets, keyed by binary name, to select the asset of the platform
+    /// when upstream renames them, e.g. `"sui": "sui-{network}-v{version}-{os}-{arch}.tgz"`
+    pub asset_patterns: BTreeMap<String, String>,
+    /// GitHub repository to download the releases of each binary from instead of the MystenLabs
+    /// one, keyed by binary name, e.g. `"sui": "acme/sui"` for a private fork
+    pub repositories: BTreeMap<String, String>,
+    /// Base URL of the API of a GitHub Enterprise Server to use instead of GitHub, e.g.
+    /// `https://github.example.com/api/v3`, for repositories mirrored there
+    pub github_api_base: Option<String>,
+    /// Fetch release lists and download release assets with the GitHub CLI (`gh`) if it is
+    /// installed, to use its login and GitHub Enterprise hosts. Falls back to suiup's own client
+    /// when `gh` fails.
+    pub gh_cli: bool,
+    /// Metadata document marking versions deprecated or end-of-life, the one published with
+    /// suiup if not set. An empty string disables the warnings.
+    pub deprecations_url: Option<String>,
+    /// How `install --nightly` builds each binary, keyed by binary name, so that custom builds
+    /// are repeatable. The `install` flags take precedence.
+    pub nightly_builds: BTreeMap<String, NightlyBuildConfig>,
+    /// How downloads and GitHub API calls are retried after a transient network failure
+    pub retry: RetryConfig,
+    /// Answer yes to every prompt, as if `--yes` was passed to every command, e.g. on servers
+    /// that must never wait for input. `--ask` asks again for one command.
+    pub assume_yes: bool,
+    /// Size the release archives in the cache may take, with a `K`, `M` or `G` suffix, e.g.
+    /// `5G`. The oldest archives of versions that are not installed are removed before a
+    /// download that would exceed it.
+    pub max_cache_size: Option<String>,
+    /// Binaries skipped by `update --all`, e.g. `["sui"]` when sui is pinned by hand but walrus
+    /// and mvr are kept up to date
+    pub update_exclude: Vec<String>,
+}
+
+/// Retries of requests failing with a transient error, e.g. a timeout or a 503 response. In an
+/// interactive terminal, suiup asks whether to retry instead.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// Attempts in total, 1 disables the retries
+    pub attempts: u32,
+    /// Wait before the first retry, doubled before each of the next ones
+    pub delay_ms: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        RetryConfig {
+            attempts: 3,
+            delay_ms: 1000,
+        }
+    }
+}
+
+/// Build settings of a binary installed from a branch
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct NightlyBuildConfig {
+    /// Extra arguments of `cargo install`, split on whitespace, e.g. `--features tracing`
+    pub cargo_args: Option<String>,
+    /// `RUSTFLAGS` of the build, e.g. `-C target-cpu=native`
+    pub rustflags: Option<String>,
+    /// Cargo profile to build with instead of `release`
+    pub profile: Option<String>,
+    /// Build in a Docker container instead of with the local Rust toolchain
+    pub in_docker: Option<bool>,
+    /// Image of the container building with `in_docker`, a pinned Rust image if not set
+    pub docker_image: Option<String>,
+}
+
+/// TLS settings for all HTTPS connections
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct TlsConfig {
+    /// Minimum TLS version to accept. Versions older than TLS 1.2 are never accepted.
+    pub min_version: TlsVersion,
+    /// PEM file with additional CA certificates to trust, e.g. the one of a corporate proxy
+    pub ca_bundle: Option<PathBuf>,
+    /// SHA-256 fingerprints of the certificates accepted for a host, e.g. a private mirror.
+    /// A connection to a pinned host fails unless one of the certificates it presents matches.
+    pub pinned_certificates: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TlsVersion {
+    #[default]
+    #[serde(rename = "1.2")]
+    Tls12,
+    #[serde(rename = "1.3")]
+    Tls13,
+}
+
+/// Download location of a binary, as a URL template or the base URL of a mirror
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct SourceConfig {
+    /// URL of the archive (`.tgz`/`.tar.gz`) or binary to download. The placeholders
+    /// `{binary}`, `{network}`, `{version}` (e.g. `v1.40.1`), `{version_number}` (e.g. `1.40.1`),
+    /// `{os}` and `{arch}` (as in `--target`) and `{asset}` (the name of the GitHub release
+    /// asset) are replaced. With a `layout` other than `template`, the base URL of the mirror.
+    pub url: String,
+    /// How release assets map to URLs under `url`
+    #[serde(default)]
+    pub layout: SourceLayout,
+    /// Headers sent with the download, e.g. an auth token. `${VAR}` in a value is replaced with
+    /// the environment variable `VAR`, so that secrets do not need to be stored in the file.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Command giving the login and password of the source like a Git credential helper, e.g.
+    /// `git credential-store`. Without it, and without an `Authorization` header, they are
+    /// read from the `.netrc` file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credential_helper: Option<String>,
+}
+
+/// URL layout of a download source
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceLayout {
+    /// `url` is the full URL, with placeholders
+    #[default]
+    Template,
+    /// `url` proxies `https://github.com`, e.g. an Artifactory generic remote repository or a
+    /// Nexus raw proxy repository: assets are at
+    /// `{url}/MystenLabs/sui/releases/download/testnet-v1.40.1/sui-testnet-v1.40.1-ubuntu-x86_64.tgz`
+    Github,
+    /// `url` is a directory with the GitHub release assets uploaded as is, e.g. a generic
+    /// hosted repository: assets are at `{url}/sui-testnet-v1.40.1-ubuntu-x86_64.tgz`
+    Flat,
+}
+
+impl SuiupConfig {
+    /// Bytes the release archives in the cache may take, from `max_cache_size`
+    pub fn max_cache_size(&self) -> Option<u64> {
+        self.max_cache_size.as_deref().and_then(http::parse_size)
+    }
+
+    /// Asset name pattern configured for the binaries released in `repo`
+    pub fn asset_pattern(&self, repo: Repo) -> Option<&str> {
+        self.asset_patterns
+            .get(repo.binary_name())
+            .map(String::as_str)
+    }
+
+    /// Repository configured for the binaries released in `repo`, as `owner/name`
+    pub fn repository(&self, repo: Repo) -> Option<&str> {
+        self.repositories
+            .get(repo.binary_name())
+            .map(String::as_str)
+    }
+
+    /// Checks the values that cannot be checked when deserializing
+    fn validate(&self) -> Result<(), Error> {
+        let released =
+            [Repo::Sui, Repo::Walrus, Repo::WalrusSites, Repo::Mvr].map(|r| r.binary_name());
+        for (binary, pattern) in &self.asset_patterns {
+            if !released.contains(&binary.as_str()) {
+                bail!(
+                    "unknown binary `{binary}` in `asset_patterns`, use one of {}",
+                    released.join(", ")
+                );
+            }
+            check_asset_pattern(pattern)?;
+        }
+        if let Some(base) = &self.github_api_base {
+            if !base.starts_with("https://") && !base.starts_with("http://") {
+                bail!("invalid `github_api_base` `{base}`, use a URL like `https://github.example.com/api/v3`");
+            }
+        }
+        for (binary, repository) in &self.repositories {
+            if !released.contains(&binary.as_str()) {
+                bail!(
+                    "unknown binary `{binary}` in `repositories`, use one of {}",
+                    released.join(", ")
+                );
+            }
+            let valid = repository.split_once('/').is_some_and(|(owner, name)| {
+                [owner, name].iter().all(|part| {
+                    !part.is_empty()
+                        && part
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+                })
+            });
+            if !valid {
+                bail!("invalid repository `{repository}` for `{binary}` in `repositories`, use `owner/name`");
+            }
+        }
+        if let Some(size) = &self.max_cache_size {
+            if http::parse_size(size).is_none() {
+                bail!("invalid `max_cache_size` `{size}`, use a size like `5G` or `500M`");
+            }
+        }
+        for binary in &self.update_exclude {
+            if BinaryName::from_str(binary, false).is_err() {
+                bail!(
+                    "unknown binary `{binary}` in `update_exclude`.{}",
+                    did_you_mean(
+                        binary,
+                        BinaryName::value_variants().iter().map(|b| b.to_str())
+                    )
+                );
+            }
+        }
+        for binary in self.nightly_builds.keys() {
+            if BinaryName::from_str(binary, false).is_err() {
+                bail!(
+                    "unknown binary `{binary}` in `nightly_builds`.{}",
+                    did_you_mean(
+                        binary,
+                        BinaryName::value_variants().iter().map(|b| b.to_str())
+                    )
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SourceConfig {
+    /// Returns the download URL of a binary release of `repo`. `asset_pattern` names the release
+    /// asset instead of the MystenLabs naming.
+    pub fn url_for(
+        &self,
+        binary: &str,
+        repo: Repo,
+        network: &str,
+        version: &str,
+        target: &Target,
+        asset_pattern: Option<&str>,
+    ) -> Result<String, Error> {
+        let version = format!("v{}", version.trim_start_matches('v'));
+        let asset = match asset_pattern {
+            Some(pattern) => target.render_asset_pattern(pattern, network, &version)?,
+            None => release_asset_name(repo, network, &version, target),
+        };
+        let base = self.url.trim_end_matches('/');
+        Ok(match self.layout {
+            SourceLayout::Template => self
+                .url
+                .replace("{binary}", binary)
+                .replace("{network}", network)
+                .replace("{version_number}", &version[1..])
+                .replace("{version}", &version)
+                .replace("{os}", target.os.name())
+                .replace("{arch}", target.arch.name())
+                .replace("{asset}", &asset),
+            SourceLayout::Github => {
+                let tag = if repo == Repo::Mvr {
+                    version
+                } else {
+                    format!("{network}-{version}")
+                };
+                format!(
+                    "{base}/{}/releases/download/{tag}/{asset}",
+                    repo.repository()?
+                )
+            }
+            SourceLayout::Flat => format!("{base}/{asset}"),
+        })
+    }
+
+    /// Returns the headers to download `url` with, with environment variables expanded. Unless
+    /// an `Authorization` header is configured, the credentials of the credential helper or of
+    /// the `.netrc` file are added.
+    pub fn resolved_headers(&self, url: &str) -> Result<Vec<(String, String)>, Error> {
+        let mut headers = self
+            .headers
+            .iter()
+            .map(|(name, value)| Ok((name.clone(), expand_env(value)?)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let has_authorization = headers
+            .iter()
+            .any(|(name, _)| name.eq_ignore_ascii_case("authorization"));
+        if !has_authorization {
+            if let Some(credentials) = credentials::lookup(url, self.credential_helper.as_deref())?
+            {
+                headers.push(("Authorization".to_string(), credentials.basic_auth()));
+            }
+        }
+        Ok(headers)
+    }
+}
+
+/// Name of the asset of a MystenLabs release, e.g. `sui-testnet-v1.40.1-ubuntu-x86_64.tgz`. Mvr
+/// releases publish the binaries themselves, e.g. `mvr-macos-arm64`.
+fn release_asset_name(repo: Repo, network: &str, version: &str, target: &Target) -> String {
+    let platform = target.release_asset_platform();
+    let binary = repo.binary_name();
+    match repo {
+        Repo::Mvr if target.os == Os::Windows => format!("{binary}-{platform}.exe"),
+        Repo::Mvr => format!("{binary}-{platform}"),
+        _ => format!("{binary}-{network}-{version}-{platform}.tgz"),
+    }
+}
+
+/// Replaces `${VAR}` with the value of the environment variable `VAR`
+fn expand_env(value: &str) -> Result<String, Error> {
+    let mut expanded = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| anyhow!("Unterminated `${{` in header value"))?;
+        let var = &rest[start + 2..start + end];
+        let var_value = std::env::var(var).map_err(|_| {
+            anyhow!("Environment variable {var} used in the config file is not set")
+        })?;
+        expanded.push_str(&rest[..start]);
+        expanded.push_str(&var_value);
+        rest = &rest[start + end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+impl TlsConfig {
+    /// Whether any setting differs from the defaults
+    pub fn is_customized(&self) -> bool {
+        self != &TlsConfig::default()
+    }
+}
+
+/// Reads the settings from `path` instead of the config directory (`--config`)
+pub fn set_config_override(path: Option<PathBuf>) {
+    if let Ok(mut guard) = CONFIG_OVERRIDE.write() {
+        *guard = path;
+    }
+}
+
+fn config_override() -> Option<PathBuf> {
+    CONFIG_OVERRIDE.read().ok().and_then(|path| path.clone())
+}
+
+/// Returns the path to the config file: the `--config` one, or `config.json` in the config
+/// directory
+pub fn config_file() -> PathBuf {
+    config_override().unwrap_or_else(|| get_config_file(CONFIG_FILE))
+}
+
+/// Loads the config file. A missing file means all the defaults, unless it was selected with
+/// `--config`. Files ending in `.toml` are read as TOML, others as JSON.
+///
+/// The file is read the first time it is needed and then served from memory for the rest of the
+/// command. If the config file path changes (e.g., tests pointing `XDG_CONFIG_HOME` somewhere
+/// else), it is read again. An invalid file is not kept, so that each caller reports it.
+pub fn load_config() -> Result<SuiupConfig, Error> {
+    let path = config_file();
+    let mut loaded = LOADED_CONFIG
+        .lock()
+        .map_err(|_| anyhow!("The suiup config lock is poisoned"))?;
+    if let Some((loaded_path, config)) = loaded.as_ref() {
+        if *loaded_path == path {
+            return Ok(config.clone());
+        }
+    }
+    let config = if path.exists() {
+        load_config_from(&path)?
+    } else if config_override().is_some() {
+        bail!("The config file {} does not exist", path.display());
+    } else {
+        SuiupConfig::default()
+    };
+    *loaded = Some((path, config.clone()));
+    Ok(config)
+}
+
+/// Drops the config loaded by [`load_config`], after the config file was written
+pub fn invalidate_config() {
+    if let Ok(mut loaded) = LOADED_CONFIG.lock() {
+        *loaded = None;
+    }
+}
+
+/// Loads the config file at `path`, as TOML if it ends in `.toml` and as JSON otherwise
+pub(crate) fn load_config_from(path: &Path) -> Result<SuiupConfig, Error> {
+    let s = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Cannot read the config file {}: {e}", path.display()))?;
+    let invalid =
+        |e: &dyn std::fmt::Display| anyhow!("Invalid config file {}: {e}", path.display());
+    let value: Value = if path.extension().is_some_and(|ext| ext == "toml") {
+        toml::from_str(&s).map_err(|e| invalid(&e))?
+    } else {
+        serde_json::from_str(&s).map_err(|e| invalid(&e))?
+    };
+    check_unknown_keys(&value).map_err(|e| invalid(&e))?;
+    let config: SuiupConfig = serde_json::from_value(value).map_err(|e| invalid(&e))?;
+    config.validate().map_err(|e| invalid(&e))?;
+    Ok(config)
+}
+
+/// Offers to repair an invalid config file instead of failing: the valid settings are kept, the
+/// invalid ones are reset to their defaults and the broken file is backed up next to it
+pub fn offer_repair() -> Result<(), Error> {
+    let Err(err) = load_config() else {
+        return Ok(());
+    };
+    let path = config_file();
+    // A missing or unreadable file is reported by the commands
+    let Ok(s) = std::fs::read_to_string(&path) else {
+        return Ok(());
+    };
+    let toml = path.extension().is_some_and(|ext| ext == "toml");
+    let parsed = if toml {
+        toml::from_str::<Value>(&s).ok()
+    } else {
+        serde_json::from_str::<Value>(&s).ok()
+    };
+
+    let (repaired, reset) = match parsed.as_ref().and_then(Value::as_object) {
+        Some(settings) => repair(settings),
+        None => (serde_json::Map::new(), Vec::new()),
+    };
+    eprintln!("{} {err}", output::warning_sign());
+    if parsed.as_ref().is_none_or(|value| !value.is_object()) {
+        eprintln!("The file cannot be parsed, repairing it resets all the settings.");
+    } else if reset.is_empty() {
+        return Ok(());
+    } else {
+        eprintln!(
+            "Repairing it keeps the other settings and resets {}.",
+            reset
+                .iter()
+                .map(|key| format!("`{key}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let backup = path.with_file_name(format!(
+        "{file_name}.{}.bak",
+        chrono::Utc::now().format("%Y%m%d%H%M%S")
+    ));
+    let confirmed = Confirm::new()
+        .with_prompt(format!(
+            "Repair {} (the broken file is kept as {})?",
+            path.display(),
+            backup.display()
+        ))
+        .default(true)
+        .interact()?;
+    if !confirmed {
+        return Ok(());
+    }
+
+    let repaired = Value::Object(repaired);
+    let contents = if toml {
+        toml::to_string_pretty(&repaired)?
+    } else {
+        serde_json::to_string_pretty(&repaired)?
+    };
+    std::fs::copy(&path, &backup).map_err(|e| {
+        anyhow!(
+            "Cannot back up the config file to {}: {e}",
+            backup.display()
+        )
+    })?;
+    write_file_atomically(&path, contents.as_bytes())
+        .map_err(|e| anyhow!("Cannot write the config file {}: {e}", path.display()))?;
+    invalidate_config();
+    status!(
+        "Config file repaired, the broken file is kept as {}",
+        backup.display()
+    );
+    Ok(())
+}
+
+/// Splits the settings of a config file into the valid ones and the names of the invalid ones.
+/// Settings made of several keys, like `tls` or `sources`, keep their valid keys.
+fn repair(
+    settings: &serde_json::Map<String, Value>,
+) -> (serde_json::Map<String, Value>, Vec<String>) {
+    let mut valid = serde_json::Map::new();
+    let mut reset = Vec::new();
+    for (key, value) in settings {
+        if is_valid_setting(&json!({ key: value })) {
+            valid.insert(key.clone(), value.clone());
+            continue;
+        }
+        let Some(entries) = value.as_object().filter(|entries| !entries.is_empty()) else {
+            reset.push(key.clone());
+            continue;
+        };
+        let mut kept = serde_json::Map::new();
+        for (entry, value) in entries {
+            if is_valid_setting(&json!({ key: { entry: value } })) {
+                kept.insert(entry.clone(), value.clone());
+            } else {
+                reset.push(format!("{key}.{entry}"));
+            }
+        }
+        if !kept.is_empty() {
+            valid.insert(key.clone(), Value::Object(kept));
+        }
+    }
+    (valid, reset)
+}
+
+fn is_valid_setting(value: &Value) -> bool {
+    check_unknown_keys(value).is_ok()
+        && serde_json::from_value::<SuiupConfig>(value.clone())
+            .is_ok_and(|config| config.validate().is_ok())
+}
+
+/// Rejects keys that are not settings, so that a misspelled key is not silently ignored
+fn check_unknown_keys(value: &Value) -> Result<(), Error> {
+    let defaults = serde_json::to_value(SuiupConfig::default())?;
+    check_keys(value, &defaults, "")?;
+    // `sources` has no default entries, each one is checked against a source with all its keys
+    let Some(sources) = value.get("sources").and_then(Value::as_object) else {
+        return Ok(());
+    };
+    let source = serde_json::to_value(SourceConfig {
+        url: String::new(),
+        layout: SourceLayout::default(),
+        headers: BTreeMap::new(),
+        credential_helper: Some(String::new()),
+    })?;
+    for (binary, value) in sources {
+        check_keys(value, &source, &format!("sources.{binary}."))?;
+    }
+    Ok(())
+}
+
+fn check_keys(value: &Value, defaults: &Value, prefix: &str) -> Result<(), Error> {
+    let (Some(object), Some(known)) = (value.as_object(), defaults.as_object()) else {
+        return Ok(());
+    };
+    // Maps like `pinned_certificates` have no default keys, any key is valid there
+    if known.is_empty() {
+        return Ok(());
+    }
+    for (key, value) in object {
+        match known.get(key) {
+            Some(defaults) => check_keys(value, defaults, &format!("{prefix}{key}."))?,
+            None => {
+                return Err(anyhow!(
+                    "unknown key `{prefix}{key}`.{}",
+                    did_you_mean(key, known.keys().map(String::as_str))
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config: SuiupConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(config, SuiupConfig::default());
+        assert!(!config.tls.is_customized());
+        assert!(!config.assume_yes);
+
+        let config: SuiupConfig = serde_json::from_str(r#"{ "assume_yes": true }"#).unwrap();
+        assert!(config.assume_yes);
+
+        let config: SuiupConfig = serde_json::from_str(
+            r#"{
+                "tls": {
+                    "min_version": "1.3",
+                    "ca_bundle": "/etc/ssl/corp.pem",
+                    "pinned_certificates": { "mirror.example.com": ["ab:cd"] }
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(config.tls.min_version, TlsVersion::Tls13);
+        assert_eq!(
+            config.tls.ca_bundle,
+            Some(PathBuf::from("/etc/ssl/corp.pem"))
+        );
+        assert_eq!(
+            config.tls.pinned_certificates["mirror.example.com"],
+            ["ab:cd"]
+        );
+
+        // `--config` files can also be TOML
+        let value: Value = toml::from_str(
+            r#"
+            [tls]
+            min_version = "1.3"
+            "#,
+        )
+        .unwrap();
+        check_unknown_keys(&value).unwrap();
+        let config: SuiupConfig = serde_json::from_value(value).unwrap();
+        assert_eq!(config.tls.min_version, TlsVersion::Tls13);
+        assert!(config.tls.is_customized());
+    }
+
+    #[test]
+    fn test_nightly_builds() {
+        let config: SuiupConfig = serde_json::from_str(
+            r#"{"nightly_builds": {"sui": {"cargo_args": "--features tracing", "profile": "dev", "in_docker": true}}}"#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+        let build = &config.nightly_builds["sui"];
+        assert_eq!(build.cargo_args.as_deref(), Some("--features tracing"));
+        assert_eq!(build.rustflags, None);
+        assert_eq!(build.profile.as_deref(), Some("dev"));
+        assert_eq!(build.in_docker, Some(true));
+        assert_eq!(build.docker_image, None);
+
+        let config: SuiupConfig =
+            serde_json::from_str(r#"{"nightly_builds": {"suii": {}}}"#).unwrap();
+        assert!(config.validate().is_err());
+        assert!(serde_json::from_str::<SuiupConfig>(
+            r#"{"nightly_builds": {"sui": {"features": "tracing"}}}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_source_url() {
+        let source: SourceConfig = serde_json::from_str(
+            r#"{
+                "url": "https://artifacts.example.com/{binary}/{network}-{version}/{binary}-{version_number}-{os}-{arch}.tgz",
+                "headers": { "Authorization": "Bearer ${SUIUP_TEST_SOURCE_TOKEN}" }
+            }"#,
+        )
+        .unwrap();
+        let target = "linux-arm64".parse().unwrap();
+        assert_eq!(
+            source
+                .url_for("sui", Repo::Sui, "testnet", "1.40.1", &target, None)
+                .unwrap(),
+            "https://artifacts.example.com/sui/testnet-v1.40.1/sui-1.40.1-linux-aarch64.tgz"
+        );
+
+        let mirror: SourceConfig = serde_json::from_str(
+            r#"{"url": "https://artifactory.example.com/artifactory/github/", "layout": "github"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            mirror.url_for("sui", Repo::Sui, "testnet", "1.40.1", &target, None).unwrap(),
+            "https://artifactory.example.com/artifactory/github/MystenLabs/sui/releases/download/testnet-v1.40.1/sui-testnet-v1.40.1-ubuntu-aarch64.tgz"
+        );
+        assert_eq!(
+            mirror.url_for("mvr", Repo::Mvr, "standalone", "0.0.8", &"macos-arm64".parse().unwrap(), None).unwrap(),
+            "https://artifactory.example.com/artifactory/github/MystenLabs/mvr/releases/download/v0.0.8/mvr-macos-arm64"
+        );
+        let flat = SourceConfig {
+            layout: SourceLayout::Flat,
+            ..mirror
+        };
+        assert_eq!(
+            flat.url_for("walrus", Repo::Walrus, "mainnet", "v1.20.0", &"linux-x86_64".parse().unwrap(), None).unwrap(),
+            "https://artifactory.example.com/artifactory/github/walrus-mainnet-v1.20.0-ubuntu-x86_64.tgz"
+        );
+        let pattern = Some("walrus-{version}-{os}-{arch}.tar.gz");
+        assert_eq!(
+            flat.url_for(
+                "walrus",
+                Repo::Walrus,
+                "mainnet",
+                "1.20.0",
+                &"linux-x86_64".parse().unwrap(),
+                pattern
+            )
+            .unwrap(),
+            "https://artifactory.example.com/artifactory/github/walrus-1.20.0-ubuntu-x86_64.tar.gz"
+        );
+
+        // With an `Authorization` header, the credentials are not looked up, e.g. in the `.netrc`
+        // file of the machine running the test
+        let url = "https://gitlab.example.com/sui.tgz";
+        assert!(source.resolved_headers(url).is_err());
+        std::env::set_var("SUIUP_TEST_SOURCE_TOKEN", "secret");
+        assert_eq!(
+            source.resolved_headers(url).unwrap(),
+            [("Authorization".to_string(), "Bearer secret".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_validate_asset_patterns() {
+        let config = |binary: &str, pattern: &str| SuiupConfig {
+            asset_patterns: BTreeMap::from([(binary.to_string(), pattern.to_string())]),
+            ..Default::default()
+        };
+        assert!(config("sui", "sui-{network}-v{version}-{os}-{arch}.tgz")
+            .validate()
+            .is_ok());
+        assert!(config("sui-node", "sui-{version}.tgz").validate().is_err());
+        assert!(config("sui", "sui-{tag}.tgz").validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_repositories() {
+        let config = |binary: &str, repository: &str| SuiupConfig {
+            repositories: BTreeMap::from([(binary.to_string(), repository.to_string())]),
+            ..Default::default()
+        };
+        assert!(config("sui", "acme/sui").validate().is_ok());
+        assert_eq!(
+            config("walrus", "acme/walrus-fork").repository(Repo::Walrus),
+            Some("acme/walrus-fork")
+        );
+        assert_eq!(config("sui", "acme/sui").repository(Repo::Mvr), None);
+        assert!(config("sui-node", "acme/sui").validate().is_err());
+        assert!(config("sui", "acme").validate().is_err());
+        assert!(config("sui", "acme/sui/releases").validate().is_err());
+        assert!(config("sui", "https://github.com/acme/sui")
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_max_cache_size() {
+        let config: SuiupConfig = serde_json::from_str(r#"{"max_cache_size": "5G"}"#).unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.max_cache_size(), Some(5 * 1024 * 1024 * 1024));
+        assert_eq!(SuiupConfig::default().max_cache_size(), None);
+        let config: SuiupConfig = serde_json::from_str(r#"{"max_cache_size": "5GB"}"#).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_update_exclude() {
+        let config: SuiupConfig =
+            serde_json::from_str(r#"{"update_exclude": ["sui", "move-analyzer"]}"#).unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.update_exclude, ["sui", "move-analyzer"]);
+        let config: SuiupConfig = serde_json::from_str(r#"{"update_exclude": ["suii"]}"#).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_github_api_base() {
+        let config = |base: &str| SuiupConfig {
+            github_api_base: Some(base.to_string()),
+            ..Default::default()
+        };
+        assert!(config("https://github.example.com/api/v3")
+            .validate()
+            .is_ok());
+        assert!(config("github.example.com").validate().is_err());
+    }
+
+    #[test]
+    fn test_unknown_keys() {
+        let check = |s: &str| check_unknown_keys(&serde_json::from_str(s).unwrap());
+        assert!(check(r#"{"tls": {"pinned_certificates": {"any.host": []}}}"#).is_ok());
+
+        let err = check(r#"{"tls": {"min_versio": "1.3"}}"#).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unknown key `tls.min_versio`. Did you mean `min_version`?"
+        );
+        let err = check(r#"{"tsl": {}}"#).unwrap_err();
+        assert_eq!(err.to_string(), "unknown key `tsl`. Did you mean `tls`?");
+
+        let source = r#"{"sources": {"sui": {"url": "https://mirror.example.com", "headrs": {}}}}"#;
+        let err = check(source).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unknown key `sources.sui.headrs`. Did you mean `headers`?"
+        );
+        assert!(serde_json::from_str::<SuiupConfig>(source).is_err());
+    }
+
+    #[test]
+    fn test_repair() {
+        let settings = json!({
+            "download_concurrency": "four",
+            "retry": { "attempts": 5, "delay_ms": "soon" },
+            "asset_patterns": { "sui": "sui-{network}-v{version}-{os}-{arch}.tgz", "suii": "x" },
+            "deprecations_url": "",
+            "unknown": true
+        });
+        let (valid, reset) = repair(settings.as_object().unwrap());
+        assert_eq!(
+            Value::Object(valid),
+            json!({
+                "retry": { "attempts": 5 },
+                "asset_patterns": { "sui": "sui-{network}-v{version}-{os}-{arch}.tgz" },
+                "deprecations_url": ""
+            })
+        );
+        assert_eq!(
+            reset,
+            [
+                "asset_patterns.suii",
+                "download_concurrency",
+                "retry.delay_ms",
+                "unknown"
+            ]
+        );
+    }
+}
@@ -0,0 +1,223 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Credentials of the download sources that are not written in the config file: given by the
+//! Git-style credential helper configured for a source, or read from the `.netrc` file.
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use anyhow::{anyhow, bail, Error};
+use base64::Engine;
+use reqwest::Url;
+
+use crate::redact::register_secret;
+
+/// A login and password for a host
+#[derive(Debug, PartialEq, Eq)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+impl Credentials {
+    /// Value of the `Authorization` header sending these credentials
+    pub fn basic_auth(&self) -> String {
+        let credentials = format!("{}:{}", self.username, self.password);
+        format!(
+            "Basic {}",
+            base64::engine::general_purpose::STANDARD.encode(credentials)
+        )
+    }
+}
+
+/// Credentials for the host of `url`, from the credential `helper` if one is configured and
+/// knows the host, else from the `.netrc` file. The password is redacted from the output.
+pub fn lookup(url: &str, helper: Option<&str>) -> Result<Option<Credentials>, Error> {
+    let url = Url::parse(url).map_err(|e| anyhow!("Invalid URL {url}: {e}"))?;
+    let Some(host) = url.host_str() else {
+        return Ok(None);
+    };
+    let mut credentials = match helper {
+        Some(helper) => from_helper(helper, &url)?,
+        None => None,
+    };
+    if credentials.is_none() {
+        credentials = netrc_file()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|netrc| netrc_lookup(&netrc, host));
+    }
+    if let Some(credentials) = &credentials {
+        register_secret(&credentials.password);
+        register_secret(&credentials.basic_auth());
+    }
+    Ok(credentials)
+}
+
+/// Asks a credential helper for the credentials of `url`, like Git does: `{helper} get` is run
+/// by the shell, gets `protocol=` and `host=` lines on stdin and prints `username=` and
+/// `password=` lines. Git's helpers can be used, e.g. `git credential-store`.
+fn from_helper(helper: &str, url: &Url) -> Result<Option<Credentials>, Error> {
+    let command_line = format!("{helper} get");
+    #[cfg(not(windows))]
+    let mut command = {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(&command_line);
+        command
+    };
+    #[cfg(windows)]
+    let mut command = {
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(&command_line);
+        command
+    };
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| anyhow!("Cannot run the credential helper `{helper}`: {e}"))?;
+
+    let host = match url.port() {
+        Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+        None => url.host_str().unwrap_or_default().to_string(),
+    };
+    let request = format!("protocol={}\nhost={host}\n\n", url.scheme());
+    if let Some(mut stdin) = child.stdin.take() {
+        // A helper that exits without reading its input is not an error
+        let _ = stdin.write_all(request.as_bytes());
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| anyhow!("Cannot run the credential helper `{helper}`: {e}"))?;
+    if !output.status.success() {
+        bail!(
+            "The credential helper `{helper}` failed for {host} ({})",
+            output.status
+        );
+    }
+    Ok(parse_helper_output(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+/// Reads the `username` and `password` lines a credential helper printed, `None` without a
+/// password
+fn parse_helper_output(output: &str) -> Option<Credentials> {
+    let mut username = String::new();
+    let mut password = None;
+    for line in output.lines().take_while(|line| !line.is_empty()) {
+        match line.split_once('=') {
+            Some(("username", value)) => username = value.to_string(),
+            Some(("password", value)) => password = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    Some(Credentials {
+        username,
+        password: password?,
+    })
+}
+
+/// `$NETRC`, or `.netrc` (`_netrc` on Windows) in the home folder
+fn netrc_file() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("NETRC") {
+        return Some(PathBuf::from(path));
+    }
+    let home = dirs::home_dir()?;
+    #[cfg(windows)]
+    if !home.join(".netrc").exists() {
+        return Some(home.join("_netrc"));
+    }
+    Some(home.join(".netrc"))
+}
+
+/// An entry of a `.netrc` file, `machine` being `None` for the `default` entry
+struct NetrcEntry<'a> {
+    machine: Option<&'a str>,
+    login: Option<&'a str>,
+    password: Option<&'a str>,
+}
+
+/// Finds the `login` and `password` of the `machine` entry for `host` in a `.netrc` file, or of
+/// the `default` entry
+fn netrc_lookup(netrc: &str, host: &str) -> Option<Credentials> {
+    let mut tokens = Vec::new();
+    // Macros run by ftp, from `macdef` to the next empty line
+    let mut in_macro = false;
+    for line in netrc.lines() {
+        if in_macro {
+            in_macro = !line.trim().is_empty();
+            continue;
+        }
+        for word in line.split_whitespace() {
+            if word == "macdef" {
+                in_macro = true;
+                break;
+            }
+            tokens.push(word);
+        }
+    }
+
+    let mut entries = Vec::<NetrcEntry>::new();
+    let mut tokens = tokens.into_iter();
+    while let Some(token) = tokens.next() {
+        match (token, entries.last_mut()) {
+            ("machine", _) => entries.push(NetrcEntry {
+                machine: Some(tokens.next().unwrap_or_default()),
+                login: None,
+                password: None,
+            }),
+            ("default", _) => entries.push(NetrcEntry {
+                machine: None,
+                login: None,
+                password: None,
+            }),
+            ("login", Some(entry)) => entry.login = tokens.next(),
+            ("password", Some(entry)) => entry.password = tokens.next(),
+            _ => {}
+        }
+    }
+    let entry = entries
+        .iter()
+        .find(|entry| entry.machine == Some(host))
+        .or_else(|| entries.iter().find(|entry| entry.machine.is_none()))?;
+    Some(Credentials {
+        username: entry.login.unwrap_or_default().to_string(),
+        password: entry.password?.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_netrc_lookup() {
+        let netrc = "machine github.com login octocat password ghp_token\n\
+            machine artifactory.example.com\n  login ci\n  password s3cret\n\
+            macdef init\ncd /pub\nmachine macro.example.com password no\n\n\
+            default login anonymous password guest\n";
+        let credentials = netrc_lookup(netrc, "artifactory.example.com").unwrap();
+        assert_eq!(credentials.username, "ci");
+        assert_eq!(credentials.password, "s3cret");
+        assert_eq!(credentials.basic_auth(), "Basic Y2k6czNjcmV0");
+        assert_eq!(
+            netrc_lookup(netrc, "macro.example.com").unwrap().password,
+            "guest"
+        );
+        assert!(netrc_lookup("machine a.example.com login ci", "a.example.com").is_none());
+    }
+
+    #[test]
+    fn test_parse_helper_output() {
+        let credentials = parse_helper_output(
+            "protocol=https\nhost=mirror.example.com\nusername=ci\npassword=s3cret\n\nignored=1",
+        )
+        .unwrap();
+        assert_eq!(credentials.username, "ci");
+        assert_eq!(credentials.password, "s3cret");
+        assert!(parse_helper_output("username=ci\n").is_none());
+    }
+}
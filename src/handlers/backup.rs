@@ -0,0 +1,541 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! `suiup backup`: a tar archive of the config file, the installed binaries and default versions
+//! files, and optionally the binaries folder, described by a `backup.json` entry.
+
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Error};
+use chrono::{DateTime, Utc};
+use dialoguer::Confirm;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::{config_file, invalidate_config, load_config_from};
+use crate::handlers::enable::remove_from_bin_dir;
+use crate::handlers::switch::{get_binary_source_path, switch_to_binary};
+use crate::interrupt::Partial;
+use crate::paths::{
+    binaries_dir, get_state_file, get_suiup_data_dir, DEFAULT_VERSIONS_FILE,
+    DISABLED_BINARIES_FILE, INSTALLED_BINARIES_FILE,
+};
+use crate::reflink;
+use crate::state::{self, migrations, read_state_file_readonly, write_file_atomically};
+use crate::status;
+use crate::types::{Binaries, BinaryVersion, InstalledBinaries};
+
+const INFO_ENTRY: &str = "backup.json";
+const CONFIG_ENTRY: &str = "config";
+const STATE_ENTRY: &str = "state";
+const BINARIES_ENTRY: &str = "binaries";
+/// Folder of the unpacked backup where the files it replaces are saved until it is restored
+const REPLACED_DIR: &str = "replaced";
+/// Names of the config file in a backup, whatever its name on the machine it was made on
+const CONFIG_FILE_NAMES: [&str; 2] = ["config.json", "config.toml"];
+/// The state files saved in a backup, under [`STATE_ENTRY`]
+const STATE_FILES: [&str; 3] = [
+    INSTALLED_BINARIES_FILE,
+    DEFAULT_VERSIONS_FILE,
+    DISABLED_BINARIES_FILE,
+];
+
+/// Contents of the `backup.json` entry
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct BackupInfo {
+    suiup_version: String,
+    created_at: DateTime<Utc>,
+    /// Name of the config file, one of [`CONFIG_FILE_NAMES`], whose extension tells its format.
+    /// None without a config file.
+    config_file: Option<String>,
+    /// Whether the binaries folder is in the archive
+    binaries: bool,
+}
+
+/// Compression of the archive, from the extension of its file
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Format {
+    Zstd,
+    Gzip,
+    Tar,
+}
+
+impl Format {
+    fn of(path: &Path) -> Format {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("zst" | "tzst") => Format::Zstd,
+            Some("gz" | "tgz") => Format::Gzip,
+            _ => Format::Tar,
+        }
+    }
+}
+
+/// Handles `suiup backup create`
+pub fn handle_backup_create(path: &Path, with_binaries: bool) -> Result<(), Error> {
+    // Pending changes of this process belong in the backup
+    state::flush()?;
+
+    let config = config_file();
+    let info = BackupInfo {
+        suiup_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: Utc::now(),
+        config_file: config.exists().then(|| {
+            let toml = config.extension().is_some_and(|ext| ext == "toml");
+            if toml { "config.toml" } else { "config.json" }.to_string()
+        }),
+        binaries: with_binaries,
+    };
+    let mut files = Vec::new();
+    if let Some(name) = &info.config_file {
+        files.push((format!("{CONFIG_ENTRY}/{name}"), config.clone()));
+    }
+    for name in STATE_FILES {
+        let file = get_state_file(name);
+        if file.exists() {
+            files.push((format!("{STATE_ENTRY}/{name}"), file));
+        }
+    }
+    let binaries = with_binaries.then(binaries_dir).filter(|dir| dir.exists());
+
+    let partial = Partial::new(path);
+    write_archive(path, &info, &files, binaries.as_deref())?;
+    partial.complete();
+
+    status!(
+        "Backup written to {}{}",
+        path.display(),
+        if with_binaries {
+            ", with the binaries"
+        } else {
+            ""
+        }
+    );
+    Ok(())
+}
+
+/// Handles `suiup backup restore`
+pub fn handle_backup_restore(path: &Path, yes: bool) -> Result<(), Error> {
+    let dir = tempfile::tempdir_in(get_suiup_data_dir())
+        .map_err(|e| anyhow!("Cannot create a folder to unpack the backup: {e}"))?;
+    let info = unpack_archive(path, dir.path())?;
+
+    let replaced = if info.binaries {
+        "config, installed binaries, default versions and disabled binaries, and adds the binaries of the backup"
+    } else {
+        "config, installed binaries, default versions and disabled binaries"
+    };
+    println!(
+        "Backup made on {} by suiup {}. Restoring it replaces the {replaced}.",
+        info.created_at.format("%Y-%m-%d %H:%M:%S UTC"),
+        info.suiup_version
+    );
+    if !yes
+        && !Confirm::new()
+            .with_prompt("Restore the backup?")
+            .default(false)
+            .interact()?
+    {
+        println!("Restore cancelled.");
+        return Ok(());
+    }
+
+    check_backup(&info, dir.path())?;
+    let previous = Binaries::from(state::default_versions()?).binaries;
+    // Save the pending changes before they are replaced, and read the restored state afterwards
+    state::invalidate()?;
+    let saved = save_replaced(dir.path())?;
+    let missing = match restore(&info, dir.path()) {
+        Ok(missing) => missing,
+        Err(e) => {
+            return Err(match put_back(&saved, &previous) {
+                Ok(()) => anyhow!("Cannot restore the backup, the previous files were put back: {e}"),
+                Err(put_back) => anyhow!(
+                    "Cannot restore the backup: {e}. Putting back the previous files failed too: {put_back}"
+                ),
+            });
+        }
+    };
+
+    status!("Backup restored from {}", path.display());
+    if !missing.is_empty() {
+        println!("These binaries are not on this machine, install them again with:");
+        for spec in missing {
+            println!("  suiup install {spec}");
+        }
+    }
+    Ok(())
+}
+
+/// Checks the config and state files of the unpacked backup, so that a broken backup fails before
+/// anything is replaced
+fn check_backup(info: &BackupInfo, dir: &Path) -> Result<(), Error> {
+    let invalid = |e: Error| anyhow!("Not restoring the backup: {e}");
+    if let Some(name) = &info.config_file {
+        load_config_from(&dir.join(CONFIG_ENTRY).join(name)).map_err(invalid)?;
+    }
+    let state = dir.join(STATE_ENTRY);
+    let path = state.join(INSTALLED_BINARIES_FILE);
+    if path.exists() {
+        read_state_file_readonly::<InstalledBinaries>(&path, migrations::INSTALLED_BINARIES)
+            .map_err(invalid)?;
+    }
+    let path = state.join(DEFAULT_VERSIONS_FILE);
+    if path.exists() {
+        state::read_default_versions_readonly(&path).map_err(invalid)?;
+    }
+    let path = state.join(DISABLED_BINARIES_FILE);
+    if path.exists() {
+        state::read_disabled_binaries(&path).map_err(invalid)?;
+    }
+    Ok(())
+}
+
+/// A file the restore replaces, and the copy of it saved aside. No copy if it did not exist.
+struct Replaced {
+    path: PathBuf,
+    saved: Option<PathBuf>,
+}
+
+/// Saves the config and state files aside in the unpacked backup, to put them back if the
+/// restore fails
+fn save_replaced(dir: &Path) -> Result<Vec<Replaced>, Error> {
+    let aside = dir.join(REPLACED_DIR);
+    std::fs::create_dir_all(&aside)
+        .map_err(|e| anyhow!("Cannot create folder {}: {e}", aside.display()))?;
+    std::iter::once((CONFIG_ENTRY, config_file()))
+        .chain(STATE_FILES.map(|name| (name, get_state_file(name))))
+        .map(|(name, path)| {
+            let saved = if path.exists() {
+                let saved = aside.join(name);
+                std::fs::copy(&path, &saved)
+                    .map_err(|e| anyhow!("Cannot save {} aside: {e}", path.display()))?;
+                Some(saved)
+            } else {
+                None
+            };
+            Ok(Replaced { path, saved })
+        })
+        .collect()
+}
+
+/// Puts back the files saved by [`save_replaced`] and the `previous` default versions in the bin
+/// folder
+fn put_back(replaced: &[Replaced], previous: &[BinaryVersion]) -> Result<(), Error> {
+    // Drop the state of the failed restore, it must not be saved over the files put back
+    state::invalidate()?;
+    invalidate_config();
+    for file in replaced {
+        match &file.saved {
+            Some(saved) => {
+                let contents = std::fs::read(saved)
+                    .map_err(|e| anyhow!("Cannot read {}: {e}", saved.display()))?;
+                write_file_atomically(&file.path, &contents)?;
+            }
+            None if file.path.exists() => std::fs::remove_file(&file.path)
+                .map_err(|e| anyhow!("Cannot remove {}: {e}", file.path.display()))?,
+            None => {}
+        }
+    }
+    for binary in previous {
+        if get_binary_source_path(binary).exists() {
+            switch_to_binary(binary)?;
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the config and state files with those of the unpacked backup, adds its binaries and
+/// switches to its default versions. Returns the specs of the installed binaries that are missing.
+fn restore(info: &BackupInfo, dir: &Path) -> Result<Vec<String>, Error> {
+    restore_config(info, dir)?;
+    invalidate_config();
+    for name in STATE_FILES {
+        let backup = dir.join(STATE_ENTRY).join(name);
+        let file = get_state_file(name);
+        if backup.exists() {
+            let contents = std::fs::read(&backup)
+                .map_err(|e| anyhow!("Cannot read {name} from the backup: {e}"))?;
+            write_file_atomically(&file, &contents)?;
+        } else if file.exists() {
+            std::fs::remove_file(&file)
+                .map_err(|e| anyhow!("Cannot remove {}: {e}", file.display()))?;
+        }
+    }
+    if info.binaries {
+        copy_tree(&dir.join(BINARIES_ENTRY), &binaries_dir())?;
+    }
+
+    for binary in Binaries::from(state::default_versions()?).binaries {
+        if get_binary_source_path(&binary).exists() {
+            switch_to_binary(&binary)?;
+        }
+    }
+    for binary in state::disabled_binaries()? {
+        remove_from_bin_dir(&binary)?;
+    }
+    Ok(state::installed_binaries()?
+        .binaries()
+        .iter()
+        .filter(|binary| !get_binary_source_path(binary).exists())
+        .map(install_spec)
+        .collect())
+}
+
+/// Writes the config file of the backup in place of the current one, converting it if one is
+/// JSON and the other TOML. The current file is removed if the backup has none.
+fn restore_config(info: &BackupInfo, dir: &Path) -> Result<(), Error> {
+    let path = config_file();
+    let Some(name) = &info.config_file else {
+        if path.exists() {
+            std::fs::remove_file(&path)
+                .map_err(|e| anyhow!("Cannot remove the config file {}: {e}", path.display()))?;
+        }
+        return Ok(());
+    };
+    let backup = dir.join(CONFIG_ENTRY).join(name);
+    let contents = std::fs::read_to_string(&backup)
+        .map_err(|e| anyhow!("Cannot read the config file from the backup: {e}"))?;
+    let is_toml = |path: &Path| path.extension().is_some_and(|ext| ext == "toml");
+    let contents = match (is_toml(&backup), is_toml(&path)) {
+        (true, false) => serde_json::to_string_pretty(&toml::from_str::<Value>(&contents)?)?,
+        (false, true) => toml::to_string_pretty(&serde_json::from_str::<Value>(&contents)?)?,
+        _ => contents,
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| anyhow!("Cannot create folder {}: {e}", parent.display()))?;
+    }
+    write_file_atomically(&path, contents.as_bytes())
+        .map_err(|e| anyhow!("Cannot write the config file {}: {e}", path.display()))
+}
+
+/// Spec to install a binary again, e.g. `sui@testnet-1.40.1+debug`
+fn install_spec(binary: &BinaryVersion) -> String {
+    let release = if binary.version == "nightly" {
+        format!("nightly:{}", binary.network_release)
+    } else {
+        format!(
+            "{}-{}",
+            binary.network_release,
+            binary.version.trim_start_matches('v')
+        )
+    };
+    let build = if binary.debug { "+debug" } else { "" };
+    format!("{}@{release}{build}", binary.binary_name)
+}
+
+fn write_archive(
+    path: &Path,
+    info: &BackupInfo,
+    files: &[(String, PathBuf)],
+    binaries: Option<&Path>,
+) -> Result<(), Error> {
+    let file = File::create(path)
+        .map_err(|e| anyhow!("Cannot create the backup {}: {e}", path.display()))?;
+    let write = |writer: &mut dyn Write| -> Result<(), Error> {
+        let mut builder = tar::Builder::new(writer);
+        let info = serde_json::to_vec_pretty(info)?;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(info.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, INFO_ENTRY, info.as_slice())?;
+        for (name, file) in files {
+            builder
+                .append_path_with_name(file, name)
+                .map_err(|e| anyhow!("Cannot add {} to the backup: {e}", file.display()))?;
+        }
+        if let Some(dir) = binaries {
+            builder
+                .append_dir_all(BINARIES_ENTRY, dir)
+                .map_err(|e| anyhow!("Cannot add the binaries to the backup: {e}"))?;
+        }
+        builder.finish()?;
+        Ok(())
+    };
+    let written = match Format::of(path) {
+        Format::Zstd => {
+            let mut encoder = zstd::Encoder::new(file, 0)?;
+            write(&mut encoder).and_then(|_| Ok(encoder.finish().map(|_| ())?))
+        }
+        Format::Gzip => {
+            let mut encoder = GzEncoder::new(file, flate2::Compression::default());
+            write(&mut encoder).and_then(|_| Ok(encoder.finish().map(|_| ())?))
+        }
+        Format::Tar => {
+            let mut file = file;
+            write(&mut file)
+        }
+    };
+    written.map_err(|e| anyhow!("Cannot write the backup {}: {e}", path.display()))
+}
+
+/// Unpacks a backup into `dir`, returning its description
+fn unpack_archive(path: &Path, dir: &Path) -> Result<BackupInfo, Error> {
+    let file =
+        File::open(path).map_err(|e| anyhow!("Cannot open the backup {}: {e}", path.display()))?;
+    let reader: Box<dyn Read> = match Format::of(path) {
+        Format::Zstd => Box::new(zstd::Decoder::new(file)?),
+        Format::Gzip => Box::new(GzDecoder::new(file)),
+        Format::Tar => Box::new(file),
+    };
+    // Entries outside of `dir` are skipped by `unpack`
+    tar::Archive::new(reader)
+        .unpack(dir)
+        .map_err(|e| anyhow!("Cannot unpack the backup {}: {e}", path.display()))?;
+
+    let info = std::fs::read_to_string(dir.join(INFO_ENTRY)).map_err(|_| {
+        anyhow!(
+            "{} is not a suiup backup, it has no {INFO_ENTRY}",
+            path.display()
+        )
+    })?;
+    let info: BackupInfo = serde_json::from_str(&info)
+        .map_err(|e| anyhow!("Invalid {INFO_ENTRY} in the backup {}: {e}", path.display()))?;
+    // The name is joined to `dir` to read the config file, it must not lead out of it
+    if let Some(name) = &info.config_file {
+        let single = Path::new(name).file_name() == Some(name.as_ref());
+        if !single || !CONFIG_FILE_NAMES.contains(&name.as_str()) {
+            bail!(
+                "Invalid config file `{name}` in {INFO_ENTRY} of the backup {}, expected one of {}",
+                path.display(),
+                CONFIG_FILE_NAMES.join(", ")
+            );
+        }
+    }
+    Ok(info)
+}
+
+/// Copies the files of `src` into `dst`, replacing those that exist
+fn copy_tree(src: &Path, dst: &Path) -> Result<(), Error> {
+    if !src.exists() {
+        return Ok(());
+    }
+    std::fs::create_dir_all(dst)
+        .map_err(|e| anyhow!("Cannot create folder {}: {e}", dst.display()))?;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dst.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_tree(&entry.path(), &target)?;
+        } else {
+            reflink::copy(entry.path(), &target)
+                .map_err(|e| anyhow!("Cannot restore {}: {e}", target.display()))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = dir.path().join("config.json");
+        std::fs::write(&config, r#"{"assume_yes": true}"#).unwrap();
+        let binaries = dir.path().join("binaries");
+        std::fs::create_dir_all(binaries.join("testnet")).unwrap();
+        std::fs::write(binaries.join("testnet").join("sui-v1.40.1"), "sui").unwrap();
+        let info = BackupInfo {
+            suiup_version: "0.0.4".to_string(),
+            created_at: Utc::now(),
+            config_file: Some("config.json".to_string()),
+            binaries: true,
+        };
+
+        for name in ["backup.tar.zst", "backup.tgz", "backup.tar"] {
+            let archive = dir.path().join(name);
+            let files = [(format!("{CONFIG_ENTRY}/config.json"), config.clone())];
+            write_archive(&archive, &info, &files, Some(&binaries)).unwrap();
+
+            let unpacked = tempfile::tempdir().unwrap();
+            assert_eq!(unpack_archive(&archive, unpacked.path()).unwrap(), info);
+            let path = unpacked.path();
+            assert_eq!(
+                std::fs::read_to_string(path.join(CONFIG_ENTRY).join("config.json")).unwrap(),
+                r#"{"assume_yes": true}"#
+            );
+            assert_eq!(
+                std::fs::read_to_string(path.join("binaries/testnet/sui-v1.40.1")).unwrap(),
+                "sui"
+            );
+        }
+    }
+
+    #[test]
+    fn test_unpack_rejects_config_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("backup.tar");
+        for name in ["../../../../etc/passwd", "../config.json", "settings.json"] {
+            let info = BackupInfo {
+                suiup_version: "0.0.4".to_string(),
+                created_at: Utc::now(),
+                config_file: Some(name.to_string()),
+                binaries: false,
+            };
+            write_archive(&archive, &info, &[], None).unwrap();
+            let unpacked = tempfile::tempdir().unwrap();
+            assert!(unpack_archive(&archive, unpacked.path()).is_err(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_check_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        std::fs::create_dir_all(path.join(CONFIG_ENTRY)).unwrap();
+        std::fs::create_dir_all(path.join(STATE_ENTRY)).unwrap();
+        let mut info = BackupInfo {
+            suiup_version: "0.0.4".to_string(),
+            created_at: Utc::now(),
+            config_file: Some("config.json".to_string()),
+            binaries: false,
+        };
+        let config = path.join(CONFIG_ENTRY).join("config.json");
+        std::fs::write(&config, r#"{"assume_yes": true}"#).unwrap();
+        assert!(check_backup(&info, path).is_ok());
+
+        std::fs::write(&config, r#"{"assume_yes": "maybe"}"#).unwrap();
+        assert!(check_backup(&info, path).is_err());
+
+        info.config_file = None;
+        for name in STATE_FILES {
+            let file = path.join(STATE_ENTRY).join(name);
+            std::fs::write(&file, "{").unwrap();
+            assert!(check_backup(&info, path).is_err(), "{name}");
+            std::fs::remove_file(&file).unwrap();
+        }
+        assert!(check_backup(&info, path).is_ok());
+    }
+
+    #[test]
+    fn test_install_spec() {
+        let binary = |network: &str, version: &str, debug: bool| BinaryVersion {
+            binary_name: "sui".to_string(),
+            network_release: network.to_string(),
+            version: version.to_string(),
+            debug,
+            path: None,
+            provenance: None,
+            last_used: None,
+        };
+        assert_eq!(
+            install_spec(&binary("testnet", "v1.40.1", false)),
+            "sui@testnet-1.40.1"
+        );
+        assert_eq!(
+            install_spec(&binary("testnet", "v1.40.1", true)),
+            "sui@testnet-1.40.1+debug"
+        );
+        assert_eq!(
+            install_spec(&binary("main", "nightly", false)),
+            "sui@nightly:main"
+        );
+    }
+}
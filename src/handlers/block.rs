@@ -0,0 +1,213 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Versions blocked on this machine with `suiup block`, which cannot be installed or switched to
+//! until they are unblocked.
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Error};
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use comfy_table::Table;
+use serde::{Deserialize, Serialize};
+
+use crate::commands::{invalid_binary_name, table_format, BinaryName};
+use crate::paths::get_config_file;
+use crate::state::write_file_atomically;
+use crate::status;
+use crate::types::Network;
+
+const BLOCKED_VERSIONS_FILE: &str = "blocked_versions.json";
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct BlockedVersionsFile {
+    blocked: Vec<BlockedVersion>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BlockedVersion {
+    pub binary: String,
+    /// Network release the block applies to, all of them if not set
+    pub network: Option<Network>,
+    /// Version without the `v` prefix
+    pub version: String,
+    pub reason: Option<String>,
+    pub blocked_at: DateTime<Utc>,
+}
+
+impl BlockedVersion {
+    /// Parses `binary@[network-]version`, e.g. `sui@testnet-1.40.0` or `sui@1.40.0` (every
+    /// network)
+    fn parse(spec: &str, reason: Option<String>) -> Result<Self, Error> {
+        let (binary, release) = spec.split_once('@').ok_or_else(|| {
+            anyhow!("A version is required to block, e.g. `sui@testnet-1.40.0` or `sui@1.40.0`")
+        })?;
+        let binary = BinaryName::from_str(binary, true).map_err(|_| invalid_binary_name(binary))?;
+        let (network, version) = match release.split_once('-') {
+            Some((network, version)) if network.chars().all(|c| c.is_ascii_alphabetic()) => {
+                (Some(network.parse::<Network>()?), version)
+            }
+            _ if release.parse::<Network>().is_ok() => {
+                bail!("A version is required to block, e.g. `{binary}@{release}-1.40.0`")
+            }
+            _ => (None, release),
+        };
+        let version = version.trim_start_matches('v');
+        if version.is_empty() {
+            bail!("A version is required to block, e.g. `{binary}@1.40.0`");
+        }
+        Ok(BlockedVersion {
+            binary: binary.to_string(),
+            network,
+            version: version.to_string(),
+            reason,
+            blocked_at: Utc::now(),
+        })
+    }
+
+    /// Whether the block applies to `version` of `binary` from `network`. Debug builds are
+    /// blocked along with release builds.
+    pub fn matches(&self, binary: &str, network: &str, version: &str) -> bool {
+        let binary = binary.strip_suffix("-debug").unwrap_or(binary);
+        self.binary == binary
+            && self.network.is_none_or(|n| n.as_str() == network)
+            && self.version == version.trim_start_matches('v')
+    }
+
+    fn spec(&self) -> String {
+        match &self.network {
+            Some(network) => format!("{}@{network}-{}", self.binary, self.version),
+            None => format!("{}@{}", self.binary, self.version),
+        }
+    }
+
+    fn same_spec(&self, other: &BlockedVersion) -> bool {
+        self.binary == other.binary
+            && self.network == other.network
+            && self.version == other.version
+    }
+}
+
+fn blocked_versions_file() -> PathBuf {
+    get_config_file(BLOCKED_VERSIONS_FILE)
+}
+
+/// Returns the blocked versions, none if nothing was ever blocked
+pub fn blocked_versions() -> Result<Vec<BlockedVersion>, Error> {
+    let path = blocked_versions_file();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| anyhow!("Cannot read {}: {e}", path.display()))?;
+    let file: BlockedVersionsFile = serde_json::from_str(&content)
+        .map_err(|e| anyhow!("Invalid blocked versions file {}: {e}", path.display()))?;
+    Ok(file.blocked)
+}
+
+fn save_blocked_versions(blocked: Vec<BlockedVersion>) -> Result<(), Error> {
+    let s = serde_json::to_string_pretty(&BlockedVersionsFile { blocked })?;
+    write_file_atomically(&blocked_versions_file(), s.as_bytes())
+}
+
+/// Fails if `version` of `binary` from `network` is blocked, telling why and how to unblock it
+pub fn ensure_not_blocked(binary: &str, network: &str, version: &str) -> Result<(), Error> {
+    let blocked = blocked_versions()?;
+    let Some(block) = blocked.iter().find(|b| b.matches(binary, network, version)) else {
+        return Ok(());
+    };
+    let reason = block
+        .reason
+        .as_ref()
+        .map(|reason| format!(" ({reason})"))
+        .unwrap_or_default();
+    bail!(
+        "{binary} v{} from {network} is blocked on this machine{reason}. Unblock it with `suiup unblock {}`",
+        version.trim_start_matches('v'),
+        block.spec()
+    )
+}
+
+/// Handles the `block` command. Without a spec, lists the blocked versions.
+pub fn handle_block(spec: Option<&str>, reason: Option<String>) -> Result<(), Error> {
+    let mut blocked = blocked_versions()?;
+    let Some(spec) = spec else {
+        print_blocked(&blocked);
+        return Ok(());
+    };
+
+    let block = BlockedVersion::parse(spec, reason)?;
+    let spec = block.spec();
+    match blocked.iter_mut().find(|b| b.same_spec(&block)) {
+        Some(existing) => *existing = block,
+        None => blocked.push(block),
+    }
+    save_blocked_versions(blocked)?;
+    status!("Blocked {spec}. It cannot be installed or switched to until `suiup unblock {spec}`.");
+    Ok(())
+}
+
+/// Handles the `unblock` command
+pub fn handle_unblock(spec: &str) -> Result<(), Error> {
+    let unblocked = BlockedVersion::parse(spec, None)?;
+    let mut blocked = blocked_versions()?;
+    let count = blocked.len();
+    blocked.retain(|b| !b.same_spec(&unblocked));
+    if blocked.len() == count {
+        bail!(
+            "{} is not blocked. Run `suiup block` to list the blocked versions.",
+            unblocked.spec()
+        );
+    }
+    save_blocked_versions(blocked)?;
+    status!("Unblocked {}", unblocked.spec());
+    Ok(())
+}
+
+fn print_blocked(blocked: &[BlockedVersion]) {
+    if blocked.is_empty() {
+        println!("No versions are blocked.");
+        return;
+    }
+    let mut table = Table::new();
+    table
+        .load_preset(table_format())
+        .set_header(vec!["Binary", "Release", "Version", "Reason", "Blocked at"])
+        .add_rows(blocked.iter().map(|b| {
+            vec![
+                b.binary.clone(),
+                b.network.map_or("any".to_string(), |n| n.to_string()),
+                b.version.clone(),
+                b.reason.clone().unwrap_or_default(),
+                b.blocked_at.format("%Y-%m-%d %H:%M UTC").to_string(),
+            ]
+        }));
+    println!("{table}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_match() {
+        let block =
+            BlockedVersion::parse("sui@testnet-v1.40.0", Some("breaks ptb".into())).unwrap();
+        assert_eq!(block.network, Some(Network::Testnet));
+        assert_eq!(block.version, "1.40.0");
+        assert_eq!(block.spec(), "sui@testnet-1.40.0");
+        assert!(block.matches("sui", "testnet", "v1.40.0"));
+        assert!(block.matches("sui-debug", "testnet", "1.40.0"));
+        assert!(!block.matches("sui", "mainnet", "v1.40.0"));
+        assert!(!block.matches("sui", "testnet", "v1.40.1"));
+
+        let block = BlockedVersion::parse("mvr@0.0.8", None).unwrap();
+        assert_eq!(block.network, None);
+        assert!(block.matches("mvr", "standalone", "v0.0.8"));
+
+        assert!(BlockedVersion::parse("sui", None).is_err());
+        assert!(BlockedVersion::parse("sui@testnet", None).is_err());
+        assert!(BlockedVersion::parse("suii@1.40.0", None).is_err());
+    }
+}
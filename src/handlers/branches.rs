@@ -0,0 +1,236 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{anyhow, Error};
+use chrono::{DateTime, Utc};
+use comfy_table::Table;
+use serde::Deserialize;
+
+use crate::commands::{table_format, BinaryName};
+use crate::handlers::spinner;
+use crate::http;
+use crate::status;
+use crate::types::Repo;
+
+/// Branches fetched per request, the most GitHub allows
+const PER_PAGE: usize = 100;
+/// Stop listing branches after this many pages
+const MAX_PAGES: usize = 10;
+/// The recent commits are only fetched for up to this many branches, one request each
+const MAX_DETAILED: usize = 20;
+
+#[derive(Deserialize, Debug)]
+struct Branch {
+    name: String,
+    commit: BranchHead,
+}
+
+#[derive(Deserialize, Debug)]
+struct BranchHead {
+    sha: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct CommitInfo {
+    pub sha: String,
+    pub commit: CommitDetails,
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct CommitDetails {
+    pub message: String,
+    pub committer: Option<Signature>,
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct Signature {
+    pub date: DateTime<Utc>,
+}
+
+/// Commits between two commits, from the compare API
+#[derive(Deserialize, Debug)]
+pub(crate) struct Comparison {
+    /// `ahead`, `behind`, `identical` or `diverged` (e.g. after a force push)
+    pub status: String,
+    pub ahead_by: usize,
+    pub commits: Vec<CommitInfo>,
+}
+
+/// Handles `suiup list branches`: lists the branches of the repository a binary is built from
+/// with `--nightly`, and the recent commits of each if there are few of them
+pub async fn handle_list_branches(
+    binary: &BinaryName,
+    filter: Option<&str>,
+    commits: usize,
+    github_token: Option<String>,
+) -> Result<(), Error> {
+    let repo = binary.repo();
+    let mut branches = Vec::new();
+    let mut truncated = false;
+    for page in 1..=MAX_PAGES {
+        let fetched = fetch_branches(&repo, page, github_token.as_deref()).await?;
+        let last_page = fetched.len() < PER_PAGE;
+        branches.extend(fetched);
+        if last_page {
+            break;
+        }
+        truncated = page == MAX_PAGES;
+    }
+    if let Some(filter) = filter {
+        branches.retain(|b| b.name.contains(filter));
+    }
+    if branches.is_empty() {
+        match filter {
+            Some(filter) => println!("No branch of {} contains `{filter}`.", repo.repository()?),
+            None => println!("No branches found in {}.", repo.repository()?),
+        }
+        return Ok(());
+    }
+
+    if branches.len() > MAX_DETAILED {
+        for branch in &branches {
+            println!("{} ({})", branch.name, short_sha(&branch.commit.sha));
+        }
+        status!(
+            "\n{} branches{}. Use `--filter` to narrow them down to {MAX_DETAILED} or fewer and see their recent commits.",
+            branches.len(),
+            if truncated {
+                format!(", only the first {} are listed", MAX_PAGES * PER_PAGE)
+            } else {
+                String::new()
+            }
+        );
+    } else {
+        let mut detailed = Vec::new();
+        for branch in branches {
+            let commits =
+                fetch_commits(&repo, &branch.name, commits, github_token.as_deref()).await?;
+            detailed.push((branch, commits));
+        }
+        // Most recently updated first
+        detailed.sort_by_key(|(_, commits)| {
+            std::cmp::Reverse(
+                commits
+                    .first()
+                    .and_then(|c| c.commit.committer.as_ref().map(|s| s.date)),
+            )
+        });
+
+        let mut table = Table::new();
+        table
+            .load_preset(table_format())
+            .set_header(vec!["Branch", "Commit", "Date", "Message"]);
+        for (branch, commits) in &detailed {
+            for (i, commit) in commits.iter().enumerate() {
+                table.add_row(vec![
+                    if i == 0 {
+                        branch.name.clone()
+                    } else {
+                        String::new()
+                    },
+                    short_sha(&commit.sha).to_string(),
+                    commit
+                        .commit
+                        .committer
+                        .as_ref()
+                        .map(|s| s.date.format("%Y-%m-%d %H:%M").to_string())
+                        .unwrap_or_default(),
+                    summary(&commit.commit.message),
+                ]);
+            }
+        }
+        println!("{table}");
+    }
+    status!("\nInstall from a branch with `suiup install {binary} --nightly <branch>`.");
+    Ok(())
+}
+
+async fn fetch_branches(
+    repo: &Repo,
+    page: usize,
+    github_token: Option<&str>,
+) -> Result<Vec<Branch>, Error> {
+    let repository = repo.repository()?;
+    let url = format!(
+        "{}/repos/{repository}/branches?per_page={PER_PAGE}&page={page}",
+        http::github_api_url()?
+    );
+    let pb = spinner("fetch", format!("Fetching the branches of {repository}..."));
+    let what = format!("fetch the branches of {repository}");
+    let response = http::send(http::get(&url, github_token)?, &what).await?;
+    pb.finish_and_clear();
+    let response = response
+        .error_for_status()
+        .map_err(|e| anyhow!("Cannot fetch the branches of {repository}: {e}"))?;
+    Ok(response.json().await?)
+}
+
+/// Fetches the latest `count` commits of `branch`, newest first
+pub(crate) async fn fetch_commits(
+    repo: &Repo,
+    branch: &str,
+    count: usize,
+    github_token: Option<&str>,
+) -> Result<Vec<CommitInfo>, Error> {
+    let url = format!(
+        "{}/repos/{}/commits?sha={branch}&per_page={count}",
+        http::github_api_url()?,
+        repo.repository()?
+    );
+    let pb = spinner("fetch", format!("Fetching the commits of {branch}..."));
+    let what = format!("fetch the commits of {branch}");
+    let response = http::send(http::get(&url, github_token)?, &what).await?;
+    pb.finish_and_clear();
+    let response = response
+        .error_for_status()
+        .map_err(|e| anyhow!("Cannot fetch the commits of {branch}: {e}"))?;
+    Ok(response.json().await?)
+}
+
+/// Fetches the commits after `base` up to `head`, oldest first. GitHub lists at most 250 of them.
+pub(crate) async fn compare_commits(
+    repo: &Repo,
+    base: &str,
+    head: &str,
+    github_token: Option<&str>,
+) -> Result<Comparison, Error> {
+    let url = format!(
+        "{}/repos/{}/compare/{base}...{head}",
+        http::github_api_url()?,
+        repo.repository()?
+    );
+    let what = format!("compare {base} with {head}");
+    let response = http::send(http::get(&url, github_token)?, &what).await?;
+    let response = response
+        .error_for_status()
+        .map_err(|e| anyhow!("Cannot compare {base} with {head}: {e}"))?;
+    Ok(response.json().await?)
+}
+
+pub(crate) fn short_sha(sha: &str) -> &str {
+    &sha[..sha.len().min(7)]
+}
+
+/// First line of a commit message, shortened to fit a table
+pub(crate) fn summary(message: &str) -> String {
+    const MAX: usize = 72;
+    let line = message.lines().next().unwrap_or_default().trim();
+    if line.chars().count() > MAX {
+        format!("{}...", line.chars().take(MAX - 3).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        assert_eq!(summary("Fix the build\n\nDetails"), "Fix the build");
+        let long = "a".repeat(100);
+        assert_eq!(summary(&long), format!("{}...", "a".repeat(69)));
+        assert_eq!(short_sha("0123456789abcdef"), "0123456");
+    }
+}
@@ -0,0 +1,226 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{anyhow, bail, Error};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::commands::BinaryName;
+use crate::handlers::spinner;
+use crate::http;
+use crate::types::{Network, Repo, Version};
+
+/// Releases fetched per request, the most GitHub allows
+const PER_PAGE: usize = 100;
+/// Stop looking for the start of the range after this many pages
+const MAX_PAGES: usize = 10;
+
+/// The parts of a GitHub release needed to render its notes
+#[derive(Deserialize, Debug)]
+struct ReleaseNotes {
+    tag_name: String,
+    name: Option<String>,
+    body: Option<String>,
+    published_at: Option<DateTime<Utc>>,
+    html_url: String,
+}
+
+/// Parses `from..to`, where either end may be left out, e.g. `testnet-1.39.3..testnet-1.41.0`
+fn parse_range(range: &str) -> Result<(Option<Version>, Option<Version>), Error> {
+    let (from, to) = range.split_once("..").ok_or_else(|| {
+        anyhow!(
+            "Invalid range `{range}`, use `<from>..<to>`, e.g. `testnet-1.39.3..testnet-1.41.0`"
+        )
+    })?;
+    let parse = |s: &str| (!s.is_empty()).then(|| s.parse::<Version>()).transpose();
+    let (from, to) = (parse(from)?, parse(to)?);
+    if from.is_none() && to.is_none() {
+        bail!("The range `{range}` needs a start or an end version");
+    }
+    Ok((from, to))
+}
+
+/// Handles `suiup changelog`: prints the notes of the releases after `from` up to and including
+/// `to`, oldest first
+pub async fn handle_changelog(
+    binary: &BinaryName,
+    range: &str,
+    github_token: Option<String>,
+) -> Result<(), Error> {
+    let repo = binary.repo();
+    let (from, to) = parse_range(range)?;
+
+    let networks = [&from, &to].map(|e| e.as_ref().and_then(|e| e.network));
+    let network = match networks {
+        [Some(from), Some(to)] if from != to => {
+            bail!("The range goes from {from} to {to}, compare releases of the same network")
+        }
+        [Some(network), _] | [_, Some(network)] => Some(network),
+        _ if repo == Repo::Mvr => None,
+        _ => Some(Network::Testnet),
+    };
+    if repo == Repo::Mvr && network.is_some() {
+        bail!("mvr releases are not per network, use versions only, e.g. `0.0.5..0.0.8`");
+    }
+    let in_network = |tag: &Version| tag.network == network;
+    let mut releases = Vec::new();
+    for page in 1..=MAX_PAGES {
+        let fetched = fetch_page(&repo, page, github_token.as_deref()).await?;
+        let last_page = fetched.len() < PER_PAGE;
+        let fetched = fetched
+            .into_iter()
+            .filter(|r| !r.tag_name.contains("nightly"))
+            .filter_map(|r| Some((r.tag_name.parse::<Version>().ok()?, r)))
+            .filter(|(tag, _)| in_network(tag))
+            .collect::<Vec<_>>();
+        // Releases are listed newest first: once the start of the range is reached, older
+        // pages are not needed
+        let reached_start = from
+            .as_ref()
+            .is_some_and(|from| fetched.iter().any(|(tag, _)| tag <= from));
+        releases.extend(fetched);
+        if last_page || reached_start {
+            break;
+        }
+    }
+
+    let mut releases = releases
+        .into_iter()
+        .filter(|(tag, _)| {
+            from.as_ref().is_none_or(|from| tag > from) && to.as_ref().is_none_or(|to| tag <= to)
+        })
+        .collect::<Vec<_>>();
+    releases.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let release_name = |version: &Version| match &network {
+        Some(network) => format!("{network}-{}", version.tag()),
+        None => version.tag(),
+    };
+    if let Some(to) = &to {
+        if !releases.iter().any(|(tag, _)| tag == to) {
+            bail!(
+                "No {binary} release {} found in {}",
+                release_name(to),
+                repo.repository()?
+            );
+        }
+    }
+    if releases.is_empty() {
+        bail!("No {binary} release in the range `{range}`");
+    }
+
+    let from = from.map(|v| release_name(&v)).unwrap_or_default();
+    let to = to.map(|v| release_name(&v)).unwrap_or_default();
+    println!(
+        "# {binary} {from}..{to} ({} release{})",
+        releases.len(),
+        if releases.len() == 1 { "" } else { "s" }
+    );
+    for (_, release) in releases {
+        println!();
+        let date = release
+            .published_at
+            .map(|date| format!(" ({})", date.format("%Y-%m-%d")))
+            .unwrap_or_default();
+        let title = release
+            .name
+            .filter(|name| !name.is_empty() && *name != release.tag_name)
+            .map(|name| format!(": {name}"))
+            .unwrap_or_default();
+        println!("## {}{title}{date}", release.tag_name);
+        println!("{}", release.html_url);
+        let body = release.body.unwrap_or_default().replace("\r\n", "\n");
+        if !body.trim().is_empty() {
+            println!();
+            println!("{}", body.trim());
+        }
+    }
+    Ok(())
+}
+
+/// Fetches the notes of the release with the given tag
+pub(crate) async fn release_body(
+    repo: &Repo,
+    tag: &str,
+    github_token: Option<&str>,
+) -> Result<String, Error> {
+    let url = format!(
+        "{}/repos/{}/releases/tags/{tag}",
+        http::github_api_url()?,
+        repo.repository()?
+    );
+    let release: ReleaseNotes = http::send(
+        http::get(&url, github_token)?,
+        &format!("fetch the {tag} release"),
+    )
+    .await?
+    .error_for_status()
+    .map_err(|e| anyhow!("Cannot fetch the {tag} release: {e}"))?
+    .json()
+    .await?;
+    Ok(release.body.unwrap_or_default())
+}
+
+/// Reads the protocol version from the notes of a Sui release, e.g.
+/// "Sui Protocol Version in this release: `72`"
+pub(crate) fn protocol_version(notes: &str) -> Option<u64> {
+    let regex = Regex::new(r"(?i)protocol version[^0-9\n]*(\d+)").ok()?;
+    regex.captures(notes)?.get(1)?.as_str().parse().ok()
+}
+
+async fn fetch_page(
+    repo: &Repo,
+    page: usize,
+    github_token: Option<&str>,
+) -> Result<Vec<ReleaseNotes>, Error> {
+    let repository = repo.repository()?;
+    let url = format!(
+        "{}/repos/{repository}/releases?per_page={PER_PAGE}&page={page}",
+        http::github_api_url()?
+    );
+    let pb = spinner(
+        "fetch",
+        format!("Fetching {} release notes...", repo.binary_name()),
+    );
+    let what = format!("fetch the {repository} releases");
+    let response = http::send(http::get(&url, github_token)?, &what).await?;
+    pb.finish_and_clear();
+    let response = response
+        .error_for_status()
+        .map_err(|e| anyhow!("Cannot fetch the {repository} releases: {e}"))?;
+    Ok(response.json().await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        let (from, to) = parse_range("testnet-1.39.3..testnet-v1.41.0").unwrap();
+        let from = from.unwrap();
+        assert_eq!(from.network, Some(Network::Testnet));
+        assert_eq!(from.tag(), "v1.39.3");
+        assert_eq!(to.unwrap().tag(), "v1.41.0");
+
+        let (from, to) = parse_range("0.0.5..").unwrap();
+        assert_eq!(from.unwrap().network, None);
+        assert!(to.is_none());
+
+        assert!(parse_range("1.39.3").is_err());
+        assert!(parse_range("..").is_err());
+        assert!(parse_range("testnet..1.41.0").is_err());
+
+        let tag = "mainnet-v1.40.1".parse::<Version>().unwrap();
+        assert_eq!(tag.network, Some(Network::Mainnet));
+        assert_eq!("v0.0.8".parse::<Version>().unwrap().network, None);
+    }
+
+    #[test]
+    fn test_protocol_version() {
+        let notes = "## Protocol\r\n#### Sui Protocol Version in this release: `72`\r\n";
+        assert_eq!(protocol_version(notes), Some(72));
+        assert_eq!(protocol_version("## Walrus CLI\n- fixes"), None);
+    }
+}
@@ -1,51 +1,91 @@
+use std::collections::BTreeSet;
 use std::fs;
-use std::path::PathBuf;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::time::{Duration, SystemTime};
 
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Utc};
+use dialoguer::{MultiSelect, Select};
 
-use crate::paths::release_archive_dir;
+use crate::commands::BinaryName;
+use crate::config::load_config;
+use crate::handlers::switch::binary_source_path_in;
+use crate::output;
+use crate::paths::{binaries_dir, release_archive_dir};
+use crate::state::{self, DefaultVersions};
+use crate::status;
+use crate::types::{default_binary_name, BinaryVersion};
+
+/// Handles the `cleanup` command. The archives installed binaries were extracted from are kept
+/// unless `force` is set, to reinstall or restore them without downloading them again. With
+/// `unused`, the versions not used for that many days are uninstalled first, so their archives
+/// are cleaned up too.
+pub async fn handle_cleanup(
+    all: bool,
+    days: u32,
+    dry_run: bool,
+    force: bool,
+    unused: Option<u32>,
+) -> Result<()> {
+    if let Some(unused) = unused {
+        uninstall_unused(unused, dry_run)?;
+    }
 
-/// Handles the `cleanup` command
-pub async fn handle_cleanup(all: bool, days: u32, dry_run: bool) -> Result<()> {
     let release_archive_dir = release_archive_dir();
-    println!(
+    status!(
         "Release archives directory: {}",
         release_archive_dir.display()
     );
 
     if !release_archive_dir.exists() {
-        println!("Release archives directory does not exist, nothing to clean up.");
+        status!("Release archives directory does not exist, nothing to clean up.");
         return Ok(());
     }
 
     // Calculate total size before cleanup
     let total_size_before = calculate_dir_size(&release_archive_dir)?;
-    println!(
+    status!(
         "Current cache size: {}",
         format_file_size(total_size_before)
     );
 
-    if all {
+    let referenced = if force {
+        BTreeSet::new()
+    } else {
+        referenced_archives(state::installed_binaries()?.binaries())
+    };
+
+    if all && referenced.is_empty() {
         if dry_run {
             println!("Would remove all release archives in cache directory (dry run)");
         } else {
-            println!("Removing all release archives in cache directory...");
+            status!("Removing all release archives in cache directory...");
             if release_archive_dir.exists() {
                 fs::remove_dir_all(&release_archive_dir)?;
                 fs::create_dir_all(&release_archive_dir)?;
             }
-            println!("{}", "Cache cleared successfully.");
+            status!("Cache cleared successfully.");
         }
         return Ok(());
     }
 
-    // Calculate cutoff duration
-    let cutoff_duration = Duration::from_secs(60 * 60 * 24 * days as u64); // days to seconds
+    // Calculate cutoff duration, every archive being old enough with `--all`
+    let cutoff_duration = if all {
+        Duration::ZERO
+    } else {
+        Duration::from_secs(60 * 60 * 24 * days as u64) // days to seconds
+    };
     let mut cleaned_size = 0;
     let mut files_removed = 0;
+    let mut files_kept = 0;
 
-    println!("Removing release archives older than {} days...", days);
+    if all {
+        status!("Removing the release archives of the versions that are not installed...");
+    } else {
+        status!("Removing release archives older than {} days...", days);
+    }
 
     // Process release_archive_dir
     if release_archive_dir.exists() {
@@ -66,7 +106,9 @@ pub async fn handle_cleanup(all: bool, days: u32, dry_run: bool) -> Result<()> {
             // Convert to days for display
             let days_old = age.as_secs() / (60 * 60 * 24);
 
-            if age > cutoff_duration {
+            if age > cutoff_duration && is_referenced(&referenced, &path) {
+                files_kept += 1;
+            } else if age > cutoff_duration {
                 let file_size = metadata.len();
                 cleaned_size += file_size;
                 files_removed += 1;
@@ -79,7 +121,7 @@ pub async fn handle_cleanup(all: bool, days: u32, dry_run: bool) -> Result<()> {
                         format_file_size(file_size)
                     );
                 } else {
-                    println!(
+                    status!(
                         "Removing: {} ({} days old, {})",
                         path.display(),
                         days_old,
@@ -91,6 +133,12 @@ pub async fn handle_cleanup(all: bool, days: u32, dry_run: bool) -> Result<()> {
         }
     }
 
+    if files_kept > 0 {
+        status!(
+            "Kept {files_kept} archives of installed versions, pass --force to remove them too"
+        );
+    }
+
     // Report results
     if dry_run {
         println!(
@@ -99,20 +147,436 @@ pub async fn handle_cleanup(all: bool, days: u32, dry_run: bool) -> Result<()> {
             format_file_size(cleaned_size)
         );
     } else {
-        println!(
-            "{} {} files removed, {} freed",
-            "Cleanup complete.",
+        status!(
+            "Cleanup complete. {} files removed, {} freed",
             files_removed,
             format_file_size(cleaned_size)
         );
 
         let total_size_after = calculate_dir_size(&release_archive_dir)?;
-        println!("New cache size: {}", format_file_size(total_size_after));
+        status!("New cache size: {}", format_file_size(total_size_after));
+    }
+
+    Ok(())
+}
+
+/// Uninstalls the versions that were not used for `days` days, keeping the default versions
+fn uninstall_unused(days: u32, dry_run: bool) -> Result<()> {
+    let installed = state::installed_binaries()?;
+    let unused = pick_unused(
+        installed.binaries(),
+        &state::default_versions()?,
+        Utc::now(),
+        days,
+    );
+    if unused.is_empty() {
+        status!("No installed version is unused for {days} days.");
+        return Ok(());
     }
 
+    for (binary, days_unused) in &unused {
+        if dry_run {
+            println!("Would uninstall: {binary} (unused for {days_unused} days)");
+            continue;
+        }
+        let path = binary_source_path_in(&binaries_dir(), binary);
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| anyhow!("Cannot remove {}: {e}", path.display()))?;
+        }
+        status!("Uninstalled: {binary} (unused for {days_unused} days)");
+    }
+    if !dry_run {
+        state::with_state(|state| {
+            for (binary, _) in &unused {
+                state.installed_binaries_mut().remove_version(binary);
+            }
+            Ok(())
+        })?;
+    }
     Ok(())
 }
 
+/// The installed versions not used for `days` days, with the days since they were last used.
+/// Versions never used count from their install, and those installed before suiup recorded it
+/// are kept, as well as the default versions.
+fn pick_unused<'a>(
+    binaries: &'a [BinaryVersion],
+    defaults: &DefaultVersions,
+    now: DateTime<Utc>,
+    days: u32,
+) -> Vec<(&'a BinaryVersion, i64)> {
+    binaries
+        .iter()
+        .filter(|b| {
+            defaults.get(&default_binary_name(&b.binary_name, b.debug))
+                != Some(&(b.network_release.clone(), b.version.clone(), b.debug))
+        })
+        .filter_map(|b| {
+            let used = b
+                .last_used
+                .or(b.provenance.as_ref().map(|p| p.installed_at))?;
+            let days_unused = (now - used).num_days();
+            (days_unused >= days as i64).then_some((b, days_unused))
+        })
+        .collect()
+}
+
+/// Names of the cached release archives that installed binaries were extracted from
+fn referenced_archives(installed: &[BinaryVersion]) -> BTreeSet<String> {
+    installed
+        .iter()
+        .filter_map(|binary| binary.provenance.as_ref()?.archive.clone())
+        .collect()
+}
+
+fn is_referenced(referenced: &BTreeSet<String>, path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| referenced.contains(name))
+}
+
+/// Makes room in the cache for a download of `size` bytes to `download_to` that would grow it
+/// past `max_cache_size`: the oldest archives are removed first, except those of installed
+/// versions. Warns if the cache still exceeds the limit, the download goes on anyway.
+pub(crate) fn make_room_in_cache(download_to: &Path, size: u64) -> Result<()> {
+    let dir = release_archive_dir();
+    let Some(max) = load_config()?.max_cache_size() else {
+        return Ok(());
+    };
+    if size == 0 || !download_to.starts_with(&dir) || !dir.exists() {
+        return Ok(());
+    }
+    let mut archives = Vec::new();
+    let mut used = 0;
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        // Replaced by the download
+        if !path.is_file() || path == download_to {
+            continue;
+        }
+        let metadata = fs::metadata(&path)?;
+        used += metadata.len();
+        // Downloads in progress are not archives yet
+        if path.extension().is_some_and(|ext| ext == "part") {
+            continue;
+        }
+        archives.push((path, metadata.len(), metadata.modified()?));
+    }
+    let excess = (used + size).saturating_sub(max);
+    if excess == 0 {
+        return Ok(());
+    }
+
+    let referenced = referenced_archives(state::installed_binaries()?.binaries());
+    let evicted = pick_evictions(archives, &referenced, excess);
+    let mut freed = 0;
+    for (path, len) in evicted {
+        fs::remove_file(&path).map_err(|e| anyhow!("Cannot remove {}: {e}", path.display()))?;
+        status!(
+            "Removed {} ({}) to keep the cache under {}",
+            path.file_name().unwrap_or_default().to_string_lossy(),
+            format_file_size(len),
+            format_file_size(max)
+        );
+        freed += len;
+    }
+    if freed < excess {
+        eprintln!(
+            "{} The cache will take {}, more than the {} of `max_cache_size`, even without the archives of versions that are not installed. Run `suiup cleanup --all --force` to also remove those of installed versions.",
+            output::warning_sign(),
+            format_file_size(used - freed + size),
+            format_file_size(max)
+        );
+    }
+    Ok(())
+}
+
+/// The archives to remove to free `excess` bytes, oldest first, keeping those of installed
+/// versions. Fewer bytes are freed if the others are not enough.
+fn pick_evictions(
+    mut archives: Vec<(PathBuf, u64, SystemTime)>,
+    referenced: &BTreeSet<String>,
+    excess: u64,
+) -> Vec<(PathBuf, u64)> {
+    archives.sort_by_key(|(_, _, modified)| *modified);
+    let mut freed = 0;
+    archives
+        .into_iter()
+        .filter(|(path, _, _)| !is_referenced(referenced, path))
+        .take_while(|(_, len, _)| {
+            let needed = freed < excess;
+            freed += len;
+            needed
+        })
+        .map(|(path, len, _)| (path, len))
+        .collect()
+}
+
+/// Handles `cleanup --interactive`: lets the user pick the release archives to remove from a
+/// list showing their size and age, oldest first
+pub fn handle_interactive_cleanup(dry_run: bool, yes: bool) -> Result<()> {
+    if yes || !std::io::stdin().is_terminal() {
+        bail!("`cleanup --interactive` needs a terminal to choose the files to remove. Use `--days` or `--all` instead.");
+    }
+    let release_archive_dir = release_archive_dir();
+    status!(
+        "Release archives directory: {}",
+        release_archive_dir.display()
+    );
+
+    let mut entries = Vec::new();
+    if release_archive_dir.exists() {
+        for entry in fs::read_dir(&release_archive_dir)? {
+            let path = entry?.path();
+            if !path.is_file() {
+                continue;
+            }
+            let metadata = fs::metadata(&path)?;
+            let age = SystemTime::now()
+                .duration_since(metadata.modified()?)
+                .unwrap_or_default();
+            entries.push((path, metadata.len(), age));
+        }
+    }
+    if entries.is_empty() {
+        status!("The cache is empty, nothing to clean up.");
+        return Ok(());
+    }
+    entries.sort_by_key(|(_, _, age)| std::cmp::Reverse(*age));
+
+    let items = entries
+        .iter()
+        .map(|(path, size, age)| {
+            format!(
+                "{} ({}, {} days old)",
+                path.file_name().unwrap_or_default().to_string_lossy(),
+                format_file_size(*size),
+                age.as_secs() / (60 * 60 * 24)
+            )
+        })
+        .collect::<Vec<_>>();
+    let selection = MultiSelect::with_theme(&*output::prompt_theme())
+        .with_prompt("Select the files to remove (space to select, enter to confirm)")
+        .items(&items)
+        .interact()?;
+    if selection.is_empty() {
+        status!("Nothing selected, nothing removed.");
+        return Ok(());
+    }
+
+    let mut cleaned_size = 0;
+    for i in &selection {
+        let (path, size, _) = &entries[*i];
+        if dry_run {
+            println!("Would remove: {}", path.display());
+        } else {
+            fs::remove_file(path).map_err(|e| anyhow!("Cannot remove {}: {e}", path.display()))?;
+            status!("Removed: {}", path.display());
+        }
+        cleaned_size += size;
+    }
+    if dry_run {
+        println!(
+            "Would remove {} files totaling {} (dry run)",
+            selection.len(),
+            format_file_size(cleaned_size)
+        );
+    } else {
+        status!(
+            "Cleanup complete. {} files removed, {} freed",
+            selection.len(),
+            format_file_size(cleaned_size)
+        );
+    }
+    Ok(())
+}
+
+/// A file in the binaries folder that no installed binary refers to, e.g. left over by an
+/// interrupted install or a manual edit of the state
+#[derive(Debug, PartialEq)]
+struct Orphan {
+    path: PathBuf,
+    /// The binary the file can be registered as, if its name follows the layout suiup uses
+    binary: Option<BinaryVersion>,
+}
+
+/// What to do with an orphaned file
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum OrphanAction {
+    Register,
+    Remove,
+    Keep,
+}
+
+/// Handles `cleanup --orphans`: finds the files in the binaries folder that are not in the
+/// installed binaries, and offers to register them again or to remove them. Without prompting,
+/// files that can be registered are registered, and the others are kept.
+pub fn handle_orphans(dry_run: bool, yes: bool) -> Result<()> {
+    let binaries_dir = binaries_dir();
+    status!("Binaries directory: {}", binaries_dir.display());
+    let installed = state::installed_binaries()?;
+    let orphans = find_orphans(&binaries_dir, installed.binaries())?;
+    if orphans.is_empty() {
+        status!("No orphaned binaries found.");
+        return Ok(());
+    }
+
+    let interactive = !yes && !dry_run && std::io::stdin().is_terminal();
+    let theme = output::prompt_theme();
+    let (mut registered, mut removed) = (0, 0);
+    for orphan in &orphans {
+        let size = fs::metadata(&orphan.path).map(|m| m.len()).unwrap_or(0);
+        let description = format!("{} ({})", orphan.path.display(), format_file_size(size));
+        let mut actions = Vec::new();
+        if let Some(binary) = &orphan.binary {
+            actions.push((
+                OrphanAction::Register,
+                format!(
+                    "Register it as {}{} {} ({})",
+                    binary.binary_name,
+                    if binary.debug { " (debug)" } else { "" },
+                    binary.version,
+                    binary.network_release
+                ),
+            ));
+        }
+        actions.push((OrphanAction::Remove, "Remove it".to_string()));
+        actions.push((OrphanAction::Keep, "Keep it".to_string()));
+
+        let action = if interactive {
+            let items = actions.iter().map(|(_, label)| label).collect::<Vec<_>>();
+            let selection = Select::with_theme(&*theme)
+                .with_prompt(format!("Orphaned binary {description}"))
+                .items(&items)
+                .default(0)
+                .interact()?;
+            actions[selection].0
+        } else {
+            println!("Orphaned binary: {description}");
+            // Registering does not lose anything, removing is only done when asked for
+            if orphan.binary.is_some() {
+                OrphanAction::Register
+            } else {
+                OrphanAction::Keep
+            }
+        };
+
+        match (action, &orphan.binary) {
+            (OrphanAction::Register, Some(binary)) => {
+                if dry_run {
+                    println!("  Would register it (dry run)");
+                } else {
+                    state::with_state(|state| {
+                        state.installed_binaries_mut().add_binary(binary.clone());
+                        Ok(())
+                    })?;
+                    println!("  Registered");
+                }
+                registered += 1;
+            }
+            (OrphanAction::Remove, _) => {
+                fs::remove_file(&orphan.path)
+                    .map_err(|e| anyhow!("Cannot remove {}: {e}", orphan.path.display()))?;
+                println!("  Removed");
+                removed += 1;
+            }
+            (_, None) if !interactive => {
+                println!("  Kept, its name does not match a binary suiup can register")
+            }
+            _ => println!("  Kept"),
+        }
+    }
+
+    let kept = orphans.len() - registered - removed;
+    if dry_run {
+        println!("Would register {registered} and keep {kept} orphaned binaries (dry run)");
+    } else {
+        println!("{registered} registered, {removed} removed, {kept} kept");
+    }
+    Ok(())
+}
+
+/// Lists the files of the binaries folder that are not the source of an installed binary.
+/// Releases are extracted to `<release>/<binary>-<version>`, and nightly builds to
+/// `<branch>/bin/<binary>-nightly`; other files (e.g. cargo's metadata) are ignored.
+fn find_orphans(binaries_dir: &Path, installed: &[BinaryVersion]) -> Result<Vec<Orphan>> {
+    let known = installed
+        .iter()
+        .flat_map(|b| {
+            [
+                Some(binary_source_path_in(binaries_dir, b)),
+                b.path.as_ref().map(PathBuf::from),
+            ]
+        })
+        .flatten()
+        .collect::<Vec<_>>();
+
+    let mut orphans = Vec::new();
+    if !binaries_dir.is_dir() {
+        return Ok(orphans);
+    }
+    for release in fs::read_dir(binaries_dir)? {
+        let release = release?.path();
+        if !release.is_dir() {
+            continue;
+        }
+        let release_name = release
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default()
+            .to_string();
+        for (dir, nightly) in [(release.clone(), false), (release.join("bin"), true)] {
+            if !dir.is_dir() {
+                continue;
+            }
+            for file in fs::read_dir(&dir)? {
+                let path = file?.path();
+                let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+                    continue;
+                };
+                if !path.is_file() || file_name.starts_with('.') || known.contains(&path) {
+                    continue;
+                }
+                let binary = parse_binary_file_name(file_name, &release_name)
+                    .filter(|b| (b.version == "nightly") == nightly)
+                    .map(|b| BinaryVersion {
+                        path: Some(path.to_string_lossy().to_string()),
+                        ..b
+                    });
+                orphans.push(Orphan { path, binary });
+            }
+        }
+    }
+    orphans.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(orphans)
+}
+
+/// Parses the name of an extracted binary, `<binary>[-debug]-<version>`, e.g. `sui-v1.40.1` or
+/// `sui-debug-nightly`
+fn parse_binary_file_name(file_name: &str, release: &str) -> Option<BinaryVersion> {
+    let file_name = file_name.strip_suffix(".exe").unwrap_or(file_name);
+    let (name, version) = file_name.match_indices('-').find_map(|(i, _)| {
+        let version = &file_name[i + 1..];
+        let is_version = version == "nightly"
+            || (version.starts_with('v') && version[1..].starts_with(|c: char| c.is_ascii_digit()));
+        is_version.then(|| (&file_name[..i], version))
+    })?;
+    let (name, debug) = match name.strip_suffix("-debug") {
+        Some(name) => (name, true),
+        None => (name, false),
+    };
+    BinaryName::from_str(name).ok()?;
+    Some(BinaryVersion {
+        binary_name: name.to_string(),
+        network_release: release.to_string(),
+        version: version.to_string(),
+        debug,
+        path: None,
+        provenance: None,
+        last_used: None,
+    })
+}
+
 fn calculate_dir_size(dir: &PathBuf) -> Result<u64> {
     if !dir.exists() {
         return Ok(0);
@@ -133,7 +597,7 @@ fn calculate_dir_size(dir: &PathBuf) -> Result<u64> {
 }
 
 /// Format file size in human readable format
-fn format_file_size(size: u64) -> String {
+pub(crate) fn format_file_size(size: u64) -> String {
     const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB", "PB", "EB"];
 
     if size == 0 {
@@ -154,3 +618,155 @@ fn format_file_size(size: u64) -> String {
         format!("{:.0} {}", value, unit)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{BuildType, Provenance};
+
+    #[test]
+    fn test_parse_binary_file_name() {
+        let sui = parse_binary_file_name("sui-debug-v1.40.1", "testnet").unwrap();
+        assert_eq!(
+            (sui.binary_name.as_str(), sui.version.as_str(), sui.debug),
+            ("sui", "v1.40.1", true)
+        );
+        let node = parse_binary_file_name("sui-node-v1.40.1-rc", "devnet").unwrap();
+        assert_eq!(
+            (node.binary_name.as_str(), node.version.as_str()),
+            ("sui-node", "v1.40.1-rc")
+        );
+        let mvr = parse_binary_file_name("mvr-nightly", "main").unwrap();
+        assert_eq!(
+            (mvr.network_release.as_str(), mvr.version.as_str()),
+            ("main", "nightly")
+        );
+        assert!(parse_binary_file_name("sui", "testnet").is_none());
+        assert!(parse_binary_file_name("cargo-v1.0.0", "testnet").is_none());
+    }
+
+    #[test]
+    fn test_find_orphans() {
+        let dir = tempfile::tempdir().unwrap();
+        let testnet = dir.path().join("testnet");
+        fs::create_dir_all(testnet.join("bin")).unwrap();
+        for file in ["sui-v1.0.0", "sui-v1.1.0", "notes.txt", ".crates.toml"] {
+            fs::write(testnet.join(file), "").unwrap();
+        }
+        let installed = parse_binary_file_name("sui-v1.0.0", "testnet").unwrap();
+
+        let orphans = find_orphans(dir.path(), &[installed]).unwrap();
+        assert_eq!(orphans.len(), 2);
+        assert_eq!(orphans[0].path, testnet.join("notes.txt"));
+        assert!(orphans[0].binary.is_none());
+        assert_eq!(orphans[1].path, testnet.join("sui-v1.1.0"));
+        assert_eq!(orphans[1].binary.as_ref().unwrap().version, "v1.1.0");
+    }
+
+    #[test]
+    fn test_pick_evictions() {
+        let day = Duration::from_secs(60 * 60 * 24);
+        let now = SystemTime::now();
+        let archive =
+            |name: &str, len: u64, days: u32| (PathBuf::from(name), len, now - day * days);
+        let archives = vec![
+            archive("sui-testnet-v1.41.0-ubuntu-x86_64.tgz", 300, 1),
+            archive("sui-testnet-v1.39.3-ubuntu-x86_64.tgz", 200, 30),
+            archive("sui-testnet-v1.40.1-ubuntu-x86_64.tgz", 100, 10),
+            archive("sui-testnet-v1.38.0-ubuntu-x86_64.tgz", 400, 60),
+        ];
+        let referenced = BTreeSet::from(["sui-testnet-v1.38.0-ubuntu-x86_64.tgz".to_string()]);
+        let names = |evicted: Vec<(PathBuf, u64)>| {
+            evicted
+                .into_iter()
+                .map(|(path, _)| path.to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            names(pick_evictions(archives.clone(), &referenced, 250)),
+            [
+                "sui-testnet-v1.39.3-ubuntu-x86_64.tgz",
+                "sui-testnet-v1.40.1-ubuntu-x86_64.tgz"
+            ]
+        );
+        assert_eq!(
+            names(pick_evictions(archives.clone(), &referenced, 200)),
+            ["sui-testnet-v1.39.3-ubuntu-x86_64.tgz"]
+        );
+        // The archive of the installed version is kept even if not enough is freed
+        assert_eq!(pick_evictions(archives, &referenced, 10_000).len(), 3);
+    }
+
+    #[test]
+    fn test_pick_unused() {
+        let now = Utc::now();
+        let binary = |file: &str, last_used: Option<i64>, installed: Option<i64>| {
+            let mut binary = parse_binary_file_name(file, "testnet").unwrap();
+            binary.last_used = last_used.map(|days| now - chrono::Duration::days(days));
+            binary.provenance = installed.map(|days| Provenance {
+                build_type: BuildType::Release,
+                source_url: None,
+                archive: None,
+                download_blake3: None,
+                download_md5: None,
+                binary_blake3: None,
+                branch: None,
+                commit: None,
+                installed_at: now - chrono::Duration::days(days),
+                installed_by: None,
+                user: None,
+                command: None,
+            });
+            binary
+        };
+        let binaries = [
+            binary("sui-v1.38.0", Some(100), Some(200)),
+            binary("sui-v1.39.0", Some(5), Some(200)),
+            binary("sui-v1.40.0", None, Some(120)),
+            binary("sui-v1.41.0", None, None),
+            binary("sui-v1.42.0", Some(100), Some(100)),
+        ];
+        let defaults = DefaultVersions::from([(
+            "sui".to_string(),
+            ("testnet".to_string(), "v1.42.0".to_string(), false),
+        )]);
+
+        let unused = pick_unused(&binaries, &defaults, now, 90)
+            .into_iter()
+            .map(|(b, days)| (b.version.as_str(), days))
+            .collect::<Vec<_>>();
+        assert_eq!(unused, [("v1.38.0", 100), ("v1.40.0", 120)]);
+    }
+
+    #[test]
+    fn test_referenced_archives() {
+        let mut installed = parse_binary_file_name("sui-v1.40.1", "testnet").unwrap();
+        installed.provenance = Some(Provenance {
+            build_type: BuildType::Release,
+            source_url: None,
+            archive: Some("sui-testnet-v1.40.1-ubuntu-x86_64.tgz".to_string()),
+            download_blake3: None,
+            download_md5: None,
+            binary_blake3: None,
+            branch: None,
+            commit: None,
+            installed_at: chrono::Utc::now(),
+            installed_by: None,
+            user: None,
+            command: None,
+        });
+        let without_provenance = parse_binary_file_name("sui-v1.39.0", "testnet").unwrap();
+
+        let referenced = referenced_archives(&[installed, without_provenance]);
+        let dir = Path::new("release_archives");
+        assert!(is_referenced(
+            &referenced,
+            &dir.join("sui-testnet-v1.40.1-ubuntu-x86_64.tgz")
+        ));
+        assert!(!is_referenced(
+            &referenced,
+            &dir.join("sui-testnet-v1.39.0-ubuntu-x86_64.tgz")
+        ));
+    }
+}
@@ -0,0 +1,94 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Completions of the values of arguments that depend on what is installed, e.g. the versions
+//! `switch` can switch to. They only read the state files, as the shell waits for them.
+
+use std::collections::BTreeSet;
+use std::ffi::OsStr;
+
+use clap_complete::CompletionCandidate;
+
+use crate::state;
+use crate::types::BinaryVersion;
+
+/// Installed versions, as `binary@network-version` and `binary@network` for the latest one of
+/// the network, or `binary@branch` for nightly builds, with `+debug` for debug builds
+pub fn installed_specs(current: &OsStr) -> Vec<CompletionCandidate> {
+    let Ok(installed) = state::installed_binaries() else {
+        return Vec::new();
+    };
+    candidates(installed.binaries().iter().flat_map(specs), current)
+}
+
+/// Names of the installed binaries
+pub fn installed_names(current: &OsStr) -> Vec<CompletionCandidate> {
+    let Ok(installed) = state::installed_binaries() else {
+        return Vec::new();
+    };
+    candidates(
+        installed.binaries().iter().map(|b| b.binary_name.clone()),
+        current,
+    )
+}
+
+fn specs(binary: &BinaryVersion) -> Vec<String> {
+    let build = if binary.debug { "+debug" } else { "" };
+    let release = format!("{}@{}", binary.binary_name, binary.network_release);
+    if binary.version == "nightly" {
+        return vec![format!("{release}{build}")];
+    }
+    let version = format!("{release}-{}", binary.version.trim_start_matches('v'));
+    vec![format!("{release}{build}"), format!("{version}{build}")]
+}
+
+/// The values starting with what was typed, sorted and without duplicates
+fn candidates(values: impl Iterator<Item = String>, current: &OsStr) -> Vec<CompletionCandidate> {
+    let current = current.to_string_lossy();
+    values
+        .filter(|value| value.starts_with(current.as_ref()))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidates() {
+        let binary = |network: &str, version: &str, debug: bool| BinaryVersion {
+            binary_name: "sui".to_string(),
+            network_release: network.to_string(),
+            version: version.to_string(),
+            debug,
+            path: None,
+            provenance: None,
+            last_used: None,
+        };
+        let installed = [
+            binary("testnet", "v1.40.1", false),
+            binary("testnet", "v1.39.3", false),
+            binary("devnet", "v1.41.0", true),
+            binary("main", "nightly", false),
+        ];
+        let values = |current: &str| {
+            candidates(installed.iter().flat_map(specs), OsStr::new(current))
+                .iter()
+                .map(|c| c.get_value().to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            values("sui@t"),
+            ["sui@testnet", "sui@testnet-1.39.3", "sui@testnet-1.40.1"]
+        );
+        assert_eq!(values("sui@m"), ["sui@main"]);
+        assert_eq!(
+            values("sui@d"),
+            ["sui@devnet+debug", "sui@devnet-1.41.0+debug"]
+        );
+        assert!(values("walrus").is_empty());
+    }
+}
@@ -0,0 +1,191 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::env;
+use std::fmt::{self, Display, Formatter};
+use std::path::{Path, PathBuf};
+
+use crate::output;
+use crate::paths::get_default_bin_dir;
+
+/// A binary found in PATH before the one managed by suiup, so running it by name does not run
+/// the version selected with suiup
+#[derive(Debug, PartialEq)]
+pub struct Conflict {
+    pub binary: String,
+    /// Path of the binary that shadows the one managed by suiup
+    pub path: PathBuf,
+    pub source: InstallSource,
+}
+
+/// How a conflicting binary was most likely installed
+#[derive(Debug, PartialEq)]
+pub enum InstallSource {
+    Cargo,
+    Homebrew,
+    SystemPackage,
+    Unknown,
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let binary = &self.binary;
+        let path = self.path.display();
+        let default_bin = get_default_bin_dir();
+        let default_bin = default_bin.display();
+        match self.source {
+            InstallSource::Cargo => write!(
+                f,
+                "{binary} at {path} was installed with `cargo install` and is used instead of the one managed by suiup. Remove it with `cargo uninstall <package>` (see `cargo install --list`), or put {default_bin} before {} in PATH.",
+                self.path.parent().unwrap_or(&self.path).display()
+            ),
+            InstallSource::Homebrew => write!(
+                f,
+                "{binary} at {path} was installed with Homebrew and is used instead of the one managed by suiup. Remove it with `brew uninstall {binary}`."
+            ),
+            InstallSource::SystemPackage => write!(
+                f,
+                "{binary} at {path} was installed by a system package and is used instead of the one managed by suiup. Remove it with your package manager (e.g., `sudo apt remove {binary}`)."
+            ),
+            InstallSource::Unknown => write!(
+                f,
+                "{binary} at {path} is used instead of the one managed by suiup. Remove it, or put {default_bin} before {} in PATH.",
+                self.path.parent().unwrap_or(&self.path).display()
+            ),
+        }
+    }
+}
+
+/// Finds the binaries in suiup's default bin folder that resolve to another installation when
+/// run by name
+pub fn find_conflicts(binaries: &[&str]) -> Vec<Conflict> {
+    let path_dirs = env::var_os("PATH")
+        .map(|path| env::split_paths(&path).collect::<Vec<_>>())
+        .unwrap_or_default();
+    find_conflicts_in(binaries, &path_dirs, &get_default_bin_dir())
+}
+
+fn find_conflicts_in(
+    binaries: &[&str],
+    path_dirs: &[PathBuf],
+    default_bin: &Path,
+) -> Vec<Conflict> {
+    binaries
+        .iter()
+        .filter(|binary| default_bin.join(executable_name(binary)).is_file())
+        .filter_map(|binary| {
+            let (dir, path) = path_dirs.iter().find_map(|dir| {
+                let path = dir.join(executable_name(binary));
+                path.is_file().then_some((dir, path))
+            })?;
+            if same_dir(dir, default_bin) {
+                return None;
+            }
+            Some(Conflict {
+                binary: binary.to_string(),
+                source: install_source(&path),
+                path,
+            })
+        })
+        .collect()
+}
+
+/// Prints a warning for each binary shadowed by another installation
+pub fn warn_about_conflicts(binaries: &[&str]) {
+    for conflict in find_conflicts(binaries) {
+        eprintln!("\n{} {conflict}", output::warning_sign());
+    }
+}
+
+fn executable_name(binary: &str) -> String {
+    if cfg!(windows) {
+        format!("{binary}.exe")
+    } else {
+        binary.to_string()
+    }
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    a == b
+        || matches!(
+            (a.canonicalize(), b.canonicalize()),
+            (Ok(a), Ok(b)) if a == b
+        )
+}
+
+fn install_source(path: &Path) -> InstallSource {
+    // Homebrew links binaries from its Cellar into its bin folder
+    let resolved = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let resolved = resolved.to_string_lossy().replace('\\', "/");
+    let path = path.to_string_lossy().replace('\\', "/");
+
+    if path.contains("/.cargo/bin/") {
+        InstallSource::Cargo
+    } else if resolved.contains("/Cellar/")
+        || path.starts_with("/opt/homebrew/")
+        || path.starts_with("/home/linuxbrew/")
+    {
+        InstallSource::Homebrew
+    } else if ["/usr/bin/", "/bin/", "/usr/sbin/", "/snap/bin/"]
+        .iter()
+        .any(|dir| path.starts_with(dir))
+    {
+        InstallSource::SystemPackage
+    } else {
+        InstallSource::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_conflicts() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cargo_bin = tmp.path().join(".cargo").join("bin");
+        let default_bin = tmp.path().join("suiup-bin");
+        std::fs::create_dir_all(&cargo_bin).unwrap();
+        std::fs::create_dir_all(&default_bin).unwrap();
+        for (dir, binary) in [
+            (&cargo_bin, "sui"),
+            (&default_bin, "sui"),
+            (&default_bin, "walrus"),
+        ] {
+            std::fs::write(dir.join(executable_name(binary)), "").unwrap();
+        }
+
+        let conflicts = find_conflicts_in(
+            &["sui", "walrus", "mvr"],
+            &[cargo_bin.clone(), default_bin.clone()],
+            &default_bin,
+        );
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].binary, "sui");
+        assert_eq!(conflicts[0].source, InstallSource::Cargo);
+
+        let conflicts =
+            find_conflicts_in(&["sui"], &[default_bin.clone(), cargo_bin], &default_bin);
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_install_source() {
+        assert_eq!(
+            install_source(Path::new("/opt/homebrew/bin/sui")),
+            InstallSource::Homebrew
+        );
+        assert_eq!(
+            install_source(Path::new("/usr/bin/sui")),
+            InstallSource::SystemPackage
+        );
+        assert_eq!(
+            install_source(Path::new("/home/me/.cargo/bin/sui")),
+            InstallSource::Cargo
+        );
+        assert_eq!(
+            install_source(Path::new("/home/me/tools/sui")),
+            InstallSource::Unknown
+        );
+    }
+}
@@ -0,0 +1,220 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Versions marked deprecated or end-of-life by a metadata document, fetched from
+//! `deprecations_url` of the config file and cached for a day.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{anyhow, Error};
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::config::load_config;
+use crate::http;
+use crate::output;
+use crate::paths::get_suiup_cache_dir;
+use crate::state::write_file_atomically;
+use crate::types::Version;
+
+/// Used when `deprecations_url` is not set in the config file
+pub const DEFAULT_DEPRECATIONS_URL: &str =
+    "https://raw.githubusercontent.com/MystenLabs/suiup/main/deprecations.json";
+const CACHE_FILE: &str = "deprecations.json";
+/// How long the fetched document is used before fetching it again
+const MAX_AGE_HOURS: i64 = 24;
+/// The document is optional, do not hold up an install for it
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The metadata document
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct Deprecations {
+    #[serde(default)]
+    pub versions: Vec<Deprecation>,
+}
+
+/// A version, or all versions before one, marked deprecated
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Deprecation {
+    pub binary: String,
+    /// Network release it applies to, all of them if not set
+    pub network: Option<String>,
+    /// The deprecated version
+    pub version: Option<String>,
+    /// All versions older than this one are deprecated
+    pub before: Option<String>,
+    #[serde(default)]
+    pub status: DeprecationStatus,
+    /// Version to use instead
+    pub replacement: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum DeprecationStatus {
+    #[default]
+    Deprecated,
+    EndOfLife,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct CachedDeprecations {
+    url: String,
+    fetched_at: DateTime<Utc>,
+    deprecations: Deprecations,
+}
+
+impl Deprecation {
+    /// Whether it applies to `version` of `binary` from `network`. Debug builds are deprecated
+    /// along with release builds, nightly builds never are.
+    pub fn matches(&self, binary: &str, network: &str, version: &str) -> bool {
+        let binary = binary.strip_suffix("-debug").unwrap_or(binary);
+        if version == "nightly"
+            || self.binary != binary
+            || self.network.as_deref().is_some_and(|n| n != network)
+        {
+            return false;
+        }
+        let Ok(version) = version.parse::<Version>() else {
+            return false;
+        };
+        let parse = |v: &Option<String>| v.as_deref().and_then(|v| v.parse::<Version>().ok());
+        parse(&self.version).is_some_and(|v| v.matches(&version))
+            || parse(&self.before).is_some_and(|before| version < before)
+    }
+}
+
+fn cache_file() -> PathBuf {
+    get_suiup_cache_dir().join(CACHE_FILE)
+}
+
+/// URL of the metadata document, none if disabled with an empty `deprecations_url`. The one
+/// published with suiup is not fetched when the GitHub API is replaced, e.g. by a mock server.
+fn deprecations_url() -> Option<String> {
+    let url = load_config().ok()?.deprecations_url.or_else(|| {
+        http::github_api_override()
+            .is_none()
+            .then(|| DEFAULT_DEPRECATIONS_URL.to_string())
+    })?;
+    (!url.is_empty()).then_some(url)
+}
+
+fn read_cache(url: &str) -> Option<CachedDeprecations> {
+    let content = std::fs::read_to_string(cache_file()).ok()?;
+    let cached: CachedDeprecations = serde_json::from_str(&content).ok()?;
+    (cached.url == url).then_some(cached)
+}
+
+/// Fetches the metadata document again if the cached one is older than a day. Failures are
+/// ignored: the cached document, if any, is used instead.
+pub async fn refresh_deprecations() {
+    let Some(url) = deprecations_url() else {
+        return;
+    };
+    if http::is_offline()
+        || read_cache(&url).is_some_and(|cached| {
+            Utc::now() - cached.fetched_at < chrono::Duration::hours(MAX_AGE_HOURS)
+        })
+    {
+        return;
+    }
+    if let Err(e) = fetch_deprecations(&url).await {
+        debug!("Cannot fetch the deprecated versions from {url}: {e}");
+    }
+}
+
+async fn fetch_deprecations(url: &str) -> Result<(), Error> {
+    let response = http::get(url, None)?.timeout(FETCH_TIMEOUT).send().await?;
+    // No document means nothing is deprecated
+    let deprecations = if response.status() == StatusCode::NOT_FOUND {
+        Deprecations::default()
+    } else {
+        response
+            .error_for_status()?
+            .json()
+            .await
+            .map_err(|e| anyhow!("Invalid deprecations document: {e}"))?
+    };
+    let cached = CachedDeprecations {
+        url: url.to_string(),
+        fetched_at: Utc::now(),
+        deprecations,
+    };
+    std::fs::create_dir_all(get_suiup_cache_dir())?;
+    write_file_atomically(
+        &cache_file(),
+        serde_json::to_string_pretty(&cached)?.as_bytes(),
+    )
+}
+
+/// Prints a warning if `version` of `binary` from `network` is deprecated according to the
+/// cached metadata document
+pub fn warn_if_deprecated(binary: &str, network: &str, version: &str) {
+    let Some(cached) = deprecations_url().and_then(|url| read_cache(&url)) else {
+        return;
+    };
+    let Some(deprecation) = cached
+        .deprecations
+        .versions
+        .iter()
+        .find(|d| d.matches(binary, network, version))
+    else {
+        return;
+    };
+
+    let version = format!("v{}", version.trim_start_matches('v'));
+    let status = match deprecation.status {
+        DeprecationStatus::Deprecated => "is deprecated",
+        DeprecationStatus::EndOfLife => "has reached end of life",
+    };
+    eprintln!(
+        "\n{} {binary} {version} ({network}) {status}",
+        output::warning_sign()
+    );
+    if let Some(message) = &deprecation.message {
+        eprintln!("   {message}");
+    }
+    if let Some(replacement) = &deprecation.replacement {
+        let replacement = replacement.trim_start_matches('v');
+        let binary = binary.strip_suffix("-debug").unwrap_or(binary);
+        let spec = if network == "standalone" {
+            format!("{binary}@{replacement}")
+        } else {
+            format!("{binary}@{network}-{replacement}")
+        };
+        eprintln!("   Use v{replacement} instead: `suiup install {spec}`");
+    }
+    eprintln!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deprecation_matches() {
+        let deprecations: Deprecations = serde_json::from_str(
+            r#"{"versions": [
+                {"binary": "sui", "network": "testnet", "version": "1.39.0", "replacement": "1.39.3"},
+                {"binary": "walrus", "before": "1.20.0", "status": "end-of-life"}
+            ]}"#,
+        )
+        .unwrap();
+        let [sui, walrus] = &deprecations.versions[..] else {
+            panic!("expected two entries");
+        };
+        assert!(sui.matches("sui", "testnet", "v1.39.0"));
+        assert!(sui.matches("sui-debug", "testnet", "1.39.0"));
+        assert!(!sui.matches("sui", "mainnet", "1.39.0"));
+        assert!(!sui.matches("sui", "testnet", "1.39.3"));
+
+        assert_eq!(walrus.status, DeprecationStatus::EndOfLife);
+        assert!(walrus.matches("walrus", "mainnet", "v1.19.2"));
+        assert!(!walrus.matches("walrus", "mainnet", "v1.20.0"));
+        assert!(!walrus.matches("walrus", "main", "nightly"));
+    }
+}
@@ -0,0 +1,250 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{bail, Error};
+use clap::ValueEnum;
+use comfy_table::Table;
+
+use crate::commands::{table_format, BinaryName};
+use crate::handlers::changelog::{protocol_version, release_body};
+use crate::handlers::cleanup::format_file_size;
+use crate::handlers::info::{installed_matches, installed_path};
+use crate::handlers::integrity::sha256_of_file;
+use crate::http;
+use crate::types::{BinaryVersion, BuildType, Network, Repo};
+
+/// What is known about one of the compared binaries
+struct Inspected {
+    spec: String,
+    binary: BinaryVersion,
+    path: String,
+    size: Option<u64>,
+    sha256: Option<String>,
+    version_output: String,
+    protocol_version: Option<u64>,
+}
+
+/// Handles the `diff` command: compares two installed binaries
+pub async fn handle_diff(a: &str, b: &str, github_token: Option<String>) -> Result<(), Error> {
+    let a = inspect(a, github_token.as_deref()).await?;
+    let b = inspect(b, github_token.as_deref()).await?;
+
+    let mut rows = Vec::new();
+    let mut differences = 0;
+    let mut row = |field: &str, a: String, b: String, diff: Option<String>| {
+        if a != b {
+            differences += 1;
+        }
+        let diff = diff.unwrap_or_else(|| if a == b { "same" } else { "differs" }.to_string());
+        rows.push(vec![field.to_string(), a, b, diff]);
+    };
+
+    row(
+        "Version",
+        a.binary.version.clone(),
+        b.binary.version.clone(),
+        None,
+    );
+    row(
+        "Release/Branch",
+        a.binary.network_release.clone(),
+        b.binary.network_release.clone(),
+        None,
+    );
+    row(
+        "Size",
+        a.size.map(format_file_size).unwrap_or_else(unknown),
+        b.size.map(format_file_size).unwrap_or_else(unknown),
+        match (a.size, b.size) {
+            (Some(a), Some(b)) => Some(size_change(a, b)),
+            _ => None,
+        },
+    );
+    row(
+        "sha256",
+        a.sha256.clone().unwrap_or_else(unknown),
+        b.sha256.clone().unwrap_or_else(unknown),
+        None,
+    );
+    row(
+        "--version",
+        a.version_output.clone(),
+        b.version_output.clone(),
+        None,
+    );
+    row(
+        "Protocol version",
+        a.protocol_version
+            .map(|v| v.to_string())
+            .unwrap_or_else(unknown),
+        b.protocol_version
+            .map(|v| v.to_string())
+            .unwrap_or_else(unknown),
+        match (a.protocol_version, b.protocol_version) {
+            (Some(a), Some(b)) if a != b => Some(format!("{:+}", b as i64 - a as i64)),
+            (Some(_), Some(_)) => None,
+            _ => Some(String::new()),
+        },
+    );
+    row(
+        "Build type",
+        build_type(&a.binary),
+        build_type(&b.binary),
+        None,
+    );
+    row("Commit", commit(&a.binary), commit(&b.binary), None);
+    row("Source", source(&a.binary), source(&b.binary), None);
+    rows.push(vec![
+        "Path".to_string(),
+        a.path.clone(),
+        b.path.clone(),
+        String::new(),
+    ]);
+
+    let mut table = Table::new();
+    table
+        .load_preset(table_format())
+        .set_header(vec!["", a.spec.as_str(), b.spec.as_str(), ""])
+        .add_rows(rows);
+    println!("{table}");
+
+    if a.sha256.is_some() && a.sha256 == b.sha256 {
+        println!("\nThe binaries are identical.");
+    } else {
+        println!("\n{differences} of the compared fields differ.");
+    }
+    Ok(())
+}
+
+/// Finds the only installed binary matching `spec` and reads its details
+async fn inspect(spec: &str, github_token: Option<&str>) -> Result<Inspected, Error> {
+    let matches = installed_matches(spec)?;
+    let (binary, system) = match matches.as_slice() {
+        [] => {
+            bail!("No installed binary matches {spec}. Use `suiup show` to see installed binaries.")
+        }
+        [only] => only.clone(),
+        _ => {
+            let list = matches
+                .iter()
+                .map(|(b, _)| {
+                    format!(
+                        "{}@{}-{}",
+                        b.binary_name,
+                        b.network_release,
+                        b.version.trim_start_matches('v')
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            bail!("{spec} matches several installed binaries ({list}). Pick one with its release and version, e.g. `sui@testnet-1.40.1`")
+        }
+    };
+    let path = installed_path(&binary, system);
+    let size = std::fs::metadata(&path).ok().map(|m| m.len());
+    let sha256 = size.and_then(|_| sha256_of_file(&path).ok());
+    Ok(Inspected {
+        spec: spec.to_string(),
+        version_output: version_output(&path),
+        protocol_version: release_protocol_version(&binary, github_token).await,
+        path: path.display().to_string(),
+        binary,
+        size,
+        sha256,
+    })
+}
+
+/// First line the binary prints with `--version`
+fn version_output(path: &Path) -> String {
+    match Command::new(path).arg("--version").output() {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+        Ok(output) => format!("failed ({})", output.status),
+        Err(e) => format!("cannot run: {e}"),
+    }
+}
+
+/// Protocol version from the notes of the Sui release the binary comes from. Unknown for other
+/// binaries, nightly builds and when offline.
+async fn release_protocol_version(
+    binary: &BinaryVersion,
+    github_token: Option<&str>,
+) -> Option<u64> {
+    let repo = BinaryName::from_str(&binary.binary_name, true).ok()?.repo();
+    let network = binary.network_release.as_str();
+    if repo != Repo::Sui || network.parse::<Network>().is_err() || http::is_offline() {
+        return None;
+    }
+    let tag = format!("{network}-v{}", binary.version.trim_start_matches('v'));
+    let notes = release_body(&repo, &tag, github_token).await.ok()?;
+    protocol_version(&notes)
+}
+
+fn unknown() -> String {
+    "unknown".to_string()
+}
+
+fn build_type(binary: &BinaryVersion) -> String {
+    match binary.provenance.as_ref().map(|p| p.build_type) {
+        Some(BuildType::Release) => "release",
+        Some(BuildType::Debug) => "debug",
+        Some(BuildType::Nightly) => "nightly",
+        None => "unknown",
+    }
+    .to_string()
+}
+
+fn commit(binary: &BinaryVersion) -> String {
+    binary
+        .provenance
+        .as_ref()
+        .and_then(|p| p.commit.clone())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn source(binary: &BinaryVersion) -> String {
+    binary
+        .provenance
+        .as_ref()
+        .and_then(|p| p.source_url.clone())
+        .unwrap_or_else(unknown)
+}
+
+/// Size difference from `a` to `b`, e.g. `+1.20 MB (+2.5%)`
+fn size_change(a: u64, b: u64) -> String {
+    if a == b {
+        return "same".to_string();
+    }
+    let sign = if b > a { "+" } else { "-" };
+    let percent = if a == 0 {
+        String::new()
+    } else {
+        format!(" ({:+.1}%)", (b as f64 - a as f64) / a as f64 * 100.0)
+    };
+    format!("{sign}{}{percent}", format_file_size(a.abs_diff(b)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_change() {
+        assert_eq!(size_change(1000, 1000), "same");
+        assert_eq!(
+            size_change(1000, 1100),
+            format!("+{} (+10.0%)", format_file_size(100))
+        );
+        assert_eq!(
+            size_change(1000, 750),
+            format!("-{} (-25.0%)", format_file_size(250))
+        );
+    }
+}
@@ -1,22 +1,30 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::config::{config_file, load_config};
+use crate::handlers::cleanup::make_room_in_cache;
+use crate::handlers::install::remove_cached;
+use crate::handlers::integrity;
+use crate::handlers::integrity::sha256_of_file;
 use crate::handlers::release::{
     ensure_version_prefix, find_last_release_by_network, find_networks_with_version,
 };
+use crate::handlers::spinner;
 use crate::handlers::version::extract_version_from_release;
-use crate::types::Repo;
+use crate::handlers::{archive_extension, gh};
+use crate::http;
+use crate::interrupt::Partial;
+use crate::output::{self, ProgressEvent};
+use crate::platform::{current_target, Target};
+use crate::status;
+use crate::timings;
+use crate::types::{Asset, Network, Repo, Version};
 use crate::{handlers::release::release_list, paths::release_archive_dir, types::Release};
 use anyhow::{anyhow, bail, Error};
 use futures_util::StreamExt;
-use indicatif::{HumanBytes, ProgressBar, ProgressStyle};
-use md5::Context;
-use reqwest::{
-    header::{HeaderMap, HeaderValue, USER_AGENT},
-    Client,
-};
-use std::fs::File;
-use std::io::Read;
+use indicatif::{HumanBytes, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use reqwest::RequestBuilder;
+use std::path::Path;
 use std::{cmp::min, io::Write, path::PathBuf, time::Instant};
 
 use tracing::debug;
@@ -68,14 +76,13 @@ fn generate_network_suggestions_error(
         }
     } else {
         // For latest release requests, check what networks are available
-        let available_networks: Vec<String> = ["testnet", "devnet", "mainnet"]
-            .iter()
-            .filter(|&net| {
+        let available_networks: Vec<Network> = Network::ALL
+            .into_iter()
+            .filter(|net| {
                 releases
                     .iter()
-                    .any(|r| r.assets.iter().any(|a| a.name.contains(net)))
+                    .any(|r| r.assets.iter().any(|a| a.name.contains(net.as_str())))
             })
-            .map(|s| s.to_string())
             .collect();
 
         if !available_networks.is_empty() {
@@ -95,101 +102,144 @@ fn generate_network_suggestions_error(
     }
 }
 
-/// Detects the current OS and architecture
-pub fn detect_os_arch() -> Result<(String, String), Error> {
-    let os = match whoami::platform() {
-        whoami::Platform::Linux => "ubuntu",
-        whoami::Platform::Windows => "windows",
-        whoami::Platform::MacOS => "macos",
-        _ => bail!("Unsupported OS. Supported only: Linux, Windows, MacOS"),
-    };
-    let arch = match std::env::consts::ARCH {
-        "x86_64" => "x86_64",
-        "aarch64" if os == "macos" => "arm64",
-        "aarch64" => "aarch64",
-        _ => bail!("Unsupported architecture. Supported only: x86_64, aarch64"),
-    };
-
-    println!("Detected: {os}-{arch}...");
-    Ok((os.to_string(), arch.to_string()))
+/// A release archive downloaded into the release archives folder
+#[derive(Debug, Clone)]
+pub struct DownloadedAsset {
+    /// File name of the archive in the release archives folder
+    pub name: String,
+    /// URL the archive was downloaded from
+    pub url: String,
 }
 
 /// Downloads a release with a specific version
-/// The network is used to filter the release
+/// The network is used to filter the release. With `force`, the cached archive is downloaded
+/// again. The asset is the one of `target`, or of the detected platform.
 pub async fn download_release_at_version(
     repo: Repo,
     network: &str,
     version: &str,
     github_token: Option<String>,
-) -> Result<String, anyhow::Error> {
-    let (os, arch) = detect_os_arch()?;
-
-    // Ensure version has 'v' prefix for GitHub release tags
-    let version = ensure_version_prefix(version);
-
-    let tag = format!("{}-{}", network, version);
-
-    println!("Searching for release with tag: {}...", tag);
-    let client = reqwest::Client::new();
-    let mut headers = HeaderMap::new();
-
-    let releases = release_list(&repo, github_token.clone()).await?.0;
-
-    if let Some(release) = releases
-        .iter()
-        .find(|r| r.assets.iter().any(|a| a.name.contains(&tag)))
-    {
-        download_asset_from_github(release, &os, &arch, github_token).await
-    } else {
-        headers.insert(USER_AGENT, HeaderValue::from_static("suiup"));
-
-        // Add authorization header if token is provided
-        if let Some(token) = &github_token {
-            headers.insert(
-                "Authorization",
-                HeaderValue::from_str(&format!("token {}", token)).unwrap(),
-            );
-        }
-
-        let url = format!("https://api.github.com/repos/{repo}/releases/tags/{}", tag);
-        let response = client.get(&url).headers(headers).send().await?;
-
-        if !response.status().is_success() {
-            return Err(generate_network_suggestions_error(
-                &repo,
-                &releases,
-                Some(&version),
-                network,
-            ));
-        }
-
-        let release: Release = response.json().await?;
-        download_asset_from_github(&release, &os, &arch, github_token).await
+    force: bool,
+    target: Option<Target>,
+) -> Result<DownloadedAsset, anyhow::Error> {
+    let target = current_target(target)?;
+    if http::is_offline() {
+        let version = ensure_version_prefix(version);
+        return cached_release_archive(&repo, network, Some(&version), &target);
     }
+    let release = find_release(&repo, network, Some(version), github_token.clone()).await?;
+    download_asset_from_github(&repo, &release, &target, github_token, force).await
 }
 
-/// Downloads the latest release for a given network
+/// Downloads the latest release for a given network. With `force`, the cached archive is
+/// downloaded again. The asset is the one of `target`, or of the detected platform.
 pub async fn download_latest_release(
     repo: Repo,
     network: &str,
     github_token: Option<String>,
-) -> Result<String, anyhow::Error> {
-    println!("Downloading release list");
-    debug!("Downloading release list for repo: {repo} and network: {network}");
-    let releases = release_list(&repo, github_token.clone()).await?;
+    force: bool,
+    target: Option<Target>,
+) -> Result<DownloadedAsset, anyhow::Error> {
+    debug!("Downloading release list for repo: {repo:?} and network: {network}");
+    if http::is_offline() {
+        return cached_release_archive(&repo, network, None, &current_target(target)?);
+    }
+    let release = find_release(&repo, network, None, github_token.clone()).await?;
+    let target = current_target(target)?;
+    download_asset_from_github(&repo, &release, &target, github_token, force).await
+}
 
-    let (os, arch) = detect_os_arch()?;
+/// Finds the release of a network with a specific version, or the latest one
+pub async fn find_release(
+    repo: &Repo,
+    network: &str,
+    version: Option<&str>,
+    github_token: Option<String>,
+) -> Result<Release, Error> {
+    let releases = release_list(repo, github_token.clone()).await?.0;
+    find_release_in(repo, releases, network, version, github_token).await
+}
 
-    let last_release = find_last_release_by_network(releases.0.clone(), network)
-        .await
-        .ok_or_else(|| generate_network_suggestions_error(&repo, &releases.0, None, network))?;
+/// Finds the release of a network with a specific version, or the latest one, in `releases`.
+/// A release missing from the list is looked up by its tag.
+pub async fn find_release_in(
+    repo: &Repo,
+    releases: Vec<Release>,
+    network: &str,
+    version: Option<&str>,
+    github_token: Option<String>,
+) -> Result<Release, Error> {
+    let Some(version) = version else {
+        let last_release = find_last_release_by_network(releases.clone(), network)
+            .await
+            .ok_or_else(|| generate_network_suggestions_error(repo, &releases, None, network))?;
+        status!(
+            "Last {network} release: {}",
+            extract_version_from_release(&last_release.assets[0].name)?
+        );
+        return Ok(last_release);
+    };
+
+    // Ensure version has 'v' prefix for GitHub release tags
+    let version = ensure_version_prefix(version);
+    let wanted = format!("{network}-{version}").parse::<Version>()?;
+    let tag = wanted.to_string();
+    status!("Searching for release with tag: {}...", tag);
 
-    println!(
-        "Last {network} release: {}",
-        extract_version_from_release(&last_release.assets[0].name)?
+    // A version like `1.40` resolves to the newest 1.40.x release
+    if let Some((_, release)) = releases
+        .iter()
+        .filter_map(|r| Some((network_release_version(r, network)?, r)))
+        .filter(|(release_version, _)| wanted.matches(release_version))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+    {
+        return Ok(release.clone());
+    }
+    if !wanted.is_exact() {
+        return Err(generate_network_suggestions_error(
+            repo,
+            &releases,
+            Some(&version),
+            network,
+        ));
+    }
+    let _timer = timings::phase("resolve");
+    let repository = repo.repository()?;
+    if gh::enabled() {
+        match gh::api(&format!("repos/{repository}/releases/tags/{tag}")).await {
+            Ok(release) => return Ok(release),
+            Err(e) => eprintln!(
+                "{} {e}. Resolving release {tag} without gh.",
+                output::warning_sign()
+            ),
+        }
+    }
+    let url = format!(
+        "{}/repos/{repository}/releases/tags/{tag}",
+        http::github_api_url()?
     );
+    let pb = spinner("resolve", format!("Resolving release {tag}..."));
+    let request = http::get(&url, github_token.as_deref())?;
+    let response = http::send(request, &format!("resolve release {tag}")).await?;
+    pb.finish_and_clear();
+
+    if !response.status().is_success() {
+        return Err(generate_network_suggestions_error(
+            repo,
+            &releases,
+            Some(&version),
+            network,
+        ));
+    }
+    Ok(response.json().await?)
+}
 
-    download_asset_from_github(&last_release, &os, &arch, github_token).await
+/// Version of a release of `network`, from the names of its assets, e.g.
+/// `sui-testnet-v1.40.1-ubuntu-x86_64.tgz`
+fn network_release_version(release: &Release, network: &str) -> Option<Version> {
+    let marker = format!("{network}-v");
+    let asset = release.assets.iter().find(|a| a.name.contains(&marker))?;
+    extract_version_from_release(&asset.name).ok()?.parse().ok()
 }
 
 pub async fn download_file(
@@ -198,153 +248,434 @@ pub async fn download_file(
     name: &str,
     github_token: Option<String>,
 ) -> Result<String, Error> {
-    let client = Client::new();
+    download_request(
+        || http::get(url, github_token.as_deref()),
+        download_to,
+        name,
+    )
+    .await
+}
 
-    // Start with a basic request
-    let mut request = client.get(url).header("User-Agent", "suiup");
+/// Downloads the response of a prepared request (e.g. with extra headers) to `download_to`,
+/// reusing a cached copy of the same size. The request is only prepared when not offline.
+pub async fn download_request(
+    request: impl FnOnce() -> Result<RequestBuilder, Error>,
+    download_to: &PathBuf,
+    name: &str,
+) -> Result<String, Error> {
+    if http::is_offline() {
+        if !download_to.exists() {
+            return Err(http::offline_error(&format!("download {name}")));
+        }
+        status!("Found {name} in cache (offline)");
+        return Ok(name.to_string());
+    }
+    let request = request()?;
 
-    // Add authorization header if token is provided and the URL is from GitHub
-    if let Some(token) = github_token {
-        if url.contains("github.com") {
-            request = request.header("Authorization", format!("token {}", token));
+    // A cached copy is checked against the size of a HEAD request, without fetching the file
+    if download_to.exists() {
+        let size = {
+            let _timer = timings::phase("download");
+            remote_size(&request).await
+        };
+        if let Some(size) = size {
+            if cached_copy_matches(download_to, size, None).await? {
+                status!("Using cached archive {name}");
+                return Ok(name.to_string());
+            }
         }
     }
 
-    let response = request.send().await?;
+    let _slot = http::download_slot().await?;
+    let what = format!("download {name}");
+    let mut attempt = 1;
+    loop {
+        let Some(this_attempt) = request.try_clone() else {
+            bail!("Cannot {what}: the request cannot be sent");
+        };
+        let response = {
+            let _timer = timings::phase("download");
+            http::send(this_attempt, &what).await?
+        };
+
+        let response = response.error_for_status();
+
+        if let Err(ref e) = response {
+            bail!("Encountered unexpected error: {e}");
+        }
+
+        let response = response.unwrap();
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to download: {}", response.status()));
+        }
+
+        let mut total_size = response.content_length().unwrap_or(0);
+        //walrus is on google storage, so different content length header
+        if total_size == 0 {
+            total_size = response
+                .headers()
+                .get("x-goog-stored-content-length")
+                .and_then(|c| c.to_str().ok())
+                .and_then(|c| c.parse::<u64>().ok())
+                .unwrap_or(0);
+        }
 
-    let response = response.error_for_status();
+        // Servers that do not answer HEAD requests tell the size when the download starts
+        if download_to.exists() {
+            if cached_copy_matches(download_to, total_size, None).await? {
+                status!("Using cached archive {name}");
+                return Ok(name.to_string());
+            }
+            status!("{name} changed in the cache, re-downloading...");
+            std::fs::remove_file(download_to)?;
+        }
 
-    if let Err(ref e) = response {
-        bail!("Encountered unexpected error: {e}");
+        make_room_in_cache(download_to, total_size)?;
+
+        // Written next to the archive and renamed once complete, so that an interrupted
+        // download is never taken for a cached archive
+        let part_file = part_path(download_to);
+        let part = Partial::new(&part_file);
+        // A connection lost in the middle of the download starts it again
+        match write_response(response, name, &part_file, total_size).await {
+            Ok(()) => {
+                std::fs::rename(&part_file, download_to).map_err(|e| {
+                    anyhow!("Cannot move the download to {}: {e}", download_to.display())
+                })?;
+                part.complete();
+                break;
+            }
+            Err(e)
+                if http::is_transient(&e)
+                    && http::retry_after_failure(&what, &e, attempt).await? =>
+            {
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
     }
 
-    let response = response.unwrap();
+    integrity::record_download(download_to).await?;
+    Ok(name.to_string())
+}
 
+/// Size of the file a download request fetches, from a HEAD request. `None` if the request
+/// fails or the server does not tell the size.
+async fn remote_size(request: &RequestBuilder) -> Option<u64> {
+    let mut head = request.try_clone()?.build().ok()?;
+    *head.method_mut() = reqwest::Method::HEAD;
+    let response = http::client().ok()?.execute(head).await.ok()?;
     if !response.status().is_success() {
-        return Err(anyhow!("Failed to download: {}", response.status()));
+        return None;
     }
+    // The body of a HEAD response is empty, the size is only in the headers. Walrus is on
+    // Google Cloud Storage, which may send the size in its own header.
+    ["content-length", "x-goog-stored-content-length"]
+        .into_iter()
+        .find_map(|header| response.headers().get(header)?.to_str().ok()?.parse().ok())
+        .filter(|size| *size > 0)
+}
 
-    let mut total_size = response.content_length().unwrap_or(0);
-    //walrus is on google storage, so different content length header
-    if total_size == 0 {
-        total_size = response
-            .headers()
-            .get("x-goog-stored-content-length")
-            .and_then(|c| c.to_str().ok())
-            .and_then(|c| c.parse::<u64>().ok())
-            .unwrap_or(0);
+/// Whether a cached download is complete and unchanged: it has the `size` the server or the
+/// GitHub API reports, and the checksum recorded when it was downloaded. Without a recorded
+/// checksum, it is compared with the `digest` published for the release asset, if any.
+async fn cached_copy_matches(path: &Path, size: u64, digest: Option<&str>) -> Result<bool, Error> {
+    if size == 0 || !path.metadata().is_ok_and(|m| m.len() == size) {
+        return Ok(false);
     }
-
-    if download_to.exists() {
-        if download_to.metadata()?.len() == total_size {
-            // Check md5 if .md5 file exists
-            let md5_path = download_to.with_extension("md5");
-            if md5_path.exists() {
-                let mut file = File::open(download_to)?;
-                let mut hasher = Context::new();
-                let mut buffer = [0u8; 8192];
-                loop {
-                    let n = file.read(&mut buffer)?;
-                    if n == 0 {
-                        break;
-                    }
-                    hasher.consume(&buffer[..n]);
-                }
-                let result = hasher.finalize();
-                let local_md5 = format!("{:x}", result);
-                let expected_md5 = std::fs::read_to_string(md5_path)?.trim().to_string();
-                if local_md5 == expected_md5 {
-                    println!("Found {name} in cache, md5 verified");
-                    return Ok(name.to_string());
-                } else {
-                    println!("MD5 mismatch for {name}, re-downloading...");
-                }
-            } else {
-                println!("Found {name} in cache (no md5 to check)");
-                return Ok(name.to_string());
+    Ok(match integrity::check_cached(path).await? {
+        Some(unchanged) => unchanged,
+        None => match digest.and_then(|digest| digest.strip_prefix("sha256:")) {
+            Some(expected) => {
+                let _timer = timings::phase("verify");
+                let path = path.to_path_buf();
+                tokio::task::spawn_blocking(move || sha256_of_file(&path))
+                    .await
+                    .map_err(|e| anyhow!("Hashing task failed: {e}"))??
+                    .eq_ignore_ascii_case(expected)
             }
-        }
-        std::fs::remove_file(download_to)?;
+            None => true,
+        },
+    })
+}
+
+/// Downloads a release asset with `gh release download` if `gh_cli` is enabled. Returns false,
+/// after a warning, if gh failed, for the caller to download the asset itself.
+pub(crate) async fn gh_download(
+    repo: &Repo,
+    tag: &str,
+    asset: &str,
+    size: u64,
+    download_to: &Path,
+) -> Result<bool, Error> {
+    if !gh::enabled() {
+        return Ok(false);
     }
+    if size != 0 && download_to.metadata().is_ok_and(|m| m.len() == size) {
+        status!("Found {asset} in cache");
+        return Ok(true);
+    }
+    let part_file = part_path(download_to);
+    let part = Partial::new(&part_file);
+    let pb = spinner("download", format!("Downloading {asset} with gh..."));
+    let downloaded = {
+        let _timer = timings::phase("download");
+        gh::release_download(repo, tag, asset, &part_file).await
+    };
+    pb.finish_and_clear();
+    if let Err(e) = downloaded {
+        eprintln!(
+            "{} {e}. Downloading {asset} without gh.",
+            output::warning_sign()
+        );
+        return Ok(false);
+    }
+    std::fs::rename(&part_file, download_to)
+        .map_err(|e| anyhow!("Cannot move the download to {}: {e}", download_to.display()))?;
+    part.complete();
+    timings::add_bytes("download", download_to.metadata().map_or(0, |m| m.len()));
+    integrity::record_download(download_to).await?;
+    status!("Downloaded {asset} with gh");
+    Ok(true)
+}
+
+/// Where an archive is downloaded to before it is complete, e.g. `sui.tgz.part`
+fn part_path(download_to: &Path) -> PathBuf {
+    let mut name = download_to.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    download_to.with_file_name(name)
+}
+
+/// Finds a release archive in the archive cache, for offline installs. Without a version, the
+/// newest cached release of the network is used.
+pub(crate) fn cached_release_archive(
+    repo: &Repo,
+    network: &str,
+    version: Option<&str>,
+    target: &Target,
+) -> Result<DownloadedAsset, Error> {
+    let config = load_config()?;
+    let asset_pattern = config.asset_pattern(*repo);
+    // Renamed assets may not start with the binary name
+    let prefix = match asset_pattern {
+        Some(_) => String::new(),
+        None => format!("{}-{network}-", repo.binary_name()),
+    };
+    let wanted = version.map(str::parse::<Version>).transpose()?;
+    let mut archives = std::fs::read_dir(release_archive_dir())
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|name| {
+            name.starts_with(&prefix)
+                && (asset_pattern.is_some() || archive_extension(name).is_some())
+                && target.matches_release_asset(asset_pattern, Some(network), name)
+        })
+        .filter_map(|name| {
+            let release = extract_version_from_release(&name).ok()?;
+            Some((release.parse::<Version>().ok()?, name))
+        })
+        .filter(|(release, _)| wanted.as_ref().is_none_or(|v| v.matches(release)))
+        .collect::<Vec<_>>();
+    archives.sort();
+
+    let Some((_, name)) = archives.pop() else {
+        let release = match version {
+            Some(version) => format!("the {} {network}-{version} release", repo.binary_name()),
+            None => format!("the latest {} {network} release", repo.binary_name()),
+        };
+        return Err(http::offline_error(&format!(
+            "download {release} ({target}), it is not in the archive cache"
+        )));
+    };
+    status!("Using {name} from the archive cache (offline)");
+    let url = release_archive_dir().join(&name).display().to_string();
+    Ok(DownloadedAsset { name, url })
+}
 
+/// Writes the body of a download response to `download_to`, showing its progress, or reporting
+/// it every percent (every MiB when the size is unknown) with `--progress json`
+async fn write_response(
+    response: reqwest::Response,
+    name: &str,
+    download_to: &Path,
+    total_size: u64,
+) -> Result<(), Error> {
     let pb = ProgressBar::new(total_size);
-    pb.set_style(ProgressStyle::default_bar()
+    let style = ProgressStyle::default_bar()
         .template("Downloading release: {spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta}) {msg}")
         .unwrap()
-        .progress_chars("=>-"));
+        .progress_chars("=>-");
+    pb.set_style(if output::is_plain() {
+        style.tick_strings(&["-", "\\", "|", "/", " "])
+    } else {
+        style
+    });
+    if output::hide_progress_bars() {
+        pb.set_draw_target(ProgressDrawTarget::hidden());
+    }
+    let mut progress = DownloadProgress::new(name, total_size);
+    progress.reported.emit();
 
     let mut file = std::fs::File::create(download_to)?;
-    let mut downloaded: u64 = 0;
-    let mut stream = response.bytes_stream();
+    let mut stream = std::pin::pin!(http::throttled(response.bytes_stream()));
     let start = Instant::now();
+    let _timer = timings::phase("download");
 
     while let Some(item) = stream.next().await {
-        let chunk = item?;
+        let chunk = match item {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                pb.abandon();
+                return Err(e.into());
+            }
+        };
         file.write_all(&chunk)?;
-        let new = min(downloaded + (chunk.len() as u64), total_size);
-        downloaded = new;
-        pb.set_position(new);
+        timings::add_bytes("download", chunk.len() as u64);
+        if let Some(event) = progress.add(chunk.len() as u64) {
+            event.emit();
+        }
+        pb.set_position(progress.position());
 
         let elapsed = start.elapsed().as_secs_f64();
         if elapsed > 0.0 {
-            let speed = downloaded as f64 / elapsed;
+            let speed = progress.downloaded as f64 / elapsed;
             pb.set_message(format!("Speed: {}/s", HumanBytes(speed as u64)));
         }
     }
 
     pb.finish_with_message("Download complete");
+    Ok(())
+}
 
-    // After download, check md5 if .md5 file exists
-    let md5_path = download_to.with_extension("md5");
-    if md5_path.exists() {
-        let mut file = File::open(download_to)?;
-        let mut hasher = Context::new();
-        let mut buffer = [0u8; 8192];
-        loop {
-            let n = file.read(&mut buffer)?;
-            if n == 0 {
-                break;
-            }
-            hasher.consume(&buffer[..n]);
+/// Bytes of a download, and the last progress event reported with `--progress json`: one is
+/// reported every percent, or every MiB when the server does not tell the size
+struct DownloadProgress<'a> {
+    name: &'a str,
+    /// 0 when the size is unknown
+    total_size: u64,
+    downloaded: u64,
+    reported: ProgressEvent<'a>,
+}
+
+impl<'a> DownloadProgress<'a> {
+    fn new(name: &'a str, total_size: u64) -> Self {
+        DownloadProgress {
+            name,
+            total_size,
+            downloaded: 0,
+            reported: ProgressEvent::new("download", name).bytes(0, total_size),
         }
-        let result = hasher.finalize();
-        let local_md5 = format!("{:x}", result);
-        let expected_md5 = std::fs::read_to_string(md5_path)?.trim().to_string();
-        if local_md5 != expected_md5 {
-            return Err(anyhow!(format!(
-                "MD5 check failed for {}: expected {}, got {}",
-                name, expected_md5, local_md5
-            )));
-        } else {
-            println!("MD5 check passed for {name}");
+    }
+
+    /// Counts `len` more bytes, returning the event to report if one is due
+    fn add(&mut self, len: u64) -> Option<&ProgressEvent<'a>> {
+        self.downloaded += len;
+        let event =
+            ProgressEvent::new("download", self.name).bytes(self.downloaded, self.total_size);
+        let due = match event.percent {
+            Some(percent) => self.reported.percent != Some(percent),
+            None => self.downloaded >= self.reported.bytes.unwrap_or(0) + 1024 * 1024,
+        };
+        if !due {
+            return None;
         }
+        self.reported = event;
+        Some(&self.reported)
     }
 
-    Ok(name.to_string())
+    /// Position of the progress bar, which does not go past the size told by the server
+    fn position(&self) -> u64 {
+        match self.total_size {
+            0 => self.downloaded,
+            total_size => min(self.downloaded, total_size),
+        }
+    }
+}
+
+/// Picks the asset of the release for the target, matching the asset pattern configured for
+/// the binary if there is one
+pub fn select_asset<'a>(
+    repo: &Repo,
+    release: &'a Release,
+    target: &Target,
+) -> Result<&'a Asset, Error> {
+    let config = load_config()?;
+    let asset_pattern = config.asset_pattern(*repo);
+    release
+        .assets
+        .iter()
+        .find(|a| target.matches_release_asset(asset_pattern, None, &a.name))
+        .ok_or_else(|| match asset_pattern {
+            Some(pattern) => anyhow!(
+                "No asset of the release matches the asset pattern `{pattern}` set for {} in {}",
+                repo.binary_name(),
+                config_file().display()
+            ),
+            None => target.no_asset_error(release.assets.iter().map(|a| a.name.as_str())),
+        })
 }
 
-/// Downloads the archived release from GitHub and returns the file name
+/// Downloads the archived release from GitHub and returns the file name and source URL
 /// The `network, os, and arch` parameters are used to retrieve the correct release for the target
 /// architecture and OS
 async fn download_asset_from_github(
+    repo: &Repo,
     release: &Release,
-    os: &str,
-    arch: &str,
+    target: &Target,
     github_token: Option<String>,
-) -> Result<String, anyhow::Error> {
-    let asset = release
-        .assets
-        .iter()
-        .find(|&a| a.name.contains(arch) && a.name.contains(os.to_string().to_lowercase().as_str()))
-        .ok_or_else(|| anyhow!("Asset not found for {os}-{arch}"))?;
-
+    force: bool,
+) -> Result<DownloadedAsset, anyhow::Error> {
+    let asset = select_asset(repo, release, target)?;
     let url = asset.clone().browser_download_url;
     let name = asset.clone().name;
     let path = release_archive_dir();
     let mut file_path = path.clone();
     file_path.push(&asset.name);
 
-    download_file(&url, &file_path, &name, github_token).await
+    if force {
+        remove_cached(&file_path)?;
+    } else if cached_copy_matches(&file_path, asset.size, asset.digest.as_deref()).await? {
+        // The API tells the size of the asset, no request is needed to reuse the cached copy
+        status!("Using cached archive {name}");
+        if let Some(digest) = &asset.digest {
+            integrity::verify_digest(&file_path, digest).await?;
+        }
+        return Ok(DownloadedAsset { name, url });
+    }
+    if let Some(tag) = &release.tag_name {
+        if gh::enabled() {
+            make_room_in_cache(&file_path, asset.size)?;
+        }
+        if gh_download(repo, tag, &asset.name, asset.size, &file_path).await? {
+            if let Some(digest) = &asset.digest {
+                integrity::verify_digest(&file_path, digest).await?;
+            }
+            return Ok(DownloadedAsset { name, url });
+        }
+    }
+    // The assets of a private fork or of a GitHub Enterprise Server are only downloaded with a
+    // token through the API
+    let through_api = repo.is_overridden()? || http::is_github_enterprise()?;
+    let name = match (&asset.url, &github_token) {
+        (Some(api_url), Some(_)) if through_api => {
+            let request = || {
+                Ok(http::get(api_url, github_token.as_deref())?
+                    .header(reqwest::header::ACCEPT, "application/octet-stream"))
+            };
+            download_request(request, &file_path, &name).await?
+        }
+        _ => download_file(&url, &file_path, &name, github_token).await?,
+    };
+    if let Some(digest) = &asset.digest {
+        integrity::verify_digest(&file_path, digest).await?;
+    }
+    Ok(DownloadedAsset { name, url })
 }
 
 #[cfg(test)]
@@ -354,16 +685,39 @@ mod tests {
 
     fn create_test_release(asset_names: Vec<&str>) -> Release {
         Release {
+            tag_name: None,
             assets: asset_names
                 .into_iter()
                 .map(|name| Asset {
                     name: name.to_string(),
                     browser_download_url: format!("https://example.com/{}", name),
+                    url: None,
+                    size: 0,
+                    digest: None,
                 })
                 .collect(),
         }
     }
 
+    #[test]
+    fn test_download_progress() {
+        const MIB: u64 = 1024 * 1024;
+        let mut progress = DownloadProgress::new("sui.tgz", 4 * MIB);
+        assert_eq!(progress.add(MIB / 2).unwrap().percent, Some(12));
+        assert!(progress.add(1).is_none());
+        assert_eq!(progress.add(4 * MIB).unwrap().percent, Some(100));
+        assert_eq!(progress.position(), 4 * MIB);
+
+        // Without a size, an event is reported every MiB
+        let mut progress = DownloadProgress::new("walrus", 0);
+        assert!(progress.add(MIB / 2).is_none());
+        let event = progress.add(MIB / 2).unwrap();
+        assert_eq!((event.bytes, event.percent), (Some(MIB), None));
+        assert!(progress.add(MIB - 1).is_none());
+        assert_eq!(progress.add(1).unwrap().bytes, Some(2 * MIB));
+        assert_eq!(progress.position(), 2 * MIB);
+    }
+
     #[test]
     fn test_binary_name() {
         assert_eq!(Repo::Sui.binary_name(), "sui");
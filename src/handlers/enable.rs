@@ -0,0 +1,122 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Binaries disabled with `suiup disable`: they stay installed and keep their default version,
+//! but are taken out of the default bin folder until `suiup enable`, so that running them by
+//! name finds another installation, or nothing.
+
+use anyhow::{anyhow, Error};
+
+use crate::commands::BinaryName;
+use crate::handlers::switch::{get_binary_destination_path, switch_to_binary};
+use crate::paths::get_default_bin_dir;
+use crate::state;
+use crate::status;
+use crate::types::default_binary_name;
+
+/// Whether `binary` is disabled, along with its debug build (`<binary>-debug`)
+pub fn is_disabled(binary: &str) -> bool {
+    state::with_state(|state| Ok(contains(state.disabled_binaries(), binary))).unwrap_or(false)
+}
+
+/// Tells that the default version of the disabled `binary` changed without being put in the bin
+/// folder
+pub fn report_disabled(binary: &str) {
+    let binary = binary.strip_suffix("-debug").unwrap_or(binary);
+    status!("{binary} is disabled, the default version is recorded but not put in the bin folder. Run `suiup enable {binary}` to use it.");
+}
+
+fn contains(disabled: &[String], binary: &str) -> bool {
+    let binary = binary.strip_suffix("-debug").unwrap_or(binary);
+    disabled.iter().any(|b| b == binary)
+}
+
+/// Handles the `disable` command: removes the release and debug builds of `binary` from the
+/// default bin folder
+pub fn handle_disable(binary: &BinaryName) -> Result<(), Error> {
+    let name = binary.to_str();
+    if is_disabled(name) {
+        status!("{name} is already disabled");
+        return Ok(());
+    }
+
+    remove_from_bin_dir(name)?;
+    state::with_state(|state| {
+        state.disabled_binaries_mut().push(name.to_string());
+        Ok(())
+    })?;
+    status!(
+        "Disabled {name}. Its versions stay installed, run `suiup enable {name}` to use them again."
+    );
+    Ok(())
+}
+
+/// Removes the release and debug builds of the disabled `binary` from the default bin folder
+pub(crate) fn remove_from_bin_dir(binary: &str) -> Result<(), Error> {
+    for debug in [false, true] {
+        let path = get_default_bin_dir().join(format!(
+            "{}{}",
+            default_binary_name(binary, debug),
+            std::env::consts::EXE_SUFFIX
+        ));
+        if path.exists() {
+            std::fs::remove_file(&path)
+                .map_err(|e| anyhow!("Cannot remove {}: {e}", path.display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Handles the `enable` command: puts the default versions of `binary` back in the default bin
+/// folder
+pub fn handle_enable(binary: &BinaryName) -> Result<(), Error> {
+    let name = binary.to_str();
+    if !is_disabled(name) {
+        status!("{name} is not disabled");
+        return Ok(());
+    }
+    state::with_state(|state| {
+        state.disabled_binaries_mut().retain(|b| b != name);
+        Ok(())
+    })?;
+
+    let defaults = state::default_versions()?;
+    let installed = state::installed_binaries()?;
+    let mut restored = false;
+    for debug in [false, true] {
+        let Some((network, version, _)) = defaults.get(&default_binary_name(name, debug)) else {
+            continue;
+        };
+        let Some(binary) = installed.binaries().iter().find(|b| {
+            b.binary_name == name
+                && &b.network_release == network
+                && &b.version == version
+                && b.debug == debug
+        }) else {
+            continue;
+        };
+        switch_to_binary(binary)?;
+        status!("Restored {}", get_binary_destination_path(binary).display());
+        restored = true;
+    }
+    if restored {
+        status!("Enabled {name}");
+    } else {
+        status!("Enabled {name}. It has no default version yet, set one with `suiup switch` or `suiup install`.");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contains() {
+        let disabled = vec!["sui".to_string(), "walrus".to_string()];
+        assert!(contains(&disabled, "sui"));
+        assert!(contains(&disabled, "sui-debug"));
+        assert!(!contains(&disabled, "sui-node"));
+        assert!(!contains(&disabled, "mvr"));
+    }
+}
@@ -0,0 +1,109 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Requests made through the GitHub CLI (`gh`) when `gh_cli` is set in the config file, so that
+//! the login and the GitHub Enterprise hosts of `gh` are used. Callers fall back to suiup's own
+//! client when `gh` is missing or fails.
+
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::OnceLock;
+
+use anyhow::{anyhow, bail, Error};
+use serde::de::DeserializeOwned;
+use tokio::process::Command;
+
+use crate::config::load_config;
+use crate::http;
+use crate::types::Repo;
+
+const GH: &str = "gh";
+
+/// Whether requests go through `gh`: it is enabled in the config file and installed, and the
+/// GitHub API is not replaced with `SUIUP_GITHUB_API_URL`, which `gh` would not use. Never in
+/// offline mode.
+pub fn enabled() -> bool {
+    static INSTALLED: OnceLock<bool> = OnceLock::new();
+    !http::is_offline()
+        && load_config().is_ok_and(|config| config.gh_cli)
+        && http::github_api_override().is_none()
+        && *INSTALLED.get_or_init(|| {
+            std::process::Command::new(GH)
+                .arg("--version")
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .is_ok_and(|status| status.success())
+        })
+}
+
+/// Calls the GitHub API with `gh api`, e.g. `repos/MystenLabs/sui/releases`
+pub async fn api<T: DeserializeOwned>(path: &str) -> Result<T, Error> {
+    let mut command = Command::new(GH);
+    command.arg("api");
+    if let Some(host) = enterprise_host()? {
+        command.args(["--hostname", &host]);
+    }
+    command.arg(path);
+    let output = run(&mut command).await?;
+    serde_json::from_slice(&output).map_err(|e| anyhow!("Invalid response of `gh api {path}`: {e}"))
+}
+
+/// Downloads the asset of a release with `gh release download`
+pub async fn release_download(
+    repo: &Repo,
+    tag: &str,
+    asset: &str,
+    output: &Path,
+) -> Result<(), Error> {
+    let repo = match enterprise_host()? {
+        Some(host) => format!("{host}/{}", repo.repository()?),
+        None => repo.repository()?,
+    };
+    let mut command = Command::new(GH);
+    command
+        .args([
+            "release",
+            "download",
+            tag,
+            "--repo",
+            &repo,
+            "--pattern",
+            asset,
+        ])
+        .arg("--output")
+        .arg(output)
+        .arg("--clobber");
+    run(&mut command).await.map(|_| ())
+}
+
+/// Host of the GitHub Enterprise Server set with `github_api_base`, which `gh` must be told
+fn enterprise_host() -> Result<Option<String>, Error> {
+    if !http::is_github_enterprise()? {
+        return Ok(None);
+    }
+    let url = http::github_url()?;
+    Ok(Some(
+        url.split("://")
+            .nth(1)
+            .unwrap_or(&url)
+            .trim_end_matches('/')
+            .to_string(),
+    ))
+}
+
+/// Runs `gh`, returning its output
+async fn run(command: &mut Command) -> Result<Vec<u8>, Error> {
+    let output = command
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| anyhow!("Cannot run gh: {e}"))?;
+    if !output.status.success() {
+        bail!(
+            "gh failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
@@ -0,0 +1,219 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Error};
+
+use chrono::Utc;
+
+use clap::ValueEnum;
+use colored::Colorize;
+
+use crate::commands::{format_last_used, BinaryName};
+use crate::handlers::cleanup::format_file_size;
+use crate::handlers::find_installed;
+use crate::handlers::integrity::sha256_of_file;
+use crate::handlers::release::{last_release_for_network, release_list};
+use crate::handlers::switch::binary_source_path_in;
+use crate::handlers::version::is_newer;
+use crate::paths::{binaries_dir, system_binaries_dir};
+use crate::standalone::StandaloneInstaller;
+use crate::state::{self, DefaultVersions};
+use crate::types::{default_binary_name, BinaryVersion, BuildType, Repo, Version};
+
+/// Handles the `info` command
+pub async fn handle_info(binary_spec: &str, github_token: Option<String>) -> Result<(), Error> {
+    let matches = installed_matches(binary_spec)?;
+    if matches.is_empty() {
+        bail!(
+            "No installed binary matches {binary_spec}. Use `suiup show` to see installed binaries."
+        );
+    }
+
+    let default_versions = state::default_versions()?;
+    for (i, (binary, system)) in matches.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let latest = latest_version(binary, github_token.clone()).await;
+        print_info(binary, *system, &default_versions, latest.as_deref());
+    }
+
+    Ok(())
+}
+
+/// Installed binaries matching the spec, user-level ones first, with whether they are installed
+/// system-wide
+pub(crate) fn installed_matches(binary_spec: &str) -> Result<Vec<(BinaryVersion, bool)>, Error> {
+    let installed_binaries = state::installed_binaries()?;
+    let system_binaries = state::system_installed_binaries()?;
+    Ok(find_installed(installed_binaries.binaries(), binary_spec)
+        .into_iter()
+        .map(|b| (b.clone(), false))
+        .chain(
+            find_installed(system_binaries.binaries(), binary_spec)
+                .into_iter()
+                .map(|b| (b.clone(), true)),
+        )
+        .collect())
+}
+
+/// Path of an installed binary
+pub(crate) fn installed_path(binary: &BinaryVersion, system: bool) -> PathBuf {
+    binary.path.as_ref().map(PathBuf::from).unwrap_or_else(|| {
+        let dir = if system {
+            system_binaries_dir()
+        } else {
+            binaries_dir()
+        };
+        binary_source_path_in(&dir, binary)
+    })
+}
+
+/// Returns the latest version available for the release of the binary, `None` if it cannot be
+/// found (e.g. offline) or does not apply, as for nightly builds.
+async fn latest_version(binary: &BinaryVersion, github_token: Option<String>) -> Option<String> {
+    if binary.version == "nightly" {
+        return None;
+    }
+    let repo = BinaryName::from_str(&binary.binary_name, true).ok()?.repo();
+    if repo == Repo::Mvr {
+        let mut installer = StandaloneInstaller::new(Repo::Mvr);
+        installer.get_releases().await.ok()?;
+        return installer
+            .versions()
+            .into_iter()
+            .max_by_key(|version| version.parse::<Version>().ok());
+    }
+    let releases = release_list(&repo, github_token).await.ok()?.0;
+    last_release_for_network(&releases, &binary.network_release)
+        .await
+        .ok()
+        .map(|(_, version)| version)
+}
+
+/// Whether `latest` is an update of `version`, e.g. `yes, v1.41.0`
+fn update_status(version: &str, latest: Option<&str>) -> String {
+    match latest {
+        _ if version == "nightly" => "unknown (nightly build)".to_string(),
+        None => "unknown".to_string(),
+        Some(latest) if is_newer(latest, version) => format!("yes, {latest}"),
+        Some(_) => "no, up to date".to_string(),
+    }
+}
+
+fn is_default(binary: &BinaryVersion, default_versions: &DefaultVersions) -> bool {
+    default_versions
+        .get(&default_binary_name(&binary.binary_name, binary.debug))
+        .is_some_and(|(network, version, debug)| {
+            network == &binary.network_release
+                && version.trim_start_matches('v') == binary.version.trim_start_matches('v')
+                && *debug == binary.debug
+        })
+}
+
+fn print_info(
+    binary: &BinaryVersion,
+    system: bool,
+    default_versions: &DefaultVersions,
+    latest: Option<&str>,
+) {
+    if system {
+        println!("{} (system-wide)", binary.to_string().bold());
+    } else {
+        println!("{}", binary.to_string().bold());
+    }
+    let path = installed_path(binary, system);
+    println!("  Version:        {}", binary.version);
+    println!("  Release/Branch: {}", binary.network_release);
+    println!("  Path:           {}", path.display());
+    match std::fs::metadata(&path) {
+        Ok(metadata) => {
+            println!("  Size:           {}", format_file_size(metadata.len()));
+            let sha256 = sha256_of_file(&path).unwrap_or_else(|_| "unknown".to_string());
+            println!("  sha256:         {sha256}");
+        }
+        Err(_) => println!("  Size:           unknown (binary is missing)"),
+    }
+    println!(
+        "  Default:        {}",
+        if !system && is_default(binary, default_versions) {
+            "yes"
+        } else {
+            "no"
+        }
+    );
+    println!(
+        "  Update:         {}",
+        update_status(&binary.version, latest)
+    );
+    println!(
+        "  Last used:      {}",
+        format_last_used(binary.last_used, Utc::now())
+    );
+
+    let Some(provenance) = &binary.provenance else {
+        println!("  Provenance:     unknown (installed with an older suiup)");
+        return;
+    };
+
+    let build_type = match provenance.build_type {
+        BuildType::Release => "release",
+        BuildType::Debug => "debug",
+        BuildType::Nightly if binary.debug => "nightly (debug)",
+        BuildType::Nightly => "nightly",
+    };
+    println!("  Build type:     {build_type}");
+    println!(
+        "  Source:         {}",
+        provenance.source_url.as_deref().unwrap_or("unknown")
+    );
+    if let Some(archive) = &provenance.archive {
+        println!("  Archive:        {archive}");
+    }
+    if let Some(blake3) = &provenance.download_blake3 {
+        println!("  BLAKE3:         {blake3}");
+    } else if let Some(md5) = &provenance.download_md5 {
+        println!("  md5:            {md5}");
+    }
+    if let Some(branch) = &provenance.branch {
+        println!("  Branch:         {branch}");
+    }
+    if provenance.build_type == BuildType::Nightly {
+        println!(
+            "  Commit:         {}",
+            provenance.commit.as_deref().unwrap_or("unknown")
+        );
+    }
+    println!(
+        "  Installed at:   {}",
+        provenance.installed_at.format("%Y-%m-%d %H:%M:%S UTC")
+    );
+    if let Some(installed_by) = provenance.installed_by {
+        match &provenance.user {
+            Some(user) => println!("  Installed by:   {installed_by} ({user})"),
+            None => println!("  Installed by:   {installed_by}"),
+        }
+    }
+    if let Some(command) = &provenance.command {
+        println!("  Command:        {command}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_status() {
+        assert_eq!(update_status("v1.40.1", Some("v1.41.0")), "yes, v1.41.0");
+        assert_eq!(update_status("v1.10.0", Some("v1.9.2")), "no, up to date");
+        assert_eq!(update_status("1.40.1", Some("v1.40.1")), "no, up to date");
+        assert_eq!(update_status("v1.40.1", None), "unknown");
+        assert_eq!(
+            update_status("nightly", Some("v1.41.0")),
+            "unknown (nightly build)"
+        );
+    }
+}
@@ -0,0 +1,229 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Error};
+use clap::ValueEnum;
+
+use crate::paths::get_default_bin_dir;
+use crate::state::write_file_atomically;
+use crate::status;
+
+/// First and last lines of the block suiup writes into the shell rc files
+const BEGIN_MARKER: &str = "# >>> suiup >>>";
+const END_MARKER: &str = "# <<< suiup <<<";
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    #[value(name = "powershell")]
+    PowerShell,
+}
+
+impl Shell {
+    const ALL: [Shell; 4] = [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell];
+
+    /// The file the shell runs at startup
+    fn rc_file(&self, home: &Path) -> PathBuf {
+        match self {
+            Shell::Bash => home.join(".bashrc"),
+            Shell::Zsh => std::env::var_os("ZDOTDIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| home.to_path_buf())
+                .join(".zshrc"),
+            Shell::Fish => home.join(".config").join("fish").join("config.fish"),
+            #[cfg(windows)]
+            Shell::PowerShell => home
+                .join("Documents")
+                .join("PowerShell")
+                .join("Microsoft.PowerShell_profile.ps1"),
+            #[cfg(not(windows))]
+            Shell::PowerShell => home
+                .join(".config")
+                .join("powershell")
+                .join("Microsoft.PowerShell_profile.ps1"),
+        }
+    }
+
+    /// The line adding `bin_dir` to the PATH
+    fn path_line(&self, bin_dir: &Path) -> String {
+        let bin_dir = bin_dir.display();
+        match self {
+            Shell::Bash | Shell::Zsh => format!("export PATH=\"{bin_dir}:$PATH\""),
+            Shell::Fish => format!("fish_add_path \"{bin_dir}\""),
+            #[cfg(windows)]
+            Shell::PowerShell => format!("$env:Path = \"{bin_dir};\" + $env:Path"),
+            #[cfg(not(windows))]
+            Shell::PowerShell => format!("$env:PATH = \"{bin_dir}:\" + $env:PATH"),
+        }
+    }
+
+    /// The shell the user is running, from `$SHELL`
+    fn current() -> Option<Shell> {
+        if cfg!(windows) {
+            return Some(Shell::PowerShell);
+        }
+        let shell = std::env::var_os("SHELL")?;
+        let name = Path::new(&shell).file_name()?.to_str()?;
+        match name {
+            "bash" => Some(Shell::Bash),
+            "zsh" => Some(Shell::Zsh),
+            "fish" => Some(Shell::Fish),
+            "pwsh" | "powershell" => Some(Shell::PowerShell),
+            _ => None,
+        }
+    }
+}
+
+/// Handles the `init` command: adds the default binaries directory to the PATH in the rc files
+/// of `shells`, or of the current shell and those with an existing rc file. `undo` removes it.
+pub fn handle_init(shells: &[Shell], undo: bool, dry_run: bool) -> Result<(), Error> {
+    let home = dirs::home_dir().ok_or_else(|| anyhow!("Cannot find the home directory"))?;
+    let bin_dir = get_default_bin_dir();
+
+    let mut shells = if !shells.is_empty() {
+        shells.to_vec()
+    } else {
+        Shell::ALL
+            .into_iter()
+            .filter(|shell| Some(*shell) == Shell::current() || shell.rc_file(&home).exists())
+            .collect()
+    };
+    shells.sort();
+    shells.dedup();
+    if shells.is_empty() {
+        bail!("Cannot detect your shell. Select it with `--shell`, e.g. `suiup init --shell bash`");
+    }
+
+    let mut changed = false;
+    for shell in shells {
+        let rc_file = shell.rc_file(&home);
+        let content = match std::fs::read_to_string(&rc_file) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => bail!("Cannot read {}: {e}", rc_file.display()),
+        };
+        let updated = if undo {
+            remove_block(&content)
+        } else {
+            add_block(&content, &shell.path_line(&bin_dir))
+        };
+        if updated == content {
+            if undo {
+                status!("{}: nothing to remove", rc_file.display());
+            } else {
+                status!("{}: already set up", rc_file.display());
+            }
+            continue;
+        }
+
+        let action = if undo {
+            "remove the suiup PATH setup from"
+        } else {
+            "add the suiup PATH setup to"
+        };
+        if dry_run {
+            println!("[dry run] Would {action} {}", rc_file.display());
+            if !undo {
+                status!(
+                    "{BEGIN_MARKER}\n{}\n{END_MARKER}",
+                    shell.path_line(&bin_dir)
+                );
+            }
+            continue;
+        }
+        if let Some(parent) = rc_file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // Never left truncated, and a startup file linked from a dotfiles repository is written
+        // through the link
+        write_file_atomically(&rc_file, updated.as_bytes())
+            .map_err(|e| anyhow!("Cannot write {}: {e}", rc_file.display()))?;
+        changed = true;
+        if undo {
+            status!("Removed the suiup PATH setup from {}", rc_file.display());
+        } else {
+            status!(
+                "Added {} to the PATH in {}",
+                bin_dir.display(),
+                rc_file.display()
+            );
+        }
+    }
+
+    if changed && !undo {
+        status!("Restart your shell for the change to take effect.");
+    }
+    Ok(())
+}
+
+/// Returns `content` with the suiup block set to `line`, replacing an existing block
+fn add_block(content: &str, line: &str) -> String {
+    let block = format!("{BEGIN_MARKER}\n{line}\n{END_MARKER}\n");
+    if let Some((start, end)) = find_block(content) {
+        return format!("{}{block}{}", &content[..start], &content[end..]);
+    }
+    let mut updated = content.to_string();
+    if !updated.is_empty() {
+        if !updated.ends_with('\n') {
+            updated.push('\n');
+        }
+        updated.push('\n');
+    }
+    updated.push_str(&block);
+    updated
+}
+
+/// Returns `content` without the suiup block
+fn remove_block(content: &str) -> String {
+    let Some((start, end)) = find_block(content) else {
+        return content.to_string();
+    };
+    let before = &content[..start];
+    // Drop the blank line separating the block from the previous content
+    let before = before
+        .strip_suffix('\n')
+        .filter(|b| b.ends_with('\n'))
+        .unwrap_or(before);
+    format!("{before}{}", &content[end..])
+}
+
+/// Byte range of the suiup block, including the newline after the end marker
+fn find_block(content: &str) -> Option<(usize, usize)> {
+    let start = content.find(BEGIN_MARKER)?;
+    let end = start + content[start..].find(END_MARKER)? + END_MARKER.len();
+    let end = if content[end..].starts_with('\n') {
+        end + 1
+    } else {
+        end
+    };
+    Some((start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_and_remove_block() {
+        let rc = "alias ll='ls -l'\n";
+        let line = "export PATH=\"/home/u/.local/bin:$PATH\"";
+        let added = add_block(rc, line);
+        assert_eq!(
+            added,
+            format!("alias ll='ls -l'\n\n{BEGIN_MARKER}\n{line}\n{END_MARKER}\n")
+        );
+        // Idempotent, and the block is updated in place
+        assert_eq!(add_block(&added, line), added);
+        let moved = add_block(&added, "export PATH=\"/opt/bin:$PATH\"");
+        assert!(moved.contains("/opt/bin") && !moved.contains("/home/u"));
+        assert_eq!(moved.matches(BEGIN_MARKER).count(), 1);
+
+        assert_eq!(remove_block(&added), rc);
+        assert_eq!(remove_block(rc), rc);
+        assert_eq!(remove_block(&add_block("", line)), "");
+    }
+}
@@ -0,0 +1,244 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! What `install --dry-run` would do: the release is resolved, but nothing is downloaded or
+//! written.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Error};
+use indicatif::HumanBytes;
+
+use crate::commands::{BinaryName, CommandMetadata};
+use crate::config::{config_file, load_config, SourceConfig};
+use crate::handlers::block::ensure_not_blocked;
+use crate::handlers::deprecation::warn_if_deprecated;
+use crate::handlers::download::{cached_release_archive, find_release_in, select_asset};
+use crate::handlers::install::InstallOptions;
+use crate::handlers::mirror::send_probe;
+use crate::handlers::release::{ensure_version_prefix, release_list_without_saving};
+use crate::handlers::version::{ensure_minimum_version, extract_version_from_release};
+use crate::handlers::{archive_extension, check_if_binaries_exist};
+use crate::http;
+use crate::paths::{binaries_dir, get_default_bin_dir, release_archive_dir};
+use crate::platform::{current_target, Target};
+use crate::redact::{redact, register_secret};
+use crate::standalone::StandaloneInstaller;
+use crate::types::default_binary_name;
+
+/// Where a release would be downloaded from
+struct Download {
+    /// Version, with the `v` prefix
+    version: String,
+    asset: String,
+    url: String,
+    /// Size in bytes, if the server tells it
+    size: Option<u64>,
+    /// Path of the archive in the archive cache, none for a binary downloaded as is
+    archive: Option<PathBuf>,
+}
+
+/// Handles `install --dry-run` for one binary
+pub async fn handle_install_dry_run(
+    metadata: CommandMetadata,
+    debug: bool,
+    github_token: Option<String>,
+    options: &InstallOptions,
+) -> Result<(), Error> {
+    let CommandMetadata {
+        name,
+        network,
+        version,
+        ..
+    } = metadata;
+    if name != BinaryName::Sui && debug {
+        bail!("Debug flag is only available for the `sui` binary");
+    }
+    let network = if name == BinaryName::Mvr {
+        "standalone"
+    } else {
+        network.as_str()
+    };
+    let target = current_target(options.target)?;
+    let config = load_config()?;
+    // Deprecations are checked against the cached document, refreshing it would write it
+
+    let download = match config.sources.get(name.to_str()) {
+        Some(source) => {
+            let asset_pattern = config.asset_pattern(name.repo());
+            from_source(&name, network, version, source, asset_pattern, &target).await?
+        }
+        None if name == BinaryName::Mvr => standalone(&name, version, &target).await?,
+        None => from_release(&name, network, version, &target, github_token).await?,
+    };
+    let version = &download.version;
+    ensure_not_blocked(name.to_str(), network, version)?;
+    ensure_minimum_version(name.to_str(), version, options.allow_below_minimum)?;
+    warn_if_deprecated(name.to_str(), network, version);
+
+    let binary = default_binary_name(name.to_str(), debug);
+    let binary_filename = format!("{binary}-{version}");
+    #[cfg(target_os = "windows")]
+    let binary_filename = format!("{}.exe", binary_filename);
+    let binary_path = binaries_dir().join(network).join(binary_filename);
+
+    if !options.force && check_if_binaries_exist(&binary, network.to_string(), version)? {
+        println!(
+            "[dry run] {binary} {version} from {network} is already installed at {}, nothing would be done. Use `--force` to install it again.",
+            binary_path.display()
+        );
+        return Ok(());
+    }
+
+    println!("[dry run] Would install {binary} {version} from {network} for {target}");
+    println!("  Asset:          {}", download.asset);
+    println!("  URL:            {}", redact(&download.url));
+    println!(
+        "  Download size:  {}",
+        download
+            .size
+            .map(|size| HumanBytes(size).to_string())
+            .unwrap_or_else(|| "unknown".to_string())
+    );
+    if let Some(archive) = &download.archive {
+        let cached = if is_cached(archive, download.size, options.force) {
+            "cached, would not be downloaded"
+        } else {
+            "not cached, would be downloaded"
+        };
+        println!("  Archive:        {} ({cached})", archive.display());
+    }
+    println!("  Binary:         {}", binary_path.display());
+    let default_path = get_default_bin_dir().join(&binary);
+    #[cfg(target_os = "windows")]
+    let default_path = default_path.with_extension("exe");
+    println!(
+        "  Default binary: {} (if set as the default)",
+        default_path.display()
+    );
+    Ok(())
+}
+
+/// Whether the archive would be taken from the cache: downloads reuse a cached archive of the
+/// same size, and offline installs any cached archive. `force` downloads it again.
+fn is_cached(archive: &Path, size: Option<u64>, force: bool) -> bool {
+    if force && !http::is_offline() {
+        return false;
+    }
+    let Ok(metadata) = archive.metadata() else {
+        return false;
+    };
+    http::is_offline() || size == Some(metadata.len())
+}
+
+async fn from_release(
+    name: &BinaryName,
+    network: &str,
+    version: Option<String>,
+    target: &Target,
+    github_token: Option<String>,
+) -> Result<Download, Error> {
+    let repo = name.repo();
+    if http::is_offline() {
+        let version = version.map(|v| ensure_version_prefix(&v));
+        let cached = cached_release_archive(&repo, network, version.as_deref(), target)?;
+        let archive = release_archive_dir().join(&cached.name);
+        return Ok(Download {
+            version: extract_version_from_release(&cached.name)?,
+            size: archive.metadata().ok().map(|m| m.len()),
+            asset: cached.name,
+            url: cached.url,
+            archive: Some(archive),
+        });
+    }
+
+    // Neither the release list nor its ETag are saved, a dry run writes nothing
+    let releases = release_list_without_saving(&repo, github_token.clone())
+        .await?
+        .0;
+    let release =
+        find_release_in(&repo, releases, network, version.as_deref(), github_token).await?;
+    let asset = select_asset(&repo, &release, target)?;
+    Ok(Download {
+        version: extract_version_from_release(&asset.name)?,
+        asset: asset.name.clone(),
+        url: asset.browser_download_url.clone(),
+        size: Some(asset.size).filter(|size| *size > 0),
+        archive: Some(release_archive_dir().join(&asset.name)),
+    })
+}
+
+async fn standalone(
+    name: &BinaryName,
+    version: Option<String>,
+    target: &Target,
+) -> Result<Download, Error> {
+    let mut installer = StandaloneInstaller::new(name.repo());
+    installer.get_releases().await?;
+    let version = match version {
+        Some(version) => ensure_version_prefix(&version),
+        None => installer
+            .versions()
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("No {name} releases found"))?,
+    };
+    let asset = installer.find_asset_for(&version, target)?;
+    Ok(Download {
+        asset: asset.name.clone(),
+        url: asset.browser_download_url.clone(),
+        size: Some(asset.size).filter(|size| *size > 0),
+        archive: None,
+        version,
+    })
+}
+
+async fn from_source(
+    name: &BinaryName,
+    network: &str,
+    version: Option<String>,
+    source: &SourceConfig,
+    asset_pattern: Option<&str>,
+    target: &Target,
+) -> Result<Download, Error> {
+    let version = version.ok_or_else(|| {
+        anyhow!(
+            "A version is required to install {name} from the source configured in {}, e.g. `suiup install {name}@{network}-1.40.1`",
+            config_file().display()
+        )
+    })?;
+    let version = ensure_version_prefix(&version);
+    let url = source.url_for(
+        name.to_str(),
+        name.repo(),
+        network,
+        &version,
+        target,
+        asset_pattern,
+    )?;
+    let headers = source.resolved_headers(&url)?;
+    for (_, value) in &headers {
+        register_secret(value);
+    }
+    // The size is only known by asking the server
+    let size = if http::is_offline() {
+        None
+    } else {
+        match send_probe(&url, &headers).await {
+            Ok(probed) => probed.size,
+            Err(e) => bail!("Cannot reach {}: {e}", redact(&url)),
+        }
+    };
+
+    let url_path = url.split(['?', '#']).next().unwrap_or_default();
+    let archive = archive_extension(url_path).map(|extension| {
+        release_archive_dir().join(format!("{name}-{network}-{version}-{target}.{extension}"))
+    });
+    Ok(Download {
+        asset: url_path.rsplit('/').next().unwrap_or_default().to_string(),
+        url,
+        size,
+        archive,
+        version,
+    })
+}
@@ -0,0 +1,331 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Integrity index of the release archive cache, and checks of the installed binaries against
+//! the checksums recorded when they were installed. Files are hashed with BLAKE3, several times
+//! faster than SHA-256 or md5 on archives and binaries of hundreds of MB. The SHA-256 digests
+//! GitHub publishes for release assets are still compared, once per downloaded archive.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use anyhow::{anyhow, bail, Error};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressFinish, ProgressStyle};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::handlers::info::installed_path;
+use crate::output::{self, ProgressEvent};
+use crate::paths::{get_suiup_cache_dir, release_archive_dir};
+use crate::state::{self, write_file_atomically};
+use crate::timings;
+
+const INDEX_FILE: &str = "integrity.json";
+
+/// Serializes the updates of the index by concurrent downloads
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+/// Checksums of the archives of the release archive cache, by file name
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+struct Index {
+    archives: BTreeMap<String, IndexEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct IndexEntry {
+    size: u64,
+    blake3: String,
+    /// SHA-256 digest published for the release asset, once the archive was compared with it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sha256: Option<String>,
+}
+
+fn index_file() -> PathBuf {
+    get_suiup_cache_dir().join(INDEX_FILE)
+}
+
+fn load_index() -> Index {
+    // A missing or broken index only means that the cached archives are not verified
+    std::fs::read(index_file())
+        .ok()
+        .and_then(|contents| serde_json::from_slice(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Applies `f` to the index and saves it, forgetting the archives removed from the cache
+fn update_index(f: impl FnOnce(&mut Index)) -> Result<(), Error> {
+    let _lock = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut index = load_index();
+    f(&mut index);
+    let dir = release_archive_dir();
+    index.archives.retain(|name, _| dir.join(name).is_file());
+    write_file_atomically(&index_file(), &serde_json::to_vec_pretty(&index)?)
+}
+
+/// Name of the archive in the release archive cache, `None` for files elsewhere, e.g. binaries
+/// downloaded without an archive
+fn cached_archive_name(path: &Path) -> Option<String> {
+    if path.parent()? != release_archive_dir() {
+        return None;
+    }
+    Some(path.file_name()?.to_str()?.to_string())
+}
+
+pub(crate) fn blake3_of_file(path: &Path) -> Result<String, Error> {
+    let file = File::open(path).map_err(|e| anyhow!("Cannot open {}: {e}", path.display()))?;
+    let mut hasher = blake3::Hasher::new();
+    hasher
+        .update_reader(file)
+        .map_err(|e| anyhow!("Cannot read {}: {e}", path.display()))?;
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+pub(crate) fn sha256_of_file(path: &Path) -> Result<String, Error> {
+    let mut file = File::open(path).map_err(|e| anyhow!("Cannot open {}: {e}", path.display()))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let n = file
+            .read(&mut buffer)
+            .map_err(|e| anyhow!("Cannot read {}: {e}", path.display()))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Hashes `paths` with SHA-256, see [`hash_files`]
+pub(crate) fn sha256_of_files(paths: &[PathBuf]) -> Vec<Result<String, Error>> {
+    hash_files(paths, sha256_of_file)
+}
+
+/// Hashes `paths` on as many threads as there are cores, showing the progress, as hashing many
+/// binaries of hundreds of MB one after another is slow. The checksums are in the order of
+/// `paths`.
+pub(crate) fn hash_files(
+    paths: &[PathBuf],
+    hash: fn(&Path) -> Result<String, Error>,
+) -> Vec<Result<String, Error>> {
+    let pb = ProgressBar::new(paths.len() as u64).with_finish(ProgressFinish::AndClear);
+    if output::hide_progress_bars() {
+        pb.set_draw_target(ProgressDrawTarget::hidden());
+    }
+    pb.set_style(
+        ProgressStyle::with_template("Hashing binaries: [{bar:40.cyan/blue}] {pos}/{len}")
+            .unwrap()
+            .progress_chars("=>-"),
+    );
+    let threads = std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(paths.len());
+    let next = AtomicUsize::new(0);
+    let mut results = std::thread::scope(|scope| {
+        let workers = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut hashed = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(path) = paths.get(i) else {
+                            break hashed;
+                        };
+                        hashed.push((i, hash(path)));
+                        pb.inc(1);
+                        ProgressEvent::new("hash", "Hashing binaries")
+                            .steps(pb.position(), paths.len() as u64)
+                            .emit();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("hashing thread panicked"))
+            .collect::<Vec<_>>()
+    });
+    results.sort_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, hash)| hash).collect()
+}
+
+/// Runs `hash` on tokio's blocking thread pool, as hashing large archives is CPU and disk bound
+async fn hash_in_background(
+    path: &Path,
+    hash: fn(&Path) -> Result<String, Error>,
+) -> Result<String, Error> {
+    let _timer = timings::phase("verify");
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || hash(&path))
+        .await
+        .map_err(|e| anyhow!("Hashing task failed: {e}"))?
+}
+
+/// Hashes a downloaded file and records the checksum if it is in the release archive cache
+pub(crate) async fn record_download(path: &Path) -> Result<String, Error> {
+    let blake3 = hash_in_background(path, blake3_of_file).await?;
+    if let Some(name) = cached_archive_name(path) {
+        let size = path.metadata()?.len();
+        let entry = IndexEntry {
+            size,
+            blake3: blake3.clone(),
+            sha256: None,
+        };
+        update_index(|index| {
+            index.archives.insert(name, entry);
+        })?;
+    }
+    Ok(blake3)
+}
+
+/// Checks a cached archive against the index: `Some(true)` if it is unchanged, `Some(false)`
+/// if it was modified or truncated since it was downloaded, `None` if it is not in the index
+pub(crate) async fn check_cached(path: &Path) -> Result<Option<bool>, Error> {
+    let Some(entry) =
+        cached_archive_name(path).and_then(|name| load_index().archives.remove(&name))
+    else {
+        return Ok(None);
+    };
+    if path.metadata()?.len() != entry.size {
+        return Ok(Some(false));
+    }
+    Ok(Some(
+        hash_in_background(path, blake3_of_file).await? == entry.blake3,
+    ))
+}
+
+/// BLAKE3 checksum of a downloaded file, from the index for an archive of the cache recorded
+/// when it was downloaded
+pub(crate) async fn download_checksum(path: &Path) -> Result<String, Error> {
+    let size = path.metadata()?.len();
+    let indexed = cached_archive_name(path)
+        .and_then(|name| load_index().archives.remove(&name))
+        .filter(|entry| entry.size == size);
+    match indexed {
+        Some(entry) => Ok(entry.blake3),
+        None => record_download(path).await,
+    }
+}
+
+/// Compares a downloaded release asset with the digest GitHub publishes for it, e.g.
+/// `sha256:<hex>`, unless that was already done for the archive. Other algorithms are not
+/// checked. An archive that does not match is removed.
+pub(crate) async fn verify_digest(path: &Path, digest: &str) -> Result<(), Error> {
+    let Some(expected) = digest.strip_prefix("sha256:") else {
+        return Ok(());
+    };
+    let name = cached_archive_name(path);
+    let verified = name
+        .as_ref()
+        .and_then(|name| load_index().archives.remove(name))
+        .and_then(|entry| entry.sha256)
+        .is_some_and(|sha256| sha256.eq_ignore_ascii_case(expected));
+    if verified {
+        return Ok(());
+    }
+    let actual = hash_in_background(path, sha256_of_file).await?;
+    if !actual.eq_ignore_ascii_case(expected) {
+        let _ = std::fs::remove_file(path);
+        bail!(
+            "SHA-256 check failed for {}: expected {expected}, got {actual}. The download was removed, run the command again to download it again.",
+            path.display()
+        );
+    }
+    if let Some(name) = name {
+        update_index(|index| {
+            if let Some(entry) = index.archives.get_mut(&name) {
+                entry.sha256 = Some(actual);
+            }
+        })?;
+    }
+    Ok(())
+}
+
+/// Parses the SHA-256 digest of `--sha256`, 64 hexadecimal digits, optionally prefixed with
+/// `sha256:` as in the digests GitHub publishes
+pub fn parse_sha256(digest: &str) -> Result<String, String> {
+    let hex = digest.trim();
+    let hex = hex.strip_prefix("sha256:").unwrap_or(hex);
+    if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!(
+            "invalid SHA-256 digest `{digest}`, expected 64 hexadecimal digits"
+        ));
+    }
+    Ok(hex.to_ascii_lowercase())
+}
+
+/// Rehashes the archives of the index and the installed binaries whose checksum was recorded
+/// when they were installed. Returns the number of files checked and those that changed.
+pub(crate) fn verify_all() -> Result<(usize, Vec<PathBuf>), Error> {
+    let dir = release_archive_dir();
+    let mut expected = load_index()
+        .archives
+        .into_iter()
+        .map(|(name, entry)| (dir.join(name), entry.blake3))
+        .filter(|(path, _)| path.is_file())
+        .collect::<Vec<_>>();
+    for binary in state::installed_binaries()?.binaries() {
+        let blake3 = binary
+            .provenance
+            .as_ref()
+            .and_then(|p| p.binary_blake3.clone());
+        let path = installed_path(binary, false);
+        if let Some(blake3) = blake3.filter(|_| path.is_file()) {
+            expected.push((path, blake3));
+        }
+    }
+
+    let paths = expected
+        .iter()
+        .map(|(path, _)| path.clone())
+        .collect::<Vec<_>>();
+    let changed = expected
+        .into_iter()
+        .zip(hash_files(&paths, blake3_of_file))
+        .filter(|((_, blake3), actual)| actual.as_ref().ok() != Some(blake3))
+        .map(|((path, _), _)| path)
+        .collect();
+    Ok((paths.len(), changed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blake3_of_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+        std::fs::write(&path, b"abc").unwrap();
+        assert_eq!(
+            blake3_of_file(&path).unwrap(),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+        assert!(blake3_of_file(&dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_sha256_of_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut paths = (0..10)
+            .map(|i| {
+                let path = dir.path().join(format!("sui-{i}"));
+                std::fs::write(&path, i.to_string()).unwrap();
+                path
+            })
+            .collect::<Vec<_>>();
+        paths.push(dir.path().join("missing"));
+
+        let hashes = sha256_of_files(&paths);
+        assert_eq!(hashes.len(), paths.len());
+        for (path, hash) in paths.iter().zip(&hashes).take(10) {
+            assert_eq!(hash.as_ref().unwrap(), &sha256_of_file(path).unwrap());
+        }
+        assert!(hashes[10].is_err());
+        assert!(sha256_of_files(&[]).is_empty());
+    }
+}
@@ -0,0 +1,408 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A local network run in the background by `sui start`, with its genesis, log and process
+//! file in the `localnet` folder of the data folder.
+
+use std::fs::File;
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Error};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::commands::ComponentCommands;
+use crate::handle_commands::handle_cmd;
+use crate::handlers::install::InstallOptions;
+use crate::handlers::run::{record_use, select_binary};
+use crate::handlers::spinner;
+use crate::output;
+use crate::paths::get_suiup_data_dir;
+use crate::state::write_file_atomically;
+use crate::status;
+
+const RPC_PORT: u16 = 9000;
+const FAUCET_PORT: u16 = 9123;
+/// How long `start` waits for the RPC port to accept connections
+const START_TIMEOUT: Duration = Duration::from_secs(120);
+/// How long `stop` waits for the network to shut down before killing it
+const STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The running local network, written by `start` and removed by `stop`
+#[derive(Serialize, Deserialize, Debug)]
+struct LocalnetProcess {
+    pid: u32,
+    /// Network release and version of the sui binary, e.g. `testnet v1.40.1`
+    sui: String,
+    started_at: DateTime<Utc>,
+}
+
+/// The sui version the genesis was generated with
+#[derive(Serialize, Deserialize, Debug)]
+struct Genesis {
+    sui: String,
+    created_at: DateTime<Utc>,
+}
+
+fn localnet_dir() -> PathBuf {
+    get_suiup_data_dir().join("localnet")
+}
+
+/// Working folder of `sui genesis` and `sui start`
+fn network_dir() -> PathBuf {
+    localnet_dir().join("network")
+}
+
+fn process_file() -> PathBuf {
+    localnet_dir().join("process.json")
+}
+
+fn genesis_file() -> PathBuf {
+    localnet_dir().join("genesis.json")
+}
+
+fn log_file() -> PathBuf {
+    localnet_dir().join("localnet.log")
+}
+
+/// Handles `suiup localnet start`: installs the sui version if it is missing, generates the
+/// genesis if there is none and runs `sui start` in the background
+pub async fn handle_localnet_start(
+    version: Option<&str>,
+    force_regenesis: bool,
+    yes: bool,
+    github_token: Option<String>,
+) -> Result<(), Error> {
+    if let Some(process) = running_process()? {
+        bail!(
+            "The local network is already running (pid {}, sui {}). Stop it with `suiup localnet stop`.",
+            process.pid,
+            process.sui
+        );
+    }
+
+    let spec = match version {
+        Some(version) => format!("sui@{version}"),
+        None => "sui".to_string(),
+    };
+    if select_binary(&spec).is_err() {
+        status!("Installing {spec} for the local network...");
+        handle_cmd(
+            ComponentCommands::Add {
+                component: spec.clone(),
+                nightly: None,
+                debug: false,
+                if_missing: true,
+                options: InstallOptions {
+                    yes,
+                    ..Default::default()
+                },
+            },
+            github_token,
+        )
+        .await?;
+    }
+    let (binary, sui) = select_binary(&spec)?;
+    record_use(std::slice::from_ref(&binary))?;
+    let sui_version = format!("{} {}", binary.network_release, binary.version);
+
+    std::fs::create_dir_all(localnet_dir())?;
+    let network_dir = network_dir();
+    if force_regenesis && network_dir.exists() {
+        std::fs::remove_dir_all(&network_dir)
+            .map_err(|e| anyhow!("Cannot remove {}: {e}", network_dir.display()))?;
+    }
+    if !network_dir.join("network.yaml").exists() {
+        generate_genesis(&sui, &network_dir, &sui_version)?;
+    } else if let Some(genesis) = read_json::<Genesis>(&genesis_file()) {
+        if genesis.sui != sui_version {
+            eprintln!(
+                "{} The local network was created with sui {}, it is started with sui {sui_version}. If it does not start, use `--force-regenesis` to create a new one.", output::warning_sign(),
+                genesis.sui
+            );
+        }
+    }
+
+    let log = File::create(log_file())
+        .map_err(|e| anyhow!("Cannot create {}: {e}", log_file().display()))?;
+    let mut command = Command::new(&sui);
+    command
+        .arg("start")
+        .arg("--network.config")
+        .arg(&network_dir)
+        .arg("--with-faucet")
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log);
+    detach(&mut command);
+    let mut child = command
+        .spawn()
+        .map_err(|e| anyhow!("Cannot run {}: {e}", sui.display()))?;
+    let process = LocalnetProcess {
+        pid: child.id(),
+        sui: sui_version,
+        started_at: Utc::now(),
+    };
+    write_file_atomically(
+        &process_file(),
+        serde_json::to_string_pretty(&process)?.as_bytes(),
+    )?;
+
+    let pb = spinner(
+        "start",
+        format!("Starting the local network with sui {}...", process.sui),
+    );
+    let started = Instant::now();
+    while !is_listening(RPC_PORT) {
+        if let Some(status) = child.try_wait()? {
+            pb.finish_and_clear();
+            let _ = std::fs::remove_file(process_file());
+            bail!(
+                "The local network exited ({status}), see the log at {}",
+                log_file().display()
+            );
+        }
+        if started.elapsed() > START_TIMEOUT {
+            pb.finish_and_clear();
+            bail!(
+                "The local network did not start within {}s, see the log at {}. Stop it with `suiup localnet stop`.",
+                START_TIMEOUT.as_secs(),
+                log_file().display()
+            );
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    pb.finish_and_clear();
+
+    status!("Local network started with sui {}", process.sui);
+    print_endpoints(&process);
+    status!("\nPoint the sui client to it with `sui client new-env --alias localnet --rpc http://127.0.0.1:{RPC_PORT}`.");
+    Ok(())
+}
+
+/// Handles `suiup localnet stop`
+pub async fn handle_localnet_stop() -> Result<(), Error> {
+    let Some(process) = running_process()? else {
+        status!("The local network is not running.");
+        return Ok(());
+    };
+    terminate(process.pid, false)?;
+    let stopped = Instant::now();
+    while is_running(process.pid) {
+        if stopped.elapsed() > STOP_TIMEOUT {
+            if is_localnet(process.pid) {
+                terminate(process.pid, true)?;
+            }
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    let _ = std::fs::remove_file(process_file());
+    status!("Local network stopped (pid {}).", process.pid);
+    Ok(())
+}
+
+/// Handles `suiup localnet status`
+pub fn handle_localnet_status() -> Result<(), Error> {
+    let Some(process) = running_process()? else {
+        println!("The local network is not running. Start it with `suiup localnet start`.");
+        return Ok(());
+    };
+    let ready = if is_listening(RPC_PORT) {
+        "running"
+    } else {
+        "starting"
+    };
+    println!(
+        "Local network {ready} with sui {} since {}",
+        process.sui,
+        process.started_at.format("%Y-%m-%d %H:%M:%S UTC")
+    );
+    print_endpoints(&process);
+    Ok(())
+}
+
+fn print_endpoints(process: &LocalnetProcess) {
+    println!("  PID:     {}", process.pid);
+    println!("  RPC:     http://127.0.0.1:{RPC_PORT}");
+    println!("  Faucet:  http://127.0.0.1:{FAUCET_PORT}");
+    println!("  Config:  {}", network_dir().display());
+    println!("  Log:     {}", log_file().display());
+}
+
+/// Runs `sui genesis` to create the network configuration in `dir`
+fn generate_genesis(sui: &Path, dir: &Path, sui_version: &str) -> Result<(), Error> {
+    let pb = spinner(
+        "genesis",
+        format!("Generating the genesis with sui {sui_version}..."),
+    );
+    let output = Command::new(sui)
+        .arg("genesis")
+        .arg("--working-dir")
+        .arg(dir)
+        .arg("--with-faucet")
+        .output()
+        .map_err(|e| anyhow!("Cannot run {}: {e}", sui.display()))?;
+    pb.finish_and_clear();
+    if !output.status.success() {
+        bail!(
+            "Cannot generate the genesis of the local network: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let genesis = Genesis {
+        sui: sui_version.to_string(),
+        created_at: Utc::now(),
+    };
+    write_file_atomically(
+        &genesis_file(),
+        serde_json::to_string_pretty(&genesis)?.as_bytes(),
+    )
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Option<T> {
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+/// The local network started by `start`, if it is still running. The process file of a
+/// network that exited is removed, as is the one of a pid reused by another process, e.g. after
+/// a reboot.
+fn running_process() -> Result<Option<LocalnetProcess>, Error> {
+    let path = process_file();
+    let Some(process) = read_json::<LocalnetProcess>(&path) else {
+        return Ok(None);
+    };
+    if is_localnet(process.pid) {
+        return Ok(Some(process));
+    }
+    std::fs::remove_file(&path).map_err(|e| anyhow!("Cannot remove {}: {e}", path.display()))?;
+    Ok(None)
+}
+
+/// Whether `pid` is the `sui start` of the local network, i.e. runs with its network folder.
+/// Its pid alone may have been given to another process since.
+fn is_localnet(pid: u32) -> bool {
+    let network_dir = network_dir();
+    command_line(pid).is_some_and(|command_line| {
+        command_line.contains(" start ") && command_line.contains(&*network_dir.to_string_lossy())
+    })
+}
+
+fn is_listening(port: u16) -> bool {
+    TcpStream::connect_timeout(&([127, 0, 0, 1], port).into(), Duration::from_millis(200)).is_ok()
+}
+
+/// Keeps the network running after suiup exits, and out of the reach of Ctrl-C in the terminal
+#[cfg(unix)]
+fn detach(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+    command.process_group(0);
+}
+
+#[cfg(windows)]
+fn detach(command: &mut Command) {
+    use std::os::windows::process::CommandExt;
+    const DETACHED_PROCESS: u32 = 0x0000_0008;
+    const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+    command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+}
+
+/// The command line of the process `pid`, if it is running
+#[cfg(unix)]
+fn command_line(pid: u32) -> Option<String> {
+    let output = Command::new("ps")
+        .args(["-o", "args=", "-p", &pid.to_string()])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(windows)]
+fn command_line(pid: u32) -> Option<String> {
+    let output = Command::new("powershell")
+        .args([
+            "-NoProfile",
+            "-Command",
+            &format!("(Get-CimInstance Win32_Process -Filter 'ProcessId={pid}').CommandLine"),
+        ])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    let command_line = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !command_line.is_empty()).then_some(command_line)
+}
+
+#[cfg(unix)]
+fn is_running(pid: u32) -> bool {
+    Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+#[cfg(windows)]
+fn is_running(pid: u32) -> bool {
+    Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {pid}"), "/NH"])
+        .output()
+        .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()))
+}
+
+/// Asks the process to exit, or kills it with `force`
+#[cfg(unix)]
+fn terminate(pid: u32, force: bool) -> Result<(), Error> {
+    let signal = if force { "-KILL" } else { "-TERM" };
+    let status = Command::new("kill")
+        .args([signal, &pid.to_string()])
+        .status()
+        .map_err(|e| anyhow!("Cannot stop the local network: {e}"))?;
+    if !status.success() && is_running(pid) {
+        bail!("Cannot stop the local network (pid {pid})");
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn terminate(pid: u32, _force: bool) -> Result<(), Error> {
+    let status = Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T", "/F"])
+        .stdout(Stdio::null())
+        .status()
+        .map_err(|e| anyhow!("Cannot stop the local network: {e}"))?;
+    if !status.success() && is_running(pid) {
+        bail!("Cannot stop the local network (pid {pid})");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_running() {
+        assert!(is_running(std::process::id()));
+        let mut child = Command::new("cargo").arg("--version").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        assert!(!is_running(pid));
+    }
+
+    #[test]
+    fn test_is_localnet() {
+        assert!(command_line(std::process::id()).is_some());
+        assert!(!is_localnet(std::process::id()));
+        let mut child = Command::new("cargo").arg("--version").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        assert!(command_line(pid).is_none());
+    }
+}
@@ -0,0 +1,293 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{btree_map::Entry, BTreeMap};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Error};
+
+use crate::commands::BinaryName;
+use crate::config::load_config;
+use crate::handlers::download::{find_release, select_asset};
+use crate::handlers::integrity::sha256_of_file;
+use crate::handlers::manifest::{Manifest, ManifestEntry};
+use crate::handlers::switch::binary_source_path_in;
+use crate::http;
+use crate::output;
+use crate::paths::{binaries_dir, release_archive_dir};
+use crate::platform::{current_target, Target};
+use crate::standalone::StandaloneInstaller;
+use crate::state;
+use crate::status;
+use crate::types::{Binaries, BinaryVersion, BuildType};
+
+const HEADER: &str = "# Generated by `suiup lock`. Install these exact binaries with\n\
+                      # `suiup install --manifest suiup.lock`.\n\n";
+
+/// Handles the `lock` command. The checksums are those of the release assets of every platform
+/// GitHub publishes digests for, and of the download of this machine otherwise.
+pub async fn handle_lock(
+    all: bool,
+    output: &Path,
+    github_token: Option<String>,
+) -> Result<(), Error> {
+    let installed = state::installed_binaries()?.binaries().to_vec();
+    let binaries = if all {
+        installed
+    } else {
+        // The installed entries have the provenance of the downloads
+        Binaries::from(state::default_versions()?)
+            .binaries
+            .into_iter()
+            .map(|default| {
+                installed
+                    .iter()
+                    .find(|b| {
+                        b.binary_name == default.binary_name
+                            && b.network_release == default.network_release
+                            && b.version == default.version
+                            && b.debug == default.debug
+                    })
+                    .cloned()
+                    .unwrap_or(default)
+            })
+            .collect()
+    };
+
+    let mut locked = Vec::new();
+    for binary in &binaries {
+        // Nightly builds are built from a branch, and cannot be installed again from a spec
+        if binary.version == "nightly" {
+            status!(
+                "Skipping {} (nightly build of {}), it cannot be locked",
+                binary.binary_name,
+                binary.network_release
+            );
+            continue;
+        }
+        locked.push(binary);
+    }
+    if locked.is_empty() {
+        bail!("No binaries to lock. Install some with `suiup install` first.");
+    }
+
+    let mut entries = Vec::new();
+    for binary in locked {
+        let mut checksums = release_checksums(binary, github_token.clone()).await?;
+        let target = current_target(None)?.to_string();
+        if let Entry::Vacant(entry) = checksums.entry(target) {
+            if let Some(sha256) = download_sha256(binary) {
+                entry.insert(sha256);
+            }
+        }
+        if checksums.is_empty() {
+            eprintln!(
+                "{} Cannot find the checksum of {} {} ({}), it is locked without one",
+                output::warning_sign(),
+                binary.binary_name,
+                binary.version,
+                binary.network_release
+            );
+        }
+        entries.push(lock_entry(binary, checksums));
+    }
+    entries.sort_by(|a, b| (&a.name, &a.network).cmp(&(&b.name, &b.network)));
+    entries.dedup();
+
+    let lock = toml::to_string(&Manifest { binaries: entries })?;
+    std::fs::write(output, format!("{HEADER}{lock}"))
+        .map_err(|e| anyhow!("Cannot write the lock file {}: {e}", output.display()))?;
+    status!("Lock file written to {}", output.display());
+    Ok(())
+}
+
+/// SHA-256 checksums GitHub publishes for the release assets of `binary`, keyed by target. None
+/// offline, or when the binary is downloaded from a source set in the config file.
+async fn release_checksums(
+    binary: &BinaryVersion,
+    github_token: Option<String>,
+) -> Result<BTreeMap<String, String>, Error> {
+    let Ok(name) = binary.binary_name.parse::<BinaryName>() else {
+        return Ok(BTreeMap::new());
+    };
+    if http::is_offline() || load_config()?.sources.contains_key(name.to_str()) {
+        return Ok(BTreeMap::new());
+    }
+    let repo = name.repo();
+    if binary.network_release == "standalone" {
+        let mut installer = StandaloneInstaller::new(repo);
+        installer.get_releases().await?;
+        return Ok(asset_checksums(|target| {
+            installer
+                .find_asset_for(&binary.version, target)
+                .ok()?
+                .digest
+                .clone()
+        }));
+    }
+    let release = find_release(
+        &repo,
+        &binary.network_release,
+        Some(&binary.version),
+        github_token,
+    )
+    .await?;
+    Ok(asset_checksums(|target| {
+        select_asset(&repo, &release, target).ok()?.digest.clone()
+    }))
+}
+
+/// The SHA-256 digests of the assets of each target, given the digest published for the asset
+/// of a target, e.g. `sha256:<hex>`
+fn asset_checksums(digest: impl Fn(&Target) -> Option<String>) -> BTreeMap<String, String> {
+    Target::all()
+        .into_iter()
+        .filter_map(|target| {
+            let digest = digest(&target)?;
+            let sha256 = digest.strip_prefix("sha256:")?;
+            Some((target.to_string(), sha256.to_ascii_lowercase()))
+        })
+        .collect()
+}
+
+/// SHA-256 of the download `binary` was installed from, if it is still on disk
+pub(crate) fn download_sha256(binary: &BinaryVersion) -> Option<String> {
+    let path = download_path_in(&binaries_dir(), &release_archive_dir(), binary)?;
+    sha256_of_file(&path).ok()
+}
+
+/// The download `binary` was installed from: the release archive in `archives_dir`, or the
+/// binary itself in `binaries_dir` when it was downloaded as is
+fn download_path_in(
+    binaries_dir: &Path,
+    archives_dir: &Path,
+    binary: &BinaryVersion,
+) -> Option<PathBuf> {
+    let provenance = binary.provenance.as_ref()?;
+    match &provenance.archive {
+        Some(archive) => Some(archives_dir.join(archive)),
+        None if provenance.build_type != BuildType::Nightly => {
+            Some(binary_source_path_in(binaries_dir, binary))
+        }
+        None => None,
+    }
+}
+
+fn lock_entry(binary: &BinaryVersion, sha256: BTreeMap<String, String>) -> ManifestEntry {
+    ManifestEntry {
+        name: binary.binary_name.clone(),
+        network: Some(binary.network_release.clone()),
+        version: Some(binary.version.trim_start_matches('v').to_string()),
+        debug: binary.debug,
+        sha256,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Provenance;
+
+    const SHA256: &str = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+
+    fn binary(provenance: Option<Provenance>) -> BinaryVersion {
+        BinaryVersion {
+            binary_name: "sui".to_string(),
+            network_release: "testnet".to_string(),
+            version: "v1.40.1".to_string(),
+            debug: false,
+            path: None,
+            provenance,
+            last_used: None,
+        }
+    }
+
+    #[test]
+    fn test_asset_checksums() {
+        let assets = [
+            (
+                "sui-testnet-v1.40.1-ubuntu-x86_64.tgz",
+                Some(format!("sha256:{SHA256}")),
+            ),
+            (
+                "sui-testnet-v1.40.1-macos-arm64.tgz",
+                Some("sha256:ABCD".to_string()),
+            ),
+            // Only SHA-256 digests are recorded
+            (
+                "sui-testnet-v1.40.1-windows-x86_64.tgz",
+                Some("sha512:00".to_string()),
+            ),
+            ("sui-testnet-v1.40.1-ubuntu-aarch64.tgz", None),
+        ];
+        let checksums = asset_checksums(|target| {
+            assets
+                .iter()
+                .find(|(name, _)| target.matches_asset(name))?
+                .1
+                .clone()
+        });
+        assert_eq!(
+            checksums,
+            BTreeMap::from([
+                ("linux-x86_64".to_string(), SHA256.to_string()),
+                ("macos-aarch64".to_string(), "abcd".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_lock_entry() {
+        let checksums = BTreeMap::from([("linux-x86_64".to_string(), SHA256.to_string())]);
+        let lock = toml::to_string(&Manifest {
+            binaries: vec![lock_entry(&binary(None), checksums.clone())],
+        })
+        .unwrap();
+        assert_eq!(
+            lock,
+            format!(
+                "[[binary]]\nname = \"sui\"\nnetwork = \"testnet\"\nversion = \"1.40.1\"\n\n[binary.sha256]\nlinux-x86_64 = \"{SHA256}\"\n"
+            )
+        );
+        let manifest: Manifest = toml::from_str(&lock).unwrap();
+        assert_eq!(manifest.binaries[0].spec(), "sui@testnet-1.40.1");
+        assert_eq!(manifest.binaries[0].sha256, checksums);
+    }
+
+    #[test]
+    fn test_download_path() {
+        let provenance = |archive: Option<&str>, build_type| Provenance {
+            build_type,
+            source_url: None,
+            archive: archive.map(str::to_string),
+            download_blake3: None,
+            download_md5: None,
+            binary_blake3: None,
+            branch: None,
+            commit: None,
+            installed_at: chrono::Utc::now(),
+            installed_by: None,
+            user: None,
+            command: None,
+        };
+        let (binaries, archives) = (Path::new("binaries"), Path::new("release_archives"));
+        let download_path = |binary: &BinaryVersion| download_path_in(binaries, archives, binary);
+
+        let archive = "sui-testnet-v1.40.1-ubuntu-x86_64.tgz";
+        assert_eq!(
+            download_path(&binary(Some(provenance(Some(archive), BuildType::Release)))),
+            Some(archives.join(archive))
+        );
+        // Binaries downloaded as is, e.g. from a source serving binaries
+        assert_eq!(
+            download_path(&binary(Some(provenance(None, BuildType::Release)))),
+            Some(binary_source_path_in(binaries, &binary(None)))
+        );
+        // Nightly builds are not downloaded, and installs of older suiup versions are unknown
+        assert_eq!(
+            download_path(&binary(Some(provenance(None, BuildType::Nightly)))),
+            None
+        );
+        assert_eq!(download_path(&binary(None)), None);
+    }
+}
@@ -0,0 +1,299 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Error};
+use comfy_table::{Cell, Table};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::commands::{parse_component_with_version, table_format, BinaryName, ComponentCommands};
+use crate::config::load_config;
+use crate::handle_commands::handle_cmd;
+use crate::handlers::download::{download_latest_release, download_release_at_version};
+use crate::handlers::install::{find_installed_version, InstallOptions};
+use crate::handlers::lock::download_sha256;
+use crate::http::{self, Skipped};
+use crate::output;
+use crate::platform::{current_target, Target};
+use crate::redact::redact;
+use crate::types::{BinaryVersion, InstalledBy};
+
+/// List of binaries to install with `suiup install --manifest`, e.g. `suiup.toml`:
+///
+/// ```toml
+/// [[binary]]
+/// name = "sui"
+/// network = "testnet"
+/// version = "1.40.1"
+/// ```
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct Manifest {
+    #[serde(default, rename = "binary")]
+    pub binaries: Vec<ManifestEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestEntry {
+    pub name: String,
+    /// Network release, the default network of the binary if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+    /// Version, the latest release of the network if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub debug: bool,
+    /// SHA-256 of the release asset (archive, or binary for standalone binaries) of each
+    /// platform, keyed by target as in `--target`, e.g. `linux-x86_64`. Set by `suiup lock`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sha256: BTreeMap<String, String>,
+}
+
+impl ManifestEntry {
+    /// The checksum the download for `target` must have. Entries with checksums for other
+    /// platforms only are installed unchecked, after a warning.
+    fn checksum(&self, target: &Target) -> Option<String> {
+        if self.sha256.is_empty() {
+            return None;
+        }
+        let checksum = self.sha256.get(&target.to_string()).cloned();
+        if checksum.is_none() {
+            eprintln!(
+                "{} No checksum of {} for {target} in the lock file, it cannot be verified",
+                output::warning_sign(),
+                self.spec()
+            );
+        }
+        checksum
+    }
+
+    /// The component spec to install, e.g. `sui@testnet-1.40.1`
+    pub fn spec(&self) -> String {
+        // Standalone binaries (mvr) are not tied to a network
+        let network = self.network.as_deref().filter(|n| *n != "standalone");
+        match (network, &self.version) {
+            (Some(network), Some(version)) => format!("{}@{network}-{version}", self.name),
+            (Some(network), None) => format!("{}@{network}", self.name),
+            (None, Some(version)) => format!("{}@{version}", self.name),
+            (None, None) => self.name.clone(),
+        }
+    }
+}
+
+pub fn load_manifest(path: &Path) -> Result<Manifest, Error> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Cannot read the manifest {}: {e}", path.display()))?;
+    toml::from_str(&content).map_err(|e| anyhow!("Invalid manifest {}: {e}", path.display()))
+}
+
+/// Installs the binaries of the manifest that are not installed yet, and prints what happened
+/// to each of them. Failures do not stop the other installs.
+pub async fn install_manifest(
+    path: &Path,
+    github_token: Option<String>,
+    options: &InstallOptions,
+) -> Result<(), Error> {
+    let manifest = load_manifest(path)?;
+    if manifest.binaries.is_empty() {
+        bail!("The manifest {} does not list any binary", path.display());
+    }
+    let options = &InstallOptions {
+        installed_by: Some(InstalledBy::Manifest),
+        ..options.clone()
+    };
+    prefetch_archives(&manifest.binaries, github_token.clone(), options.force).await;
+
+    let mut rows = Vec::new();
+    let mut failed = 0;
+    for entry in &manifest.binaries {
+        let spec = entry.spec();
+        let status = match install_entry(entry, &spec, github_token.clone(), options).await {
+            Ok(true) => "installed".to_string(),
+            Ok(false) => "skipped (already installed)".to_string(),
+            Err(e) if e.is::<Skipped>() => "skipped (network failure)".to_string(),
+            Err(e) => {
+                failed += 1;
+                format!("failed: {}", redact(&e.to_string()))
+            }
+        };
+        rows.push(vec![Cell::new(spec), Cell::new(status)]);
+    }
+
+    let mut table = Table::new();
+    table
+        .load_preset(table_format())
+        .set_header(vec![Cell::new("Binary"), Cell::new("Status")])
+        .add_rows(rows);
+    println!("{table}");
+
+    if failed > 0 {
+        bail!(
+            "{failed} of {} binaries failed to install",
+            manifest.binaries.len()
+        );
+    }
+    Ok(())
+}
+
+/// Downloads the release archives of the entries to install together, at most
+/// `download_concurrency` at a time, so that installing the entries one after the other finds
+/// them in the cache. Failures are left to the installs to report. `force` downloads the cached
+/// archives again.
+async fn prefetch_archives(entries: &[ManifestEntry], github_token: Option<String>, force: bool) {
+    if http::is_offline() {
+        return;
+    }
+    let Ok(config) = load_config() else {
+        return;
+    };
+    let downloads = entries
+        .iter()
+        .filter_map(|entry| {
+            let metadata = parse_component_with_version(&entry.spec()).ok()?;
+            let (nightly, debug) = metadata.with_flags(None, entry.debug).ok()?;
+            // Nightly builds, mvr and binaries from a configured source are not release archives
+            if nightly.is_some()
+                || metadata.name == BinaryName::Mvr
+                || config.sources.contains_key(metadata.name.to_str())
+            {
+                return None;
+            }
+            if !force
+                && find_installed_version(&metadata, None, debug)
+                    .ok()?
+                    .is_some()
+            {
+                return None;
+            }
+            let github_token = github_token.clone();
+            Some(async move {
+                let repo = metadata.name.repo();
+                let network = metadata.network.as_str();
+                let downloaded = match &metadata.version {
+                    Some(version) => {
+                        download_release_at_version(
+                            repo,
+                            network,
+                            version,
+                            github_token,
+                            force,
+                            None,
+                        )
+                        .await
+                    }
+                    None => download_latest_release(repo, network, github_token, force, None).await,
+                };
+                if let Err(e) = downloaded {
+                    debug!(
+                        "Cannot download {} ahead of installing it: {e}",
+                        entry.spec()
+                    );
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    // A single download gains nothing from running ahead of its install
+    if downloads.len() > 1 {
+        futures_util::future::join_all(downloads).await;
+    }
+}
+
+/// Installs an entry, returning `false` if it was already installed. The download of an entry
+/// with a checksum is verified before it is extracted, and an installed entry fails if the
+/// download it was installed from is still around and does not match.
+async fn install_entry(
+    entry: &ManifestEntry,
+    spec: &str,
+    github_token: Option<String>,
+    options: &InstallOptions,
+) -> Result<bool, Error> {
+    let metadata = parse_component_with_version(spec)?;
+    let (nightly, debug) = metadata.with_flags(None, entry.debug)?;
+    let checksum = entry.checksum(&current_target(None)?);
+    if !options.force {
+        if let Some(installed) = find_installed_version(&metadata, nightly.as_deref(), debug)? {
+            verify_installed(checksum.as_deref(), &installed)?;
+            return Ok(false);
+        }
+    }
+    handle_cmd(
+        ComponentCommands::Add {
+            component: spec.to_string(),
+            nightly: None,
+            debug: entry.debug,
+            if_missing: false,
+            options: InstallOptions {
+                sha256: checksum,
+                ..options.clone()
+            },
+        },
+        github_token,
+    )
+    .await?;
+    Ok(true)
+}
+
+fn verify_installed(expected: Option<&str>, installed: &BinaryVersion) -> Result<(), Error> {
+    let Some(expected) = expected else {
+        return Ok(());
+    };
+    match download_sha256(installed) {
+        Some(actual) if !actual.eq_ignore_ascii_case(expected) => bail!(
+            "installed from a download that does not match the lock file: expected sha256 {expected}, got {actual}. Install it again with `--force`."
+        ),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_manifest() {
+        let manifest: Manifest = toml::from_str(
+            r#"
+            [[binary]]
+            name = "sui"
+            network = "testnet"
+            version = "1.40.1"
+            debug = true
+
+            [[binary]]
+            name = "walrus"
+            network = "mainnet"
+
+            [[binary]]
+            name = "mvr"
+            network = "standalone"
+            version = "0.0.8"
+
+            [binary.sha256]
+            linux-x86_64 = "abc"
+            macos-aarch64 = "def"
+            "#,
+        )
+        .unwrap();
+        let specs = manifest
+            .binaries
+            .iter()
+            .map(|b| b.spec())
+            .collect::<Vec<_>>();
+        assert_eq!(specs, ["sui@testnet-1.40.1", "walrus@mainnet", "mvr@0.0.8"]);
+        assert!(manifest.binaries[0].debug);
+        let linux: Target = "linux-x86_64".parse().unwrap();
+        let windows: Target = "windows-x86_64".parse().unwrap();
+        assert_eq!(
+            manifest.binaries[2].checksum(&linux).as_deref(),
+            Some("abc")
+        );
+        assert_eq!(manifest.binaries[2].checksum(&windows), None);
+        assert_eq!(manifest.binaries[0].checksum(&linux), None);
+
+        assert!(toml::from_str::<Manifest>("[[binary]]\nname = \"sui\"\nverison = \"1\"").is_err());
+    }
+}
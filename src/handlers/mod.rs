@@ -1,17 +1,28 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::paths::{binaries_dir, get_default_bin_dir, release_archive_dir};
-use crate::{paths::default_file_path, types::Version};
-use anyhow::anyhow;
+use crate::commands::parse_version_spec;
+use crate::interrupt::Partial;
+use crate::output::{self, ProgressEvent};
+use crate::paths::{binaries_dir, get_default_bin_dir, get_sandbox, release_archive_dir};
+use crate::reflink;
+use crate::state;
+use crate::status;
+use crate::timings;
 use anyhow::Error;
+use anyhow::{anyhow, bail, Context};
 use flate2::read::GzDecoder;
-use std::env;
-use std::io::Write;
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressFinish, ProgressStyle, WeakProgressBar};
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+#[cfg(windows)]
 use std::path::PathBuf;
-use std::{fs::File, io::BufReader};
+use std::sync::Mutex;
+use std::time::Duration;
 
-use crate::types::{BinaryVersion, InstalledBinaries};
+use crate::types::{default_binary_name, BinaryVersion, InstalledBinaries, Version};
 use std::collections::BTreeMap;
 #[cfg(not(windows))]
 use std::fs::set_permissions;
@@ -20,21 +31,136 @@ use std::os::unix::fs::PermissionsExt;
 use tar::Archive;
 use version::extract_version_from_release;
 
+pub mod backup;
+pub mod block;
+pub mod branches;
+pub mod changelog;
 pub mod cleanup;
+pub mod completions;
+pub mod conflicts;
+pub mod deprecation;
+pub mod diff;
 pub mod download;
+pub mod enable;
+pub mod env;
+pub mod gh;
+pub mod info;
+pub mod init;
 pub mod install;
+pub mod install_plan;
+pub mod integrity;
+pub mod localnet;
+pub mod lock;
+pub mod manifest;
+pub mod mirror;
+pub mod nightly;
+pub mod plugin;
+pub mod prompt;
 pub mod release;
+pub mod run;
+pub mod sbom;
 pub mod self_;
+pub mod shell;
 pub mod show;
+pub mod status;
 pub mod switch;
+pub mod try_;
 pub mod update;
+pub mod update_notice;
+pub mod upgrade;
 pub mod version;
 pub mod which;
+pub mod wizard;
 
 pub const RELEASES_ARCHIVES_FOLDER: &str = "releases";
 
+/// The last spinner started, hidden while suiup asks a question, see [`suspend_spinner`]
+static ACTIVE_SPINNER: Mutex<Option<WeakProgressBar>> = Mutex::new(None);
+
+/// Starts a spinner showing the current phase of a long operation, e.g. `Extracting sui...`.
+/// With `--progress json`, a `phase` event is printed instead.
+/// It is only drawn when the output is a terminal and without `--quiet`, and cleared once
+/// dropped.
+pub fn spinner(phase: &str, message: impl Into<Cow<'static, str>>) -> ProgressBar {
+    let message = message.into();
+    ProgressEvent::new(phase, &message).emit();
+    let pb = ProgressBar::new_spinner().with_finish(ProgressFinish::AndClear);
+    if output::hide_progress_bars() {
+        pb.set_draw_target(ProgressDrawTarget::hidden());
+    }
+    pb.set_style(
+        ProgressStyle::with_template("{spinner:.green} {msg}")
+            .unwrap()
+            .tick_strings(&["-", "\\", "|", "/"]),
+    );
+    pb.enable_steady_tick(Duration::from_millis(100));
+    pb.set_message(message);
+    if let Ok(mut active) = ACTIVE_SPINNER.lock() {
+        *active = Some(pb.downgrade());
+    }
+    pb
+}
+
+/// Runs `f` with the running spinner, if any, hidden, e.g. to prompt the user
+pub fn suspend_spinner<R>(f: impl FnOnce() -> R) -> R {
+    let spinner = ACTIVE_SPINNER
+        .lock()
+        .ok()
+        .and_then(|active| active.as_ref().and_then(WeakProgressBar::upgrade));
+    match spinner {
+        Some(spinner) if !spinner.is_finished() => spinner.suspend(f),
+        _ => f(),
+    }
+}
+
 pub fn available_components() -> &'static [&'static str] {
-    &["sui", "mvr", "walrus", "site-builder"]
+    &[
+        "sui",
+        "sui-node",
+        "sui-tool",
+        "sui-faucet",
+        "sui-bridge",
+        "move-analyzer",
+        "mvr",
+        "walrus",
+        "site-builder",
+    ]
+}
+
+/// A set of binaries installed together, e.g. `suiup install walrus-suite@testnet`
+pub struct ComponentGroup {
+    pub name: &'static str,
+    pub members: &'static [&'static str],
+}
+
+pub fn component_groups() -> &'static [ComponentGroup] {
+    &[
+        ComponentGroup {
+            name: "sui-full",
+            members: &["sui", "sui-node", "sui-tool"],
+        },
+        ComponentGroup {
+            name: "walrus-suite",
+            members: &["walrus", "site-builder"],
+        },
+    ]
+}
+
+/// Expands a spec naming a group, e.g. `sui-full@testnet`, into the specs of its members
+/// (`sui@testnet`, `sui-node@testnet`, ...). Returns `None` if the spec is not for a group.
+pub fn expand_group(spec: &str) -> Option<Vec<String>> {
+    let split = spec.find(['@', '=', ' ']).unwrap_or(spec.len());
+    let (name, version) = spec.split_at(split);
+    let group = component_groups()
+        .iter()
+        .find(|g| g.name.eq_ignore_ascii_case(name))?;
+    Some(
+        group
+            .members
+            .iter()
+            .map(|member| format!("{member}{version}"))
+            .collect(),
+    )
 }
 
 // Main component handling function
@@ -46,29 +172,36 @@ pub fn update_default_version_file(
     version: &str,
     debug: bool,
 ) -> Result<(), Error> {
-    let path = default_file_path()?;
-    let file = File::open(&path)?;
-    let reader = BufReader::new(file);
-    let mut map: BTreeMap<String, (String, Version, bool)> = serde_json::from_reader(reader)?;
-
-    for binary in binaries {
-        let b = map.get_mut(binary);
-        if let Some(b) = b {
-            b.0 = network.clone();
-            b.1 = version.to_string();
-            b.2 = debug;
-        } else {
-            map.insert(
-                binary.to_string(),
-                (network.clone(), version.to_string(), debug),
-            );
+    state::with_state(|state| {
+        let map = state.default_versions_mut();
+        for binary in binaries {
+            let binary = &default_binary_name(binary, debug);
+            let b = map.get_mut(binary);
+            if let Some(b) = b {
+                b.0 = network.clone();
+                b.1 = version.to_string();
+                b.2 = debug;
+            } else {
+                map.insert(
+                    binary.to_string(),
+                    (network.clone(), version.to_string(), debug),
+                );
+            }
         }
-    }
-
-    let mut file = File::create(path)?;
-    file.write_all(serde_json::to_string_pretty(&map)?.as_bytes())?;
-
-    Ok(())
+        // Switching to a version counts as using it
+        for binary in binaries {
+            state.installed_binaries_mut().mark_used(&BinaryVersion {
+                binary_name: binary.clone(),
+                network_release: network.clone(),
+                version: version.to_string(),
+                debug,
+                path: None,
+                provenance: None,
+                last_used: None,
+            });
+        }
+        Ok(())
+    })
 }
 
 /// Prompts the user and asks if they want to update the default version with the one that was just
@@ -80,13 +213,13 @@ pub fn update_after_install(
     debug: bool,
     yes: bool,
 ) -> Result<(), Error> {
+    // Binaries tried with `try` are run from where they are installed
+    if get_sandbox().is_some() {
+        return Ok(());
+    }
     // First check if the binary exists
     for binary in name {
-        let binary_name = if *binary == "sui" && debug {
-            format!("{}-debug", binary)
-        } else {
-            binary.clone()
-        };
+        let binary_name = default_binary_name(binary, debug);
 
         let binary_path = if version == "nightly" {
             // cargo install places the binary in a `bin` folder
@@ -104,7 +237,7 @@ pub fn update_after_install(
         let binary_path = binary_path.with_extension("exe");
 
         if !binary_path.exists() {
-            println!(
+            status!(
                 "Binary not found at {}. Skipping default version update.",
                 binary_path.display()
             );
@@ -161,23 +294,28 @@ pub fn update_after_install(
                 #[cfg(target_os = "windows")]
                 let filename = format!("{}.exe", filename);
 
-                println!(
+                status!(
                     "Installing binary to {}/{}",
                     binary_folder.display(),
                     filename
                 );
 
                 let src = binary_folder.join(&filename);
-                let dst = get_default_bin_dir().join(binary);
+                let default_name = default_binary_name(binary, debug);
+                let dst = get_default_bin_dir().join(&default_name);
 
-                println!("Setting {} as default", binary);
+                if enable::is_disabled(binary) {
+                    enable::report_disabled(binary);
+                    continue;
+                }
+                status!("Setting {} as default", default_name);
 
                 #[cfg(target_os = "windows")]
                 let mut dst = dst.clone();
                 #[cfg(target_os = "windows")]
                 dst.set_extension("exe");
 
-                std::fs::copy(&src, &dst).map_err(|e| {
+                replace_binary(&src, &dst).map_err(|e| {
                     anyhow!(
                         "Error copying {binary} to the default folder (src: {}, dst: {}): {e}",
                         src.display(),
@@ -185,21 +323,15 @@ pub fn update_after_install(
                     )
                 })?;
 
-                #[cfg(unix)]
-                {
-                    let mut perms = std::fs::metadata(&dst)?.permissions();
-                    perms.set_mode(0o755);
-                    std::fs::set_permissions(&dst, perms)?;
-                }
-
-                println!("[{network}] {binary}-{version} set as default");
+                status!("[{network}] {binary}-{version} set as default");
             }
             update_default_version_file(name, network, version, debug)?;
             check_path_and_warn()?;
+            conflicts::warn_about_conflicts(&name.iter().map(String::as_str).collect::<Vec<_>>());
         }
 
         "" | "n" | "no" => {
-            println!("Keeping the current default version.");
+            status!("Keeping the current default version.");
         }
         _ => {
             println!("Invalid input. Please enter 'y' or 'n'.");
@@ -209,11 +341,87 @@ pub fn update_after_install(
     Ok(())
 }
 
+/// Copies `src` over `dst`, even if `dst` is currently running. The copy shares the data of
+/// `src` on filesystems supporting reflinks.
+///
+/// The binary is copied next to `dst` first and then renamed into place. Unix allows replacing a
+/// running executable this way, Windows does not, but it allows renaming it. So on Windows the
+/// running binary is moved out of the way to `<name>.old` first, and removed on a later run by
+/// [`cleanup_stale_binaries`].
+pub fn replace_binary(src: &Path, dst: &Path) -> Result<(), Error> {
+    let file_name = dst
+        .file_name()
+        .ok_or_else(|| anyhow!("Invalid binary path: {}", dst.display()))?
+        .to_string_lossy();
+    let tmp = dst.with_file_name(format!(".{file_name}.tmp"));
+    // Removed unless renamed into place
+    let partial = Partial::new(&tmp);
+    reflink::copy(src, &tmp).map_err(|e| anyhow!("Cannot copy to {}: {e}", tmp.display()))?;
+
+    #[cfg(not(windows))]
+    set_permissions(&tmp, PermissionsExt::from_mode(0o755))?;
+
+    #[cfg(windows)]
+    if dst.exists() && std::fs::remove_file(dst).is_err() {
+        // Most likely running, which prevents deleting it but not renaming it
+        let old = stale_binary_path(dst);
+        if let Err(e) = std::fs::rename(dst, &old) {
+            return Err(anyhow!("Cannot replace {}: {e}", dst.display()));
+        }
+    }
+
+    std::fs::rename(&tmp, dst).map_err(|e| anyhow!("Cannot replace {}: {e}", dst.display()))?;
+    partial.complete();
+    Ok(())
+}
+
+/// Returns a free `<name>.old` path to move a running binary to. An older one may still be
+/// running as well, in which case a numbered name is used.
+#[cfg(windows)]
+fn stale_binary_path(dst: &Path) -> PathBuf {
+    let file_name = dst.file_name().unwrap_or_default().to_string_lossy();
+    (0..)
+        .map(|i| match i {
+            0 => dst.with_file_name(format!("{file_name}.old")),
+            i => dst.with_file_name(format!("{file_name}.{i}.old")),
+        })
+        .find(|path| !path.exists() || std::fs::remove_file(path).is_ok())
+        .expect("there is always a free name")
+}
+
+/// Removes the binaries that [`replace_binary`] moved out of the way because they were running,
+/// from the default bin folder and the folder of suiup itself. Binaries still running are left
+/// for the next time.
+pub fn cleanup_stale_binaries() {
+    #[cfg(windows)]
+    {
+        let mut dirs = vec![get_default_bin_dir()];
+        if let Some(dir) = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        {
+            dirs.push(dir);
+        }
+
+        for dir in dirs {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_lowercase();
+                if name.ends_with(".old") && name.contains(".exe.") {
+                    let _ = std::fs::remove_file(entry.path());
+                }
+            }
+        }
+    }
+}
+
 fn check_path_and_warn() -> Result<(), Error> {
     let local_bin = get_default_bin_dir();
 
     // Check if the bin directory exists in PATH
-    if let Ok(path) = env::var("PATH") {
+    if let Ok(path) = std::env::var("PATH") {
         #[cfg(windows)]
         let path_separator = ';';
         #[cfg(not(windows))]
@@ -221,9 +429,18 @@ fn check_path_and_warn() -> Result<(), Error> {
 
         if !path
             .split(path_separator)
-            .any(|p| PathBuf::from(p) == local_bin)
+            .any(|p| local_bin == std::path::Path::new(p))
         {
-            println!("\nWARNING: {} is not in your PATH", local_bin.display());
+            // On stderr, so that it is shown with `--quiet` without mixing with the output
+            eprintln!(
+                "\n{} {} is not in your PATH",
+                output::warning_sign(),
+                local_bin.display()
+            );
+            eprintln!("Run `suiup init` to add it to your shell startup files.");
+            if output::is_quiet() {
+                return Ok(());
+            }
 
             #[cfg(windows)]
             {
@@ -263,71 +480,107 @@ fn check_path_and_warn() -> Result<(), Error> {
 /// `binary` parameter.
 ///
 /// This extracts the component to the binaries folder under the network from which release comes
-/// from, and sets the correct permissions for Unix based systems.
+/// from, and sets the correct permissions for Unix based systems. Other files of the archive are
+/// skipped, release archives bundle all the binaries of a release.
 fn extract_component(orig_binary: &str, network: String, filename: &str) -> Result<(), Error> {
+    let _timer = timings::phase("extract");
     let mut archive_path = release_archive_dir();
     archive_path.push(filename);
 
-    let file = File::open(archive_path.as_path())
-        .map_err(|_| anyhow!("Cannot open archive file: {}", archive_path.display()))?;
-    let tar = GzDecoder::new(file);
-    let mut archive = Archive::new(tar);
-
     #[cfg(not(windows))]
     let binary = orig_binary.to_string();
     #[cfg(windows)]
     let binary = format!("{}.exe", orig_binary);
 
-    // Check if the current entry matches the file name
-    for file in archive
+    let mut output_path = binaries_dir();
+    output_path.push(&network);
+    if !output_path.is_dir() {
+        std::fs::create_dir_all(output_path.as_path())?;
+    }
+    let version = extract_version_from_release(filename)?;
+    let binary_version = format!("{}-{}", orig_binary, version);
+    #[cfg(not(windows))]
+    output_path.push(&binary_version);
+    #[cfg(windows)]
+    output_path.push(&format!("{}.exe", binary_version));
+
+    state::journal::begin(&output_path)?;
+    let pb = spinner("extract", format!("Extracting {binary}..."));
+    if !extract_file(&archive_path, &binary, &output_path)? {
+        bail!("The archive {filename} does not contain {binary}");
+    }
+    pb.suspend(|| status!(" '{}' extracted successfully!", &binary));
+    Ok(())
+}
+
+/// The extension of `name` if it is a release archive suiup can extract: a tarball compressed
+/// with gzip or zstd
+pub(crate) fn archive_extension(name: &str) -> Option<&'static str> {
+    ["tgz", "tar.gz", "tzst", "tar.zst"]
+        .into_iter()
+        .find(|extension| {
+            name.strip_suffix(extension)
+                .is_some_and(|name| name.ends_with('.'))
+        })
+}
+
+/// Extracts the file named `file_name` (in any folder) from a `.tar.gz` or `.tar.zst` archive to
+/// `output_path`, keeping its permissions on Unix. The archive is streamed and only read up to
+/// that file. Returns `false` if the archive does not contain it.
+pub(crate) fn extract_file(
+    archive_path: &Path,
+    file_name: &str,
+    output_path: &Path,
+) -> Result<bool, Error> {
+    let file = File::open(archive_path)
+        .with_context(|| format!("Cannot open archive file: {}", archive_path.display()))?;
+    let zstd = archive_path
+        .to_str()
+        .and_then(archive_extension)
+        .is_some_and(|extension| extension.ends_with("zst"));
+    let reader: Box<dyn Read> = if zstd {
+        Box::new(zstd::Decoder::new(file)?)
+    } else {
+        Box::new(GzDecoder::new(file))
+    };
+    let mut archive = Archive::new(reader);
+
+    for entry in archive
         .entries()
         .map_err(|e| anyhow!("Cannot iterate through archive entries: {e}"))?
     {
-        let mut f = file.unwrap();
-        if f.path()?.file_name().and_then(|x| x.to_str()) == Some(&binary) {
-            println!("Extracting file: {}", &binary);
-
-            let mut output_path = binaries_dir();
-            output_path.push(&network);
-            if !output_path.is_dir() {
-                std::fs::create_dir_all(output_path.as_path())?;
-            }
-            let version = extract_version_from_release(filename)?;
-            let binary_version = format!("{}-{}", orig_binary, version);
-            #[cfg(not(windows))]
-            output_path.push(&binary_version);
-            #[cfg(windows)]
-            output_path.push(&format!("{}.exe", binary_version));
-
-            let mut output_file = File::create(&output_path).map_err(|e| {
-                anyhow!(
-                    "Cannot create output path ({}) for extracting this file {binary_version}: {e}",
-                    output_path.display()
-                )
-            })?;
-
-            std::io::copy(&mut f, &mut output_file).map_err(|e| {
-                anyhow!("Cannot copy the file ({orig_binary}) into the output path: {e}")
-            })?;
-            println!(" '{}' extracted successfully!", &binary);
-            #[cfg(not(target_os = "windows"))]
-            {
-                // Retrieve and apply the original file permissions on Unix-like systems
-                if let Ok(permissions) = f.header().mode() {
-                    set_permissions(output_path, PermissionsExt::from_mode(permissions)).map_err(
-                        |e| {
-                            anyhow!(
-                                "Cannot apply the original file permissions in a unix system: {e}"
-                            )
-                        },
-                    )?;
-                }
+        let mut entry = entry.map_err(|e| {
+            anyhow!(
+                "Cannot read the archive {}, it may be corrupted: {e}",
+                archive_path.display()
+            )
+        })?;
+        if entry.path()?.file_name().and_then(|x| x.to_str()) != Some(file_name) {
+            continue;
+        }
+
+        let partial = Partial::new(output_path);
+        let mut output_file = File::create(output_path).map_err(|e| {
+            anyhow!(
+                "Cannot create output path ({}) for extracting {file_name}: {e}",
+                output_path.display()
+            )
+        })?;
+        std::io::copy(&mut entry, &mut output_file)
+            .map_err(|e| anyhow!("Cannot copy the file ({file_name}) into the output path: {e}"))?;
+        #[cfg(not(target_os = "windows"))]
+        {
+            // Retrieve and apply the original file permissions on Unix-like systems
+            if let Ok(permissions) = entry.header().mode() {
+                set_permissions(output_path, PermissionsExt::from_mode(permissions)).map_err(
+                    |e| anyhow!("Cannot apply the original file permissions in a unix system: {e}"),
+                )?;
             }
-            break;
         }
+        partial.complete();
+        return Ok(true);
     }
-
-    Ok(())
+    Ok(false)
 }
 
 /// Checks if the binaries exist in the binaries folder
@@ -350,7 +603,7 @@ pub fn check_if_binaries_exist(
         path.push(format!("{}.exe", binary_version));
     }
     path.push(&binary_version);
-    Ok(path.exists())
+    Ok(path.exists() && !state::journal::is_incomplete(&path))
 }
 
 /// Returns a map of installed binaries grouped by network releases
@@ -360,7 +613,7 @@ pub fn installed_binaries_grouped_by_network(
     let installed_binaries = if let Some(installed_binaries) = installed_binaries {
         installed_binaries
     } else {
-        InstalledBinaries::new()?
+        state::installed_binaries()?
     };
     let binaries = installed_binaries.binaries();
     let mut files_by_folder: BTreeMap<String, Vec<BinaryVersion>> = BTreeMap::new();
@@ -375,3 +628,142 @@ pub fn installed_binaries_grouped_by_network(
 
     Ok(files_by_folder)
 }
+
+/// Finds the installed binaries matching a `binary[@network_release[-version]]` spec
+pub fn find_installed<'a>(binaries: &'a [BinaryVersion], spec: &str) -> Vec<&'a BinaryVersion> {
+    let (name, release) = match spec.split_once('@') {
+        Some((name, release)) => (name, Some(release)),
+        None => (spec, None),
+    };
+
+    binaries
+        .iter()
+        .filter(|b| b.binary_name == name)
+        .filter(|b| match release {
+            None => true,
+            // network releases and nightly branches match as-is (e.g., `sui@testnet`, `mvr@main`)
+            Some(release) if b.network_release == release => true,
+            Some(release) => {
+                let (network, version) = match parse_version_spec(Some(release.to_string())) {
+                    Ok(spec) => spec,
+                    Err(_) => return false,
+                };
+                let version_matches = version.is_none_or(|v| {
+                    let (Ok(v), Some(installed)) = (v.parse::<Version>(), b.release_version())
+                    else {
+                        return false;
+                    };
+                    v.matches(&installed)
+                });
+                // standalone binaries (mvr) are not tied to a network
+                (b.network_release == network.as_str() || b.network_release == "standalone")
+                    && version_matches
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::GzEncoder, Compression};
+
+    fn append_release<W: Write>(builder: &mut tar::Builder<W>) {
+        for (name, content) in [
+            ("target/release/sui-node", "node"),
+            ("target/release/sui", "sui"),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o755);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, content.as_bytes())
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_extract_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive_path = dir.path().join("release.tgz");
+        let mut builder = tar::Builder::new(GzEncoder::new(
+            File::create(&archive_path).unwrap(),
+            Compression::default(),
+        ));
+        append_release(&mut builder);
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let output = dir.path().join("sui-v1.0.0");
+        assert!(extract_file(&archive_path, "sui", &output).unwrap());
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "sui");
+        assert!(!extract_file(&archive_path, "walrus", &dir.path().join("walrus")).unwrap());
+        assert!(!dir.path().join("walrus").exists());
+    }
+
+    #[test]
+    fn test_extract_file_zstd() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive_path = dir.path().join("release.tar.zst");
+        let mut builder =
+            tar::Builder::new(zstd::Encoder::new(File::create(&archive_path).unwrap(), 0).unwrap());
+        append_release(&mut builder);
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let output = dir.path().join("sui-node-v1.0.0");
+        assert!(extract_file(&archive_path, "sui-node", &output).unwrap());
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "node");
+    }
+
+    #[test]
+    fn test_archive_extension() {
+        assert_eq!(
+            archive_extension("sui-v1.0.0-ubuntu-x86_64.tgz"),
+            Some("tgz")
+        );
+        assert_eq!(archive_extension("sui.tar.gz"), Some("tar.gz"));
+        assert_eq!(archive_extension("sui.tar.zst"), Some("tar.zst"));
+        assert_eq!(archive_extension("sui.tzst"), Some("tzst"));
+        assert_eq!(archive_extension("sui-macos-arm64"), None);
+        assert_eq!(archive_extension("sui.zip"), None);
+    }
+
+    #[test]
+    fn test_find_installed() {
+        let binary = |name: &str, network: &str, version: &str| BinaryVersion {
+            binary_name: name.to_string(),
+            network_release: network.to_string(),
+            version: version.to_string(),
+            debug: false,
+            path: None,
+            provenance: None,
+            last_used: None,
+        };
+        let installed = [
+            binary("sui", "testnet", "v1.40.1"),
+            binary("sui", "testnet", "v1.41.0"),
+            binary("sui", "devnet", "v1.40.1"),
+            binary("sui", "main", "nightly"),
+            binary("mvr", "standalone", "v0.0.8"),
+        ];
+        let found = |spec: &str| {
+            find_installed(&installed, spec)
+                .into_iter()
+                .map(|b| format!("{}@{}-{}", b.binary_name, b.network_release, b.version))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(found("sui").len(), 4);
+        assert_eq!(
+            found("sui@testnet"),
+            ["sui@testnet-v1.40.1", "sui@testnet-v1.41.0"]
+        );
+        assert_eq!(found("sui@testnet-1.40.1"), ["sui@testnet-v1.40.1"]);
+        assert_eq!(found("sui@testnet-1.40"), ["sui@testnet-v1.40.1"]);
+        assert_eq!(found("sui@main"), ["sui@main-nightly"]);
+        assert!(found("sui@mainnet").is_empty());
+        assert!(found("walrus").is_empty());
+        // Standalone binaries match whatever the network
+        assert_eq!(found("mvr@0.0.8"), ["mvr@standalone-v0.0.8"]);
+    }
+}
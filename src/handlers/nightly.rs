@@ -0,0 +1,221 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Nightly builds kept after they are replaced, to list them and switch back to one.
+//! Each is copied to `<branch>/builds/<commit>/` in the binaries folder.
+
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Error};
+use chrono::Utc;
+use comfy_table::Table;
+
+use crate::commands::table_format;
+use crate::handlers::branches::short_sha;
+use crate::handlers::integrity::blake3_of_file;
+use crate::handlers::switch::get_binary_source_path;
+use crate::paths::binaries_dir;
+use crate::reflink;
+use crate::state;
+use crate::status;
+use crate::types::{BinaryVersion, NightlyBuild};
+
+/// Nightly builds kept for each binary and branch, the oldest are deleted
+const MAX_KEPT_BUILDS: usize = 5;
+
+/// Keeps a copy of the nightly build just installed at `binary`, deleting the oldest kept
+/// builds of the same binary and branch
+pub fn keep_nightly_build(
+    name: &str,
+    branch: &str,
+    commit: &str,
+    debug: bool,
+    binary: &Path,
+) -> Result<(), Error> {
+    let dir = binaries_dir().join(branch).join("builds").join(commit);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| anyhow!("Cannot create folder {}: {e}", dir.display()))?;
+    let path = dir.join(binary.file_name().unwrap_or_default());
+    reflink::copy(binary, &path)
+        .map_err(|e| anyhow!("Cannot keep a copy of the build in {}: {e}", path.display()))?;
+
+    let build = NightlyBuild {
+        binary_name: name.to_string(),
+        branch: branch.to_string(),
+        commit: commit.to_string(),
+        debug,
+        built_at: Utc::now(),
+        path: path.to_string_lossy().to_string(),
+    };
+    let removed = state::with_state(|state| {
+        Ok(state
+            .installed_binaries_mut()
+            .add_nightly_build(build, MAX_KEPT_BUILDS))
+    })?;
+    for build in removed {
+        let path = Path::new(&build.path);
+        let _ = std::fs::remove_file(path);
+        // Other binaries may have been built from the same commit
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::remove_dir(dir);
+        }
+    }
+    Ok(())
+}
+
+/// Handles `suiup list nightly`: lists the kept nightly builds, most recent first
+pub fn handle_list_nightly() -> Result<(), Error> {
+    let installed = state::installed_binaries()?;
+    let system = state::system_installed_binaries()?;
+    let mut builds = installed
+        .nightly_builds()
+        .iter()
+        .map(|build| (build, false))
+        .chain(system.nightly_builds().iter().map(|build| (build, true)))
+        .collect::<Vec<_>>();
+    if builds.is_empty() {
+        println!("No nightly builds installed. Install one with `suiup install <binary> --nightly <branch>`.");
+        return Ok(());
+    }
+    builds.sort_by(|(a, a_system), (b, b_system)| {
+        (a_system, &a.binary_name, &a.branch, b.built_at).cmp(&(
+            b_system,
+            &b.binary_name,
+            &b.branch,
+            a.built_at,
+        ))
+    });
+
+    let mut table = Table::new();
+    table.load_preset(table_format()).set_header(vec![
+        "Binary", "Branch", "Commit", "Built", "Mode", "Active",
+    ]);
+    for (build, system_wide) in builds {
+        let binaries = match system_wide {
+            true => system.binaries(),
+            false => installed.binaries(),
+        };
+        let active = active_commit(binaries, build).as_deref() == Some(&build.commit);
+        table.add_row(vec![
+            match system_wide {
+                true => format!("{} (system-wide)", build.binary_name),
+                false => build.binary_name.clone(),
+            },
+            build.branch.clone(),
+            short_sha(&build.commit).to_string(),
+            build.built_at.format("%Y-%m-%d %H:%M").to_string(),
+            if build.debug { "debug" } else { "release" }.to_string(),
+            if active { "*" } else { "" }.to_string(),
+        ]);
+    }
+    println!("{table}");
+    status!("\nSwitch to another build with `suiup switch <binary>@<branch> --commit <commit>`.");
+    Ok(())
+}
+
+/// Commit of the installed nightly build of the same binary and branch as `build`
+fn active_commit(binaries: &[BinaryVersion], build: &NightlyBuild) -> Option<String> {
+    binaries
+        .iter()
+        .find(|b| {
+            b.binary_name == build.binary_name
+                && b.network_release == build.branch
+                && b.version == "nightly"
+                && b.debug == build.debug
+        })?
+        .provenance
+        .as_ref()?
+        .commit
+        .clone()
+}
+
+/// Makes the kept build of `binary` whose commit starts with `commit` the installed nightly build
+/// of its branch, returning the full commit
+pub fn restore_nightly_build(binary: &BinaryVersion, commit: &str) -> Result<String, Error> {
+    if binary.version != "nightly" {
+        bail!(
+            "`--commit` selects a nightly build, but {}@{} is a release",
+            binary.binary_name,
+            binary.network_release
+        );
+    }
+    let installed = state::installed_binaries()?;
+    let matching = installed
+        .nightly_builds()
+        .iter()
+        .filter(|b| {
+            b.binary_name == binary.binary_name
+                && b.branch == binary.network_release
+                && b.debug == binary.debug
+                && b.commit.starts_with(commit)
+        })
+        .collect::<Vec<_>>();
+    let build = match matching.as_slice() {
+        [] => bail!(
+            "No kept nightly build of {}@{} at commit {commit}. Use `suiup list nightly` to see the kept builds.",
+            binary.binary_name,
+            binary.network_release
+        ),
+        [build] => *build,
+        _ => bail!("Several builds match the commit {commit}, give more of its characters"),
+    };
+
+    let dst = get_binary_source_path(binary);
+    reflink::copy(&build.path, &dst).map_err(|e| {
+        anyhow!(
+            "Cannot restore the build of {} to {}: {e}",
+            short_sha(&build.commit),
+            dst.display()
+        )
+    })?;
+    let mut restored = binary.clone();
+    if let Some(provenance) = restored.provenance.as_mut() {
+        provenance.commit = Some(build.commit.clone());
+        provenance.installed_at = build.built_at;
+        provenance.binary_blake3 = blake3_of_file(&dst).ok();
+    }
+    state::with_state(|state| {
+        state.installed_binaries_mut().add_binary(restored);
+        Ok(())
+    })?;
+    Ok(build.commit.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::InstalledBinaries;
+    use chrono::Duration;
+
+    #[test]
+    fn test_add_nightly_build() {
+        let build = |commit: &str, hours_ago: i64| NightlyBuild {
+            binary_name: "sui".to_string(),
+            branch: "main".to_string(),
+            commit: commit.to_string(),
+            debug: false,
+            built_at: Utc::now() - Duration::hours(hours_ago),
+            path: format!("/builds/{commit}/sui-nightly"),
+        };
+        let mut installed = InstalledBinaries::default();
+        for (i, commit) in ["a", "b", "c"].iter().enumerate() {
+            assert!(installed
+                .add_nightly_build(build(commit, 10 - i as i64), 3)
+                .is_empty());
+        }
+        // Building the same commit again replaces it
+        assert!(installed.add_nightly_build(build("b", 5), 3).is_empty());
+        assert_eq!(installed.nightly_builds().len(), 3);
+
+        let removed = installed.add_nightly_build(build("d", 1), 3);
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].commit, "a");
+        let mut commits = installed
+            .nightly_builds()
+            .iter()
+            .map(|b| b.commit.as_str())
+            .collect::<Vec<_>>();
+        commits.sort();
+        assert_eq!(commits, ["b", "c", "d"]);
+    }
+}
@@ -0,0 +1,179 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! External subcommands: `suiup <name>` runs a `suiup-<name>` executable found on the `PATH`,
+//! like cargo does, with the suiup folders and default versions in its environment.
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Error};
+use comfy_table::Table;
+use serde_json::json;
+
+use crate::commands::table_format;
+use crate::config::config_file;
+use crate::http;
+use crate::paths::{
+    binaries_dir, get_default_bin_dir, get_suiup_cache_dir, get_suiup_config_dir,
+    get_suiup_data_dir, get_suiup_state_dir, is_portable_mode, is_system_mode,
+};
+use crate::state;
+use crate::suggest::did_you_mean;
+
+const PREFIX: &str = "suiup-";
+
+/// Handles `suiup <name> [args]` for a name that is not a command of suiup, exiting with the exit
+/// code of the plugin
+pub fn handle_plugin(args: &[OsString], commands: &[String]) -> Result<(), Error> {
+    let (name, args) = args
+        .split_first()
+        .ok_or_else(|| anyhow!("Missing command"))?;
+    let name = name.to_string_lossy();
+    let plugins = list_plugins();
+    let Some(path) = plugins.get(name.as_ref()) else {
+        let candidates = commands
+            .iter()
+            .map(String::as_str)
+            .chain(plugins.keys().map(String::as_str));
+        bail!(
+            "No such command `{name}`.{} Run `suiup --help` to see the commands, or put a `{PREFIX}{name}` executable on your PATH to add it.",
+            did_you_mean(&name, candidates)
+        );
+    };
+
+    let status = std::process::Command::new(path)
+        .args(args)
+        .envs(plugin_env()?)
+        .status()
+        .map_err(|e| anyhow!("Cannot run the plugin {}: {e}", path.display()))?;
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
+
+/// Handles `suiup list plugins`
+pub fn handle_list_plugins() -> Result<(), Error> {
+    let plugins = list_plugins();
+    if plugins.is_empty() {
+        println!("No plugins found. Put a `{PREFIX}<name>` executable on your PATH to add the `suiup <name>` command.");
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table
+        .load_preset(table_format())
+        .set_header(vec!["Command", "Path"]);
+    for (name, path) in &plugins {
+        table.add_row(vec![name.clone(), path.display().to_string()]);
+    }
+    println!("{table}");
+    Ok(())
+}
+
+/// The `suiup-<name>` executables on the `PATH` by name. The first one found wins, as when the
+/// shell looks for a command.
+pub fn list_plugins() -> BTreeMap<String, PathBuf> {
+    let mut plugins = BTreeMap::new();
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    for dir in std::env::split_paths(&path) {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(name) = plugin_name(&path) else {
+                continue;
+            };
+            if is_executable(&path) {
+                plugins.entry(name).or_insert(path);
+            }
+        }
+    }
+    plugins
+}
+
+/// Name of the command a plugin executable adds, e.g. `bench` for `suiup-bench`
+fn plugin_name(path: &Path) -> Option<String> {
+    let file_name = path.file_name()?.to_str()?;
+    #[cfg(windows)]
+    let file_name = [".exe", ".cmd", ".bat"]
+        .iter()
+        .find_map(|ext| file_name.strip_suffix(ext))?;
+    let name = file_name.strip_prefix(PREFIX)?;
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(windows)]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Environment of a plugin: where suiup keeps its files, the default versions, and the global
+/// flags, so that running `$SUIUP` from the plugin behaves the same
+fn plugin_env() -> Result<Vec<(&'static str, OsString)>, Error> {
+    let defaults = state::default_versions()?
+        .into_iter()
+        .map(|(binary, (network, version, debug))| {
+            (
+                binary,
+                json!({"network": network, "version": version, "debug": debug}),
+            )
+        })
+        .collect::<serde_json::Map<_, _>>();
+    let mut env = vec![
+        ("SUIUP", std::env::current_exe()?.into_os_string()),
+        ("SUIUP_VERSION", env!("CARGO_PKG_VERSION").into()),
+        ("SUIUP_CONFIG_FILE", config_file().into_os_string()),
+        ("SUIUP_CONFIG_DIR", get_suiup_config_dir().into_os_string()),
+        ("SUIUP_DATA_DIR", get_suiup_data_dir().into_os_string()),
+        ("SUIUP_BINARIES_DIR", binaries_dir().into_os_string()),
+        ("SUIUP_STATE_DIR", get_suiup_state_dir().into_os_string()),
+        ("SUIUP_CACHE_DIR", get_suiup_cache_dir().into_os_string()),
+        (
+            "SUIUP_DEFAULT_BIN_DIR",
+            get_default_bin_dir().into_os_string(),
+        ),
+        (
+            "SUIUP_DEFAULT_VERSIONS",
+            serde_json::Value::Object(defaults).to_string().into(),
+        ),
+    ];
+    for (var, set) in [
+        ("SUIUP_SYSTEM", is_system_mode()),
+        ("SUIUP_PORTABLE", is_portable_mode()),
+        ("SUIUP_OFFLINE", http::is_offline()),
+    ] {
+        if set {
+            env.push((var, "true".into()));
+        }
+    }
+    Ok(env)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plugin_name() {
+        #[cfg(windows)]
+        let (plugin, other) = ("suiup-bench.exe", "suiup-bench.txt");
+        #[cfg(not(windows))]
+        let (plugin, other) = ("suiup-bench", "suiup");
+        assert_eq!(
+            plugin_name(Path::new("/usr/bin").join(plugin).as_path()),
+            Some("bench".to_string())
+        );
+        assert_eq!(plugin_name(Path::new(other)), None);
+        assert_eq!(plugin_name(Path::new("sui-bench")), None);
+    }
+}
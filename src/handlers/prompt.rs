@@ -0,0 +1,67 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The `prompt` command, which runs every time a shell prompt is drawn and must return within a
+//! few milliseconds: it only reads the state files and the manifest of the current folder.
+
+use anyhow::Error;
+
+use crate::handlers::status::{active_versions, ActiveVersion};
+
+/// Prints the versions in use, e.g. `sui:testnet-1.40.1 walrus:testnet-1.29.1`. A prompt must
+/// not show errors, so versions that cannot be read are left out.
+pub fn handle_prompt(binaries: &[String]) -> Result<(), Error> {
+    let segments = active_versions()
+        .iter()
+        .map(segment)
+        .filter(|(name, _)| binaries.is_empty() || binaries.contains(name))
+        .map(|(name, version)| format!("{name}:{version}"))
+        .collect::<Vec<_>>();
+    if !segments.is_empty() {
+        println!("{}", segments.join(" "));
+    }
+    Ok(())
+}
+
+/// Name and short version of a binary in use: `testnet-1.40.1`, `main-nightly` for nightly
+/// builds, only the version for standalone binaries, which have no network, and `latest` when
+/// the manifest does not pin it
+fn segment(active: &ActiveVersion) -> (String, String) {
+    let name = match active.debug {
+        true => format!("{}-debug", active.binary),
+        false => active.binary.clone(),
+    };
+    let version = active.version.as_deref().unwrap_or("latest");
+    let version = match active.network.as_deref() {
+        None | Some("standalone") => version.to_string(),
+        Some(network) => format!("{network}-{version}"),
+    };
+    (name, version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::status::VersionSource;
+
+    #[test]
+    fn test_segment() {
+        let mut active = ActiveVersion {
+            binary: "sui".to_string(),
+            network: Some("testnet".to_string()),
+            version: Some("1.40.1".to_string()),
+            debug: false,
+            source: VersionSource::Default,
+        };
+        assert_eq!(segment(&active).1, "testnet-1.40.1");
+        active.debug = true;
+        active.version = None;
+        assert_eq!(
+            segment(&active),
+            ("sui-debug".to_string(), "testnet-latest".to_string())
+        );
+        active.network = Some("standalone".to_string());
+        active.version = Some("0.0.5".to_string());
+        assert_eq!(segment(&active).1, "0.0.5");
+    }
+}
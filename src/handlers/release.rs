@@ -3,12 +3,23 @@
 
 use anyhow::anyhow;
 use anyhow::bail;
+use anyhow::Context;
 use anyhow::Error;
+use indicatif::ProgressBar;
 use reqwest::header::ETAG;
 use reqwest::header::IF_NONE_MATCH;
 
+use crate::handlers::gh;
+use crate::handlers::spinner;
 use crate::handlers::version::extract_version_from_release;
+use crate::http;
+use crate::output;
 use crate::paths::get_suiup_cache_dir;
+use crate::standalone::StandaloneInstaller;
+use crate::state::write_file_atomically;
+use crate::status;
+use crate::timings;
+use crate::types::Network;
 use crate::types::Release;
 use crate::types::Repo;
 
@@ -17,22 +28,79 @@ pub async fn release_list(
     repo: &Repo,
     github_token: Option<String>,
 ) -> Result<(Vec<Release>, Option<String>), anyhow::Error> {
-    let release_url = format!("https://api.github.com/repos/{}/releases", repo);
-    let client = reqwest::Client::new();
-    let mut request = client.get(&release_url).header("User-Agent", "suiup");
+    fetch_release_list(repo, github_token, false, true).await
+}
 
-    // Add authorization header if token is provided
-    if let Some(token) = github_token {
-        request = request.header("Authorization", format!("token {}", token));
+/// Same as [`release_list`], without the spinner and status messages, for output that must only
+/// be the command's own, e.g. JSON
+pub async fn release_list_quietly(
+    repo: &Repo,
+    github_token: Option<String>,
+) -> Result<(Vec<Release>, Option<String>), anyhow::Error> {
+    fetch_release_list(repo, github_token, true, true).await
+}
+
+/// Same as [`release_list`], without saving the list and its ETag to the cache, for commands
+/// that must not write anything, e.g. `install --dry-run`
+pub async fn release_list_without_saving(
+    repo: &Repo,
+    github_token: Option<String>,
+) -> Result<(Vec<Release>, Option<String>), anyhow::Error> {
+    fetch_release_list(repo, github_token, false, false).await
+}
+
+async fn fetch_release_list(
+    repo: &Repo,
+    github_token: Option<String>,
+    quiet: bool,
+    save: bool,
+) -> Result<(Vec<Release>, Option<String>), anyhow::Error> {
+    if http::is_offline() {
+        return match load_cached_releases(repo)? {
+            Some(releases) => Ok((releases, None)),
+            None => Err(http::offline_error(&format!(
+                "fetch the list of {} releases",
+                repo.binary_name()
+            ))),
+        };
+    }
+
+    let _timer = timings::phase("resolve");
+    let repository = repo.repository()?;
+    if gh::enabled() {
+        match gh::api::<Vec<Release>>(&format!("repos/{repository}/releases")).await {
+            Ok(releases) => {
+                if save {
+                    save_release_list(repo, &releases, None, quiet)?;
+                }
+                return Ok((releases, None));
+            }
+            Err(e) => eprintln!(
+                "{} {e}. Fetching the {} releases without gh.",
+                output::warning_sign(),
+                repo.binary_name()
+            ),
+        }
     }
 
+    let release_url = format!("{}/repos/{repository}/releases", http::github_api_url()?);
+    let mut request = http::get(&release_url, github_token.as_deref())?;
+
     // Add ETag for caching
     if let Ok(etag) = read_etag_file(repo) {
         request = request.header(IF_NONE_MATCH, etag);
     }
 
-    let response = request
-        .send()
+    let pb = if quiet {
+        ProgressBar::hidden()
+    } else {
+        spinner(
+            "fetch",
+            format!("Fetching {} releases...", repo.binary_name()),
+        )
+    };
+    let what = format!("fetch the list of {} releases", repo.binary_name());
+    let response = http::send(request, &what)
         .await
         .map_err(|e| anyhow!("Could not send request: {e}"))?;
 
@@ -53,19 +121,22 @@ pub async fn release_list(
         .map(String::from);
     let response = response.error_for_status()?;
     let releases: Vec<Release> = response.json().await?;
-    save_release_list(repo, &releases, etag.clone())?;
+    pb.finish_and_clear();
+    if save {
+        save_release_list(repo, &releases, etag.clone(), quiet)?;
+    }
 
     Ok((releases, etag))
 }
 
 fn read_etag_file(repo: &Repo) -> Result<String, anyhow::Error> {
-    let repo_name = repo.to_string();
+    let repo_name = repo.repository()?;
     let repo_name = repo_name.replace("/", "_");
     let filename = format!("etag_{}.txt", repo_name);
     let etag_file = get_suiup_cache_dir().join(filename);
     if etag_file.exists() {
         std::fs::read_to_string(&etag_file)
-            .map_err(|_| anyhow!("Cannot read from file {}", etag_file.display()))
+            .with_context(|| format!("Cannot read from file {}", etag_file.display()))
     } else {
         Ok("".to_string())
     }
@@ -85,9 +156,12 @@ fn save_release_list(
     repo: &Repo,
     releases: &[Release],
     etag: Option<String>,
+    quiet: bool,
 ) -> Result<(), anyhow::Error> {
-    println!("Saving releases list to cache");
-    let repo_name = repo.to_string();
+    if !quiet {
+        status!("Saving releases list to cache");
+    }
+    let repo_name = repo.repository()?;
     let repo_name = repo_name.replace("/", "_");
     let etag_filename = format!("etag_{}.txt", repo_name);
     let releases_filename = format!("releases_{}.txt", repo_name);
@@ -100,45 +174,76 @@ fn save_release_list(
     let cache_content =
         serde_json::to_string_pretty(releases).expect("Could not serialize releases file: {}");
 
-    std::fs::write(&cache_file, cache_content).map_err(|_| {
-        anyhow!(
+    write_file_atomically(&cache_file, cache_content.as_bytes()).with_context(|| {
+        format!(
             "Could not write cache releases file: {}",
             cache_file.display(),
         )
     })?;
     if let Some(etag) = etag {
-        std::fs::write(&etag_file, etag)
-            .map_err(|_| anyhow!("Could not write ETag file: {}", etag_file.display()))?;
+        write_file_atomically(&etag_file, etag.as_bytes())
+            .with_context(|| format!("Could not write ETag file: {}", etag_file.display()))?;
     }
     Ok(())
 }
 
 fn load_cached_release_list(repo: &Repo) -> Result<Option<(Vec<Release>, String)>, anyhow::Error> {
-    let repo_name = repo.to_string();
+    let repo_name = repo.repository()?;
     let repo_name = repo_name.replace("/", "_");
     let etag_filename = format!("etag_{}.txt", repo_name);
-    let releases_filename = format!("releases_{}.txt", repo_name);
-    let cache_file = get_suiup_cache_dir().join(releases_filename);
     let etag_file = get_suiup_cache_dir().join(etag_filename);
 
-    if cache_file.exists() && etag_file.exists() {
-        let cache_content: Vec<Release> = serde_json::from_str(
-            &std::fs::read_to_string(&cache_file)
-                .map_err(|_| anyhow!("Cannot read from file {}", cache_file.display()))?,
+    if !etag_file.exists() {
+        return Ok(None);
+    }
+    let Some(releases) = load_cached_releases(repo)? else {
+        return Ok(None);
+    };
+    let etag_content = std::fs::read_to_string(&etag_file)
+        .with_context(|| format!("Cannot read from file {}", etag_file.display()))?;
+    Ok(Some((releases, etag_content)))
+}
+
+/// Loads the release list saved by the last [`release_list`] call, if any
+pub(crate) fn load_cached_releases(repo: &Repo) -> Result<Option<Vec<Release>>, anyhow::Error> {
+    let repo_name = repo.repository()?.replace("/", "_");
+    let cache_file = get_suiup_cache_dir().join(format!("releases_{}.txt", repo_name));
+    if !cache_file.exists() {
+        return Ok(None);
+    }
+    let releases = serde_json::from_str(
+        &std::fs::read_to_string(&cache_file)
+            .with_context(|| format!("Cannot read from file {}", cache_file.display()))?,
+    )
+    .with_context(|| {
+        format!(
+            "Cannot deserialize the releases cached file {}",
+            cache_file.display()
         )
-        .map_err(|_| {
-            anyhow!(
-                "Cannot deserialize the releases cached file {}",
-                cache_file.display()
-            )
-        })?;
-        let etag_content = std::fs::read_to_string(&etag_file)
-            .map_err(|_| anyhow!("Cannot read from file {}", etag_file.display()))?;
-
-        Ok(Some((cache_content, etag_content)))
-    } else {
-        Ok(None)
+    })?;
+    Ok(Some(releases))
+}
+
+/// Latest release version of `repo` for the network, from GitHub
+pub(crate) async fn latest_version(
+    repo: Repo,
+    network: &str,
+    github_token: Option<String>,
+) -> Result<String, Error> {
+    if repo == Repo::Mvr {
+        let mut installer = StandaloneInstaller::new(repo);
+        installer.get_releases().await?;
+        return installer
+            .versions()
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("no mvr release"));
     }
+    let (releases, _) = release_list(&repo, github_token).await?;
+    let release = find_last_release_by_network(releases, network)
+        .await
+        .ok_or_else(|| anyhow!("no {network} release"))?;
+    extract_version_from_release(&release.assets[0].name)
 }
 
 pub async fn last_release_for_network<'a>(
@@ -159,19 +264,18 @@ pub async fn last_release_for_network<'a>(
 }
 
 /// Find all networks that have a specific version available
-pub fn find_networks_with_version(releases: &[Release], version: &str) -> Vec<String> {
+pub fn find_networks_with_version(releases: &[Release], version: &str) -> Vec<Network> {
     let version = ensure_version_prefix(version);
 
-    let networks = ["testnet", "devnet", "mainnet"];
     let mut available_networks = Vec::new();
 
-    for network in networks {
+    for network in Network::ALL {
         let tag = format!("{}-{}", network, version);
         if releases
             .iter()
             .any(|r| r.assets.iter().any(|a| a.name.contains(&tag)))
         {
-            available_networks.push(network.to_string());
+            available_networks.push(network);
         }
     }
 
@@ -195,11 +299,15 @@ mod tests {
 
     fn create_test_release(asset_names: Vec<&str>) -> Release {
         Release {
+            tag_name: None,
             assets: asset_names
                 .into_iter()
                 .map(|name| Asset {
                     name: name.to_string(),
                     browser_download_url: format!("https://example.com/{}", name),
+                    url: None,
+                    size: 0,
+                    digest: None,
                 })
                 .collect(),
         }
@@ -217,14 +325,14 @@ mod tests {
         // Test finding version 1.53.0
         let networks = find_networks_with_version(&releases, "1.53.0");
         assert_eq!(networks.len(), 2);
-        assert!(networks.contains(&"testnet".to_string()));
-        assert!(networks.contains(&"devnet".to_string()));
+        assert!(networks.contains(&Network::Testnet));
+        assert!(networks.contains(&Network::Devnet));
 
         // Test finding version with 'v' prefix
         let networks = find_networks_with_version(&releases, "v1.53.0");
         assert_eq!(networks.len(), 2);
-        assert!(networks.contains(&"testnet".to_string()));
-        assert!(networks.contains(&"devnet".to_string()));
+        assert!(networks.contains(&Network::Testnet));
+        assert!(networks.contains(&Network::Devnet));
 
         // Test finding version that doesn't exist
         let networks = find_networks_with_version(&releases, "1.99.0");
@@ -233,7 +341,7 @@ mod tests {
         // Test finding version that exists only in one network
         let networks = find_networks_with_version(&releases, "1.52.0");
         assert_eq!(networks.len(), 1);
-        assert!(networks.contains(&"testnet".to_string()));
+        assert!(networks.contains(&Network::Testnet));
     }
 
     #[test]
@@ -0,0 +1,93 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{anyhow, bail, Error};
+use std::cmp::Reverse;
+use std::path::PathBuf;
+
+use crate::handlers::deprecation::warn_if_deprecated;
+use crate::handlers::find_installed;
+use crate::handlers::switch::{binary_source_path_in, get_binary_source_path};
+use crate::paths::system_binaries_dir;
+use crate::state;
+use crate::types::{default_binary_name, BinaryVersion};
+
+/// Handles the `run` command
+pub fn handle_run(binary_spec: &str, args: &[String]) -> Result<(), Error> {
+    let (binary, path) = select_binary(binary_spec)?;
+    if !path.exists() {
+        bail!(
+            "Binary {binary} not found at {}. Try reinstalling it.",
+            path.display()
+        );
+    }
+    // Only the cached metadata is used, running a binary does not wait for the network
+    warn_if_deprecated(
+        &binary.binary_name,
+        &binary.network_release,
+        &binary.version,
+    );
+
+    // The process exits with the binary's exit code, so persist the usage before running it
+    record_use(std::slice::from_ref(&binary))?;
+
+    let status = std::process::Command::new(&path)
+        .args(args)
+        .status()
+        .map_err(|e| anyhow!("Cannot run {}: {e}", path.display()))?;
+
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
+
+/// Records that the binaries are used now and persists it right away, as the callers may exit
+/// with the exit code of the binaries
+pub(crate) fn record_use(binaries: &[BinaryVersion]) -> Result<(), Error> {
+    state::with_state(|state| {
+        for binary in binaries {
+            state.installed_binaries_mut().mark_used(binary);
+        }
+        Ok(())
+    })?;
+    state::flush()
+}
+
+/// Picks the installed binary to run and returns it with its path. If several match, the default
+/// one wins, otherwise the latest version. Binaries of the user's installation take precedence
+/// over the system-wide ones.
+pub(crate) fn select_binary(binary_spec: &str) -> Result<(BinaryVersion, PathBuf), Error> {
+    let installed_binaries = state::installed_binaries()?;
+    let mut matches = find_installed(installed_binaries.binaries(), binary_spec);
+
+    if matches.is_empty() {
+        let system_binaries = state::system_installed_binaries()?;
+        let mut system_matches = find_installed(system_binaries.binaries(), binary_spec);
+        if system_matches.is_empty() {
+            bail!(
+                "No installed binary matches {binary_spec}. Use `suiup show` to see installed binaries."
+            );
+        }
+        system_matches.sort_by_key(|b| Reverse(b.release_version()));
+        let binary = system_matches[0].clone();
+        let path = binary_source_path_in(&system_binaries_dir(), &binary);
+        return Ok((binary, path));
+    }
+
+    let default_versions = state::default_versions()?;
+    if let Some(default) = matches.iter().find(|b| {
+        default_versions
+            .get(&default_binary_name(&b.binary_name, b.debug))
+            .is_some_and(|(network, version, debug)| {
+                b.network_release == *network && b.version == *version && b.debug == *debug
+            })
+    }) {
+        let path = get_binary_source_path(default);
+        return Ok(((*default).clone(), path));
+    }
+
+    // Sort by version to get the latest one, same as `switch`
+    matches.sort_by_key(|b| Reverse(b.release_version()));
+    Ok((matches[0].clone(), get_binary_source_path(matches[0])))
+}
@@ -0,0 +1,297 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Error};
+use chrono::{DateTime, SecondsFormat, Utc};
+use clap::ValueEnum;
+use serde_json::{json, Value};
+
+use crate::commands::BinaryName;
+use crate::handlers::integrity::sha256_of_files;
+use crate::handlers::switch::get_binary_source_path;
+use crate::http;
+use crate::state;
+use crate::status;
+use crate::types::BinaryVersion;
+
+/// All binaries installed by suiup are published by Mysten Labs
+const SUPPLIER: &str = "Mysten Labs";
+const SUPPLIER_URL: &str = "https://mystenlabs.com";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SbomFormat {
+    /// CycloneDX 1.5 JSON
+    Cyclonedx,
+    /// SPDX 2.3 JSON
+    Spdx,
+}
+
+/// An installed binary with the data needed to describe it in an SBOM
+struct Component {
+    binary: BinaryVersion,
+    /// GitHub repository the binary is built from, e.g. `MystenLabs/sui`
+    repo: Option<String>,
+    /// URL the binary was downloaded from, or the repository for older installs
+    source_url: Option<String>,
+    /// SHA-256 of the installed binary, if it is still on disk
+    sha256: Option<String>,
+}
+
+impl Component {
+    fn new(binary: BinaryVersion, sha256: Option<String>) -> Self {
+        let repo_url = <BinaryName as FromStr>::from_str(&binary.binary_name)
+            .ok()
+            .and_then(|name| name.repo_url().ok());
+        let github_url = http::github_url().ok();
+        let repo = repo_url
+            .as_deref()
+            .zip(github_url)
+            .and_then(|(url, github_url)| url.strip_prefix(&format!("{github_url}/")))
+            .map(str::to_string);
+        let source_url = binary
+            .provenance
+            .as_ref()
+            .and_then(|p| p.source_url.clone())
+            .or(repo_url);
+
+        Component {
+            binary,
+            repo,
+            source_url,
+            sha256,
+        }
+    }
+
+    /// Package URL of the binary, e.g. `pkg:github/MystenLabs/sui@testnet-v1.39.3`
+    fn purl(&self) -> Option<String> {
+        self.repo
+            .as_ref()
+            .map(|repo| format!("pkg:github/{repo}@{}", self.version()))
+    }
+
+    /// Version as shown to users, the release is part of it as nightly builds are versioned by
+    /// their branch
+    fn version(&self) -> String {
+        format!("{}-{}", self.binary.network_release, self.binary.version)
+    }
+
+    fn name(&self) -> String {
+        if self.binary.debug {
+            format!("{}-debug", self.binary.binary_name)
+        } else {
+            self.binary.binary_name.clone()
+        }
+    }
+}
+
+/// Handles the `sbom` command
+pub fn handle_sbom(format: SbomFormat, output: Option<&Path>) -> Result<(), Error> {
+    let binaries = state::installed_binaries()?.binaries().to_vec();
+    // Binaries removed by hand have no checksum
+    let paths = binaries
+        .iter()
+        .map(|binary| Some(get_binary_source_path(binary)).filter(|path| path.exists()))
+        .collect::<Vec<_>>();
+    let mut hashes =
+        sha256_of_files(&paths.iter().flatten().cloned().collect::<Vec<_>>()).into_iter();
+    let components = binaries
+        .into_iter()
+        .zip(paths)
+        .map(|(binary, path)| {
+            let sha256 = path.and_then(|_| hashes.next()).transpose()?;
+            Ok(Component::new(binary, sha256))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let now = Utc::now();
+    let sbom = match format {
+        SbomFormat::Cyclonedx => cyclonedx(&components, now),
+        SbomFormat::Spdx => spdx(&components, now),
+    };
+    let sbom = serde_json::to_string_pretty(&sbom)?;
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, sbom)
+                .map_err(|e| anyhow!("Cannot write the SBOM to {}: {e}", path.display()))?;
+            status!("SBOM written to {}", path.display());
+        }
+        None => println!("{sbom}"),
+    }
+    Ok(())
+}
+
+/// Builds a CycloneDX 1.5 JSON document
+fn cyclonedx(components: &[Component], now: DateTime<Utc>) -> Value {
+    let components = components
+        .iter()
+        .map(|c| {
+            let mut component = json!({
+                "type": "application",
+                "bom-ref": format!("{}@{}", c.name(), c.version()),
+                "name": c.name(),
+                "version": c.version(),
+                "supplier": { "name": SUPPLIER, "url": [SUPPLIER_URL] },
+                "properties": [
+                    { "name": "suiup:network_release", "value": c.binary.network_release },
+                    { "name": "suiup:debug", "value": c.binary.debug.to_string() },
+                ],
+            });
+            if let Some(sha256) = &c.sha256 {
+                component["hashes"] = json!([{ "alg": "SHA-256", "content": sha256 }]);
+            }
+            if let Some(purl) = c.purl() {
+                component["purl"] = json!(purl);
+            }
+            if let Some(url) = &c.source_url {
+                component["externalReferences"] = json!([{ "type": "distribution", "url": url }]);
+            }
+            component
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.5",
+        "version": 1,
+        "metadata": {
+            "timestamp": now.to_rfc3339_opts(SecondsFormat::Secs, true),
+            "tools": {
+                "components": [{
+                    "type": "application",
+                    "name": "suiup",
+                    "version": env!("CARGO_PKG_VERSION"),
+                }],
+            },
+        },
+        "components": components,
+    })
+}
+
+/// Builds an SPDX 2.3 JSON document
+fn spdx(components: &[Component], now: DateTime<Utc>) -> Value {
+    let packages = components
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            let mut package = json!({
+                "name": c.name(),
+                "SPDXID": spdx_id(i, c),
+                "versionInfo": c.version(),
+                "supplier": format!("Organization: {SUPPLIER}"),
+                "downloadLocation": c.source_url.as_deref().unwrap_or("NOASSERTION"),
+                "filesAnalyzed": false,
+                "licenseConcluded": "NOASSERTION",
+                "licenseDeclared": "NOASSERTION",
+                "copyrightText": "NOASSERTION",
+            });
+            if let Some(sha256) = &c.sha256 {
+                package["checksums"] = json!([{ "algorithm": "SHA256", "checksumValue": sha256 }]);
+            }
+            if let Some(purl) = c.purl() {
+                package["externalRefs"] = json!([{
+                    "referenceCategory": "PACKAGE-MANAGER",
+                    "referenceType": "purl",
+                    "referenceLocator": purl,
+                }]);
+            }
+            package
+        })
+        .collect::<Vec<_>>();
+
+    let relationships = components
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            json!({
+                "spdxElementId": "SPDXRef-DOCUMENT",
+                "relationshipType": "DESCRIBES",
+                "relatedSpdxElement": spdx_id(i, c),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let created = now.to_rfc3339_opts(SecondsFormat::Secs, true);
+    json!({
+        "spdxVersion": "SPDX-2.3",
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": "suiup-installed-binaries",
+        "documentNamespace": format!(
+            "https://github.com/MystenLabs/suiup/spdx/{}-{}",
+            whoami::fallible::hostname().unwrap_or_else(|_| "unknown".to_string()),
+            now.timestamp()
+        ),
+        "creationInfo": {
+            "created": created,
+            "creators": [format!("Tool: suiup-{}", env!("CARGO_PKG_VERSION"))],
+        },
+        "packages": packages,
+        "relationships": relationships,
+    })
+}
+
+/// SPDX identifiers may only contain letters, numbers, `.` and `-`
+fn spdx_id(index: usize, component: &Component) -> String {
+    let id = format!("{}-{}", component.name(), component.version())
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>();
+    format!("SPDXRef-Package-{index}-{id}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn component() -> Component {
+        Component {
+            binary: BinaryVersion {
+                binary_name: "sui".to_string(),
+                network_release: "testnet".to_string(),
+                version: "v1.39.3".to_string(),
+                debug: false,
+                path: None,
+                provenance: None,
+                last_used: None,
+            },
+            repo: Some("MystenLabs/sui".to_string()),
+            source_url: Some("https://github.com/MystenLabs/sui".to_string()),
+            sha256: Some("abc".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_cyclonedx() {
+        let bom = cyclonedx(&[component()], Utc::now());
+        assert_eq!(bom["bomFormat"], "CycloneDX");
+        let c = &bom["components"][0];
+        assert_eq!(c["name"], "sui");
+        assert_eq!(c["version"], "testnet-v1.39.3");
+        assert_eq!(c["purl"], "pkg:github/MystenLabs/sui@testnet-v1.39.3");
+        assert_eq!(c["hashes"][0]["content"], "abc");
+        assert_eq!(c["supplier"]["name"], SUPPLIER);
+    }
+
+    #[test]
+    fn test_spdx() {
+        let doc = spdx(&[component()], Utc::now());
+        assert_eq!(doc["spdxVersion"], "SPDX-2.3");
+        let p = &doc["packages"][0];
+        assert_eq!(p["SPDXID"], "SPDXRef-Package-0-sui-testnet-v1.39.3");
+        assert_eq!(p["checksums"][0]["checksumValue"], "abc");
+        assert_eq!(
+            doc["relationships"][0]["relatedSpdxElement"],
+            "SPDXRef-Package-0-sui-testnet-v1.39.3"
+        );
+    }
+}
@@ -1,17 +1,25 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use super::download::detect_os_arch;
-
 use crate::handlers::download::download_file;
-use anyhow::{anyhow, Result};
+use crate::handlers::{extract_file, replace_binary};
+use crate::http;
+use crate::interrupt::Partial;
+use crate::output;
+use crate::paths::get_suiup_cache_dir;
+use crate::platform::{Arch, Os, Target};
+use crate::status;
+use anyhow::{anyhow, bail, Result};
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use std::path::PathBuf;
 use std::{fmt::Display, process::Command};
 use tokio::task;
 
-use flate2::read::GzDecoder;
-use serde::Deserialize;
-use std::fs::File;
-use tar::Archive;
+use serde::{Deserialize, Serialize};
+
+/// Latest release of suiup, cached for the update check
+const LATEST_RELEASE_CACHE_FILE: &str = "suiup_latest_release.json";
 
 #[derive(Debug, Deserialize)]
 struct GitHubRelease {
@@ -37,28 +45,100 @@ async fn check_for_updates_impl() -> Option<()> {
 
     if current_version < latest_version {
         eprintln!(
-            "\n⚠️  A new version of suiup is available: v{} → v{}",
-            current_version, latest_version
+            "\n{} A new version of suiup is available: v{current_version} {} v{latest_version}",
+            output::warning_sign(),
+            output::symbol("→", "->")
         );
         eprintln!("   Run 'suiup self update' to update to the latest version.\n");
     }
     Some(())
 }
 
+/// Latest release of suiup as last fetched, with the validators to ask GitHub whether it changed
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedLatestRelease {
+    tag_name: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+/// suiup's own releases are not mirrored on a GitHub Enterprise Server, only
+/// `SUIUP_GITHUB_API_URL` replaces the public API for them
+fn latest_release_url() -> String {
+    let api = http::github_api_override().unwrap_or_else(|| http::GITHUB_API_URL.to_string());
+    format!("{api}/repos/MystenLabs/suiup/releases/latest")
+}
+
+fn latest_release_cache_file() -> PathBuf {
+    get_suiup_cache_dir().join(LATEST_RELEASE_CACHE_FILE)
+}
+
+/// Fetches the latest version of suiup. The request is conditional on the cached release, so
+/// that it usually costs a `304 Not Modified` response only.
 async fn get_latest_version() -> Result<Ver> {
-    let client = reqwest::Client::new();
-    let response = client
-        .get("https://api.github.com/repos/MystenLabs/suiup/releases/latest")
-        .header("User-Agent", "suiup")
-        .send()
-        .await?;
+    let cached = std::fs::read_to_string(latest_release_cache_file())
+        .ok()
+        .and_then(|content| serde_json::from_str::<CachedLatestRelease>(&content).ok());
+    let mut request = http::get(&latest_release_url(), None)?;
+    if let Some(cached) = &cached {
+        if let Some(etag) = &cached.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &cached.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+    let response = request.send().await?;
 
+    if response.status() == StatusCode::NOT_MODIFIED {
+        if let Some(cached) = cached {
+            return Ver::from_str(&cached.tag_name);
+        }
+    }
     if !response.status().is_success() {
         return Err(anyhow!("Failed to fetch latest version from GitHub"));
     }
 
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from)
+    };
+    let (etag, last_modified) = (header(ETAG), header(LAST_MODIFIED));
     let release: GitHubRelease = response.json().await?;
-    Ver::from_str(&release.tag_name)
+    let cached = CachedLatestRelease {
+        tag_name: release.tag_name,
+        etag,
+        last_modified,
+    };
+    // The check still works without the cache, only less cheaply
+    let _ = std::fs::create_dir_all(get_suiup_cache_dir()).and_then(|_| {
+        std::fs::write(
+            latest_release_cache_file(),
+            serde_json::to_string_pretty(&cached)?,
+        )
+    });
+    Ver::from_str(&cached.tag_name)
+}
+
+/// Newer release of suiup than the one running, if any. With `fast`, only the release cached by
+/// the last update check is looked at.
+pub(crate) async fn newer_version(fast: bool) -> Option<String> {
+    let latest = if fast {
+        let cached = std::fs::read_to_string(latest_release_cache_file()).ok()?;
+        Ver::from_str(
+            &serde_json::from_str::<CachedLatestRelease>(&cached)
+                .ok()?
+                .tag_name,
+        )
+        .ok()?
+    } else {
+        get_latest_version().await.ok()?
+    };
+    let current = Ver::from_str(env!("CARGO_PKG_VERSION")).ok()?;
+    (latest > current).then(|| latest.to_string())
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -119,11 +199,7 @@ pub async fn handle_update() -> Result<()> {
     let current_version = Ver::from_str(split[1])?;
 
     // find the latest version on github in releases
-    let repo = "https://api.github.com/repos/MystenLabs/suiup/releases/latest";
-    let client = reqwest::Client::new();
-    let response = client
-        .get(repo)
-        .header("User-Agent", "suiup")
+    let response = http::get(&latest_release_url(), None)?
         .send()
         .await?
         .json::<serde_json::Value>()
@@ -135,42 +211,60 @@ pub async fn handle_update() -> Result<()> {
     let latest_version = Ver::from_str(tag)?;
 
     if current_version == latest_version {
-        println!("suiup is already up to date");
+        status!("suiup is already up to date");
         return Ok(());
     } else {
-        println!("Updating to latest version: {}", latest_version);
+        status!("Updating to latest version: {}", latest_version);
     }
 
     // download the latest version from github
     // https://github.com/MystenLabs/suiup/releases/download/v0.0.1/suiup-Linux-musl-x86_64.tar.gz
 
     let archive_name = find_archive_name()?;
-    let url =
-        format!("https://github.com/MystenLabs/suiup/releases/download/{tag}/{archive_name}",);
+    // The URL of the asset in the release, which a mock of the GitHub API can point to itself
+    let url = response["assets"]
+        .as_array()
+        .and_then(|assets| {
+            assets
+                .iter()
+                .find(|asset| asset["name"] == archive_name.as_str())
+        })
+        .and_then(|asset| asset["browser_download_url"].as_str())
+        .map(String::from)
+        .unwrap_or_else(|| {
+            format!("https://github.com/MystenLabs/suiup/releases/download/{tag}/{archive_name}")
+        });
 
     let temp_dir = tempfile::tempdir()?;
+    // Not removed by its destructor when interrupted
+    let _partial = Partial::new(temp_dir.path());
     let archive_path = temp_dir.path().join(&archive_name);
     download_file(&url, &temp_dir.path().join(archive_name), "suiup", None).await?;
 
-    // extract the archive
-    let file = File::open(archive_path.as_path())
-        .map_err(|_| anyhow!("Cannot open archive file: {}", archive_path.display()))?;
-    let tar = GzDecoder::new(file);
-    let mut archive = Archive::new(tar);
-    archive
-        .unpack(temp_dir.path())
-        .map_err(|_| anyhow!("Cannot unpack archive file: {}", archive_path.display()))?;
-
     #[cfg(not(windows))]
     let binary = "suiup";
     #[cfg(windows)]
     let binary = "suiup.exe";
 
-    // replace the current binary with the new one
+    // extract the binary on the blocking thread pool
     let binary_path = temp_dir.path().join(binary);
-    std::fs::copy(binary_path, current_exe)?;
+    let output_path = binary_path.clone();
+    task::spawn_blocking(move || -> Result<()> {
+        if !extract_file(&archive_path, binary, &output_path)? {
+            bail!(
+                "The archive {} does not contain {binary}",
+                archive_path.display()
+            );
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| anyhow!("Extraction task failed: {e}"))??;
+
+    // replace the current binary with the new one
+    replace_binary(&binary_path, &current_exe)?;
 
-    println!("suiup updated to version {}", latest_version);
+    status!("suiup updated to version {}", latest_version);
     // cleanup
     temp_dir.close()?;
     Ok(())
@@ -180,27 +274,26 @@ pub fn handle_uninstall() -> Result<()> {
     let current_exe = std::env::current_exe()?;
     if current_exe.exists() {
         std::fs::remove_file(current_exe)?;
-        println!("suiup uninstalled");
+        status!("suiup uninstalled");
     } else {
-        println!("suiup is not installed");
+        status!("suiup is not installed");
     }
     Ok(())
 }
 
 fn find_archive_name() -> Result<String> {
-    let (os, arch) = detect_os_arch()?;
+    // suiup itself always matches the platform it runs on, regardless of `--target`
+    let target = Target::detect()?;
 
-    let os = match os.as_str() {
-        "linux" => "Linux-musl",
-        "windows" => "Windows",
-        "macos" => "macOS",
-        _ => &os,
+    let os = match target.os {
+        Os::Linux => "Linux-musl",
+        Os::Windows => "Windows",
+        Os::MacOS => "macOS",
     };
 
-    let arch = match arch.as_str() {
-        "x86_64" => "x86_64",
-        "aarch64" => "arm64",
-        _ => &arch,
+    let arch = match target.arch {
+        Arch::X86_64 => "x86_64",
+        Arch::Aarch64 => "arm64",
     };
 
     let filename = if os == "Windows" && arch == "arm64" {
@@ -268,6 +361,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::nonminimal_bool)]
     fn test_ver_ordering() {
         // Test major version differences
         let v1_0_0 = Ver::from_str("1.0.0").unwrap();
@@ -0,0 +1,171 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A child shell whose `PATH` starts with a temporary folder holding the selected versions under
+//! their default names, so they are used instead of the default binaries until it exits.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Error};
+
+use crate::commands::parse_component_with_version;
+use crate::handlers::install::find_installed_version;
+use crate::handlers::manifest::load_manifest;
+use crate::handlers::run::{record_use, select_binary};
+use crate::handlers::switch::get_binary_source_path;
+use crate::types::{default_binary_name, BinaryVersion};
+
+/// Manifest used when neither binaries nor a manifest are given
+pub(crate) const DEFAULT_MANIFEST: &str = "suiup.toml";
+/// Set in the shell to the versions it uses, e.g. for a prompt
+pub(crate) const SUIUP_SHELL: &str = "SUIUP_SHELL";
+
+/// Handles the `shell` command, exiting with the exit code of the shell
+pub fn handle_shell(specs: &[String], manifest: Option<&Path>) -> Result<(), Error> {
+    let binaries = select_binaries(specs, manifest)?;
+    let dir = tempfile::Builder::new()
+        .prefix("suiup-shell-")
+        .tempdir()
+        .map_err(|e| anyhow!("Cannot create the folder of the shell's binaries: {e}"))?;
+    for (binary, path) in &binaries {
+        link_binary(
+            path,
+            dir.path(),
+            &default_binary_name(&binary.binary_name, binary.debug),
+        )?;
+    }
+
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let path = std::env::join_paths(
+        std::iter::once(dir.path().to_path_buf()).chain(std::env::split_paths(&path)),
+    )
+    .map_err(|e| anyhow!("Cannot set the PATH of the shell: {e}"))?;
+    let versions = binaries
+        .iter()
+        .map(|(b, _)| format!("{}@{}-{}", b.binary_name, b.network_release, b.version))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    record_use(&binaries.iter().map(|(b, _)| b.clone()).collect::<Vec<_>>())?;
+
+    let shell = user_shell();
+    println!("Starting {} with {versions}", shell.display());
+    println!("Type `exit` to return to the default binaries.");
+    let status = std::process::Command::new(&shell)
+        .env("PATH", path)
+        .env(SUIUP_SHELL, &versions)
+        .status()
+        .map_err(|e| anyhow!("Cannot start {}: {e}", shell.display()))?;
+    // Removed before exiting, which skips destructors
+    drop(dir);
+
+    println!("Back to the default binaries.");
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
+
+/// The installed binaries the shell uses, with their paths: the given binaries, or those of the
+/// manifest
+fn select_binaries(
+    specs: &[String],
+    manifest: Option<&Path>,
+) -> Result<Vec<(BinaryVersion, PathBuf)>, Error> {
+    if !specs.is_empty() {
+        return specs.iter().map(|spec| select_binary(spec)).collect();
+    }
+    let manifest = match manifest {
+        Some(manifest) => manifest.to_path_buf(),
+        None if Path::new(DEFAULT_MANIFEST).exists() => PathBuf::from(DEFAULT_MANIFEST),
+        None => bail!(
+            "Name the binaries to use, e.g. `suiup shell sui@testnet-1.40.1`, or a manifest with `--manifest`"
+        ),
+    };
+    let entries = load_manifest(&manifest)?.binaries;
+    if entries.is_empty() {
+        bail!(
+            "The manifest {} does not list any binary",
+            manifest.display()
+        );
+    }
+    entries
+        .iter()
+        .map(|entry| {
+            let spec = entry.spec();
+            let metadata = parse_component_with_version(&spec)?;
+            let (nightly, debug) = metadata.with_flags(None, entry.debug)?;
+            let binary = find_installed_version(&metadata, nightly.as_deref(), debug)?.ok_or_else(|| {
+                anyhow!(
+                    "{spec} is not installed. Install the binaries of the manifest with `suiup install --manifest {}`.",
+                    manifest.display()
+                )
+            })?;
+            let path = get_binary_source_path(&binary);
+            Ok((binary, path))
+        })
+        .collect()
+}
+
+/// Makes `binary` available as `name` in `dir`
+fn link_binary(binary: &Path, dir: &Path, name: &str) -> Result<(), Error> {
+    #[cfg(unix)]
+    {
+        let link = dir.join(name);
+        std::os::unix::fs::symlink(binary, &link).map_err(|e| {
+            anyhow!(
+                "Cannot link {} to {}: {e}",
+                binary.display(),
+                link.display()
+            )
+        })
+    }
+
+    // Symbolic links need privileges on Windows
+    #[cfg(windows)]
+    {
+        let link = dir.join(format!("{name}.exe"));
+        std::fs::hard_link(binary, &link)
+            .or_else(|_| std::fs::copy(binary, &link).map(|_| ()))
+            .map_err(|e| {
+                anyhow!(
+                    "Cannot copy {} to {}: {e}",
+                    binary.display(),
+                    link.display()
+                )
+            })
+    }
+}
+
+/// The shell of the user, `SHELL` on Unix and `COMSPEC` on Windows
+fn user_shell() -> PathBuf {
+    #[cfg(windows)]
+    let (var, fallback) = ("COMSPEC", "cmd.exe");
+    #[cfg(not(windows))]
+    let (var, fallback) = ("SHELL", "/bin/sh");
+    std::env::var_os(var)
+        .filter(|shell| !shell.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(fallback))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_binary() {
+        let dir = tempfile::tempdir().unwrap();
+        let binary = dir.path().join("sui-v1.40.1");
+        std::fs::write(&binary, "binary").unwrap();
+        let shell_dir = dir.path().join("shell");
+        std::fs::create_dir(&shell_dir).unwrap();
+
+        link_binary(&binary, &shell_dir, "sui").unwrap();
+        #[cfg(windows)]
+        let linked = shell_dir.join("sui.exe");
+        #[cfg(not(windows))]
+        let linked = shell_dir.join("sui");
+        assert_eq!(std::fs::read_to_string(linked).unwrap(), "binary");
+    }
+}
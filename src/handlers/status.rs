@@ -0,0 +1,230 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The `status` command: the versions in use in the current folder, where they come from and
+//! whether updates are pending, for prompts and editors.
+
+use std::path::Path;
+
+use anyhow::Error;
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::commands::BinaryName;
+use crate::handlers::manifest::load_manifest;
+use crate::handlers::release::{
+    last_release_for_network, load_cached_releases, release_list, release_list_quietly,
+};
+use crate::handlers::self_::newer_version;
+use crate::handlers::shell::{DEFAULT_MANIFEST, SUIUP_SHELL};
+use crate::handlers::version::is_newer;
+use crate::state;
+
+/// Where the version in use comes from
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum VersionSource {
+    /// The default version
+    Default,
+    /// Selected for the `suiup shell` running
+    Override,
+    /// Listed in the `suiup.toml` of the current folder
+    ToolchainFile,
+    /// The default version of the system-wide installation, for binaries without a default
+    /// version of the user's own
+    System,
+}
+
+/// A binary in use in the current folder
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ActiveVersion {
+    pub binary: String,
+    /// Network release, or branch for nightly builds. Not set when a toolchain file leaves the
+    /// network to the default one.
+    pub network: Option<String>,
+    /// Not set when a toolchain file does not pin the version
+    pub version: Option<String>,
+    pub debug: bool,
+    pub source: VersionSource,
+}
+
+#[derive(Serialize, Debug)]
+pub struct StatusReport {
+    pub binaries: Vec<BinaryStatus>,
+    /// Newer release of suiup, if any
+    pub suiup_update: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct BinaryStatus {
+    #[serde(flatten)]
+    pub active: ActiveVersion,
+    /// Latest release of the network, when known
+    pub latest_version: Option<String>,
+    pub update_available: bool,
+}
+
+/// Handles the `status` command. With `fast`, only local files are read: pending updates come
+/// from the release lists cached by earlier commands.
+pub async fn handle_status(
+    json: bool,
+    fast: bool,
+    github_token: Option<String>,
+) -> Result<(), Error> {
+    let mut binaries = Vec::new();
+    for active in active_versions() {
+        let latest_version = latest_version(&active, fast, json, github_token.clone()).await;
+        let update_available = match (&active.version, &latest_version) {
+            (Some(version), Some(latest)) => is_newer(latest, version),
+            _ => false,
+        };
+        binaries.push(BinaryStatus {
+            active,
+            latest_version,
+            update_available,
+        });
+    }
+    let report = StatusReport {
+        binaries,
+        suiup_update: newer_version(fast).await,
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&report);
+    }
+    Ok(())
+}
+
+fn print_report(report: &StatusReport) {
+    if report.binaries.is_empty() {
+        println!("No binary in use. Install one with `suiup install sui`.");
+    }
+    for binary in &report.binaries {
+        let active = &binary.active;
+        let name = match active.debug {
+            true => format!("{}-debug", active.binary),
+            false => active.binary.clone(),
+        };
+        let release = [active.network.as_deref(), active.version.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join("-");
+        let source = match active.source {
+            VersionSource::Default => "default",
+            VersionSource::Override => "suiup shell",
+            VersionSource::ToolchainFile => DEFAULT_MANIFEST,
+            VersionSource::System => "system-wide",
+        };
+        let update = match (&binary.latest_version, binary.update_available) {
+            (Some(latest), true) => format!("  update available: {latest}"),
+            _ => String::new(),
+        };
+        println!("{name:<12} {release:<20} ({source}){update}");
+    }
+    if let Some(version) = &report.suiup_update {
+        println!("suiup {version} is available, run `suiup self update`.");
+    }
+}
+
+/// The binaries in use: those of the `suiup shell` running, else those of the toolchain file
+/// (`suiup.toml`) of the current folder, else the default versions, of the user's installation
+/// first and then of the system-wide one. Only local files are read.
+pub(crate) fn active_versions() -> Vec<ActiveVersion> {
+    if let Ok(versions) = std::env::var(SUIUP_SHELL) {
+        return versions.split_whitespace().filter_map(parse_spec).collect();
+    }
+    if let Ok(manifest) = load_manifest(Path::new(DEFAULT_MANIFEST)) {
+        return manifest
+            .binaries
+            .into_iter()
+            .map(|entry| ActiveVersion {
+                binary: entry.name,
+                network: entry.network,
+                version: entry.version.map(|v| v.trim_start_matches('v').to_string()),
+                debug: entry.debug,
+                source: VersionSource::ToolchainFile,
+            })
+            .collect();
+    }
+    let defaults = state::default_versions().unwrap_or_default();
+    let system_defaults = state::system_default_versions()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(name, _)| !defaults.contains_key(name))
+        .map(|default| (default, VersionSource::System))
+        .collect::<Vec<_>>();
+    defaults
+        .into_iter()
+        .map(|default| (default, VersionSource::Default))
+        .chain(system_defaults)
+        .map(
+            |((name, (network, version, debug)), source)| ActiveVersion {
+                binary: match debug {
+                    true => name.trim_end_matches("-debug").to_string(),
+                    false => name,
+                },
+                network: Some(network),
+                version: Some(version.trim_start_matches('v').to_string()),
+                debug,
+                source,
+            },
+        )
+        .collect()
+}
+
+/// Parses a version of `SUIUP_SHELL`, e.g. `sui@testnet-v1.40.1`
+fn parse_spec(spec: &str) -> Option<ActiveVersion> {
+    let (binary, release) = spec.split_once('@')?;
+    let (network, version) = release.rsplit_once('-')?;
+    Some(ActiveVersion {
+        binary: binary.to_string(),
+        network: Some(network.to_string()),
+        version: Some(version.trim_start_matches('v').to_string()),
+        debug: false,
+        source: VersionSource::Override,
+    })
+}
+
+/// Latest release of the network of the binary, from the cached release list with `fast`.
+/// Unknown for nightly builds and standalone binaries. With `quiet`, the release list is fetched
+/// without status messages, which would break the JSON output.
+async fn latest_version(
+    active: &ActiveVersion,
+    fast: bool,
+    quiet: bool,
+    github_token: Option<String>,
+) -> Option<String> {
+    let network = active.network.as_deref()?;
+    if network == "standalone" || active.version.as_deref() == Some("nightly") {
+        return None;
+    }
+    let repo = BinaryName::from_str(&active.binary, true).ok()?.repo();
+    let releases = match (fast, quiet) {
+        (true, _) => load_cached_releases(&repo).ok()??,
+        (false, true) => release_list_quietly(&repo, github_token).await.ok()?.0,
+        (false, false) => release_list(&repo, github_token).await.ok()?.0,
+    };
+    let (_, version) = last_release_for_network(&releases, network).await.ok()?;
+    Some(version.trim_start_matches('v').to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_spec() {
+        let active = parse_spec("sui@testnet-v1.40.1").unwrap();
+        assert_eq!(active.binary, "sui");
+        assert_eq!(active.network.as_deref(), Some("testnet"));
+        assert_eq!(active.version.as_deref(), Some("1.40.1"));
+        assert_eq!(active.source, VersionSource::Override);
+        let active = parse_spec("sui@main-nightly").unwrap();
+        assert_eq!(active.network.as_deref(), Some("main"));
+        assert_eq!(active.version.as_deref(), Some("nightly"));
+        assert_eq!(parse_spec("sui"), None);
+    }
+}
@@ -2,33 +2,62 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::{anyhow, bail, Result};
+use std::cmp::Reverse;
 use tracing::info;
 
+use crate::status;
 use crate::{
-    handlers::update_default_version_file,
+    handlers::{
+        block::ensure_not_blocked,
+        branches::short_sha,
+        enable::{is_disabled, report_disabled},
+        nightly::restore_nightly_build,
+        replace_binary, update_default_version_file,
+        version::ensure_minimum_version,
+    },
     paths::{binaries_dir, get_default_bin_dir},
-    types::{BinaryVersion, InstalledBinaries},
+    state,
+    types::{default_binary_name, BinaryVersion, InstalledBinaries, Network, Version},
 };
 
-#[cfg(not(windows))]
-use std::os::unix::fs::PermissionsExt;
-
-/// Handle the switch command
-pub fn handle_switch(binary_spec: &str) -> Result<()> {
+/// Handle the switch command. `commit` selects a kept nightly build.
+pub fn handle_switch(
+    binary_spec: &str,
+    allow_below_minimum: bool,
+    commit: Option<&str>,
+) -> Result<()> {
     // Parse the binary@network_release format
     let (binary_name, network_release) = parse_binary_spec(binary_spec)?;
 
     // Find the matching installed binary
-    let installed_binaries = InstalledBinaries::new()?;
+    let installed_binaries = state::installed_binaries()?;
     let matching_binary =
         find_matching_binary(&installed_binaries, &binary_name, &network_release)?;
 
+    ensure_not_blocked(
+        &matching_binary.binary_name,
+        &matching_binary.network_release,
+        &matching_binary.version,
+    )?;
+    ensure_minimum_version(
+        &matching_binary.binary_name,
+        &matching_binary.version,
+        allow_below_minimum,
+    )?;
+
+    if let Some(commit) = commit {
+        let commit = restore_nightly_build(&matching_binary, commit)?;
+        status!("Restored the build of commit {}", short_sha(&commit));
+    }
+
     // Switch to the found binary
     switch_to_binary(&matching_binary)?;
 
-    println!(
+    status!(
         "Successfully switched to {}-{} from {}",
-        matching_binary.binary_name, matching_binary.version, matching_binary.network_release
+        default_binary_name(&matching_binary.binary_name, matching_binary.debug),
+        matching_binary.version,
+        matching_binary.network_release
     );
 
     Ok(())
@@ -40,7 +69,7 @@ pub fn parse_binary_spec(spec: &str) -> Result<(String, String)> {
 
     if parts.len() != 2 {
         bail!(
-            "Invalid format. Use 'binary@network_release' format (e.g., 'sui@testnet', 'mvr@main')"
+            "Invalid format. Use 'binary@network_release' format (e.g., 'sui@testnet', 'mvr@main'), optionally with a version (e.g., 'sui@testnet-1.39.3', 'sui@1.39.3')"
         );
     }
 
@@ -54,45 +83,107 @@ pub fn parse_binary_spec(spec: &str) -> Result<(String, String)> {
     Ok((binary_name, network_release))
 }
 
-/// Find the matching binary from installed binaries
+/// Find the matching binary from installed binaries. `network_release` is a network release or
+/// a branch, whose latest installed version is selected, or a version with or without its network
+/// (e.g. `testnet-1.39.3` or `1.39.3`).
 pub fn find_matching_binary(
     installed_binaries: &InstalledBinaries,
     binary_name: &str,
     network_release: &str,
 ) -> Result<BinaryVersion> {
-    let binaries = installed_binaries.binaries();
+    // `sui-debug@testnet` selects the debug builds, which are installed next to release builds
+    let (name, debug) = match binary_name.strip_suffix("-debug") {
+        Some(name) => (name, true),
+        None => (binary_name, false),
+    };
+    let installed = installed_binaries
+        .binaries()
+        .iter()
+        .filter(|b| b.binary_name == name && b.debug == debug)
+        .collect::<Vec<_>>();
 
     // Find all matching binaries for the given binary name and network/release
-    let mut matching_binaries: Vec<&BinaryVersion> = binaries
+    let mut matching_binaries = installed
         .iter()
-        .filter(|b| b.binary_name == binary_name && b.network_release == network_release)
-        .collect();
-
+        .copied()
+        .filter(|b| b.network_release == network_release)
+        .collect::<Vec<_>>();
     if matching_binaries.is_empty() {
-        bail!(
-            "No installed binary found for {}@{}. Use 'suiup show' to see available binaries.",
+        matching_binaries = matching_versions(&installed, network_release);
+    }
+
+    match matching_binaries.as_slice() {
+        [] => bail!(
+            "No installed binary found for {}@{}. {}",
             binary_name,
-            network_release
+            network_release,
+            if installed.is_empty() {
+                "Use 'suiup show' to see available binaries.".to_string()
+            } else {
+                format!("Installed: {}", switch_specs(binary_name, &installed))
+            }
+        ),
+        [binary] => return Ok((*binary).clone()),
+        _ => {}
+    }
+    if matching_binaries
+        .iter()
+        .any(|b| b.network_release != matching_binaries[0].network_release)
+    {
+        bail!(
+            "{binary_name} {network_release} is installed for several networks, name the one to switch to: {}",
+            switch_specs(binary_name, &matching_binaries)
         );
     }
 
-    // Sort by version to get the latest one (this is a simple string sort, might need improvement)
-    matching_binaries.sort_by(|a, b| b.version.cmp(&a.version));
+    // The newest version first
+    matching_binaries.sort_by_key(|b| Reverse(b.release_version()));
 
     Ok(matching_binaries[0].clone())
 }
 
+/// The binaries of the version `spec`, e.g. `testnet-1.39.3`, `1.39.3` for any network, or
+/// `testnet-1.39` for any 1.39.x version. Standalone binaries (mvr) are not tied to a network.
+fn matching_versions<'a>(installed: &[&'a BinaryVersion], spec: &str) -> Vec<&'a BinaryVersion> {
+    let Ok(spec) = spec.parse::<Version>() else {
+        return Vec::new();
+    };
+    installed
+        .iter()
+        .copied()
+        .filter(|b| b.release_version().is_some_and(|v| spec.matches(&v)))
+        .collect()
+}
+
+/// The specs to switch to each of the binaries, e.g. `sui@testnet-1.39.3, sui@main`
+fn switch_specs(binary_name: &str, binaries: &[&BinaryVersion]) -> String {
+    binaries
+        .iter()
+        .map(|b| {
+            let version = b.version.trim_start_matches('v');
+            if b.network_release.parse::<Network>().is_ok() {
+                format!("{binary_name}@{}-{version}", b.network_release)
+            } else if b.network_release == "standalone" {
+                format!("{binary_name}@{version}")
+            } else {
+                format!("{binary_name}@{}", b.network_release)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 /// Switch to the specified binary by copying it to the default bin directory
-fn switch_to_binary(binary: &BinaryVersion) -> Result<()> {
+pub(crate) fn switch_to_binary(binary: &BinaryVersion) -> Result<()> {
     let src = get_binary_source_path(binary);
     let dst = get_binary_destination_path(binary);
 
-    // Copy the binary file
-    copy_binary_file(&src, &dst, &binary.binary_name)?;
-
-    // Set executable permissions on Unix systems
-    #[cfg(unix)]
-    set_executable_permissions(&dst)?;
+    // A disabled binary keeps its default version, but stays out of the bin folder
+    if is_disabled(&binary.binary_name) {
+        report_disabled(&binary.binary_name);
+    } else {
+        copy_binary_file(&src, &dst, &binary.binary_name)?;
+    }
 
     // Update the default version file
     update_default_version_file(
@@ -106,8 +197,16 @@ fn switch_to_binary(binary: &BinaryVersion) -> Result<()> {
 }
 
 /// Construct the source path for a binary
-fn get_binary_source_path(binary: &BinaryVersion) -> std::path::PathBuf {
-    let mut src = binaries_dir();
+pub(crate) fn get_binary_source_path(binary: &BinaryVersion) -> std::path::PathBuf {
+    binary_source_path_in(&binaries_dir(), binary)
+}
+
+/// Construct the source path for a binary installed in the given binaries folder
+pub(crate) fn binary_source_path_in(
+    binaries_dir: &std::path::Path,
+    binary: &BinaryVersion,
+) -> std::path::PathBuf {
+    let mut src = binaries_dir.to_path_buf();
     src.push(&binary.network_release);
 
     // Handle nightly builds which have a different directory structure
@@ -130,15 +229,9 @@ fn get_binary_source_path(binary: &BinaryVersion) -> std::path::PathBuf {
 }
 
 /// Construct the destination path for a binary
-fn get_binary_destination_path(binary: &BinaryVersion) -> std::path::PathBuf {
+pub(crate) fn get_binary_destination_path(binary: &BinaryVersion) -> std::path::PathBuf {
     let mut dst = get_default_bin_dir();
-    let dst_name = if binary.debug {
-        format!("{}-debug", binary.binary_name)
-    } else {
-        binary.binary_name.clone()
-    };
-
-    dst.push(&dst_name);
+    dst.push(default_binary_name(&binary.binary_name, binary.debug));
 
     #[cfg(target_os = "windows")]
     dst.set_extension("exe");
@@ -150,13 +243,8 @@ fn get_binary_destination_path(binary: &BinaryVersion) -> std::path::PathBuf {
 fn copy_binary_file(src: &std::path::Path, dst: &std::path::Path, binary_name: &str) -> Result<()> {
     info!("Copying from {} to {}", src.display(), dst.display());
 
-    // Remove existing file if it exists
-    if dst.exists() {
-        std::fs::remove_file(dst)?;
-    }
-
-    // Copy the binary
-    std::fs::copy(src, dst).map_err(|e| {
+    // Copy the binary, replacing the current one even if it is running
+    replace_binary(src, dst).map_err(|e| {
         anyhow!(
             "Error copying {} to the default folder (src: {}, dst: {}): {}",
             binary_name,
@@ -168,12 +256,3 @@ fn copy_binary_file(src: &std::path::Path, dst: &std::path::Path, binary_name: &
 
     Ok(())
 }
-
-/// Set executable permissions on Unix systems
-#[cfg(unix)]
-fn set_executable_permissions(path: &std::path::Path) -> Result<()> {
-    let mut perms = std::fs::metadata(path)?.permissions();
-    perms.set_mode(0o755);
-    std::fs::set_permissions(path, perms)?;
-    Ok(())
-}
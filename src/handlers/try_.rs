@@ -0,0 +1,93 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Runs a version without installing it: it is installed into a temporary sandbox that is
+//! deleted afterwards, and the installed binaries and default versions are left untouched.
+
+use std::path::Path;
+use std::process::ExitStatus;
+
+use anyhow::{anyhow, Error};
+
+use crate::commands::ComponentCommands;
+use crate::handle_commands::handle_cmd;
+use crate::handlers::install::InstallOptions;
+use crate::handlers::run::select_binary;
+use crate::interrupt::{ignore_interrupts, Partial};
+use crate::paths::{get_suiup_state_dir, set_sandbox};
+use crate::state;
+use crate::status;
+
+/// Switches back to the user's installation when the sandbox is no longer used
+struct SandboxGuard;
+
+impl Drop for SandboxGuard {
+    fn drop(&mut self) {
+        // Written to the sandbox, so that it is not mixed with the user's state
+        let _ = state::invalidate();
+        set_sandbox(None);
+    }
+}
+
+/// Handles the `try` command, exiting with the exit code of the binary
+pub async fn handle_try(
+    binary_spec: &str,
+    args: &[String],
+    github_token: Option<String>,
+) -> Result<(), Error> {
+    let sandbox = tempfile::Builder::new()
+        .prefix("suiup-try-")
+        .tempdir()
+        .map_err(|e| anyhow!("Cannot create the sandbox: {e}"))?;
+    let status = {
+        // Pending changes of the user's state are saved before switching
+        state::invalidate()?;
+        set_sandbox(Some(sandbox.path().to_path_buf()));
+        let _guard = SandboxGuard;
+        run_in_sandbox(sandbox.path(), binary_spec, args, github_token).await?
+    };
+    // Deleted before exiting, which skips destructors
+    drop(sandbox);
+
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
+
+async fn run_in_sandbox(
+    sandbox: &Path,
+    binary_spec: &str,
+    args: &[String],
+    github_token: Option<String>,
+) -> Result<ExitStatus, Error> {
+    // Deleted on Ctrl-C during the install. Once the binary runs, Ctrl-C is its own.
+    let partial = Partial::new(sandbox);
+    std::fs::create_dir_all(get_suiup_state_dir())?;
+    handle_cmd(
+        ComponentCommands::Add {
+            component: binary_spec.to_string(),
+            nightly: None,
+            debug: false,
+            if_missing: false,
+            options: InstallOptions {
+                yes: true,
+                ..Default::default()
+            },
+        },
+        github_token,
+    )
+    .await?;
+
+    let (binary, path) = select_binary(binary_spec)?;
+    // The sandbox is deleted by the caller once the binary exits
+    partial.complete();
+    status!(
+        "Running {} {} ({}) from a sandbox...\n",
+        binary.binary_name,
+        binary.version,
+        binary.network_release
+    );
+    ignore_interrupts(|| std::process::Command::new(&path).args(args).status())
+        .map_err(|e| anyhow!("Cannot run {}: {e}", path.display()))
+}
@@ -1,19 +1,37 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::status;
 use crate::{
     commands::{parse_component_with_version, BinaryName, CommandMetadata, ComponentCommands},
     handle_commands::handle_cmd,
-    types::InstalledBinaries,
+    state,
 };
+use crate::{config::load_config, output};
 use crate::{
     handlers::{
-        available_components, installed_binaries_grouped_by_network,
+        available_components,
+        branches::{compare_commits, fetch_commits, short_sha, summary},
+        install::{install_from_nightly, InstallOptions},
+        installed_binaries_grouped_by_network,
         release::{last_release_for_network, release_list},
     },
-    types::Repo,
+    types::{InstalledBy, Repo},
 };
-use anyhow::{bail, Error};
+use anyhow::{anyhow, bail, Error};
+use clap::ValueEnum;
+
+/// Commits listed when a nightly build is updated, the older ones are counted only
+const MAX_LISTED_COMMITS: usize = 20;
+
+/// Installs made by `update` are recorded as such in their provenance
+fn install_options(yes: bool) -> InstallOptions {
+    InstallOptions {
+        yes,
+        installed_by: Some(InstalledBy::Update),
+        ..Default::default()
+    }
+}
 
 /// Handles the `update` command
 pub async fn handle_update(
@@ -24,8 +42,22 @@ pub async fn handle_update(
     if binary_name.is_empty() {
         bail!("Invalid number of arguments for `update` command");
     }
+    if let Some(name) = binary_name.strip_suffix("@nightly") {
+        let name = BinaryName::from_str(name, true)
+            .map_err(|_| anyhow!("Invalid component name: {name}"))?;
+        return update_nightly(&name, yes, github_token).await;
+    }
 
-    let CommandMetadata { name, version, .. } = parse_component_with_version(&binary_name)?;
+    let CommandMetadata {
+        name,
+        version,
+        nightly,
+        ..
+    } = parse_component_with_version(&binary_name)?;
+    // The nightly builds of every branch are checked
+    if nightly.is_some() {
+        return update_nightly(&name, yes, github_token).await;
+    }
 
     if version.is_some() {
         bail!("Update should be done without a version. Use `suiup install` to specify a version");
@@ -35,7 +67,7 @@ pub async fn handle_update(
         bail!("Invalid component name: {}", name);
     }
 
-    let installed_binaries = InstalledBinaries::new()?;
+    let installed_binaries = state::installed_binaries()?;
     let binaries = installed_binaries.binaries();
     if !binaries.iter().any(|x| x.binary_name == name.to_str()) {
         bail!("Binary {name} not found in installed binaries. Use `suiup show` to see installed binaries and `suiup install` to install the binary.")
@@ -55,7 +87,7 @@ pub async fn handle_update(
         let last_version = if last_version.len() > 1 {
             last_version
                 .iter()
-                .max_by(|a, b| a.version.cmp(&b.version))
+                .max_by_key(|b| b.release_version())
                 .unwrap()
         } else {
             last_version.first().unwrap()
@@ -73,7 +105,8 @@ pub async fn handle_update(
                 component: binary_name,
                 debug: false,
                 nightly: None,
-                yes,
+                if_missing: false,
+                options: install_options(yes),
             },
             github_token,
         )
@@ -87,7 +120,8 @@ pub async fn handle_update(
                 component: binary_name,
                 debug: false,
                 nightly: None,
-                yes,
+                if_missing: false,
+                options: install_options(yes),
             },
             github_token,
         )
@@ -101,21 +135,22 @@ pub async fn handle_update(
         let last_release = last_release_for_network(&releases, n).await?;
         let last_version = last_release.1;
         if v == &last_version {
-            println!("[{n} release] {name} is up to date");
+            status!("[{n} release] {name} is up to date");
         } else {
-            println!("[{n} release] {name} is outdated. Local: {v}, Latest: {last_version}");
+            status!("[{n} release] {name} is outdated. Local: {v}, Latest: {last_version}");
             to_update.push((n, last_version));
         }
     }
 
     for (n, v) in to_update.iter() {
-        println!("Updating {name} to {v} from {n} release");
+        status!("Updating {name} to {v} from {n} release");
         handle_cmd(
             ComponentCommands::Add {
                 component: binary_name.clone(),
                 debug: false,
                 nightly: None,
-                yes,
+                if_missing: false,
+                options: install_options(yes),
             },
             github_token.clone(),
         )
@@ -124,3 +159,162 @@ pub async fn handle_update(
 
     Ok(())
 }
+
+/// Handles `update --all`: updates every installed binary but those in `except` and in the
+/// `update_exclude` list of the config file. A failed update does not stop the others.
+pub async fn handle_update_all(
+    except: &[BinaryName],
+    yes: bool,
+    github_token: Option<String>,
+) -> Result<(), Error> {
+    let installed = state::installed_binaries()?;
+    let excluded = except
+        .iter()
+        .map(|b| b.to_str().to_string())
+        .chain(load_config()?.update_exclude)
+        .collect::<Vec<_>>();
+    let (to_update, skipped) = bulk_update_names(
+        installed
+            .binaries()
+            .iter()
+            .filter(|b| b.version != "nightly")
+            .map(|b| b.binary_name.as_str()),
+        &excluded,
+    );
+    if !skipped.is_empty() {
+        status!("Skipping {}", skipped.join(", "));
+    }
+    if to_update.is_empty() {
+        status!("No installed binaries to update");
+        return Ok(());
+    }
+
+    let mut failed = vec![];
+    for name in to_update {
+        if let Err(e) = handle_update(name.to_string(), yes, github_token.clone()).await {
+            eprintln!("{} Cannot update {name}: {e}", output::warning_sign());
+            failed.push(name);
+        }
+    }
+    if !failed.is_empty() {
+        bail!("Failed to update {}", failed.join(", "));
+    }
+    Ok(())
+}
+
+/// The binaries among `installed` to update and those `excluded`, once each and in the order of
+/// [`available_components`]
+fn bulk_update_names<'a>(
+    installed: impl IntoIterator<Item = &'a str>,
+    excluded: &[String],
+) -> (Vec<&'static str>, Vec<&'static str>) {
+    let installed = installed.into_iter().collect::<Vec<_>>();
+    available_components()
+        .iter()
+        .filter(|name| installed.contains(name))
+        .partition(|name| !excluded.iter().any(|e| e == *name))
+}
+
+/// Updates the nightly builds of `name`, one per branch: each is rebuilt only if its branch has
+/// new commits since the one it was built from
+async fn update_nightly(
+    name: &BinaryName,
+    yes: bool,
+    github_token: Option<String>,
+) -> Result<(), Error> {
+    let installed = state::installed_binaries()?;
+    let nightlies = installed
+        .binaries()
+        .iter()
+        .filter(|b| b.binary_name == name.to_str() && b.version == "nightly")
+        .collect::<Vec<_>>();
+    if nightlies.is_empty() {
+        bail!("No nightly build of {name} is installed. Install one with `suiup install {name} --nightly <branch>`.");
+    }
+    let repo = name.repo();
+    let repository = repo.repository()?;
+    for binary in nightlies {
+        let provenance = binary.provenance.as_ref();
+        let branch = provenance
+            .and_then(|p| p.branch.clone())
+            .unwrap_or_else(|| binary.network_release.clone());
+        let latest = fetch_commits(&repo, &branch, 1, github_token.as_deref())
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("The {branch} branch of {repository} has no commits"))?;
+
+        match provenance.and_then(|p| p.commit.as_deref()) {
+            Some(commit) if commit == latest.sha => {
+                status!(
+                    "[{branch} branch] {name} nightly is up to date ({})",
+                    short_sha(commit)
+                );
+                continue;
+            }
+            Some(commit) => {
+                status!(
+                    "[{branch} branch] {name} nightly is outdated: {} -> {}",
+                    short_sha(commit),
+                    short_sha(&latest.sha)
+                );
+                print_commit_log(&repo, commit, &latest.sha, github_token.as_deref()).await;
+            }
+            None => status!(
+                "[{branch} branch] {name} nightly was built from an unknown commit, rebuilding at {}",
+                short_sha(&latest.sha)
+            ),
+        }
+        install_from_nightly(name, &branch, binary.debug, &install_options(yes)).await?;
+    }
+    Ok(())
+}
+
+/// Prints the commits after `old` up to `new`, newest first. The log is informative only, it is
+/// left out if it cannot be fetched.
+async fn print_commit_log(repo: &Repo, old: &str, new: &str, github_token: Option<&str>) {
+    let comparison = match compare_commits(repo, old, new, github_token).await {
+        Ok(comparison) => comparison,
+        Err(e) => {
+            status!("  Cannot fetch the commit log: {e}");
+            return;
+        }
+    };
+    if comparison.status == "diverged" || comparison.status == "behind" {
+        status!(
+            "  The branch was rewritten, {} is no longer in its history",
+            short_sha(old)
+        );
+    }
+    for commit in comparison.commits.iter().rev().take(MAX_LISTED_COMMITS) {
+        status!(
+            "  {} {}",
+            short_sha(&commit.sha),
+            summary(&commit.commit.message)
+        );
+    }
+    if comparison.ahead_by > MAX_LISTED_COMMITS {
+        status!(
+            "  ... and {} older commits",
+            comparison.ahead_by - MAX_LISTED_COMMITS
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bulk_update_names() {
+        let installed = ["walrus", "sui", "mvr", "sui", "custom"];
+        assert_eq!(
+            bulk_update_names(installed, &["sui".to_string()]),
+            (vec!["mvr", "walrus"], vec!["sui"])
+        );
+        assert_eq!(
+            bulk_update_names(installed, &[]),
+            (vec!["sui", "mvr", "walrus"], vec![])
+        );
+    }
+}
@@ -0,0 +1,121 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Notices, after the commands managing binaries, that the installed binaries have newer
+//! releases on their network. The releases are checked at most once a day, and the notice is
+//! shown after the check only, so that it does not repeat after every command.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::Error;
+use chrono::{DateTime, Duration, Utc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::commands::BinaryName;
+use crate::handlers::release::{last_release_for_network, release_list_quietly};
+use crate::output;
+use crate::paths::get_suiup_cache_dir;
+use crate::state::{self, write_file_atomically};
+use crate::types::{Release, Repo, Version};
+
+/// When the installed binaries were last checked for updates
+const LAST_CHECK_FILE: &str = "binary_updates.json";
+const CHECK_INTERVAL: Duration = Duration::hours(24);
+
+#[derive(Serialize, Deserialize, Debug)]
+struct LastCheck {
+    checked_at: DateTime<Utc>,
+}
+
+fn last_check_file() -> PathBuf {
+    get_suiup_cache_dir().join(LAST_CHECK_FILE)
+}
+
+/// Prints a one-line notice listing the installed binaries with a newer release on their
+/// network, if they were not checked in the last day. Failures are ignored.
+pub async fn notify_binary_updates(github_token: Option<String>) {
+    let last_check = std::fs::read_to_string(last_check_file())
+        .ok()
+        .and_then(|content| serde_json::from_str::<LastCheck>(&content).ok());
+    if last_check.is_some_and(|check| Utc::now() - check.checked_at < CHECK_INTERVAL) {
+        return;
+    }
+    // Recorded before checking, so that a failing check is not repeated after every command
+    let checked = LastCheck {
+        checked_at: Utc::now(),
+    };
+    let _ = std::fs::create_dir_all(get_suiup_cache_dir())
+        .map_err(Error::from)
+        .and_then(|_| {
+            write_file_atomically(
+                &last_check_file(),
+                serde_json::to_string_pretty(&checked)?.as_bytes(),
+            )
+        });
+
+    let updates = find_updates(github_token).await;
+    if !updates.is_empty() {
+        eprintln!(
+            "\n{} Newer releases are available: {}. Run `suiup update` to install them.",
+            output::warning_sign(),
+            updates.join(", ")
+        );
+    }
+}
+
+/// Newer releases of the installed binaries, e.g. `sui testnet-1.41.0 (installed 1.40.1)`,
+/// comparing the newest installed version of each binary and network with the latest release
+/// of the network. Nightly builds and standalone binaries are not checked.
+async fn find_updates(github_token: Option<String>) -> Vec<String> {
+    let Ok(installed) = state::installed_binaries() else {
+        return Vec::new();
+    };
+    let mut newest = BTreeMap::<(String, String), Version>::new();
+    for binary in installed.binaries() {
+        // Only the releases of a network have a network version
+        let Some(version) = binary.release_version().filter(|v| v.network.is_some()) else {
+            continue;
+        };
+        newest
+            .entry((binary.binary_name.clone(), binary.network_release.clone()))
+            .and_modify(|newest| {
+                if version > *newest {
+                    *newest = version.clone();
+                }
+            })
+            .or_insert(version);
+    }
+
+    let mut releases = Vec::<(Repo, Vec<Release>)>::new();
+    let mut updates = Vec::new();
+    for ((binary, network), version) in newest {
+        let Ok(repo) = BinaryName::from_str(&binary, true).map(|name| name.repo()) else {
+            continue;
+        };
+        if !releases.iter().any(|(r, _)| *r == repo) {
+            // The notice is the only output of the check
+            let Ok((list, _)) = release_list_quietly(&repo, github_token.clone()).await else {
+                continue;
+            };
+            releases.push((repo, list));
+        }
+        let Some((_, list)) = releases.iter().find(|(r, _)| *r == repo) else {
+            continue;
+        };
+        if let Ok((_, latest)) = last_release_for_network(list, &network).await {
+            if latest
+                .parse::<Version>()
+                .is_ok_and(|latest| latest > version)
+            {
+                updates.push(format!(
+                    "{binary} {network}-{} (installed {})",
+                    latest.trim_start_matches('v'),
+                    version.tag().trim_start_matches('v')
+                ));
+            }
+        }
+    }
+    updates
+}
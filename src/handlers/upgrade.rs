@@ -0,0 +1,83 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{anyhow, bail, Error};
+
+use crate::commands::{BinaryName, ComponentCommands};
+use crate::handle_commands::handle_cmd;
+use crate::handlers::install::InstallOptions;
+use crate::handlers::switch::switch_to_binary;
+use crate::handlers::version::is_newer;
+use crate::state;
+use crate::status;
+use crate::types::{default_binary_name, InstalledBy};
+
+/// Handles the `upgrade` command: installs the latest release of the network of the default
+/// version, and makes it the default unless `no_switch` is set
+pub async fn handle_upgrade(
+    name: &BinaryName,
+    no_switch: bool,
+    github_token: Option<String>,
+) -> Result<(), Error> {
+    let Some((network, current, debug)) = state::default_versions()?.get(name.to_str()).cloned()
+    else {
+        bail!("{name} has no default version to upgrade. Install it with `suiup install {name}`.");
+    };
+    if current == "nightly" {
+        bail!("The default {name} is a nightly build of {network}. Rebuild it with `suiup update {name}@nightly`.");
+    }
+
+    // Standalone binaries (mvr) are not tied to a network
+    let spec = if network == "standalone" {
+        name.to_string()
+    } else {
+        format!("{name}@{network}")
+    };
+    handle_cmd(
+        ComponentCommands::Add {
+            component: spec,
+            nightly: None,
+            debug,
+            if_missing: false,
+            options: InstallOptions {
+                yes: true,
+                keep_default: true,
+                installed_by: Some(InstalledBy::Update),
+                ..Default::default()
+            },
+        },
+        github_token,
+    )
+    .await?;
+
+    let latest = state::installed_binaries()?
+        .binaries()
+        .iter()
+        .filter(|b| {
+            b.binary_name == name.to_str()
+                && b.network_release == network
+                && b.debug == debug
+                && b.version != "nightly"
+        })
+        .max_by_key(|b| b.release_version())
+        .cloned()
+        .ok_or_else(|| anyhow!("{name} from {network} was not installed"))?;
+    let binary = default_binary_name(name.to_str(), debug);
+    if !is_newer(&latest.version, &current) {
+        status!("[{network}] {binary} {current} is the latest release, nothing to upgrade");
+        return Ok(());
+    }
+    if no_switch {
+        status!(
+            "[{network}] Installed {binary} {}, the default is still {current}. Switch to it with `suiup switch {binary}@{network}`.",
+            latest.version
+        );
+        return Ok(());
+    }
+    switch_to_binary(&latest)?;
+    status!(
+        "[{network}] Upgraded {binary} from {current} to {}",
+        latest.version
+    );
+    Ok(())
+}
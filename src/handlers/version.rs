@@ -1,9 +1,12 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::{anyhow, Error};
+use anyhow::{anyhow, bail, Error};
 use lazy_static::lazy_static;
 
+use crate::config::{config_file, load_config};
+use crate::types::Version;
+
 lazy_static! {
     static ref VERSION_REGEX: regex::Regex = regex::Regex::new(r"v\d+\.\d+\.\d+").unwrap();
 }
@@ -16,3 +19,37 @@ pub fn extract_version_from_release(release: &str) -> Result<String, Error> {
 
     Ok(captures.get(0).unwrap().as_str().to_string())
 }
+
+/// Whether `version` is a newer release than `than`, e.g. `v1.10.0` than `1.9.2`. False if
+/// either is not a release version, e.g. `nightly`.
+pub(crate) fn is_newer(version: &str, than: &str) -> bool {
+    match (version.parse::<Version>(), than.parse::<Version>()) {
+        (Ok(version), Ok(than)) => version > than,
+        _ => false,
+    }
+}
+
+/// Fails if `version` of `binary` is older than its entry in `minimum_versions` of the config
+/// file, unless `allow_below` is set. Nightly builds are not checked.
+pub fn ensure_minimum_version(binary: &str, version: &str, allow_below: bool) -> Result<(), Error> {
+    if allow_below || version == "nightly" {
+        return Ok(());
+    }
+    let binary = binary.strip_suffix("-debug").unwrap_or(binary);
+    let config = load_config()?;
+    let Some(minimum) = config.minimum_versions.get(binary) else {
+        return Ok(());
+    };
+    let minimum_version = minimum
+        .parse::<Version>()
+        .map_err(|e| anyhow!("{e} in minimum_versions of {}", config_file().display()))?;
+    if version.parse::<Version>()? < minimum_version {
+        bail!(
+            "{binary} v{} is older than the minimum version v{} set in {}. Use `--allow-below-minimum` to use it anyway.",
+            version.trim_start_matches('v'),
+            minimum.trim_start_matches('v'),
+            config_file().display()
+        );
+    }
+    Ok(())
+}
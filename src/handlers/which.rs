@@ -1,12 +1,36 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::paths::get_default_bin_dir;
-use anyhow::Error;
+use crate::commands::BinaryName;
+use crate::handlers::enable::is_disabled;
+use crate::paths::{get_default_bin_dir, get_system_bin_dir};
+use crate::state;
+use anyhow::{bail, Error};
 
-/// Handles the `which` command
-pub fn handle_which() -> Result<(), Error> {
+/// Handles the `which` command. With a binary, prints the path of its default version in the
+/// default bin folder, or else in the one of the system-wide installation.
+pub fn handle_which(binary: Option<&BinaryName>) -> Result<(), Error> {
     let default_bin = get_default_bin_dir();
-    println!("{}", default_bin.display());
-    Ok(())
+    let Some(binary) = binary else {
+        println!("{}", default_bin.display());
+        return Ok(());
+    };
+    if is_disabled(binary.to_str()) {
+        bail!(
+            "{binary} is disabled. Run `suiup enable {binary}` to put it back in {}",
+            default_bin.display()
+        );
+    }
+    let file_name = format!("{binary}{}", std::env::consts::EXE_SUFFIX);
+    let path = default_bin.join(&file_name);
+    if path.is_file() {
+        println!("{}", path.display());
+        return Ok(());
+    }
+    let system_path = get_system_bin_dir().join(&file_name);
+    if state::system_default_versions()?.contains_key(binary.to_str()) && system_path.is_file() {
+        println!("{}", system_path.display());
+        return Ok(());
+    }
+    bail!("{binary} has no default version. Install one with `suiup install {binary}`");
 }
@@ -0,0 +1,169 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io::IsTerminal;
+
+use anyhow::{bail, Error};
+use dialoguer::theme::Theme;
+use dialoguer::{Confirm, Select};
+
+use crate::commands::BinaryName;
+use crate::handlers::release::release_list;
+use crate::handlers::version::extract_version_from_release;
+use crate::output;
+use crate::paths::get_default_bin_dir;
+use crate::standalone::StandaloneInstaller;
+use crate::types::{Network, Release, Repo};
+
+/// Number of versions offered in the version picker
+const MAX_VERSIONS: usize = 20;
+
+/// Guides the user through picking the binary, network and version to install, for
+/// `suiup install` without arguments. Returns the component spec to install, e.g.
+/// `sui@testnet-v1.40.1`, or `None` if the user did not confirm. Fails when prompting is not
+/// possible or disabled with `--yes`.
+pub async fn install_wizard(
+    nightly: bool,
+    yes: bool,
+    github_token: Option<String>,
+) -> Result<Option<String>, Error> {
+    if yes || !std::io::stdin().is_terminal() {
+        bail!("Specify the binary to install, e.g. `suiup install sui@testnet`. Use `suiup list` to find available binaries.");
+    }
+    let theme = output::prompt_theme();
+
+    let binaries = [
+        BinaryName::Sui,
+        BinaryName::Walrus,
+        BinaryName::Mvr,
+        BinaryName::WalrusSites,
+        BinaryName::MoveAnalyzer,
+        BinaryName::SuiNode,
+        BinaryName::SuiTool,
+        BinaryName::SuiFaucet,
+        BinaryName::SuiBridge,
+    ];
+    let binary = &binaries[Select::with_theme(&*theme)
+        .with_prompt("Binary to install")
+        .items(binaries.iter().map(|b| b.to_str()))
+        .default(0)
+        .interact()?];
+
+    // Nightly builds are compiled from a branch, there is no network or version to pick
+    let spec = if nightly {
+        binary.to_string()
+    } else {
+        match binary {
+            BinaryName::Mvr => {
+                let mut installer = StandaloneInstaller::new(Repo::Mvr);
+                installer.get_releases().await?;
+                match pick_version(&*theme, &installer.versions())? {
+                    Some(version) => format!("{binary}@{version}"),
+                    None => binary.to_string(),
+                }
+            }
+            _ => {
+                let (repo, networks) = match binary {
+                    BinaryName::Walrus => (Repo::Walrus, &Network::ALL[..]),
+                    BinaryName::WalrusSites => {
+                        (Repo::WalrusSites, &[Network::Mainnet, Network::Testnet][..])
+                    }
+                    _ => (Repo::Sui, &Network::ALL[..]),
+                };
+                let network = networks[Select::with_theme(&*theme)
+                    .with_prompt("Network")
+                    .items(networks)
+                    .default(0)
+                    .interact()?];
+                let (releases, _) = release_list(&repo, github_token).await?;
+                let versions = versions_for_network(&releases, network.as_str());
+                match pick_version(&*theme, &versions)? {
+                    Some(version) => format!("{binary}@{network}-{version}"),
+                    None => format!("{binary}@{network}"),
+                }
+            }
+        }
+    };
+
+    let confirmed = Confirm::with_theme(&*theme)
+        .with_prompt(format!(
+            "Install {spec} and set it as the default in {}?",
+            get_default_bin_dir().display()
+        ))
+        .default(true)
+        .interact()?;
+    if !confirmed {
+        println!("Installation cancelled.");
+        return Ok(None);
+    }
+    println!("Next time, you can run: suiup install {spec} --yes");
+    Ok(Some(spec))
+}
+
+/// Asks for a version, newest first. Returns `None` for the latest release.
+fn pick_version(theme: &dyn Theme, versions: &[String]) -> Result<Option<String>, Error> {
+    let items = std::iter::once("latest")
+        .chain(versions.iter().take(MAX_VERSIONS).map(String::as_str))
+        .collect::<Vec<_>>();
+    let selected = Select::with_theme(theme)
+        .with_prompt("Version")
+        .items(&items)
+        .default(0)
+        .interact()?;
+    Ok((selected > 0).then(|| items[selected].to_string()))
+}
+
+/// Lists the versions released for a network, in release order, from asset names like
+/// `sui-testnet-v1.40.1-ubuntu-x86_64.tgz`
+fn versions_for_network(releases: &[Release], network: &str) -> Vec<String> {
+    let prefix = format!("-{network}-v");
+    let mut versions = Vec::new();
+    for asset in releases.iter().flat_map(|r| &r.assets) {
+        if !asset.name.contains(&prefix) {
+            continue;
+        }
+        if let Ok(version) = extract_version_from_release(&asset.name) {
+            if !versions.contains(&version) {
+                versions.push(version);
+            }
+        }
+    }
+    versions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Asset;
+
+    #[test]
+    fn test_versions_for_network() {
+        let release = |names: &[&str]| Release {
+            tag_name: None,
+            assets: names
+                .iter()
+                .map(|name| Asset {
+                    browser_download_url: String::new(),
+                    url: None,
+                    name: name.to_string(),
+                    size: 0,
+                    digest: None,
+                })
+                .collect(),
+        };
+        let releases = [
+            release(&[
+                "sui-testnet-v1.41.0-ubuntu-x86_64.tgz",
+                "sui-testnet-v1.41.0-macos-arm64.tgz",
+            ]),
+            release(&["sui-mainnet-v1.40.2-ubuntu-x86_64.tgz"]),
+            release(&["sui-testnet-v1.40.1-ubuntu-x86_64.tgz"]),
+        ];
+        assert_eq!(
+            versions_for_network(&releases, "testnet"),
+            ["v1.41.0", "v1.40.1"]
+        );
+        assert_eq!(versions_for_network(&releases, "mainnet"), ["v1.40.2"]);
+        assert!(versions_for_network(&releases, "devnet").is_empty());
+    }
+}
@@ -0,0 +1,587 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Error};
+use futures_util::{Stream, StreamExt};
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::config::load_config;
+use crate::handlers::suspend_spinner;
+use crate::output;
+use crate::redact::redact;
+use crate::tls;
+
+/// User agent sent with every request. GitHub rejects API requests without one.
+pub const USER_AGENT: &str = "suiup";
+
+/// Base URL of the public GitHub API
+pub const GITHUB_API_URL: &str = "https://api.github.com";
+/// Base URL of the public GitHub web pages and git repositories
+const GITHUB_URL: &str = "https://github.com";
+/// Path of the API on a GitHub Enterprise Server, under its web URL
+const ENTERPRISE_API_PATH: &str = "/api/v3";
+/// Environment variable replacing the URL of the GitHub API, see [`github_api_override`]
+pub const GITHUB_API_URL_ENV: &str = "SUIUP_GITHUB_API_URL";
+
+/// How long to wait for a connection to be established.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long to wait for a read to make progress. Downloads of large archives can take a long
+/// time overall, so this is applied per read rather than to the whole request.
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How many downloads may run at the same time, unless set with `download_concurrency`
+const DEFAULT_DOWNLOAD_CONCURRENCY: usize = 4;
+
+static CLIENT: OnceLock<Client> = OnceLock::new();
+
+/// Limits the number of simultaneous downloads, see [`download_slot`]
+static DOWNLOAD_SLOTS: OnceLock<Arc<Semaphore>> = OnceLock::new();
+
+/// Set by `--limit-rate`, in bytes per second. 0 means unlimited.
+static LIMIT_RATE: AtomicU64 = AtomicU64::new(0);
+/// When the bandwidth used so far allows reading more, shared by all downloads
+static NEXT_READ: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Set by `--offline`, see [`set_offline`]
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Whether the user can be asked what to do after a network failure, see [`set_interactive`]
+static INTERACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Forbids network access for the rest of the process. Installs are then served from the archive
+/// cache, and any request fails when it is created, see [`client`].
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// Fails in offline mode, telling what needed the network, e.g. "download sui-v1.40.1"
+pub fn ensure_online(what: &str) -> Result<(), Error> {
+    if is_offline() {
+        return Err(offline_error(what));
+    }
+    Ok(())
+}
+
+/// Error for something that needs the network in offline mode
+pub fn offline_error(what: &str) -> Error {
+    anyhow!(
+        "Offline mode: would need network access to {what}. Run without `--offline` to allow it."
+    )
+}
+
+/// Lets suiup ask whether to retry after a transient network failure, when `interactive` (not
+/// running with `--yes`) and stdin is a terminal. Otherwise, the `retry` settings of the config
+/// file apply.
+pub fn set_interactive(interactive: bool) {
+    INTERACTIVE.store(
+        interactive && std::io::stdin().is_terminal(),
+        Ordering::Relaxed,
+    );
+}
+
+/// Whether the user can be asked questions, see [`set_interactive`]
+pub fn is_interactive() -> bool {
+    INTERACTIVE.load(Ordering::Relaxed)
+}
+
+/// The user chose to skip something after a network failure. Commands working on several
+/// binaries go on with the next one.
+#[derive(Debug)]
+pub struct Skipped(pub String);
+
+impl std::fmt::Display for Skipped {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Skipped: could not {}", self.0)
+    }
+}
+
+impl std::error::Error for Skipped {}
+
+/// Whether a response status is worth retrying: the server is overloaded or briefly unavailable
+fn is_transient_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+        || matches!(status.as_u16(), 500 | 502 | 503 | 504)
+}
+
+/// Whether an error is a transient network failure: a connection that could not be established
+/// or was lost, a timeout, or a transient response status
+pub fn is_transient(err: &Error) -> bool {
+    err.chain().any(|cause| {
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            return e.is_connect()
+                || e.is_timeout()
+                || e.is_body()
+                || e.status().is_some_and(is_transient_status);
+        }
+        cause.downcast_ref::<std::io::Error>().is_some_and(|e| {
+            matches!(
+                e.kind(),
+                std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::TimedOut
+                    | std::io::ErrorKind::UnexpectedEof
+            )
+        })
+    })
+}
+
+/// Decides what to do after the `attempt`-th try to `what` (e.g. "download sui") failed with a
+/// transient error: returns whether to try again. In an interactive terminal the user is asked to
+/// retry, skip or abort; otherwise it is retried after a delay, as set in the config file.
+pub async fn retry_after_failure(what: &str, err: &Error, attempt: u32) -> Result<bool, Error> {
+    if is_interactive() {
+        let (what, err) = (what.to_string(), redact(&err.to_string()));
+        // Waiting for the answer blocks, keep it off the async runtime
+        let prompt = move || {
+            eprintln!("\n{} Could not {what}: {err}", output::warning_sign());
+            loop {
+                eprint!("[r]etry / [s]kip / [a]bort? ");
+                let _ = std::io::stderr().flush();
+                let mut input = String::new();
+                if std::io::stdin().read_line(&mut input).unwrap_or(0) == 0 {
+                    return Ok(false);
+                }
+                match input.trim().to_lowercase().as_str() {
+                    "" | "r" | "retry" => return Ok(true),
+                    "s" | "skip" => return Err(Error::new(Skipped(what))),
+                    "a" | "abort" => return Ok(false),
+                    _ => continue,
+                }
+            }
+        };
+        return tokio::task::spawn_blocking(move || suspend_spinner(prompt))
+            .await
+            .map_err(|e| anyhow!("Prompt task failed: {e}"))?;
+    }
+    let retry = load_config()?.retry;
+    if attempt >= retry.attempts {
+        return Ok(false);
+    }
+    let delay = Duration::from_millis(retry.delay_ms.saturating_mul(1 << (attempt - 1).min(16)));
+    eprintln!(
+        "Could not {what}: {}. Retrying in {:.1}s ({} of {} attempts)...",
+        redact(&err.to_string()),
+        delay.as_secs_f64(),
+        attempt + 1,
+        retry.attempts
+    );
+    tokio::time::sleep(delay).await;
+    Ok(true)
+}
+
+/// Sends a request, trying again after a transient network failure (see
+/// [`retry_after_failure`]). When the retries run out, the last response or error is returned.
+pub async fn send(request: RequestBuilder, what: &str) -> Result<Response, Error> {
+    let mut attempt = 1;
+    loop {
+        // Requests with a streamed body cannot be sent again
+        let Some(this_attempt) = request.try_clone() else {
+            return Ok(request.send().await?);
+        };
+        let err = match this_attempt.send().await {
+            Ok(response) if is_transient_status(response.status()) => {
+                let err = anyhow!("the server responded {}", response.status());
+                if !retry_after_failure(what, &err, attempt).await? {
+                    return Ok(response);
+                }
+                attempt += 1;
+                continue;
+            }
+            Ok(response) => return Ok(response),
+            Err(e) => Error::from(e),
+        };
+        if !is_transient(&err) || !retry_after_failure(what, &err, attempt).await? {
+            return Err(err);
+        }
+        attempt += 1;
+    }
+}
+
+/// How long to wait for each endpoint probed by [`check_reachability`]
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Probes the endpoints a download needs before starting it (`--check-network`): the GitHub
+/// API, the GitHub downloads and the mirrors set in `sources`. Fails with the first that cannot
+/// be reached, e.g. because of DNS, TLS, a proxy or an error status, instead of failing half
+/// way through a long operation.
+pub async fn check_reachability(github_token: Option<&str>) -> Result<(), Error> {
+    if is_offline() {
+        return Ok(());
+    }
+    let api = github_api_url()?;
+    let response = get(&api, github_token)?
+        .timeout(PROBE_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| Error::new(e).context(format!("Cannot reach the GitHub API at {api}")))?;
+    let rate_limit_remaining = response
+        .headers()
+        .get("x-ratelimit-remaining")
+        .and_then(|remaining| remaining.to_str().ok());
+    if let Some(e) = api_refusal(response.status(), rate_limit_remaining) {
+        return Err(e.context(format!("Cannot use the GitHub API at {api}")));
+    }
+
+    let mut endpoints = vec![github_url()?];
+    for source in load_config()?.sources.values() {
+        if let Some(origin) = origin(&source.url) {
+            if !endpoints.contains(&origin) {
+                endpoints.push(origin);
+            }
+        }
+    }
+    for endpoint in endpoints {
+        // Any response will do, the root of a mirror often has no page
+        client()?
+            .head(&endpoint)
+            .timeout(PROBE_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| Error::new(e).context(format!("Cannot reach {endpoint}")))?;
+    }
+    Ok(())
+}
+
+/// The error the GitHub API would fail the next requests with, from the status of a response
+/// and its `x-ratelimit-remaining` header
+fn api_refusal(status: StatusCode, rate_limit_remaining: Option<&str>) -> Option<Error> {
+    if rate_limit_remaining == Some("0") && matches!(status.as_u16(), 403 | 429) {
+        Some(anyhow!("API rate limit exceeded ({status})"))
+    } else if matches!(status.as_u16(), 401 | 403) || status.is_server_error() {
+        Some(anyhow!("the server responded {status}"))
+    } else {
+        None
+    }
+}
+
+/// The scheme, host and port of `url`, e.g. `https://mirror.example.com:8443/` for a mirror URL
+/// with placeholders in its path
+fn origin(url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(url).ok()?;
+    url.host_str()?;
+    Some(url.origin().ascii_serialization() + "/")
+}
+
+/// Limits the bandwidth of all downloads together to `bytes_per_second` (`--limit-rate`).
+/// 0 removes the limit.
+pub fn set_limit_rate(bytes_per_second: u64) {
+    LIMIT_RATE.store(bytes_per_second, Ordering::Relaxed);
+}
+
+/// The `--limit-rate` bandwidth in bytes per second, if limited
+pub fn limit_rate() -> Option<u64> {
+    Some(LIMIT_RATE.load(Ordering::Relaxed)).filter(|rate| *rate > 0)
+}
+
+/// Parses a rate in bytes per second, with an optional `K`, `M` or `G` suffix (powers of 1024)
+/// as in curl, e.g. `500K` or `5M`
+pub fn parse_rate(rate: &str) -> Result<u64, String> {
+    parse_size(rate).ok_or_else(|| format!("invalid rate `{rate}`, expected e.g. `500K` or `5M`"))
+}
+
+/// Parses a positive number of bytes with an optional `K`, `M` or `G` suffix (powers of 1024),
+/// e.g. `5G`
+pub fn parse_size(size: &str) -> Option<u64> {
+    let size = size.trim();
+    let (number, multiplier) = match size.char_indices().last() {
+        Some((i, 'k' | 'K')) => (&size[..i], 1024),
+        Some((i, 'm' | 'M')) => (&size[..i], 1024 * 1024),
+        Some((i, 'g' | 'G')) => (&size[..i], 1024 * 1024 * 1024),
+        _ => (size, 1),
+    };
+    let number = number
+        .parse::<f64>()
+        .ok()
+        .filter(|n| n.is_finite() && *n > 0.0)?;
+    Some(((number * multiplier as f64) as u64).max(1))
+}
+
+/// Waits until reading `len` more bytes stays within the `--limit-rate` bandwidth
+async fn throttle(len: usize) {
+    let rate = LIMIT_RATE.load(Ordering::Relaxed);
+    if rate == 0 {
+        return;
+    }
+    let wait_until = {
+        let mut next = NEXT_READ.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let start = next.filter(|next| *next > now).unwrap_or(now);
+        let until = start + Duration::from_secs_f64(len as f64 / rate as f64);
+        *next = Some(until);
+        until
+    };
+    tokio::time::sleep_until(wait_until.into()).await;
+}
+
+/// Wraps a download stream so that it is read no faster than the `--limit-rate` bandwidth
+pub fn throttled<S, T, E>(stream: S) -> impl Stream<Item = Result<T, E>>
+where
+    S: Stream<Item = Result<T, E>>,
+    T: AsRef<[u8]>,
+{
+    stream.then(|item| async move {
+        if let Ok(chunk) = &item {
+            throttle(chunk.as_ref().len()).await;
+        }
+        item
+    })
+}
+
+/// Waits for a download slot, so that no more than `download_concurrency` downloads run at the
+/// same time. The slot is released when the returned permit is dropped.
+pub async fn download_slot() -> Result<OwnedSemaphorePermit, Error> {
+    let slots = match DOWNLOAD_SLOTS.get() {
+        Some(slots) => slots,
+        None => {
+            let concurrency = load_config()?
+                .download_concurrency
+                .unwrap_or(DEFAULT_DOWNLOAD_CONCURRENCY)
+                .max(1);
+            DOWNLOAD_SLOTS.get_or_init(|| Arc::new(Semaphore::new(concurrency)))
+        }
+    };
+    slots
+        .clone()
+        .acquire_owned()
+        .await
+        .map_err(|e| anyhow!("Cannot start the download: {e}"))
+}
+
+/// Builds the HTTP client used for all requests.
+///
+/// Proxies are read from the standard `HTTP_PROXY`, `HTTPS_PROXY`, `ALL_PROXY` and `NO_PROXY`
+/// environment variables. TLS settings (minimum version, extra CA certificates and pinned
+/// certificates) come from the `tls` section of the config file.
+fn build_client() -> Result<Client, Error> {
+    let mut builder = Client::builder()
+        .user_agent(USER_AGENT)
+        .connect_timeout(CONNECT_TIMEOUT)
+        .read_timeout(READ_TIMEOUT);
+
+    let config = load_config()?;
+    if config.tls.is_customized() {
+        builder = builder.use_preconfigured_tls(tls::client_config(&config.tls)?);
+    }
+
+    builder
+        .build()
+        .map_err(|e| anyhow!("Cannot build the HTTP client: {e}"))
+}
+
+/// Returns the shared HTTP client. Connections are pooled and reused between requests, so
+/// this should be used instead of creating a new `reqwest::Client`. Fails in offline mode, so
+/// that no request goes out whatever needs it.
+pub fn client() -> Result<&'static Client, Error> {
+    ensure_online("send a request")?;
+    if let Some(client) = CLIENT.get() {
+        return Ok(client);
+    }
+    let client = build_client()?;
+    Ok(CLIENT.get_or_init(|| client))
+}
+
+/// Creates a GET request for the given URL using the shared client. The GitHub token is only
+/// attached when the request goes to GitHub, so it is never sent to third party hosts (e.g.,
+/// walrus releases are downloaded from Google Cloud Storage).
+pub fn get(url: &str, github_token: Option<&str>) -> Result<RequestBuilder, Error> {
+    ensure_online(&format!("fetch {}", redact(url)))?;
+    let request = client()?.get(url);
+    Ok(match github_token {
+        Some(token) if is_github_url(url)? => {
+            request.header("Authorization", format!("token {}", token))
+        }
+        _ => request,
+    })
+}
+
+/// Base URL of the GitHub API: the one set with `SUIUP_GITHUB_API_URL`, the one of the GitHub
+/// Enterprise Server set with `github_api_base` in the config file, or the public one
+pub fn github_api_url() -> Result<String, Error> {
+    if let Some(url) = github_api_override() {
+        return Ok(url);
+    }
+    Ok(match load_config()?.github_api_base {
+        Some(base) => api_url(&base),
+        None => GITHUB_API_URL.to_string(),
+    })
+}
+
+/// The URL used as is for every GitHub API request, e.g. a mock server in tests, set with
+/// `SUIUP_GITHUB_API_URL`
+pub fn github_api_override() -> Option<String> {
+    std::env::var(GITHUB_API_URL_ENV)
+        .ok()
+        .filter(|url| !url.is_empty())
+        .map(|url| url.trim_end_matches('/').to_string())
+}
+
+/// Base URL of the GitHub web pages and git repositories matching [`github_api_url`]
+pub fn github_url() -> Result<String, Error> {
+    Ok(web_url(&github_api_url()?))
+}
+
+/// Whether the GitHub API is the one of a GitHub Enterprise Server
+pub fn is_github_enterprise() -> Result<bool, Error> {
+    Ok(github_api_url()? != GITHUB_API_URL)
+}
+
+/// The API URL of `base`. The API of a GitHub Enterprise Server is under `/api/v3`, which may be
+/// left out, e.g. `https://github.example.com`.
+fn api_url(base: &str) -> String {
+    let base = base.trim_end_matches('/');
+    if base == GITHUB_API_URL || base.ends_with(ENTERPRISE_API_PATH) {
+        base.to_string()
+    } else {
+        format!("{base}{ENTERPRISE_API_PATH}")
+    }
+}
+
+fn web_url(api_url: &str) -> String {
+    match api_url.strip_suffix(ENTERPRISE_API_PATH) {
+        Some(web_url) => web_url.to_string(),
+        None => GITHUB_URL.to_string(),
+    }
+}
+
+/// Checks if the URL points to GitHub or its API, or to the GitHub Enterprise Server if one is
+/// configured, in which case the token is only sent there.
+fn is_github_url(url: &str) -> Result<bool, Error> {
+    Ok(is_github_host(url, &github_api_url()?))
+}
+
+/// Whether `url` is on the host of the GitHub instance whose API is at `api_url`, or on one of
+/// its subdomains
+fn is_github_host(url: &str, api_url: &str) -> bool {
+    let web_url = web_url(api_url);
+    let (host, github) = (host(url), host(&web_url));
+    host == github || host.ends_with(&format!(".{github}"))
+}
+
+fn host(url: &str) -> &str {
+    url.split("://")
+        .nth(1)
+        .unwrap_or(url)
+        .split(['/', ':'])
+        .next()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_github_url() {
+        // Not `is_github_url`, which depends on `SUIUP_GITHUB_API_URL` and the config file
+        let is_github_url = |url| is_github_host(url, GITHUB_API_URL);
+        assert!(is_github_url(
+            "https://api.github.com/repos/MystenLabs/sui/releases"
+        ));
+        assert!(is_github_url(
+            "https://github.com/MystenLabs/suiup/releases/download/v0.0.4/suiup.tar.gz"
+        ));
+        assert!(!is_github_url(
+            "https://storage.googleapis.com/mysten-walrus-binaries/walrus"
+        ));
+        assert!(!is_github_url("https://github.com.example.org/file"));
+    }
+
+    #[test]
+    fn test_github_enterprise() {
+        for base in [
+            "https://github.example.com",
+            "https://github.example.com/",
+            "https://github.example.com/api/v3",
+        ] {
+            assert_eq!(api_url(base), "https://github.example.com/api/v3");
+        }
+        assert_eq!(api_url(GITHUB_API_URL), GITHUB_API_URL);
+        assert_eq!(
+            web_url("https://github.example.com/api/v3"),
+            "https://github.example.com"
+        );
+        assert_eq!(web_url(GITHUB_API_URL), GITHUB_URL);
+
+        let enterprise = "https://github.example.com/api/v3";
+        assert!(is_github_host(
+            "https://github.example.com/api/v3/repos/MystenLabs/sui/releases",
+            enterprise
+        ));
+        assert!(is_github_host(
+            "https://media.github.example.com/file",
+            enterprise
+        ));
+        assert!(!is_github_host(
+            "https://api.github.com/repos/MystenLabs/suiup/releases/latest",
+            enterprise
+        ));
+    }
+
+    #[test]
+    fn test_parse_rate() {
+        assert_eq!(parse_rate("1000"), Ok(1000));
+        assert_eq!(parse_rate("500K"), Ok(500 * 1024));
+        assert_eq!(parse_rate("5M"), Ok(5 * 1024 * 1024));
+        assert_eq!(parse_rate("1.5m"), Ok(1536 * 1024));
+        assert_eq!(parse_rate("1G"), Ok(1024 * 1024 * 1024));
+        assert!(parse_rate("0").is_err());
+        assert!(parse_rate("fast").is_err());
+        assert!(parse_rate("M").is_err());
+    }
+
+    #[test]
+    fn test_api_refusal() {
+        assert!(api_refusal(StatusCode::OK, Some("59")).is_none());
+        // The root of a mock or Enterprise API may have no page
+        assert!(api_refusal(StatusCode::NOT_FOUND, None).is_none());
+        let message = |status, remaining| api_refusal(status, remaining).unwrap().to_string();
+        assert_eq!(
+            message(StatusCode::FORBIDDEN, Some("0")),
+            "API rate limit exceeded (403 Forbidden)"
+        );
+        assert_eq!(
+            message(StatusCode::UNAUTHORIZED, Some("59")),
+            "the server responded 401 Unauthorized"
+        );
+        assert_eq!(
+            message(StatusCode::BAD_GATEWAY, None),
+            "the server responded 502 Bad Gateway"
+        );
+    }
+
+    #[test]
+    fn test_origin() {
+        assert_eq!(
+            origin("https://mirror.example.com:8443/sui/{version}/{asset}").as_deref(),
+            Some("https://mirror.example.com:8443/")
+        );
+        assert_eq!(
+            origin("https://storage.googleapis.com/mysten-walrus-binaries/").as_deref(),
+            Some("https://storage.googleapis.com/")
+        );
+        assert_eq!(origin("file:///srv/mirror/{asset}"), None);
+        assert_eq!(origin("not a url"), None);
+    }
+
+    #[test]
+    fn test_is_transient() {
+        assert!(is_transient_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(is_transient_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_transient_status(StatusCode::NOT_FOUND));
+        let reset = std::io::Error::from(std::io::ErrorKind::ConnectionReset);
+        assert!(is_transient(&Error::new(reset).context("Cannot download")));
+        assert!(!is_transient(&anyhow::anyhow!("Invalid archive")));
+        assert!(!is_transient(&Error::new(Skipped("download".into()))));
+    }
+}
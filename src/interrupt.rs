@@ -0,0 +1,138 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Ctrl-C handling. Files and folders being written when suiup is interrupted, like a download
+//! or an extracted binary, are removed rather than left half written for the next install to
+//! trip over.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, Once};
+
+/// Exit code of a process interrupted by Ctrl-C, as shells report it
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// Paths of the [`Partial`] files being written
+static PARTIAL: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+static HANDLER: Once = Once::new();
+/// Set while a program run by suiup waits for Ctrl-C, see [`ignore_interrupts`]
+static IGNORE: AtomicBool = AtomicBool::new(false);
+
+/// A file or folder being written. It is removed if suiup is interrupted, or if the guard is
+/// dropped before [`Partial::complete`], e.g. on an error.
+#[derive(Debug)]
+pub struct Partial {
+    path: PathBuf,
+    complete: bool,
+}
+
+impl Partial {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        install_handler();
+        if let Ok(mut partial) = PARTIAL.lock() {
+            partial.push(path.clone());
+        }
+        Partial {
+            path,
+            complete: false,
+        }
+    }
+
+    /// Keeps the file, now that it is written
+    pub fn complete(mut self) {
+        self.complete = true;
+    }
+}
+
+impl Drop for Partial {
+    fn drop(&mut self) {
+        if let Ok(mut partial) = PARTIAL.lock() {
+            if let Some(i) = partial.iter().position(|p| *p == self.path) {
+                partial.swap_remove(i);
+            }
+        }
+        if !self.complete {
+            remove(&self.path);
+        }
+    }
+}
+
+/// Runs `f`, e.g. waiting on a program run by suiup, without exiting on Ctrl-C: the program
+/// gets it and decides whether to stop, and its exit code is what suiup reports
+pub fn ignore_interrupts<T>(f: impl FnOnce() -> T) -> T {
+    install_handler();
+    IGNORE.store(true, Ordering::SeqCst);
+    let result = f();
+    IGNORE.store(false, Ordering::SeqCst);
+    result
+}
+
+/// Handles Ctrl-C from the first [`Partial`] on, so that commands that never write files, and
+/// the programs they run, keep the default behavior
+fn install_handler() {
+    HANDLER.call_once(|| {
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        runtime.spawn(async {
+            loop {
+                if tokio::signal::ctrl_c().await.is_err() {
+                    return;
+                }
+                if !IGNORE.load(Ordering::SeqCst) {
+                    break;
+                }
+            }
+            let partial = PARTIAL
+                .lock()
+                .map(|mut partial| std::mem::take(&mut *partial))
+                .unwrap_or_default();
+            for path in &partial {
+                remove(path);
+            }
+            eprintln!();
+            match partial.len() {
+                0 => eprintln!("Interrupted"),
+                1 => eprintln!(
+                    "Interrupted, removed the partial file {}",
+                    partial[0].display()
+                ),
+                n => eprintln!("Interrupted, removed {n} partial files"),
+            }
+            std::process::exit(INTERRUPTED_EXIT_CODE);
+        });
+    });
+}
+
+fn remove(path: &Path) {
+    let _ = if path.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial() {
+        let dir = tempfile::tempdir().unwrap();
+        let failed = dir.path().join("failed");
+        let written = dir.path().join("written");
+        std::fs::write(&failed, "partial").unwrap();
+        std::fs::write(&written, "complete").unwrap();
+
+        drop(Partial::new(&failed));
+        Partial::new(&written).complete();
+        assert!(!failed.exists());
+        assert!(written.exists());
+        assert!(PARTIAL
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|p| !p.starts_with(dir.path())));
+    }
+}
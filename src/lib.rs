@@ -3,8 +3,20 @@
 
 pub mod commands;
 pub mod component;
+pub mod config;
+pub mod credentials;
 pub mod handle_commands;
 pub mod handlers;
+pub mod http;
+pub mod interrupt;
+pub mod output;
 pub mod paths;
+pub mod platform;
+pub mod redact;
+pub mod reflink;
 pub mod standalone;
+pub mod state;
+pub mod suggest;
+pub mod timings;
+pub mod tls;
 pub mod types;
@@ -1,20 +1,104 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use clap::Parser;
-use suiup::commands::Command;
-use suiup::paths::initialize;
+use std::io::Write;
+
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
+use clap_complete::CompleteEnv;
+use suiup::commands::{expand_release_shorthand, Command, ErrorFormat};
+use suiup::config::set_config_override;
+use suiup::paths::{initialize, set_portable_mode, set_system_mode};
+use suiup::redact::{redact, register_secret};
+use suiup::suggest::{causes, hint, ErrorReport};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    env_logger::init();
-    initialize()?;
+    // Completes the command line when the shell asks for it, see `COMPLETE=bash suiup`
+    CompleteEnv::with_factory(Command::command).complete();
+
+    // Logs can contain request URLs and headers, so they go through the redaction as well
+    env_logger::Builder::from_default_env()
+        .format(|buf, record| {
+            writeln!(
+                buf,
+                "[{} {} {}] {}",
+                buf.timestamp(),
+                record.level(),
+                record.target(),
+                redact(&record.args().to_string())
+            )
+        })
+        .init();
+
+    if let Ok(token) = std::env::var("GITHUB_TOKEN") {
+        register_secret(&token);
+    }
+    let args = expand_release_shorthand(std::env::args_os().collect());
+    let cmd = match Command::try_parse_from(&args) {
+        Ok(cmd) => cmd,
+        Err(err)
+            if ErrorFormat::from_raw_args(&args) == ErrorFormat::Json
+                && !matches!(
+                    err.kind(),
+                    ErrorKind::DisplayHelp
+                        | ErrorKind::DisplayVersion
+                        | ErrorKind::DisplayHelpOnMissingArgumentOrSubcommand
+                ) =>
+        {
+            let message = err.to_string();
+            let message = message.lines().next().unwrap_or_default();
+            print_json_error(&ErrorReport::usage(
+                message.strip_prefix("error: ").unwrap_or(message),
+            ));
+            std::process::exit(2);
+        }
+        Err(err) => err.exit(),
+    };
+    if let Some(token) = &cmd.github_token {
+        register_secret(token);
+    }
+    set_system_mode(cmd.system);
+    set_portable_mode(cmd.portable);
+    set_config_override(cmd.config.clone());
+    if let Err(err) = initialize() {
+        exit_with_error(err, cmd.error_format, cmd.verbose);
+    }
 
-    let cmd = Command::parse();
     if let Err(err) = cmd.exec().await {
-        eprintln!("Error: {}", err);
-        std::process::exit(1);
+        exit_with_error(err, cmd.error_format, cmd.verbose);
     }
 
     Ok(())
 }
+
+fn exit_with_error(err: anyhow::Error, format: ErrorFormat, verbose: bool) -> ! {
+    match format {
+        ErrorFormat::Human => {
+            eprintln!("Error: {}", redact(&err.to_string()));
+            let causes = causes(&err);
+            if verbose {
+                if !causes.is_empty() {
+                    eprintln!("\nCaused by:");
+                }
+                for cause in &causes {
+                    eprintln!("    {cause}");
+                }
+            } else if !causes.is_empty() {
+                eprintln!("Run with `-v` to see the underlying error.");
+            }
+            if let Some(hint) = hint(&err) {
+                eprintln!("Hint: {hint}");
+            }
+        }
+        ErrorFormat::Json => print_json_error(&ErrorReport::new(&err)),
+    }
+    std::process::exit(1);
+}
+
+fn print_json_error(report: &ErrorReport) {
+    match serde_json::to_string(report) {
+        Ok(json) => eprintln!("{json}"),
+        Err(_) => eprintln!("Error: {}", report.message),
+    }
+}
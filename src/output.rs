@@ -0,0 +1,166 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Status messages, which `--quiet` turns off so that scripts only get the errors and the data
+//! they asked for, and progress, shown with progress bars or, with `--progress json`, as JSON
+//! lines on stderr for programs running suiup. `--plain` restricts the output to ASCII.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::Serialize;
+
+/// Set by `--quiet`, see [`set_quiet`]
+static QUIET: AtomicBool = AtomicBool::new(false);
+/// Set by `--progress json`, see [`set_json_progress`]
+static JSON_PROGRESS: AtomicBool = AtomicBool::new(false);
+/// Set by `--plain`, see [`set_plain`]
+static PLAIN: AtomicBool = AtomicBool::new(false);
+
+/// A progress event printed with `--progress json`, e.g.
+/// `{"phase":"download","message":"sui-testnet-v1.40.1-ubuntu-x86_64.tgz","bytes":1048576,"total_bytes":4194304,"percent":25}`
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ProgressEvent<'a> {
+    /// What suiup is doing, e.g. `fetch`, `download`, `extract` or `build`
+    pub phase: &'a str,
+    pub message: &'a str,
+    /// Bytes downloaded so far
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<u64>,
+    /// Size of the download, when the server tells it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percent: Option<u64>,
+}
+
+impl<'a> ProgressEvent<'a> {
+    pub fn new(phase: &'a str, message: &'a str) -> Self {
+        ProgressEvent {
+            phase,
+            message,
+            bytes: None,
+            total_bytes: None,
+            percent: None,
+        }
+    }
+
+    /// Progress of a download, `total` being 0 when the size is unknown
+    pub fn bytes(mut self, bytes: u64, total: u64) -> Self {
+        self.bytes = Some(bytes);
+        if total > 0 {
+            self.total_bytes = Some(total);
+            self.percent = Some(bytes.min(total) * 100 / total);
+        }
+        self
+    }
+
+    /// Progress of `done` out of `total` steps, e.g. files
+    pub fn steps(mut self, done: u64, total: u64) -> Self {
+        self.percent = (total > 0).then(|| done.min(total) * 100 / total);
+        self
+    }
+
+    /// Prints the event on stderr with `--progress json`
+    pub fn emit(&self) {
+        if is_json_progress() {
+            if let Ok(line) = serde_json::to_string(self) {
+                eprintln!("{line}");
+            }
+        }
+    }
+}
+
+/// Turns off the status messages, progress bars and spinners for the rest of the process.
+/// Warnings and errors are still printed.
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Prints ASCII only for the rest of the process: symbols are replaced with words or ASCII
+/// (see [`symbol`]), tables are drawn with `-` and `=`, and colors are turned off
+pub fn set_plain(plain: bool) {
+    PLAIN.store(plain, Ordering::Relaxed);
+    if plain {
+        colored::control::set_override(false);
+        console::set_colors_enabled(false);
+        console::set_colors_enabled_stderr(false);
+    }
+}
+
+pub fn is_plain() -> bool {
+    PLAIN.load(Ordering::Relaxed)
+}
+
+/// `fancy`, or its ASCII replacement `plain` with `--plain`
+pub fn symbol(fancy: &'static str, plain: &'static str) -> &'static str {
+    if is_plain() {
+        plain
+    } else {
+        fancy
+    }
+}
+
+/// Prefix of warnings, followed by a space: `⚠️ ` or `Warning:` with `--plain`
+pub fn warning_sign() -> &'static str {
+    symbol("⚠️ ", "Warning:")
+}
+
+/// Theme of the interactive prompts, without symbols or colors with `--plain`
+pub fn prompt_theme() -> Box<dyn dialoguer::theme::Theme> {
+    if is_plain() {
+        Box::new(dialoguer::theme::SimpleTheme)
+    } else {
+        Box::new(dialoguer::theme::ColorfulTheme::default())
+    }
+}
+
+/// Reports progress with [`ProgressEvent`]s instead of progress bars for the rest of the process
+pub fn set_json_progress(json: bool) {
+    JSON_PROGRESS.store(json, Ordering::Relaxed);
+}
+
+pub fn is_json_progress() -> bool {
+    JSON_PROGRESS.load(Ordering::Relaxed)
+}
+
+/// Whether progress bars and spinners are hidden, with `--quiet` or `--progress json`
+pub fn hide_progress_bars() -> bool {
+    is_quiet() || is_json_progress()
+}
+
+/// Prints a status message like `println!`, unless `--quiet` was passed, e.g. download and
+/// install progress or a success message. The data a command is asked for (tables, paths,
+/// reports) is printed with `println!`.
+#[macro_export]
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if !$crate::output::is_quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_event() {
+        let event = ProgressEvent::new("download", "sui.tgz").bytes(1024, 4096);
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"phase":"download","message":"sui.tgz","bytes":1024,"total_bytes":4096,"percent":25}"#
+        );
+        let event = ProgressEvent::new("download", "walrus").bytes(1024, 0);
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"phase":"download","message":"walrus","bytes":1024}"#
+        );
+        let event = ProgressEvent::new("hash", "Hashing binaries").steps(1, 3);
+        assert_eq!(event.percent, Some(33));
+    }
+}
@@ -1,14 +1,18 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::Error;
+use anyhow::{anyhow, bail, Error};
 use std::collections::BTreeMap;
 use std::env;
 use std::fs::{create_dir_all, File};
-use std::io::Write;
-use std::path::PathBuf;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
 
 use crate::handlers::RELEASES_ARCHIVES_FOLDER;
+use crate::state::create_file_atomically;
+use crate::state::migrations::SCHEMA_VERSION;
 use crate::types::InstalledBinaries;
 
 #[cfg(not(windows))]
@@ -18,8 +22,186 @@ const XDG_CONFIG_HOME: &str = "XDG_CONFIG_HOME";
 #[cfg(not(windows))]
 const XDG_CACHE_HOME: &str = "XDG_CACHE_HOME";
 #[cfg(not(windows))]
+const XDG_STATE_HOME: &str = "XDG_STATE_HOME";
+#[cfg(not(windows))]
 const HOME: &str = "HOME";
 
+pub const INSTALLED_BINARIES_FILE: &str = "installed_binaries.json";
+pub const DEFAULT_VERSIONS_FILE: &str = "default_version.json";
+pub const DISABLED_BINARIES_FILE: &str = "disabled_binaries.json";
+/// Left in the config folder once the state files are moved out of it
+const STATE_MOVED_FILE: &str = "STATE_MOVED.txt";
+
+/// Folder next to the suiup executable used in portable mode
+const PORTABLE_FOLDER: &str = "suiup-portable";
+
+/// Folder holding everything for the user's installation, instead of the XDG or Windows folders
+pub(crate) const SUIUP_HOME: &str = "SUIUP_HOME";
+
+/// Overrides the root of the system-wide installation
+pub(crate) const SUIUP_SYSTEM_ROOT: &str = "SUIUP_SYSTEM_ROOT";
+
+/// Overrides the folder the default binaries are copied to
+pub(crate) const SUIUP_DEFAULT_BIN_DIR: &str = "SUIUP_DEFAULT_BIN_DIR";
+
+#[cfg(not(windows))]
+const ELEVATION_HINT: &str = "Re-run the command with sudo, e.g. `sudo suiup --system ...`";
+#[cfg(windows)]
+const ELEVATION_HINT: &str = "Re-run the command from a terminal opened as Administrator";
+
+/// Whether suiup manages the system-wide installation instead of the user's one
+static SYSTEM_MODE: AtomicBool = AtomicBool::new(false);
+/// Whether everything lives in a folder next to the suiup executable
+static PORTABLE_MODE: AtomicBool = AtomicBool::new(false);
+/// Temporary installation used by `try`
+static SANDBOX: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Switches all paths to the system-wide installation (`--system`)
+pub fn set_system_mode(system: bool) {
+    SYSTEM_MODE.store(system, Ordering::Relaxed);
+}
+
+pub fn is_system_mode() -> bool {
+    SYSTEM_MODE.load(Ordering::Relaxed)
+}
+
+/// Switches all paths to the folder next to the suiup executable (`--portable`)
+pub fn set_portable_mode(portable: bool) {
+    PORTABLE_MODE.store(portable, Ordering::Relaxed);
+}
+
+pub fn is_portable_mode() -> bool {
+    PORTABLE_MODE.load(Ordering::Relaxed)
+}
+
+/// Returns the folder holding the config, state, caches and binaries in portable mode: a
+/// `suiup-portable` folder next to the suiup executable, symlinks resolved
+pub fn get_portable_root() -> PathBuf {
+    let exe = env::current_exe()
+        .and_then(|exe| exe.canonicalize())
+        .unwrap_or_default();
+    exe.parent().unwrap_or(Path::new(".")).join(PORTABLE_FOLDER)
+}
+
+/// Installs binaries into `dir` instead of the user's installation, or back into it with `None`.
+/// The config and the caches of the user's installation are still used.
+pub fn set_sandbox(dir: Option<PathBuf>) {
+    if let Ok(mut sandbox) = SANDBOX.write() {
+        *sandbox = dir;
+    }
+}
+
+pub fn get_sandbox() -> Option<PathBuf> {
+    SANDBOX.read().ok()?.clone()
+}
+
+/// Returns the single folder holding the config, state, caches and binaries of the user's
+/// installation, if there is one: the portable folder with `--portable`, otherwise the one
+/// `SUIUP_HOME` points to. A relative `SUIUP_HOME` is taken from the current folder.
+pub fn get_suiup_home() -> Option<PathBuf> {
+    if is_system_mode() {
+        return None;
+    }
+    if is_portable_mode() {
+        return Some(get_portable_root());
+    }
+    let home = env::var_os(SUIUP_HOME).filter(|home| !home.is_empty())?;
+    Some(std::path::absolute(&home).unwrap_or_else(|_| PathBuf::from(home)))
+}
+
+/// Returns the root of the system-wide installation: `/usr/local` on Unix and
+/// `%ProgramData%\suiup` on Windows.
+pub fn get_system_root() -> PathBuf {
+    if let Some(root) = env::var_os(SUIUP_SYSTEM_ROOT) {
+        return PathBuf::from(root);
+    }
+
+    #[cfg(windows)]
+    {
+        let program_data = env::var_os("ProgramData").unwrap_or_else(|| "C:\\ProgramData".into());
+        PathBuf::from(program_data).join("suiup")
+    }
+
+    #[cfg(not(windows))]
+    {
+        PathBuf::from("/usr/local")
+    }
+}
+
+/// Returns a folder of the system-wide installation. On Unix the folders follow the usual
+/// `/usr/local` layout, on Windows they all live in the suiup folder.
+#[allow(unused_variables)]
+fn system_dir(unix: &[&str], windows: &str) -> PathBuf {
+    #[cfg(windows)]
+    {
+        get_system_root().join(windows)
+    }
+
+    #[cfg(not(windows))]
+    {
+        unix.iter()
+            .fold(get_system_root(), |path, part| path.join(part))
+    }
+}
+
+pub fn get_system_data_dir() -> PathBuf {
+    system_dir(&["share", "suiup"], "data")
+}
+
+pub fn get_system_config_dir() -> PathBuf {
+    system_dir(&["etc", "suiup"], "config")
+}
+
+pub fn get_system_cache_dir() -> PathBuf {
+    system_dir(&["var", "cache", "suiup"], "cache")
+}
+
+pub fn get_system_bin_dir() -> PathBuf {
+    system_dir(&["bin"], "bin")
+}
+
+/// Returns the binaries folder of the system-wide installation
+pub fn system_binaries_dir() -> PathBuf {
+    get_system_data_dir().join("binaries")
+}
+
+/// Returns the path to the installed binaries file of the system-wide installation. Unlike
+/// [`installed_binaries_file`], the file is not created if it does not exist.
+pub fn system_installed_binaries_file() -> PathBuf {
+    get_system_config_dir().join(INSTALLED_BINARIES_FILE)
+}
+
+/// Returns the path to the default versions file of the system-wide installation, which is not
+/// created if it does not exist either
+pub fn system_default_file() -> PathBuf {
+    get_system_config_dir().join(DEFAULT_VERSIONS_FILE)
+}
+
+/// Checks that the folders of the system-wide installation (state, binaries, cache and default
+/// binaries) can be modified by the current user, so that commands fail early with a hint about
+/// elevating privileges instead of half way through.
+pub fn ensure_system_root_writable() -> Result<(), Error> {
+    for dir in [
+        get_suiup_state_dir(),
+        binaries_dir(),
+        get_suiup_cache_dir(),
+        get_default_bin_dir(),
+    ] {
+        let probe = dir.join(".suiup-write-test");
+        match File::create(&probe) {
+            Ok(_) => {
+                let _ = std::fs::remove_file(&probe);
+            }
+            Err(e) if e.kind() == ErrorKind::PermissionDenied => bail!(
+                "Cannot modify the system-wide installation in {}. {ELEVATION_HINT}",
+                dir.display()
+            ),
+            Err(e) => bail!("Cannot write to {}: {e}", probe.display()),
+        }
+    }
+    Ok(())
+}
+
 pub fn get_data_home() -> PathBuf {
     #[cfg(windows)]
     {
@@ -73,6 +255,27 @@ pub fn get_config_home() -> PathBuf {
     }
 }
 
+/// Returns the folder of files written by suiup that are neither settings nor caches. On Windows
+/// it is the same as the data folder.
+pub fn get_state_home() -> PathBuf {
+    #[cfg(windows)]
+    {
+        get_data_home()
+    }
+
+    #[cfg(not(windows))]
+    {
+        env::var_os(XDG_STATE_HOME)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| {
+                let mut home = PathBuf::from(env::var_os(HOME).expect("HOME not set"));
+                home.push(".local");
+                home.push("state");
+                home
+            })
+    }
+}
+
 pub fn get_cache_home() -> PathBuf {
     #[cfg(windows)]
     {
@@ -98,18 +301,64 @@ pub fn get_cache_home() -> PathBuf {
 }
 
 pub fn get_suiup_data_dir() -> PathBuf {
+    if let Some(sandbox) = get_sandbox() {
+        return sandbox.join("data");
+    }
+    if let Some(home) = get_suiup_home() {
+        return home.join("data");
+    }
+    if is_system_mode() {
+        return get_system_data_dir();
+    }
     get_data_home().join("suiup")
 }
 
 pub fn get_suiup_config_dir() -> PathBuf {
+    if let Some(home) = get_suiup_home() {
+        return home.join("config");
+    }
+    if is_system_mode() {
+        return get_system_config_dir();
+    }
     get_config_home().join("suiup")
 }
 
+/// Returns the folder of the installed binaries and default versions files. The system-wide
+/// installation keeps them in its config folder, where every user reads them from.
+pub fn get_suiup_state_dir() -> PathBuf {
+    if let Some(sandbox) = get_sandbox() {
+        return sandbox.join("state");
+    }
+    if let Some(home) = get_suiup_home() {
+        return home.join("state");
+    }
+    if is_system_mode() {
+        return get_system_config_dir();
+    }
+    get_state_home().join("suiup")
+}
+
 pub fn get_suiup_cache_dir() -> PathBuf {
+    if let Some(home) = get_suiup_home() {
+        return home.join("cache");
+    }
+    if is_system_mode() {
+        return get_system_cache_dir();
+    }
     get_cache_home().join("suiup")
 }
 
 pub fn get_default_bin_dir() -> PathBuf {
+    if let Some(sandbox) = get_sandbox() {
+        return sandbox.join("bin");
+    }
+    if let Some(home) = get_suiup_home() {
+        return home.join("bin");
+    }
+    if is_system_mode() {
+        return get_system_bin_dir();
+    }
+
     #[cfg(windows)]
     {
         let mut path = PathBuf::from(env::var_os("LOCALAPPDATA").expect("LOCALAPPDATA not set"));
@@ -122,7 +371,7 @@ pub fn get_default_bin_dir() -> PathBuf {
 
     #[cfg(not(windows))]
     {
-        env::var_os("SUIUP_DEFAULT_BIN_DIR")
+        env::var_os(SUIUP_DEFAULT_BIN_DIR)
             .map(PathBuf::from)
             .unwrap_or_else(|| {
                 let mut path = PathBuf::from(env::var_os(HOME).expect("HOME not set"));
@@ -137,21 +386,27 @@ pub fn get_config_file(name: &str) -> PathBuf {
     get_suiup_config_dir().join(name)
 }
 
+pub fn get_state_file(name: &str) -> PathBuf {
+    get_suiup_state_dir().join(name)
+}
+
 /// Returns the path to the default version file
 pub fn default_file_path() -> Result<PathBuf, Error> {
-    let path = get_config_file("default_version.json");
+    let path = get_state_file(DEFAULT_VERSIONS_FILE);
     if !path.exists() {
-        let mut file = File::create(&path)?;
-        let default = BTreeMap::<String, (String, String)>::new();
+        let default = serde_json::json!({
+            "schema_version": SCHEMA_VERSION,
+            "binaries": BTreeMap::<String, (String, String, bool)>::new(),
+        });
         let default_str = serde_json::to_string_pretty(&default)?;
-        file.write_all(default_str.as_bytes())?;
+        create_file_atomically(&path, default_str.as_bytes())?;
     }
     Ok(path)
 }
 
 /// Returns the path to the installed binaries file
 pub fn installed_binaries_file() -> Result<PathBuf, Error> {
-    let path = get_config_file("installed_binaries.json");
+    let path = get_state_file(INSTALLED_BINARIES_FILE);
     if !path.exists() {
         // We'll need to adjust this reference after moving more code
         InstalledBinaries::create_file(&path)?;
@@ -169,7 +424,21 @@ pub fn binaries_dir() -> PathBuf {
 }
 
 pub fn initialize() -> Result<(), Error> {
+    if is_system_mode() {
+        return initialize_dirs().map_err(|e| {
+            anyhow!(
+                "Cannot set up the system-wide installation in {}: {e}. {ELEVATION_HINT}",
+                get_system_root().display()
+            )
+        });
+    }
+    initialize_dirs()
+}
+
+fn initialize_dirs() -> Result<(), Error> {
     create_dir_all(get_suiup_config_dir())?;
+    create_dir_all(get_suiup_state_dir())?;
+    move_state_files()?;
     create_dir_all(get_suiup_data_dir())?;
     create_dir_all(get_suiup_cache_dir())?;
     create_dir_all(binaries_dir())?;
@@ -179,3 +448,58 @@ pub fn initialize() -> Result<(), Error> {
     installed_binaries_file()?;
     Ok(())
 }
+
+/// Moves the installed binaries and default versions files, and their backups, from the config
+/// folder where older versions of suiup kept them to the state folder. A note is left behind
+/// pointing to their new location. Files already in the state folder are never overwritten.
+fn move_state_files() -> Result<(), Error> {
+    let (from, to) = (get_suiup_config_dir(), get_suiup_state_dir());
+    if from == to {
+        return Ok(());
+    }
+    let mut moved = Vec::new();
+    for entry in std::fs::read_dir(&from)? {
+        let name = entry?.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        let is_state = [INSTALLED_BINARIES_FILE, DEFAULT_VERSIONS_FILE]
+            .iter()
+            .any(|file| name == *file || name.starts_with(&format!("{file}.")));
+        let dst = to.join(name);
+        if !is_state || dst.exists() {
+            continue;
+        }
+        move_file(&from.join(name), &dst)
+            .map_err(|e| anyhow!("Cannot move {name} to {}: {e}", to.display()))?;
+        moved.push(name.to_string());
+    }
+    if moved.is_empty() {
+        return Ok(());
+    }
+
+    moved.sort();
+    std::fs::write(
+        from.join(STATE_MOVED_FILE),
+        format!(
+            "suiup now keeps these files in {}:\n{}\n",
+            to.display(),
+            moved.join("\n")
+        ),
+    )?;
+    eprintln!(
+        "Moved the installed binaries and default versions from {} to {}",
+        from.display(),
+        to.display()
+    );
+    Ok(())
+}
+
+/// Renames a file, copying it if the destination is on another file system
+fn move_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    std::fs::copy(from, to)?;
+    std::fs::remove_file(from)
+}
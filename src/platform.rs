@@ -0,0 +1,420 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Error};
+use regex::Regex;
+
+use crate::status;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Os {
+    Linux,
+    MacOS,
+    Windows,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Arch {
+    X86_64,
+    Aarch64,
+}
+
+/// Platform that binaries are downloaded for
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Target {
+    pub os: Os,
+    pub arch: Arch,
+}
+
+impl Os {
+    /// Name used in `--target` and URL templates
+    pub fn name(&self) -> &'static str {
+        match self {
+            Os::Linux => "linux",
+            Os::MacOS => "macos",
+            Os::Windows => "windows",
+        }
+    }
+
+    /// Names used for the OS in release asset names, most common first
+    fn asset_names(&self) -> &'static [&'static str] {
+        match self {
+            Os::Linux => &["ubuntu", "linux"],
+            Os::MacOS => &["macos", "darwin"],
+            Os::Windows => &["windows"],
+        }
+    }
+}
+
+impl Arch {
+    /// Name used in `--target` and URL templates
+    pub fn name(&self) -> &'static str {
+        match self {
+            Arch::X86_64 => "x86_64",
+            Arch::Aarch64 => "aarch64",
+        }
+    }
+
+    /// Names used for the architecture in release asset names, most common first
+    fn asset_names(&self) -> &'static [&'static str] {
+        match self {
+            Arch::X86_64 => &["x86_64", "amd64"],
+            Arch::Aarch64 => &["aarch64", "arm64"],
+        }
+    }
+}
+
+impl Target {
+    /// Detects the platform suiup runs on
+    pub fn detect() -> Result<Self, Error> {
+        let os = match whoami::platform() {
+            whoami::Platform::Linux => Os::Linux,
+            whoami::Platform::Windows => Os::Windows,
+            whoami::Platform::MacOS => Os::MacOS,
+            _ => bail!("Unsupported OS. Supported only: Linux, Windows, MacOS"),
+        };
+        let arch = match std::env::consts::ARCH {
+            // An x86_64 suiup on Apple Silicon runs under Rosetta, but the binaries it installs
+            // should still be the native ones
+            "x86_64" if os == Os::MacOS && running_under_rosetta() => {
+                status!(
+                    "suiup is running under Rosetta, using native arm64 binaries. Use `--target macos-x86_64` to install x86_64 binaries instead."
+                );
+                Arch::Aarch64
+            }
+            "x86_64" => Arch::X86_64,
+            "aarch64" => Arch::Aarch64,
+            _ => bail!("Unsupported architecture. Supported only: x86_64, aarch64"),
+        };
+        Ok(Target { os, arch })
+    }
+
+    /// Platform part of the asset names of the MystenLabs releases, e.g. `ubuntu-x86_64` or
+    /// `macos-arm64`
+    pub fn release_asset_platform(&self) -> String {
+        let (os, arch) = self.release_asset_names();
+        format!("{os}-{arch}")
+    }
+
+    /// OS and architecture names used in the MystenLabs release assets
+    fn release_asset_names(&self) -> (&'static str, &'static str) {
+        let arch = match (self.os, self.arch) {
+            (Os::MacOS, Arch::Aarch64) => "arm64",
+            (_, arch) => arch.name(),
+        };
+        (self.os.asset_names()[0], arch)
+    }
+
+    /// Whether `asset_name` is the release asset for this target: the one matching the asset
+    /// `pattern` configured for the binary if there is one, with `{network}` set to `network`
+    /// if given.
+    pub fn matches_release_asset(
+        &self,
+        pattern: Option<&str>,
+        network: Option<&str>,
+        asset_name: &str,
+    ) -> bool {
+        match pattern {
+            Some(pattern) => self
+                .asset_regex(pattern, network)
+                .is_ok_and(|regex| regex.is_match(asset_name)),
+            None => self.matches_asset(asset_name),
+        }
+    }
+
+    fn asset_regex(&self, pattern: &str, network: Option<&str>) -> Result<Regex, Error> {
+        let alternatives = |names: &[&str]| {
+            let names = names.iter().map(|n| regex::escape(n)).collect::<Vec<_>>();
+            format!("(?:{})", names.join("|"))
+        };
+        let mut regex = "(?i)^".to_string();
+        for part in parse_asset_pattern(pattern)? {
+            match part {
+                PatternPart::Literal(text) => regex.push_str(&regex::escape(text)),
+                PatternPart::Network => match network {
+                    Some(network) => regex.push_str(&regex::escape(network)),
+                    None => regex.push_str("[a-z]+"),
+                },
+                PatternPart::Version => regex.push_str("[0-9][0-9a-z.+-]*?"),
+                PatternPart::Os => regex.push_str(&alternatives(self.os.asset_names())),
+                PatternPart::Arch => regex.push_str(&alternatives(self.arch.asset_names())),
+            }
+        }
+        regex.push('$');
+        Ok(Regex::new(&regex)?)
+    }
+
+    /// Name of the release asset for this target given an asset pattern, e.g.
+    /// `sui-testnet-v1.40.1-ubuntu-x86_64.tgz` for `sui-{network}-v{version}-{os}-{arch}.tgz`
+    pub fn render_asset_pattern(
+        &self,
+        pattern: &str,
+        network: &str,
+        version: &str,
+    ) -> Result<String, Error> {
+        let (os, arch) = self.release_asset_names();
+        Ok(parse_asset_pattern(pattern)?
+            .into_iter()
+            .map(|part| match part {
+                PatternPart::Literal(text) => text,
+                PatternPart::Network => network,
+                PatternPart::Version => version.trim_start_matches('v'),
+                PatternPart::Os => os,
+                PatternPart::Arch => arch,
+            })
+            .collect())
+    }
+
+    /// Checks if a release asset is built for this target, e.g.
+    /// `sui-testnet-v1.40.1-ubuntu-aarch64.tgz` for linux-aarch64
+    pub fn matches_asset(&self, asset_name: &str) -> bool {
+        let name = asset_name.to_lowercase();
+        self.os.asset_names().iter().any(|os| name.contains(os))
+            && self
+                .arch
+                .asset_names()
+                .iter()
+                .any(|arch| name.contains(arch))
+    }
+
+    /// Every target suiup knows the release assets of
+    pub fn all() -> Vec<Target> {
+        [Os::Linux, Os::MacOS, Os::Windows]
+            .into_iter()
+            .flat_map(|os| [Arch::X86_64, Arch::Aarch64].map(|arch| Target { os, arch }))
+            .collect()
+    }
+
+    /// Lists the targets for which assets are published, given the asset names of a release
+    pub fn available<'a>(asset_names: impl IntoIterator<Item = &'a str>) -> Vec<Target> {
+        let asset_names = asset_names.into_iter().collect::<Vec<_>>();
+        Target::all()
+            .into_iter()
+            .filter(|target| asset_names.iter().any(|name| target.matches_asset(name)))
+            .collect()
+    }
+
+    /// Error for a release that has no asset for this target, listing the targets it has
+    pub fn no_asset_error<'a>(&self, asset_names: impl IntoIterator<Item = &'a str>) -> Error {
+        let available = Target::available(asset_names);
+        if available.is_empty() {
+            return anyhow!("Asset not found for {self}");
+        }
+
+        let list = available
+            .iter()
+            .map(|t| t.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let emulated = Target {
+            os: self.os,
+            arch: Arch::X86_64,
+        };
+        let suggestion = if self.arch == Arch::Aarch64
+            && self.os != Os::Linux
+            && available.contains(&emulated)
+        {
+            format!(
+                "Use `--target {emulated}` to install the x86_64 build, which runs under emulation on {self}."
+            )
+        } else {
+            "Use `--target <os-arch>` to install the build for another platform.".to_string()
+        };
+        anyhow!("Asset not found for {self}. This release is available for: {list}. {suggestion}")
+    }
+}
+
+/// Piece of an asset name pattern
+#[derive(Debug, PartialEq)]
+enum PatternPart<'a> {
+    Literal(&'a str),
+    Network,
+    /// Version number, without the `v` prefix
+    Version,
+    Os,
+    Arch,
+}
+
+/// Splits an asset name pattern into literal text and placeholders
+fn parse_asset_pattern(pattern: &str) -> Result<Vec<PatternPart<'_>>, Error> {
+    let mut parts = Vec::new();
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| anyhow!("unterminated `{{` in asset pattern `{pattern}`"))?;
+        if start > 0 {
+            parts.push(PatternPart::Literal(&rest[..start]));
+        }
+        parts.push(match &rest[start + 1..start + end] {
+            "network" => PatternPart::Network,
+            "version" => PatternPart::Version,
+            "os" => PatternPart::Os,
+            "arch" => PatternPart::Arch,
+            other => bail!(
+                "unknown placeholder `{{{other}}}` in asset pattern `{pattern}`. Use `{{network}}`, `{{version}}`, `{{os}}` or `{{arch}}`"
+            ),
+        });
+        rest = &rest[start + end + 1..];
+    }
+    if !rest.is_empty() {
+        parts.push(PatternPart::Literal(rest));
+    }
+    Ok(parts)
+}
+
+/// Fails if an asset pattern from the config file cannot be used
+pub fn check_asset_pattern(pattern: &str) -> Result<(), Error> {
+    parse_asset_pattern(pattern).map(|_| ())
+}
+
+impl Display for Target {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.os.name(), self.arch.name())
+    }
+}
+
+impl FromStr for Target {
+    type Err = String;
+
+    /// Parses `os-arch`, e.g. `linux-aarch64`, `windows-arm64` or `macos-x86_64`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "Invalid target: {s}. Use <os>-<arch> with os one of linux, macos, windows and arch one of x86_64, aarch64 (e.g., linux-aarch64)"
+            )
+        };
+        let (os, arch) = s
+            .to_lowercase()
+            .split_once('-')
+            .ok_or_else(invalid)
+            .map(|(os, arch)| (os.to_string(), arch.to_string()))?;
+        let os = match os.as_str() {
+            "linux" | "ubuntu" => Os::Linux,
+            "macos" | "darwin" => Os::MacOS,
+            "windows" => Os::Windows,
+            _ => return Err(invalid()),
+        };
+        let arch = match arch.as_str() {
+            "x86_64" | "amd64" => Arch::X86_64,
+            "aarch64" | "arm64" => Arch::Aarch64,
+            _ => return Err(invalid()),
+        };
+        Ok(Target { os, arch })
+    }
+}
+
+/// Checks if the process is an x86_64 binary translated by Rosetta on Apple Silicon
+#[cfg(target_os = "macos")]
+fn running_under_rosetta() -> bool {
+    std::process::Command::new("sysctl")
+        .args(["-n", "sysctl.proc_translated"])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim() == "1")
+        .unwrap_or(false)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn running_under_rosetta() -> bool {
+    false
+}
+
+/// Returns the target to download binaries for: `target`, selected with `--target`, or the
+/// detected platform
+pub fn current_target(target: Option<Target>) -> Result<Target, Error> {
+    if let Some(target) = target {
+        status!("Target: {target}...");
+        return Ok(target);
+    }
+    let target = Target::detect()?;
+    status!("Detected: {target}...");
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_target() {
+        assert_eq!(
+            "linux-aarch64".parse::<Target>().unwrap(),
+            Target {
+                os: Os::Linux,
+                arch: Arch::Aarch64
+            }
+        );
+        assert_eq!(
+            "Windows-ARM64".parse::<Target>().unwrap(),
+            Target {
+                os: Os::Windows,
+                arch: Arch::Aarch64
+            }
+        );
+        assert!("linux".parse::<Target>().is_err());
+        assert!("freebsd-x86_64".parse::<Target>().is_err());
+    }
+
+    #[test]
+    fn test_matches_asset() {
+        let linux_arm: Target = "linux-aarch64".parse().unwrap();
+        assert!(linux_arm.matches_asset("sui-testnet-v1.40.1-ubuntu-aarch64.tgz"));
+        assert!(!linux_arm.matches_asset("sui-testnet-v1.40.1-ubuntu-x86_64.tgz"));
+        assert!(!linux_arm.matches_asset("sui-testnet-v1.40.1-macos-arm64.tgz"));
+
+        let windows_arm: Target = "windows-aarch64".parse().unwrap();
+        assert!(windows_arm.matches_asset("sui-testnet-v1.40.1-windows-arm64.tgz"));
+        assert!(!windows_arm.matches_asset("sui-testnet-v1.40.1-windows-x86_64.tgz"));
+    }
+
+    #[test]
+    fn test_asset_pattern() {
+        let linux: Target = "linux-x86_64".parse().unwrap();
+        let pattern = "sui-{network}-v{version}-{os}-{arch}.tar.zst";
+        assert!(linux.matches_release_asset(
+            Some(pattern),
+            None,
+            "sui-testnet-v1.40.1-linux-amd64.tar.zst"
+        ));
+        assert!(linux.matches_release_asset(
+            Some(pattern),
+            Some("testnet"),
+            "sui-testnet-v1.40.1-ubuntu-x86_64.tar.zst"
+        ));
+        assert!(!linux.matches_release_asset(
+            Some(pattern),
+            Some("mainnet"),
+            "sui-testnet-v1.40.1-ubuntu-x86_64.tar.zst"
+        ));
+        assert!(!linux.matches_release_asset(
+            Some(pattern),
+            None,
+            "sui-testnet-v1.40.1-ubuntu-x86_64.tgz"
+        ));
+
+        let mac_arm: Target = "macos-arm64".parse().unwrap();
+        assert_eq!(
+            mac_arm
+                .render_asset_pattern(pattern, "mainnet", "v1.40.1")
+                .unwrap(),
+            "sui-mainnet-v1.40.1-macos-arm64.tar.zst"
+        );
+        assert!(check_asset_pattern("sui-{net}.tgz").is_err());
+        assert!(check_asset_pattern("sui-{network.tgz").is_err());
+    }
+
+    #[test]
+    fn test_no_asset_error() {
+        let assets = [
+            "sui-testnet-v1.40.1-ubuntu-x86_64.tgz",
+            "sui-testnet-v1.40.1-windows-x86_64.tgz",
+        ];
+        let windows_arm: Target = "windows-arm64".parse().unwrap();
+        let err = windows_arm.no_asset_error(assets).to_string();
+        assert!(err.contains("available for: linux-x86_64, windows-x86_64"));
+        assert!(err.contains("--target windows-x86_64"));
+    }
+}
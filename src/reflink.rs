@@ -0,0 +1,56 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Copies of binaries that share their data with the original (reflinks) on filesystems that
+//! support it, e.g. btrfs and XFS on Linux or APFS on macOS. Switching the default version or
+//! keeping a nightly build is then near-instant and does not use more disk. Other filesystems,
+//! or a copy to another filesystem, fall back to a full copy.
+
+use std::io;
+use std::path::Path;
+
+/// Copies `src` to `dst` like [`std::fs::copy`], cloning the file when the filesystem supports
+/// it. `dst` is overwritten and gets the permissions of `src`.
+pub fn copy(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Result<()> {
+    let (src, dst) = (src.as_ref(), dst.as_ref());
+    #[cfg(target_os = "linux")]
+    if clone_file(src, dst).is_ok() {
+        return Ok(());
+    }
+    // The standard library already clones files on macOS
+    std::fs::copy(src, dst).map(|_| ())
+}
+
+/// Clones `src` to `dst` with the `FICLONE` ioctl, which fails on filesystems without reflinks
+/// and across filesystems
+#[cfg(target_os = "linux")]
+fn clone_file(src: &Path, dst: &Path) -> io::Result<()> {
+    use std::fs::File;
+    use std::os::fd::AsRawFd;
+
+    let source = File::open(src)?;
+    let permissions = source.metadata()?.permissions();
+    let destination = File::create(dst)?;
+    // SAFETY: both file descriptors are open for the duration of the call
+    if unsafe { libc::ioctl(destination.as_raw_fd(), libc::FICLONE, source.as_raw_fd()) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    destination.set_permissions(permissions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("sui-v1.40.1");
+        let dst = dir.path().join("sui");
+        std::fs::write(&src, b"binary").unwrap();
+        std::fs::write(&dst, b"older binary").unwrap();
+        copy(&src, &dst).unwrap();
+        assert_eq!(std::fs::read(&dst).unwrap(), b"binary");
+        assert!(copy(dir.path().join("missing"), &dst).is_err());
+    }
+}
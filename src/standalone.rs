@@ -2,9 +2,20 @@
 // SPDX-License-Identifier: Apache-2.0
 
 // use crate::handle_commands::{binaries_folder, detect_os_arch, download_file};
+use crate::output;
+use crate::status;
+use crate::timings;
 use crate::{
-    handlers::download::{detect_os_arch, download_file},
+    config::load_config,
+    handlers::download::{download_file, gh_download},
+    handlers::gh,
+    handlers::install::{remove_cached, InstallOptions},
+    handlers::integrity,
+    handlers::spinner,
+    http,
     paths::binaries_dir,
+    platform::{current_target, Target},
+    state,
     types::Repo,
 };
 use anyhow::{anyhow, Error};
@@ -20,6 +31,12 @@ pub struct StandaloneRelease {
 pub struct StandaloneAsset {
     pub name: String,
     pub browser_download_url: String,
+    /// Size in bytes
+    #[serde(default)]
+    pub size: u64,
+    /// Checksum GitHub publishes for the asset, e.g. `sha256:<hex>`
+    #[serde(default)]
+    pub digest: Option<String>,
 }
 
 pub struct StandaloneInstaller {
@@ -36,17 +53,34 @@ impl StandaloneInstaller {
     }
 
     pub async fn get_releases(&mut self) -> Result<(), Error> {
-        let client = reqwest::Client::new();
-        let url = format!("https://api.github.com/repos/{}/releases", self.repo);
+        let repository = self.repo.repository()?;
+        let url = format!("{}/repos/{repository}/releases", http::github_api_url()?);
 
         if !self.releases.is_empty() {
             return Ok(());
         }
 
-        let releases: Vec<StandaloneRelease> = client
-            .get(&url)
-            .header("User-Agent", "suiup")
-            .send()
+        let _timer = timings::phase("resolve");
+        if gh::enabled() {
+            match gh::api(&format!("repos/{repository}/releases")).await {
+                Ok(releases) => {
+                    self.releases = releases;
+                    return Ok(());
+                }
+                Err(e) => eprintln!(
+                    "{} {e}. Fetching the {} releases without gh.",
+                    output::warning_sign(),
+                    self.repo.binary_name()
+                ),
+            }
+        }
+
+        let _pb = spinner(
+            "fetch",
+            format!("Fetching {} releases...", self.repo.binary_name()),
+        );
+        let what = format!("fetch the list of {} releases", self.repo.binary_name());
+        let releases: Vec<StandaloneRelease> = http::send(http::get(&url, None)?, &what)
             .await?
             .json()
             .await?;
@@ -54,8 +88,39 @@ impl StandaloneInstaller {
         Ok(())
     }
 
+    /// Lists the fetched release tags, newest first
+    pub fn versions(&self) -> Vec<String> {
+        self.releases.iter().map(|r| r.tag_name.clone()).collect()
+    }
+
+    /// Finds the binary of a fetched release for `target`
+    pub fn find_asset_for(
+        &self,
+        version: &str,
+        target: &Target,
+    ) -> Result<&StandaloneAsset, Error> {
+        let release = self
+            .releases
+            .iter()
+            .find(|r| r.tag_name == version)
+            .ok_or_else(|| anyhow!("Version {} not found", version))?;
+
+        let binary_prefix = format!("{}-", self.repo.binary_name());
+        let config = load_config()?;
+        let asset_pattern = config.asset_pattern(self.repo);
+
+        release
+            .assets
+            .iter()
+            .find(|a| match asset_pattern {
+                Some(pattern) => target.matches_release_asset(Some(pattern), None, &a.name),
+                None => a.name.starts_with(&binary_prefix) && target.matches_asset(&a.name),
+            })
+            .ok_or_else(|| target.no_asset_error(release.assets.iter().map(|a| a.name.as_str())))
+    }
+
     pub fn get_latest_release(&self) -> Result<&StandaloneRelease, Error> {
-        println!("Downloading release list");
+        status!("Downloading release list");
         let releases = &self.releases;
         releases
             .first()
@@ -63,7 +128,12 @@ impl StandaloneInstaller {
     }
 
     /// Download the CLI binary, if it does not exist in the binary folder.
-    pub async fn download_version(&mut self, version: Option<String>) -> Result<String, Error> {
+    /// Returns the version and the URL it was downloaded from (`None` if it already existed).
+    pub async fn download_version(
+        &mut self,
+        version: Option<String>,
+        options: &InstallOptions,
+    ) -> Result<(String, Option<String>), Error> {
         let version = if let Some(v) = version {
             // Ensure version has 'v' prefix for GitHub release tags
             crate::handlers::release::ensure_version_prefix(&v)
@@ -72,7 +142,7 @@ impl StandaloneInstaller {
                 self.get_releases().await?;
             }
             let latest_release = self.get_latest_release()?.tag_name.clone();
-            println!("No version specified. Downloading latest release: {latest_release}");
+            status!("No version specified. Downloading latest release: {latest_release}");
             latest_release
         };
 
@@ -87,46 +157,43 @@ impl StandaloneInstaller {
         let standalone_binary_path =
             cache_folder.join(format!("{}-{}.exe", self.repo.binary_name(), version));
 
-        if standalone_binary_path.exists() {
-            println!("Binary {}-{version} already installed. Use `suiup default set standalone {version}` to set the default version to the desired one", self.repo.binary_name());
-            return Ok(version);
+        if options.force {
+            remove_cached(&standalone_binary_path)?;
+        } else if standalone_binary_path.exists()
+            && !state::journal::is_incomplete(&standalone_binary_path)
+        {
+            status!("Binary {}-{version} already installed. Use `suiup default set standalone {version}` to set the default version to the desired one", self.repo.binary_name());
+            return Ok((version, None));
         }
 
         if self.releases.is_empty() {
             self.get_releases().await?;
         }
 
-        let release = self
-            .releases
-            .iter()
-            .find(|r| r.tag_name == version)
-            .ok_or_else(|| anyhow!("Version {} not found", version))?;
-
-        let (os, arch) = detect_os_arch()?;
-        let asset_name = format!("{}-{}-{}", self.repo.binary_name(), os, arch);
-
-        #[cfg(target_os = "windows")]
-        let asset_name = format!("{}.exe", asset_name);
+        let asset = self.find_asset_for(&version, &current_target(options.target)?)?;
 
-        let asset = release
-            .assets
-            .iter()
-            .find(|a| a.name.starts_with(&asset_name))
-            .ok_or_else(|| {
-                anyhow!(
-                    "No compatible binary found for your system: {}-{}",
-                    os,
-                    arch
-                )
-            })?;
-
-        download_file(
-            &asset.browser_download_url,
+        state::journal::begin(&standalone_binary_path)?;
+        if !gh_download(
+            &self.repo,
+            &version,
+            &asset.name,
+            asset.size,
             &standalone_binary_path,
-            format!("{}-{version}", self.repo.binary_name()).as_str(),
-            None,
         )
-        .await?;
+        .await?
+        {
+            download_file(
+                &asset.browser_download_url,
+                &standalone_binary_path,
+                format!("{}-{version}", self.repo.binary_name()).as_str(),
+                None,
+            )
+            .await?;
+        }
+
+        if let Some(sha256) = &options.sha256 {
+            integrity::verify_digest(&standalone_binary_path, &format!("sha256:{sha256}")).await?;
+        }
 
         #[cfg(unix)]
         {
@@ -136,6 +203,6 @@ impl StandaloneInstaller {
             std::fs::set_permissions(&standalone_binary_path, perms)?;
         }
 
-        Ok(version)
+        Ok((version, Some(asset.browser_download_url.clone())))
     }
 }
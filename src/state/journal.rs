@@ -0,0 +1,101 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Journal of the binaries being installed. A binary is added before it is extracted or
+//! downloaded into the binaries folder and removed once it is registered in the installed
+//! binaries, so a binary left behind by a killed suiup is recognized as incomplete on the next
+//! run, instead of being taken for an installed one.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{anyhow, Error};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::output;
+use crate::paths::get_suiup_state_dir;
+use crate::state::write_file_atomically;
+
+const JOURNAL_FILE: &str = "extraction_journal.json";
+
+/// Serializes the updates of the journal by the installs running in this process
+static JOURNAL_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct Journal {
+    extractions: Vec<Extraction>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Extraction {
+    path: PathBuf,
+    started_at: DateTime<Utc>,
+}
+
+fn journal_file() -> PathBuf {
+    get_suiup_state_dir().join(JOURNAL_FILE)
+}
+
+/// Records that the binary at `path` is about to be written
+pub fn begin(path: &Path) -> Result<(), Error> {
+    update(|journal| {
+        journal.extractions.retain(|e| e.path != path);
+        journal.extractions.push(Extraction {
+            path: path.to_path_buf(),
+            started_at: Utc::now(),
+        });
+    })
+}
+
+/// Records that the binary at `path` is written and registered
+pub fn finish(path: &Path) -> Result<(), Error> {
+    update(|journal| journal.extractions.retain(|e| e.path != path))
+}
+
+/// Whether writing the binary at `path` was started but never finished, warning that it is
+/// installed again if so. What is left of it is overwritten by the new install.
+pub fn is_incomplete(path: &Path) -> bool {
+    let journal = read(&journal_file());
+    let Some(extraction) = journal.extractions.iter().find(|e| e.path == path) else {
+        return false;
+    };
+    eprintln!(
+        "{} The install of {} started at {} did not finish, it is installed again",
+        output::warning_sign(),
+        path.display(),
+        extraction.started_at.format("%Y-%m-%d %H:%M:%S UTC")
+    );
+    true
+}
+
+fn update(f: impl FnOnce(&mut Journal)) -> Result<(), Error> {
+    let _lock = JOURNAL_LOCK.lock();
+    let mut journal = read(&journal_file());
+    f(&mut journal);
+    write(&journal)
+}
+
+/// An unreadable journal is treated as empty, like a missing one
+fn read(path: &Path) -> Journal {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn write(journal: &Journal) -> Result<(), Error> {
+    let path = journal_file();
+    if journal.extractions.is_empty() {
+        if path.exists() {
+            std::fs::remove_file(&path)
+                .map_err(|e| anyhow!("Cannot remove {}: {e}", path.display()))?;
+        }
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| anyhow!("Cannot create folder {}: {e}", dir.display()))?;
+    }
+    write_file_atomically(&path, serde_json::to_string_pretty(journal)?.as_bytes())
+}
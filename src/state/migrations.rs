@@ -0,0 +1,172 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Schema migrations for the state files.
+//!
+//! Every state file carries a `schema_version` field. Files written before versioning was
+//! introduced do not have it and are treated as version 0. When a file is loaded, the
+//! migrations from its version up to [`SCHEMA_VERSION`] are applied in order on the raw JSON,
+//! so new fields can be introduced without breaking existing installations.
+
+use anyhow::{anyhow, bail, Error};
+use serde_json::{json, Value};
+
+/// Current version of the state files schema.
+pub const SCHEMA_VERSION: u64 = 2;
+
+/// A migration upgrades the raw JSON of a state file by exactly one schema version.
+pub type Migration = fn(Value) -> Result<Value, Error>;
+
+/// Migrations for `installed_binaries.json`. The migration at index `i` upgrades from
+/// version `i` to version `i + 1`.
+pub const INSTALLED_BINARIES: &[Migration] =
+    &[installed_binaries_v0_to_v1, installed_binaries_v1_to_v2];
+
+/// Migrations for `default_version.json`. The migration at index `i` upgrades from version
+/// `i` to version `i + 1`.
+pub const DEFAULT_VERSIONS: &[Migration] = &[default_versions_v0_to_v1, default_versions_v1_to_v2];
+
+/// Returns the schema version of the raw state file content
+pub fn schema_version(value: &Value) -> u64 {
+    value
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .unwrap_or(0)
+}
+
+/// Applies the migrations needed to bring `value` to [`SCHEMA_VERSION`]. Returns the migrated
+/// value and whether any migration was applied.
+pub fn migrate(mut value: Value, migrations: &[Migration]) -> Result<(Value, bool), Error> {
+    debug_assert_eq!(migrations.len() as u64, SCHEMA_VERSION);
+
+    let from = schema_version(&value);
+    if from > SCHEMA_VERSION {
+        bail!(
+            "The state file has schema version {from}, but this suiup only supports up to \
+            version {SCHEMA_VERSION}. It was probably written by a newer suiup, please update \
+            with `suiup self update`."
+        );
+    }
+
+    for (version, migration) in migrations.iter().enumerate().skip(from as usize) {
+        value = migration(value)
+            .map_err(|e| anyhow!("Cannot migrate state from version {version}: {e}"))?;
+    }
+
+    Ok((value, from < SCHEMA_VERSION))
+}
+
+/// Version 0 stored `{"binaries": [...]}`; version 1 adds the schema version.
+fn installed_binaries_v0_to_v1(mut value: Value) -> Result<Value, Error> {
+    let obj = value
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("expected a JSON object"))?;
+    obj.insert("schema_version".to_string(), json!(1));
+    Ok(value)
+}
+
+/// Version 0 stored a bare `{binary: [network, version, debug]}` map; version 1 moves it
+/// under `binaries` next to the schema version.
+fn default_versions_v0_to_v1(value: Value) -> Result<Value, Error> {
+    if !value.is_object() {
+        bail!("expected a JSON object");
+    }
+    Ok(json!({
+        "schema_version": 1,
+        "binaries": value,
+    }))
+}
+
+/// Version 2 only changes the default versions, the installed binaries already tell debug
+/// builds apart.
+fn installed_binaries_v1_to_v2(mut value: Value) -> Result<Value, Error> {
+    let obj = value
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("expected a JSON object"))?;
+    obj.insert("schema_version".to_string(), json!(2));
+    Ok(value)
+}
+
+/// Version 1 keyed debug defaults by the binary name, so a debug default replaced the release
+/// one; version 2 keys them as `<binary>-debug`.
+fn default_versions_v1_to_v2(mut value: Value) -> Result<Value, Error> {
+    let obj = value
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("expected a JSON object"))?;
+    if let Some(Value::Object(binaries)) = obj.get_mut("binaries") {
+        let entries = std::mem::take(binaries);
+        for (name, entry) in entries {
+            let debug = entry.get(2).and_then(Value::as_bool).unwrap_or(false);
+            let name = if debug && !name.ends_with("-debug") {
+                format!("{name}-debug")
+            } else {
+                name
+            };
+            binaries.insert(name, entry);
+        }
+    }
+    obj.insert("schema_version".to_string(), json!(2));
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_installed_binaries_from_v0() {
+        let legacy = json!({
+            "binaries": [{
+                "binary_name": "sui",
+                "network_release": "testnet",
+                "version": "v1.39.3",
+                "debug": false,
+                "path": null
+            }]
+        });
+        let (migrated, changed) = migrate(legacy, INSTALLED_BINARIES).unwrap();
+        assert!(changed);
+        assert_eq!(schema_version(&migrated), SCHEMA_VERSION);
+        assert_eq!(migrated["binaries"][0]["binary_name"], "sui");
+    }
+
+    #[test]
+    fn test_migrate_default_versions_from_v0() {
+        let legacy = json!({ "sui": ["testnet", "v1.39.3", false] });
+        let (migrated, changed) = migrate(legacy, DEFAULT_VERSIONS).unwrap();
+        assert!(changed);
+        assert_eq!(schema_version(&migrated), SCHEMA_VERSION);
+        assert_eq!(migrated["binaries"]["sui"][1], "v1.39.3");
+    }
+
+    #[test]
+    fn test_migrate_default_versions_keys_debug_builds() {
+        let v1 = json!({
+            "schema_version": 1,
+            "binaries": {
+                "sui": ["testnet", "v1.39.3", true],
+                "walrus": ["testnet", "v1.18.2", false]
+            }
+        });
+        let (migrated, changed) = migrate(v1, DEFAULT_VERSIONS).unwrap();
+        assert!(changed);
+        assert_eq!(migrated["binaries"]["sui-debug"][1], "v1.39.3");
+        assert!(migrated["binaries"].get("sui").is_none());
+        assert_eq!(migrated["binaries"]["walrus"][1], "v1.18.2");
+    }
+
+    #[test]
+    fn test_migrate_current_version_is_noop() {
+        let current = json!({ "schema_version": SCHEMA_VERSION, "binaries": [] });
+        let (migrated, changed) = migrate(current.clone(), INSTALLED_BINARIES).unwrap();
+        assert!(!changed);
+        assert_eq!(migrated, current);
+    }
+
+    #[test]
+    fn test_migrate_rejects_newer_version() {
+        let newer = json!({ "schema_version": SCHEMA_VERSION + 1, "binaries": [] });
+        let err = migrate(newer, INSTALLED_BINARIES).unwrap_err();
+        assert!(err.to_string().contains("newer suiup"));
+    }
+}
@@ -0,0 +1,471 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod journal;
+pub mod migrations;
+
+use anyhow::{anyhow, bail, Error};
+use lazy_static::lazy_static;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::paths::{
+    default_file_path, get_state_file, get_suiup_home, get_suiup_state_dir, is_system_mode,
+    system_default_file, system_installed_binaries_file, DISABLED_BINARIES_FILE,
+};
+use crate::types::InstalledBinaries;
+use migrations::SCHEMA_VERSION;
+
+/// Map of binary name to its default (network release, version, debug) triple, as stored in the
+/// default version file.
+pub type DefaultVersions = BTreeMap<String, (String, String, bool)>;
+
+/// On-disk format of the default version file
+#[derive(Serialize, Deserialize, Debug)]
+struct DefaultVersionsFile {
+    schema_version: u64,
+    binaries: DefaultVersions,
+}
+
+/// On-disk format of the disabled binaries file
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct DisabledBinariesFile {
+    disabled: Vec<String>,
+}
+
+lazy_static! {
+    static ref APP_STATE: Mutex<Option<AppState>> = Mutex::new(None);
+}
+
+/// Process-wide view of suiup's persistent state (installed binaries, default versions and
+/// disabled binaries).
+///
+/// The state is read from disk the first time it is needed and then served from memory for the
+/// rest of the command. Changes are kept in memory and written back by [`flush`], which the CLI
+/// calls once a command finishes.
+#[derive(Debug)]
+pub struct AppState {
+    /// State directory the state was loaded from. If it changes (e.g., tests pointing
+    /// `XDG_STATE_HOME` somewhere else), the cached state is stale and gets reloaded.
+    state_dir: PathBuf,
+    installed_binaries: InstalledBinaries,
+    default_versions: DefaultVersions,
+    /// Binaries taken out of the default bin folder with `suiup disable`
+    disabled_binaries: Vec<String>,
+    installed_binaries_dirty: bool,
+    default_versions_dirty: bool,
+    disabled_binaries_dirty: bool,
+}
+
+impl AppState {
+    /// Load the state from the files in the state directory
+    fn load() -> Result<Self, Error> {
+        Ok(Self {
+            state_dir: get_suiup_state_dir(),
+            installed_binaries: InstalledBinaries::read_from_file()?,
+            default_versions: read_default_versions()?,
+            disabled_binaries: read_disabled_binaries_file()?,
+            installed_binaries_dirty: false,
+            default_versions_dirty: false,
+            disabled_binaries_dirty: false,
+        })
+    }
+
+    pub fn installed_binaries(&self) -> &InstalledBinaries {
+        &self.installed_binaries
+    }
+
+    /// Mutable access to the installed binaries. The changes are persisted on the next flush.
+    pub fn installed_binaries_mut(&mut self) -> &mut InstalledBinaries {
+        self.installed_binaries_dirty = true;
+        &mut self.installed_binaries
+    }
+
+    pub fn default_versions(&self) -> &DefaultVersions {
+        &self.default_versions
+    }
+
+    /// Mutable access to the default versions. The changes are persisted on the next flush.
+    pub fn default_versions_mut(&mut self) -> &mut DefaultVersions {
+        self.default_versions_dirty = true;
+        &mut self.default_versions
+    }
+
+    pub fn disabled_binaries(&self) -> &[String] {
+        &self.disabled_binaries
+    }
+
+    /// Mutable access to the disabled binaries. The changes are persisted on the next flush.
+    pub fn disabled_binaries_mut(&mut self) -> &mut Vec<String> {
+        self.disabled_binaries_dirty = true;
+        &mut self.disabled_binaries
+    }
+
+    /// Write the modified parts of the state back to disk
+    fn save(&mut self) -> Result<(), Error> {
+        if self.installed_binaries_dirty {
+            self.installed_binaries.save_to_file()?;
+            self.installed_binaries_dirty = false;
+        }
+        if self.default_versions_dirty {
+            let file = DefaultVersionsFile {
+                schema_version: SCHEMA_VERSION,
+                binaries: self.default_versions.clone(),
+            };
+            let s = serde_json::to_string_pretty(&file)
+                .map_err(|e| anyhow!("Cannot serialize the default versions: {e}"))?;
+            write_file_atomically(&default_file_path()?, s.as_bytes())?;
+            self.default_versions_dirty = false;
+        }
+        if self.disabled_binaries_dirty {
+            let file = DisabledBinariesFile {
+                disabled: self.disabled_binaries.clone(),
+            };
+            let s = serde_json::to_string_pretty(&file)
+                .map_err(|e| anyhow!("Cannot serialize the disabled binaries: {e}"))?;
+            write_file_atomically(&get_state_file(DISABLED_BINARIES_FILE), s.as_bytes())?;
+            self.disabled_binaries_dirty = false;
+        }
+        Ok(())
+    }
+}
+
+/// Runs `f` with the process-wide state, loading it from disk if it was not loaded yet or if the
+/// state directory changed since it was loaded.
+///
+/// `f` must not call `with_state` itself, as the state is locked while it runs.
+pub fn with_state<R>(f: impl FnOnce(&mut AppState) -> Result<R, Error>) -> Result<R, Error> {
+    let mut guard = APP_STATE
+        .lock()
+        .map_err(|_| anyhow!("The suiup state lock is poisoned"))?;
+
+    let stale = guard
+        .as_ref()
+        .is_none_or(|state| state.state_dir != get_suiup_state_dir());
+    if stale {
+        // Do not drop unsaved changes of the previous state
+        if let Some(state) = guard.as_mut() {
+            state.save()?;
+        }
+        *guard = Some(AppState::load()?);
+    }
+
+    f(guard.as_mut().expect("state was loaded above"))
+}
+
+/// Returns a copy of the installed binaries
+pub fn installed_binaries() -> Result<InstalledBinaries, Error> {
+    with_state(|state| Ok(state.installed_binaries().clone()))
+}
+
+/// Returns a copy of the default versions
+pub fn default_versions() -> Result<DefaultVersions, Error> {
+    with_state(|state| Ok(state.default_versions().clone()))
+}
+
+/// Returns a copy of the disabled binaries, none if nothing was ever disabled
+pub fn disabled_binaries() -> Result<Vec<String>, Error> {
+    with_state(|state| Ok(state.disabled_binaries().to_vec()))
+}
+
+/// Returns the binaries of the system-wide installation, which are visible (read-only) to every
+/// user. Empty when running with `--system`, as they are then the regular installed binaries,
+/// and with `--portable` or `SUIUP_HOME`, which are kept apart from the rest of the machine.
+pub fn system_installed_binaries() -> Result<InstalledBinaries, Error> {
+    let path = system_installed_binaries_file();
+    if is_system_mode() || get_suiup_home().is_some() || !path.exists() {
+        return Ok(InstalledBinaries::default());
+    }
+    read_state_file_readonly(&path, migrations::INSTALLED_BINARIES)
+}
+
+/// Returns the default versions of the system-wide installation, empty in the same cases as
+/// [`system_installed_binaries`]
+pub fn system_default_versions() -> Result<DefaultVersions, Error> {
+    let path = system_default_file();
+    if is_system_mode() || get_suiup_home().is_some() || !path.exists() {
+        return Ok(DefaultVersions::default());
+    }
+    read_default_versions_readonly(&path)
+}
+
+/// Reads a default versions file without migrating it on disk, see [`read_state_file_readonly`]
+pub(crate) fn read_default_versions_readonly(path: &Path) -> Result<DefaultVersions, Error> {
+    let file: DefaultVersionsFile = read_state_file_readonly(path, migrations::DEFAULT_VERSIONS)?;
+    Ok(file.binaries)
+}
+
+/// Persists any pending changes to disk
+pub fn flush() -> Result<(), Error> {
+    let mut guard = APP_STATE
+        .lock()
+        .map_err(|_| anyhow!("The suiup state lock is poisoned"))?;
+    if let Some(state) = guard.as_mut() {
+        state.save()?;
+    }
+    Ok(())
+}
+
+/// Drops the cached state (saving pending changes first), so the next access reads from disk
+pub fn invalidate() -> Result<(), Error> {
+    let mut guard = APP_STATE
+        .lock()
+        .map_err(|_| anyhow!("The suiup state lock is poisoned"))?;
+    if let Some(mut state) = guard.take() {
+        state.save()?;
+    }
+    Ok(())
+}
+
+fn read_disabled_binaries_file() -> Result<Vec<String>, Error> {
+    let path = get_state_file(DISABLED_BINARIES_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    read_disabled_binaries(&path)
+}
+
+/// Reads the disabled binaries from the file at `path`
+pub(crate) fn read_disabled_binaries(path: &Path) -> Result<Vec<String>, Error> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Cannot read {}: {e}", path.display()))?;
+    let file: DisabledBinariesFile = serde_json::from_str(&content)
+        .map_err(|e| anyhow!("Invalid disabled binaries file {}: {e}", path.display()))?;
+    Ok(file.disabled)
+}
+
+fn read_default_versions() -> Result<DefaultVersions, Error> {
+    let file: DefaultVersionsFile =
+        read_state_file(&default_file_path()?, migrations::DEFAULT_VERSIONS)?;
+    Ok(file.binaries)
+}
+
+/// Reads a versioned state file, migrating it to the current schema if needed.
+///
+/// When a migration is applied, the original file is kept next to it with a
+/// `.v<schema_version>.bak` suffix and the migrated content is written back.
+pub(crate) fn read_state_file<T: DeserializeOwned>(
+    path: &Path,
+    migrations: &[migrations::Migration],
+) -> Result<T, Error> {
+    let (value, from, migrated) = load_state_value(path, migrations)?;
+
+    if migrated {
+        let backup = path.with_extension(format!("json.v{from}.bak"));
+        std::fs::copy(path, &backup)
+            .map_err(|e| anyhow!("Cannot back up {}: {e}", path.display()))?;
+        write_file_atomically(path, serde_json::to_string_pretty(&value)?.as_bytes())?;
+    }
+
+    serde_json::from_value(value)
+        .map_err(|e| anyhow!("Cannot deserialize from {}: {e}", path.display()))
+}
+
+/// Reads a versioned state file that the current user may not be allowed to modify (e.g., the
+/// system-wide installation). Migrations are only applied in memory.
+pub(crate) fn read_state_file_readonly<T: DeserializeOwned>(
+    path: &Path,
+    migrations: &[migrations::Migration],
+) -> Result<T, Error> {
+    let (value, _, _) = load_state_value(path, migrations)?;
+    serde_json::from_value(value)
+        .map_err(|e| anyhow!("Cannot deserialize from {}: {e}", path.display()))
+}
+
+/// Reads a state file and migrates it, returning the migrated content, the schema version it had
+/// and whether a migration was applied.
+fn load_state_value(
+    path: &Path,
+    migrations: &[migrations::Migration],
+) -> Result<(serde_json::Value, u64, bool), Error> {
+    let s = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Cannot read from {}: {e}", path.display()))?;
+    let value: serde_json::Value = serde_json::from_str(&s).map_err(|e| {
+        anyhow!(
+            "Cannot decode {} as JSON. Is the file corrupted? {e}",
+            path.display()
+        )
+    })?;
+
+    let from = migrations::schema_version(&value);
+    let (value, migrated) = migrations::migrate(value, migrations)
+        .map_err(|e| anyhow!("Cannot load {}: {e}", path.display()))?;
+    Ok((value, from, migrated))
+}
+
+/// Writes `contents` to a temporary file next to `path`, flushes it to disk and renames it into
+/// place, so that neither a crash nor another suiup process writing the same file at the same
+/// time ever leaves it truncated. A symlink, e.g. to a config file kept with dotfiles, is written
+/// through, and the permissions of the replaced file are kept.
+pub(crate) fn write_file_atomically(path: &Path, contents: &[u8]) -> Result<(), Error> {
+    let path = match std::fs::canonicalize(path) {
+        Ok(target) if path.is_symlink() => target,
+        _ => path.to_path_buf(),
+    };
+    let tmp_path = write_temp_file(&path, contents)?;
+    if let Ok(metadata) = std::fs::metadata(&path) {
+        let _ = std::fs::set_permissions(&tmp_path, metadata.permissions());
+    }
+    if let Err(e) = rename_with_retries(&tmp_path, &path) {
+        let _ = std::fs::remove_file(&tmp_path);
+        bail!("Cannot replace {}: {e}", path.display());
+    }
+    sync_parent_dir(&path);
+    Ok(())
+}
+
+/// Creates `path` with `contents` unless it exists, without another process ever reading it
+/// empty or half written
+pub(crate) fn create_file_atomically(path: &Path, contents: &[u8]) -> Result<(), Error> {
+    let tmp_path = write_temp_file(path, contents)?;
+    // Unlike a rename, a hard link fails if another process created the file first
+    let linked = std::fs::hard_link(&tmp_path, path);
+    let _ = std::fs::remove_file(&tmp_path);
+    match linked {
+        Ok(()) => {
+            sync_parent_dir(path);
+            Ok(())
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(()),
+        // Hard links are not supported by every file system
+        Err(_) if !path.exists() => write_file_atomically(path, contents),
+        Err(_) => Ok(()),
+    }
+}
+
+/// Writes `contents` to a new file next to `path` and flushes it to disk. Its name is unique to
+/// this process, so that concurrent writers do not write to the same temporary file.
+fn write_temp_file(path: &Path, contents: &[u8]) -> Result<PathBuf, Error> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("Invalid state file path: {}", path.display()))?
+        .to_string_lossy();
+    let tmp_path = path.with_file_name(format!(
+        ".{file_name}.{}.{}.tmp",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let written = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&tmp_path)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        });
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&tmp_path);
+        bail!("Cannot write to {}: {e}", tmp_path.display());
+    }
+    Ok(tmp_path)
+}
+
+/// Renames `from` to `to`, retrying for a while when Windows refuses to replace `to` because
+/// another process (another suiup, an antivirus, an indexer) has it open
+fn rename_with_retries(from: &Path, to: &Path) -> std::io::Result<()> {
+    const ATTEMPTS: u32 = 10;
+
+    let mut attempt = 1;
+    loop {
+        match std::fs::rename(from, to) {
+            Err(e) if attempt < ATTEMPTS && is_sharing_violation(&e) => {
+                std::thread::sleep(Duration::from_millis(20 * u64::from(attempt)));
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(windows)]
+fn is_sharing_violation(e: &std::io::Error) -> bool {
+    // ERROR_ACCESS_DENIED, ERROR_SHARING_VIOLATION and ERROR_LOCK_VIOLATION
+    matches!(e.raw_os_error(), Some(5 | 32 | 33))
+}
+
+#[cfg(not(windows))]
+fn is_sharing_violation(_: &std::io::Error) -> bool {
+    false
+}
+
+/// Flushes the rename of a file in its folder to disk, so that it survives a crash. Windows has
+/// no such thing as syncing a folder.
+fn sync_parent_dir(path: &Path) {
+    #[cfg(unix)]
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::File::open(parent).and_then(|dir| dir.sync_all());
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concurrent_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("installed_binaries.json");
+        let contents = |i: usize| {
+            format!(
+                "{{\"writer\": {i}, \"padding\": \"{}\"}}",
+                "x".repeat(i * 1000)
+            )
+        };
+        std::thread::scope(|scope| {
+            for i in 0..8 {
+                let path = &path;
+                scope.spawn(move || {
+                    for _ in 0..20 {
+                        write_file_atomically(path, contents(i).as_bytes()).unwrap();
+                        let read = std::fs::read_to_string(path).unwrap();
+                        assert!((0..8).any(|i| read == contents(i)), "torn write: {read}");
+                    }
+                });
+            }
+        });
+        // No temporary file is left behind
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_create_file_atomically() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("default_version.json");
+        create_file_atomically(&path, b"first").unwrap();
+        create_file_atomically(&path, b"second").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "first");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_through_symlink() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("dotfiles-config.json");
+        std::fs::write(&target, "{}").unwrap();
+        std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o640)).unwrap();
+        let link = dir.path().join("config.json");
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        write_file_atomically(&link, br#"{"assume_yes": true}"#).unwrap();
+        assert!(link.is_symlink());
+        assert_eq!(
+            std::fs::read_to_string(&target).unwrap(),
+            r#"{"assume_yes": true}"#
+        );
+        let mode = std::fs::metadata(&target).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+    }
+}
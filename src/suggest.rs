@@ -0,0 +1,426 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Suggestions shown along with errors: "did you mean" for misspelled binary names and config
+//! keys, and a fix for common failures such as hitting the GitHub rate limit.
+
+use std::io::ErrorKind;
+
+use anyhow::Error;
+use serde::Serialize;
+
+use crate::redact::redact;
+
+/// Finds the candidate closest to `input`, if it is close enough to be a likely typo
+pub fn closest<'a>(input: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let input = input.to_lowercase();
+    // Allow about one edit (or swap of adjacent characters) per three characters, so short names only match single typos
+    let max_distance = (input.chars().count() / 3).max(1);
+    candidates
+        .into_iter()
+        .map(|candidate| {
+            (
+                strsim::osa_distance(&input, &candidate.to_lowercase()),
+                candidate,
+            )
+        })
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Returns ` Did you mean `<candidate>`?` for the closest candidate, or an empty string, to be
+/// appended to an error message
+pub fn did_you_mean<'a>(input: &str, candidates: impl IntoIterator<Item = &'a str>) -> String {
+    closest(input, candidates)
+        .map(|candidate| format!(" Did you mean `{candidate}`?"))
+        .unwrap_or_default()
+}
+
+/// Failures that have a specific fix
+#[derive(Debug, PartialEq)]
+enum Failure {
+    RateLimit,
+    Dns,
+    Connection,
+    /// The certificate of the server is not trusted, or the TLS handshake failed
+    Tls,
+    /// The proxy set in the environment cannot be reached or refused the connection
+    Proxy,
+    /// The server refused the credentials, or that there are none (HTTP 401 or 403)
+    Unauthorized,
+    /// The server failed to handle the request (HTTP 5xx)
+    ServerError,
+    PermissionDenied,
+    MissingAsset,
+    DiskFull,
+}
+
+impl Failure {
+    /// Stable identifier of the failure, for tools that handle errors (`--error-format json`)
+    fn code(&self) -> &'static str {
+        match self {
+            Failure::RateLimit => "rate_limited",
+            Failure::Dns => "dns_error",
+            Failure::Connection => "connection_failed",
+            Failure::Tls => "tls_error",
+            Failure::Proxy => "proxy_error",
+            Failure::Unauthorized => "unauthorized",
+            Failure::ServerError => "server_error",
+            Failure::PermissionDenied => "permission_denied",
+            Failure::MissingAsset => "release_not_found",
+            Failure::DiskFull => "disk_full",
+        }
+    }
+
+    fn category(&self) -> &'static str {
+        match self {
+            Failure::RateLimit
+            | Failure::Dns
+            | Failure::Connection
+            | Failure::Tls
+            | Failure::Proxy
+            | Failure::Unauthorized
+            | Failure::ServerError => "network",
+            Failure::PermissionDenied | Failure::DiskFull => "filesystem",
+            Failure::MissingAsset => "release",
+        }
+    }
+}
+
+/// An error in the form printed by `--error-format json`
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ErrorReport {
+    pub code: &'static str,
+    pub category: &'static str,
+    pub message: String,
+    pub suggestion: Option<&'static str>,
+}
+
+impl ErrorReport {
+    /// Describes an error suiup failed with. Errors without a specific fix have the `error`
+    /// code and `general` category.
+    pub fn new(err: &Error) -> Self {
+        let failure = classify(err);
+        ErrorReport {
+            code: failure.as_ref().map_or("error", Failure::code),
+            category: failure.as_ref().map_or("general", Failure::category),
+            message: redact(&err.to_string()),
+            suggestion: hint(err),
+        }
+    }
+
+    /// Describes invalid command line arguments
+    pub fn usage(message: &str) -> Self {
+        ErrorReport {
+            code: "invalid_arguments",
+            category: "usage",
+            message: redact(message),
+            suggestion: Some("Run `suiup --help` to see the available commands and options."),
+        }
+    }
+}
+
+/// Returns the underlying errors of `err`, outermost first, leaving out those already included in
+/// the message of the error they caused
+pub fn causes(err: &Error) -> Vec<String> {
+    let mut causes = Vec::new();
+    let mut previous = err.to_string();
+    for cause in err.chain().skip(1) {
+        let cause = cause.to_string();
+        if !previous.contains(&cause) {
+            causes.push(redact(&cause));
+        }
+        previous = cause;
+    }
+    causes
+}
+
+/// Returns an actionable suggestion for an error that suiup failed with, if it is a common
+/// failure
+pub fn hint(err: &Error) -> Option<&'static str> {
+    let hint = match classify(err)? {
+        Failure::RateLimit => {
+            "GitHub limits unauthenticated requests. Pass a token with `--github-token` or the GITHUB_TOKEN environment variable, or wait for the limit to reset."
+        }
+        Failure::Dns => {
+            "The host name could not be resolved. Check your internet connection and DNS settings, and set HTTPS_PROXY if you are behind a proxy. `suiup doctor` checks the connectivity to GitHub."
+        }
+        Failure::Connection => {
+            "Could not connect to the server. Check your internet connection and firewall, and set HTTPS_PROXY if you are behind a proxy. `suiup doctor` checks the connectivity to GitHub."
+        }
+        Failure::Tls => {
+            "The certificate of the server is not trusted. Behind a proxy that inspects HTTPS traffic, add its certificate with `tls.ca_bundle` in the config file, and update `tls.pinned_certificates` if the server changed its certificate. Also check the system clock is right."
+        }
+        Failure::Proxy => {
+            "Could not connect through the proxy. Check the HTTPS_PROXY, HTTP_PROXY and ALL_PROXY environment variables, and NO_PROXY for the hosts to reach directly."
+        }
+        Failure::Unauthorized => {
+            "The server refused the request. Check the token passed with `--github-token` or GITHUB_TOKEN is valid and not expired, or leave it out for public releases."
+        }
+        Failure::ServerError => {
+            "The server failed to handle the request, which is usually temporary. Try again later, or check https://www.githubstatus.com for a GitHub outage."
+        }
+        Failure::PermissionDenied => {
+            "Check that you can write to the suiup folders listed by `suiup doctor`. Managing the system-wide installation (`--system`) requires sudo or an Administrator terminal."
+        }
+        Failure::MissingAsset => {
+            "Check the version and network exist on the releases page of the project, or omit the version to install the latest one (e.g., `suiup install sui@testnet`)."
+        }
+        Failure::DiskFull => {
+            "The disk is full. Free up space, e.g. by removing cached release archives with `suiup cleanup --all`."
+        }
+    };
+    Some(hint)
+}
+
+fn classify(err: &Error) -> Option<Failure> {
+    classify_with_proxy(err, proxy_configured())
+}
+
+/// Whether requests go through a proxy set in the environment, as reqwest reads it
+fn proxy_configured() -> bool {
+    [
+        "HTTPS_PROXY",
+        "https_proxy",
+        "HTTP_PROXY",
+        "http_proxy",
+        "ALL_PROXY",
+        "all_proxy",
+    ]
+    .iter()
+    .any(|var| std::env::var(var).is_ok_and(|value| !value.is_empty()))
+}
+
+/// Classifies `err`. Through a proxy, the connection that failed is the one to the proxy.
+fn classify_with_proxy(err: &Error, proxy: bool) -> Option<Failure> {
+    let failure = classify_cause(err)?;
+    if proxy && matches!(failure, Failure::Dns | Failure::Connection) {
+        return Some(Failure::Proxy);
+    }
+    Some(failure)
+}
+
+fn classify_cause(err: &Error) -> Option<Failure> {
+    // Most errors are wrapped into messages, so fall back to their text
+    let text = err
+        .chain()
+        .map(|cause| cause.to_string().to_lowercase())
+        .collect::<Vec<_>>()
+        .join(": ");
+    let contains_any = |patterns: &[&str]| patterns.iter().any(|p| text.contains(p));
+    let is_rate_limit = contains_any(&["rate limit", "429 too many requests"]);
+
+    for cause in err.chain() {
+        if let Some(e) = cause.downcast_ref::<std::io::Error>() {
+            match e.kind() {
+                ErrorKind::PermissionDenied => return Some(Failure::PermissionDenied),
+                ErrorKind::StorageFull => return Some(Failure::DiskFull),
+                _ => {}
+            }
+        }
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            match e.status() {
+                Some(reqwest::StatusCode::TOO_MANY_REQUESTS) => return Some(Failure::RateLimit),
+                Some(reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN)
+                    if !is_rate_limit =>
+                {
+                    return Some(Failure::Unauthorized)
+                }
+                Some(status) if status.is_server_error() => return Some(Failure::ServerError),
+                _ => {}
+            }
+        }
+    }
+
+    // Checked from the most to the least specific, a TLS failure is also a connection failure
+    if is_rate_limit {
+        Some(Failure::RateLimit)
+    } else if contains_any(&[
+        "tunnel error",
+        "unsuccessful tunnel",
+        "proxy authentication required",
+    ]) {
+        Some(Failure::Proxy)
+    } else if contains_any(&[
+        "invalid peer certificate",
+        "certificate verify failed",
+        "received fatal alert",
+        "handshake",
+        "pinned certificate",
+    ]) {
+        Some(Failure::Tls)
+    } else if contains_any(&[
+        "dns error",
+        "failed to lookup address",
+        "name or service not known",
+        "no such host",
+    ]) {
+        Some(Failure::Dns)
+    } else if contains_any(&[
+        "error sending request",
+        "connection refused",
+        "operation timed out",
+    ]) {
+        Some(Failure::Connection)
+    } else if contains_any(&["401 unauthorized", "403 forbidden", "bad credentials"]) {
+        Some(Failure::Unauthorized)
+    } else if contains_any(&[
+        "500 internal server error",
+        "502 bad gateway",
+        "503 service unavailable",
+        "504 gateway timeout",
+    ]) {
+        Some(Failure::ServerError)
+    } else if contains_any(&["permission denied", "access is denied", "os error 13"]) {
+        Some(Failure::PermissionDenied)
+    } else if contains_any(&["no space left", "not enough space", "os error 28"]) {
+        Some(Failure::DiskFull)
+    } else if text.contains("not found")
+        && contains_any(&["release", "asset", "version"])
+        && !text.contains("installed")
+    {
+        Some(Failure::MissingAsset)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_closest() {
+        let binaries = ["sui", "mvr", "walrus", "site-builder"];
+        assert_eq!(closest("walruss", binaries), Some("walrus"));
+        assert_eq!(closest("Sui", binaries), Some("sui"));
+        assert_eq!(closest("site_builder", binaries), Some("site-builder"));
+        assert_eq!(closest("su", binaries), Some("sui"));
+        assert_eq!(closest("cargo", binaries), None);
+        assert_eq!(did_you_mean("mvrr", binaries), " Did you mean `mvr`?");
+        assert_eq!(did_you_mean("foo", binaries), "");
+    }
+
+    #[test]
+    fn test_causes() {
+        let err = Error::from(std::io::Error::from(ErrorKind::NotFound))
+            .context("Cannot read from file etag_MystenLabs_sui.txt");
+        assert_eq!(causes(&err), ["entity not found"]);
+        // Causes already in the message are not repeated
+        let err = anyhow!("Cannot open archive: entity not found").context("Cannot install sui");
+        assert_eq!(causes(&err), ["Cannot open archive: entity not found"]);
+        assert!(causes(&anyhow!("Cannot open archive: entity not found")).is_empty());
+    }
+
+    #[test]
+    fn test_classify() {
+        let classify = |err: &Error| classify_with_proxy(err, false);
+        let io = |kind: ErrorKind| Error::from(std::io::Error::from(kind));
+        assert_eq!(
+            classify(&io(ErrorKind::PermissionDenied).context("Cannot copy")),
+            Some(Failure::PermissionDenied)
+        );
+        assert_eq!(
+            classify(&io(ErrorKind::StorageFull)),
+            Some(Failure::DiskFull)
+        );
+        assert_eq!(
+            classify(&anyhow!(
+                "Could not send request: error sending request: dns error: failed to lookup address information"
+            )),
+            Some(Failure::Dns)
+        );
+        assert_eq!(
+            classify(&anyhow!(
+                "Could not send request: error sending request for url (https://api.github.com/)"
+            )),
+            Some(Failure::Connection)
+        );
+        assert_eq!(
+            classify(&anyhow!(
+                "403 Forbidden: API rate limit exceeded for 1.2.3.4"
+            )),
+            Some(Failure::RateLimit)
+        );
+        assert_eq!(
+            classify(&anyhow!("Release testnet-v1.99.0 not found")),
+            Some(Failure::MissingAsset)
+        );
+        assert_eq!(
+            classify(&anyhow!(
+                "Binary sui not found in installed binaries. Use `suiup show` to see installed binaries."
+            )),
+            None
+        );
+        assert!(hint(&anyhow!("something else")).is_none());
+    }
+
+    #[test]
+    fn test_classify_network() {
+        let classify = |message: &str, proxy| classify_with_proxy(&anyhow!("{message}"), proxy);
+        let dns = "Cannot reach https://api.github.com/: error sending request: client error (Connect): dns error: failed to lookup address information";
+        assert_eq!(classify(dns, false), Some(Failure::Dns));
+        assert_eq!(classify(dns, true), Some(Failure::Proxy));
+        assert_eq!(
+            classify(
+                "error sending request: client error (Connect): invalid peer certificate: UnknownIssuer",
+                false
+            ),
+            Some(Failure::Tls)
+        );
+        assert_eq!(
+            classify(
+                "error sending request: client error (Connect): unexpected error: the certificate of mirror.example.com does not match any pinned certificate",
+                true
+            ),
+            Some(Failure::Tls)
+        );
+        assert_eq!(
+            classify("error sending request: unsuccessful tunnel", false),
+            Some(Failure::Proxy)
+        );
+        assert_eq!(
+            classify(
+                "Cannot use the GitHub API: the server responded 401 Unauthorized",
+                false
+            ),
+            Some(Failure::Unauthorized)
+        );
+        assert_eq!(
+            classify("API rate limit exceeded (403 Forbidden)", false),
+            Some(Failure::RateLimit)
+        );
+        assert_eq!(
+            classify("the server responded 503 Service Unavailable", false),
+            Some(Failure::ServerError)
+        );
+        // A host name is not taken for a cause
+        assert_eq!(
+            classify(
+                "Cannot reach https://tls-proxy.example.com/: error sending request: connection refused",
+                false
+            ),
+            Some(Failure::Connection)
+        );
+
+        let report = ErrorReport::new(&anyhow!("invalid peer certificate: UnknownIssuer"));
+        assert_eq!(report.code, "tls_error");
+        assert_eq!(report.category, "network");
+    }
+
+    #[test]
+    fn test_error_report() {
+        let report = ErrorReport::new(&anyhow!("Release testnet-v1.99.0 not found"));
+        assert_eq!(report.code, "release_not_found");
+        assert_eq!(report.category, "release");
+        assert!(report.suggestion.is_some());
+
+        let report = ErrorReport::new(&anyhow!("something else"));
+        assert_eq!(
+            serde_json::to_string(&report).unwrap(),
+            r#"{"code":"error","category":"general","message":"something else","suggestion":null}"#
+        );
+    }
+}
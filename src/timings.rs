@@ -0,0 +1,149 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Time spent in each phase of a command (resolving releases, downloading, verifying,
+//! extracting, registering, cleaning up), reported on stderr with `--timings` to find slow
+//! mirrors, disks or API calls.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use indicatif::HumanBytes;
+
+/// Set by `--timings`, see [`set_enabled`]
+static ENABLED: AtomicBool = AtomicBool::new(false);
+/// Phases in the order they first ran
+static PHASES: Mutex<Vec<PhaseTime>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Clone, PartialEq)]
+struct PhaseTime {
+    name: &'static str,
+    elapsed: Duration,
+    bytes: u64,
+}
+
+/// Records the time of the phases for the rest of the process
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Measures a phase until the returned timer is dropped, e.g.
+/// `let _timer = timings::phase("extract");`. The times of a phase measured several times add
+/// up.
+pub fn phase(name: &'static str) -> PhaseTimer {
+    PhaseTimer {
+        name,
+        start: is_enabled().then(Instant::now),
+    }
+}
+
+pub struct PhaseTimer {
+    name: &'static str,
+    start: Option<Instant>,
+}
+
+impl Drop for PhaseTimer {
+    fn drop(&mut self) {
+        if let Some(start) = self.start {
+            record(self.name, start.elapsed(), 0);
+        }
+    }
+}
+
+/// Adds the bytes transferred in a phase, to report its throughput
+pub fn add_bytes(name: &'static str, bytes: u64) {
+    if is_enabled() {
+        record(name, Duration::ZERO, bytes);
+    }
+}
+
+fn record(name: &'static str, elapsed: Duration, bytes: u64) {
+    let mut phases = PHASES.lock().unwrap_or_else(|e| e.into_inner());
+    match phases.iter_mut().find(|p| p.name == name) {
+        Some(phase) => {
+            phase.elapsed += elapsed;
+            phase.bytes += bytes;
+        }
+        None => phases.push(PhaseTime {
+            name,
+            elapsed,
+            bytes,
+        }),
+    }
+}
+
+/// Prints the time of each phase and the total time of the command on stderr, with `--timings`
+pub fn report(total: Duration) {
+    if !is_enabled() {
+        return;
+    }
+    let phases = PHASES.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    eprintln!("\nTimings:");
+    for line in report_lines(&phases, total) {
+        eprintln!("  {line}");
+    }
+}
+
+fn report_lines(phases: &[PhaseTime], total: Duration) -> Vec<String> {
+    let mut lines = phases
+        .iter()
+        .map(|phase| {
+            let mut line = format!("{:<10} {:>8}", phase.name, seconds(phase.elapsed));
+            if phase.bytes > 0 {
+                line.push_str(&format!("  {}", HumanBytes(phase.bytes)));
+                let secs = phase.elapsed.as_secs_f64();
+                if secs > 0.0 {
+                    let rate = (phase.bytes as f64 / secs) as u64;
+                    line.push_str(&format!(" at {}/s", HumanBytes(rate)));
+                }
+            }
+            line
+        })
+        .collect::<Vec<_>>();
+    // Phases of parallel installs overlap, their sum can exceed the total
+    let other = total.saturating_sub(phases.iter().map(|p| p.elapsed).sum());
+    if !phases.is_empty() && other > Duration::ZERO {
+        lines.push(format!("{:<10} {:>8}", "other", seconds(other)));
+    }
+    lines.push(format!("{:<10} {:>8}", "total", seconds(total)));
+    lines
+}
+
+fn seconds(duration: Duration) -> String {
+    format!("{:.2}s", duration.as_secs_f64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_lines() {
+        let phases = vec![
+            PhaseTime {
+                name: "resolve",
+                elapsed: Duration::from_millis(250),
+                bytes: 0,
+            },
+            PhaseTime {
+                name: "download",
+                elapsed: Duration::from_secs(2),
+                bytes: 4 * 1024 * 1024,
+            },
+        ];
+        assert_eq!(
+            report_lines(&phases, Duration::from_secs(3)),
+            vec![
+                "resolve       0.25s",
+                "download      2.00s  4.00 MiB at 2.00 MiB/s",
+                "other         0.75s",
+                "total         3.00s",
+            ]
+        );
+    }
+}
@@ -0,0 +1,175 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Error};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::crypto::ring;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use sha2::{Digest, Sha256};
+
+use crate::config::{TlsConfig, TlsVersion};
+
+/// Builds the rustls configuration for the TLS settings of the config file: trusted roots
+/// extended with the CA bundle, the minimum protocol version and certificate pinning.
+pub fn client_config(tls: &TlsConfig) -> Result<ClientConfig, Error> {
+    let provider = Arc::new(ring::default_provider());
+
+    let mut roots = RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    if let Some(path) = &tls.ca_bundle {
+        let certs = CertificateDer::pem_file_iter(path)
+            .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| anyhow!("Cannot read the CA bundle {}: {e}", path.display()))?;
+        if certs.is_empty() {
+            return Err(anyhow!(
+                "The CA bundle {} does not contain any certificate",
+                path.display()
+            ));
+        }
+        for cert in certs {
+            roots
+                .add(cert)
+                .map_err(|e| anyhow!("Invalid certificate in {}: {e}", path.display()))?;
+        }
+    }
+
+    let versions: &[&rustls::SupportedProtocolVersion] = match tls.min_version {
+        TlsVersion::Tls12 => &[&rustls::version::TLS13, &rustls::version::TLS12],
+        TlsVersion::Tls13 => &[&rustls::version::TLS13],
+    };
+
+    let verifier = PinningVerifier {
+        inner: WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+            .build()?,
+        pins: tls
+            .pinned_certificates
+            .iter()
+            .map(|(host, pins)| {
+                let pins = pins.iter().map(|pin| normalize_fingerprint(pin)).collect();
+                (host.to_lowercase(), pins)
+            })
+            .collect(),
+    };
+
+    let mut config = ClientConfig::builder_with_provider(provider)
+        .with_protocol_versions(versions)?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier))
+        .with_no_client_auth();
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    Ok(config)
+}
+
+/// Verifies certificates as usual and, for pinned hosts, additionally requires one of the
+/// certificates in the chain to match a pinned fingerprint.
+#[derive(Debug)]
+struct PinningVerifier {
+    inner: Arc<WebPkiServerVerifier>,
+    /// Host to the accepted SHA-256 fingerprints (lowercase hex, no separators)
+    pins: BTreeMap<String, Vec<String>>,
+}
+
+impl ServerCertVerifier for PinningVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let verified = self.inner.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            ocsp_response,
+            now,
+        )?;
+
+        let host = server_name.to_str().to_lowercase();
+        if let Some(pins) = self.pins.get(&host) {
+            let pinned = std::iter::once(end_entity)
+                .chain(intermediates)
+                .any(|cert| pins.contains(&fingerprint(cert)));
+            if !pinned {
+                return Err(rustls::Error::General(format!(
+                    "the certificate of {host} does not match any pinned certificate"
+                )));
+            }
+        }
+        Ok(verified)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
+/// SHA-256 fingerprint of a DER encoded certificate, as lowercase hex
+fn fingerprint(cert: &CertificateDer<'_>) -> String {
+    format!("{:x}", Sha256::digest(cert.as_ref()))
+}
+
+/// Accepts fingerprints as printed by common tools, e.g. `SHA256:AB:CD:...` or `abcd...`
+fn normalize_fingerprint(pin: &str) -> String {
+    let pin = pin.trim();
+    let pin = pin
+        .get(..7)
+        .filter(|prefix| prefix.eq_ignore_ascii_case("sha256:"))
+        .map_or(pin, |_| &pin[7..]);
+    pin.chars()
+        .filter(|c| *c != ':')
+        .collect::<String>()
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_fingerprint() {
+        assert_eq!(normalize_fingerprint("SHA256:AB:cd:01"), "abcd01");
+        assert_eq!(normalize_fingerprint(" abcd01 "), "abcd01");
+        assert_eq!(normalize_fingerprint("AB:CD"), "abcd");
+    }
+
+    #[test]
+    fn test_client_config() {
+        let mut tls = TlsConfig {
+            min_version: TlsVersion::Tls13,
+            ca_bundle: None,
+            pinned_certificates: [("Mirror.Example.com".to_string(), vec!["AB:CD".to_string()])]
+                .into(),
+        };
+        assert!(client_config(&tls).is_ok());
+
+        tls.ca_bundle = Some("/does/not/exist.pem".into());
+        assert!(client_config(&tls).is_err());
+    }
+}
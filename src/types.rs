@@ -2,22 +2,152 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::{anyhow, Error};
-use std::io::Write;
 use std::{
     collections::BTreeMap,
     fmt::{self, Display, Formatter},
-    path::PathBuf,
+    path::Path,
     str::FromStr,
 };
 
+use chrono::{DateTime, Utc};
 use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
 
+use crate::config::load_config;
 use crate::paths::{default_file_path, installed_binaries_file};
+use crate::state::migrations::{self, SCHEMA_VERSION};
+use crate::state::{create_file_atomically, read_state_file, write_file_atomically};
 
-pub type Version = String;
+/// A release version, parsed from the strings of release tags, asset names, specs and the
+/// state files, e.g. `testnet-v1.40.1`, `v1.40.1`, `1.40.1+debug` or `1.40`.
+///
+/// Versions are ordered by their numbers as semver, so that `v1.10.0` is newer than `v1.9.0`
+/// and a pre-release older than its release. The network is not part of the order: compare it
+/// separately when it matters.
+#[derive(Debug, Clone)]
+pub struct Version {
+    /// Network of a network release
+    pub network: Option<Network>,
+    pub semver: semver::Version,
+    /// Number of the components given, 2 for `1.40`, which [`Version::matches`] any `1.40.x`
+    components: usize,
+}
+
+impl Version {
+    /// Whether `version` is in the range this version stands for: the same release, or any
+    /// patch release for e.g. `1.40`, on the same network if both have one
+    pub fn matches(&self, version: &Version) -> bool {
+        let (range, version_semver) = (&self.semver, &version.semver);
+        let same_network = match (&self.network, &version.network) {
+            (Some(a), Some(b)) => a == b,
+            _ => true,
+        };
+        same_network
+            && range.major == version_semver.major
+            && (self.components < 2 || range.minor == version_semver.minor)
+            && (self.components < 3
+                || (range.patch == version_semver.patch && range.pre == version_semver.pre))
+            && (range.build.is_empty() || range.build == version_semver.build)
+    }
+
+    /// Whether it names a single release rather than a range, e.g. `1.40.1` but not `1.40`
+    pub fn is_exact(&self) -> bool {
+        self.components == 3
+    }
+
+    /// The version with the `v` prefix and without the network, e.g. `v1.40.1`, as stored in
+    /// the state files and shown to users
+    pub fn tag(&self) -> String {
+        let semver = &self.semver;
+        let mut tag = format!("v{}", semver.major);
+        if self.components >= 2 {
+            tag.push_str(&format!(".{}", semver.minor));
+        }
+        if self.components >= 3 {
+            tag.push_str(&format!(".{}", semver.patch));
+        }
+        if !semver.pre.is_empty() {
+            tag.push_str(&format!("-{}", semver.pre));
+        }
+        if !semver.build.is_empty() {
+            tag.push_str(&format!("+{}", semver.build));
+        }
+        tag
+    }
+}
+
+impl FromStr for Version {
+    type Err = Error;
 
-#[derive(Debug)]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid =
+            || anyhow!("Invalid version `{s}`, expected e.g. `1.40.1` or `testnet-v1.40.1`");
+        let (network, rest) = match s.trim().split_once('-') {
+            Some((network, rest))
+                if !network.is_empty() && network.chars().all(|c| c.is_ascii_alphabetic()) =>
+            {
+                (Some(network.parse::<Network>()?), rest)
+            }
+            _ => (None, s.trim()),
+        };
+        let rest = rest.strip_prefix(['v', 'V']).unwrap_or(rest);
+        let (rest, build) = rest.split_once('+').unwrap_or((rest, ""));
+        let (numbers, pre) = rest.split_once('-').unwrap_or((rest, ""));
+        let numbers = numbers
+            .split('.')
+            .map(|n| {
+                n.parse::<u64>()
+                    .ok()
+                    .filter(|_| n.chars().all(|c| c.is_ascii_digit()))
+            })
+            .collect::<Option<Vec<_>>>()
+            .filter(|numbers| (1..=3).contains(&numbers.len()))
+            .ok_or_else(invalid)?;
+        let semver = semver::Version {
+            major: numbers[0],
+            minor: numbers.get(1).copied().unwrap_or(0),
+            patch: numbers.get(2).copied().unwrap_or(0),
+            pre: semver::Prerelease::new(pre).map_err(|_| invalid())?,
+            build: semver::BuildMetadata::new(build).map_err(|_| invalid())?,
+        };
+        Ok(Version {
+            network,
+            semver,
+            components: numbers.len(),
+        })
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match &self.network {
+            Some(network) => write!(f, "{network}-{}", self.tag()),
+            None => write!(f, "{}", self.tag()),
+        }
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.semver == other.semver
+    }
+}
+
+impl Eq for Version {}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.semver.cmp(&other.semver)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Repo {
     Sui,
     Mvr,
@@ -37,26 +167,56 @@ impl Repo {
     }
 }
 
-impl Display for Repo {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+impl Repo {
+    /// The MystenLabs repository, as `owner/name`
+    pub fn upstream(&self) -> &'static str {
         match self {
-            Self::Mvr => write!(f, "MystenLabs/mvr"),
-            Self::Sui => write!(f, "MystenLabs/sui"),
-            Self::Walrus => write!(f, "MystenLabs/walrus"),
-            Self::WalrusSites => write!(f, "MystenLabs/walrus-sites"),
+            Self::Mvr => "MystenLabs/mvr",
+            Self::Sui => "MystenLabs/sui",
+            Self::Walrus => "MystenLabs/walrus",
+            Self::WalrusSites => "MystenLabs/walrus-sites",
         }
     }
+
+    /// The repository as `owner/name`: the one set in `repositories` in the config file, or the
+    /// MystenLabs one. Fails if the config file is invalid, rather than downloading from the
+    /// MystenLabs repository in place of a private fork.
+    pub fn repository(&self) -> Result<String, Error> {
+        Ok(load_config()?
+            .repository(*self)
+            .unwrap_or(self.upstream())
+            .to_string())
+    }
+
+    /// Whether `repositories` in the config file replaces the MystenLabs repository, e.g. with
+    /// a private fork
+    pub fn is_overridden(&self) -> Result<bool, Error> {
+        Ok(load_config()?.repository(*self).is_some())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Release {
+    /// Tag of the release, e.g. `testnet-v1.40.1`. Missing in release lists cached by older
+    /// versions of suiup.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag_name: Option<String>,
     pub assets: Vec<Asset>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Asset {
     pub browser_download_url: String,
+    /// API URL of the asset, which downloads it with a token when the repository is private
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
     pub name: String,
+    /// Size in bytes
+    #[serde(default)]
+    pub size: u64,
+    /// Checksum GitHub publishes for the asset, e.g. `sha256:<hex>`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
 }
 
 pub struct Binaries {
@@ -69,9 +229,26 @@ pub struct DefaultBinaries {
 }
 
 /// Struct to store the installed binaries
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct InstalledBinaries {
+    /// Version of the file schema, see [`crate::state::migrations`]
+    schema_version: u64,
     binaries: Vec<BinaryVersion>,
+    /// Nightly builds kept to switch back to, the active one included
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    nightly_builds: Vec<NightlyBuild>,
+}
+
+/// A nightly build kept after it was installed, in its own folder named after the commit
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct NightlyBuild {
+    pub binary_name: String,
+    pub branch: String,
+    pub commit: String,
+    pub debug: bool,
+    pub built_at: DateTime<Utc>,
+    /// Path to the kept copy of the binary
+    pub path: String,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
@@ -86,8 +263,125 @@ pub struct BinaryVersion {
     pub debug: bool,
     /// Path to the binary
     pub path: Option<String>,
+    /// Where the binary came from and when it was installed. Not available for binaries
+    /// installed with older versions of suiup.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    /// When the binary was last run through `suiup run`, `shell` or `localnet`, or switched to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used: Option<DateTime<Utc>>,
 }
 
+/// Name of the binary in the default bin folder and key of its entry in the default versions,
+/// debug builds are `<binary>-debug` so they can be the default next to release builds
+pub fn default_binary_name(binary_name: &str, debug: bool) -> String {
+    if debug {
+        format!("{binary_name}-debug")
+    } else {
+        binary_name.to_string()
+    }
+}
+
+impl BinaryVersion {
+    /// Checks if both entries refer to the same installed binary, ignoring the metadata
+    pub fn same_binary(&self, other: &BinaryVersion) -> bool {
+        self.binary_name == other.binary_name
+            && self.network_release == other.network_release
+            && self.version == other.version
+            && self.debug == other.debug
+    }
+
+    /// The parsed version, with the network of network releases. `None` for nightly builds,
+    /// which are versioned by their branch.
+    pub fn release_version(&self) -> Option<Version> {
+        let mut version = self.version.parse::<Version>().ok()?;
+        version.network = self.network_release.parse().ok();
+        Some(version)
+    }
+}
+
+/// How a binary was built
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum BuildType {
+    /// Release build from a published release archive
+    Release,
+    /// Debug build from a published release archive (sui-debug)
+    Debug,
+    /// Built from a branch with `cargo install`
+    Nightly,
+}
+
+/// What installed a binary, for auditing shared machines
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum InstalledBy {
+    /// A user running `suiup install`
+    User,
+    /// A CI job, detected with the `CI` environment variable
+    Ci,
+    /// `suiup install --manifest`
+    Manifest,
+    /// `suiup update` or `suiup upgrade`
+    Update,
+}
+
+impl std::fmt::Display for InstalledBy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            InstalledBy::User => "user",
+            InstalledBy::Ci => "CI",
+            InstalledBy::Manifest => "manifest",
+            InstalledBy::Update => "update",
+        })
+    }
+}
+
+/// Records where an installed binary came from, for auditing what exactly is running
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct Provenance {
+    pub build_type: BuildType,
+    /// URL the release archive or binary was downloaded from
+    pub source_url: Option<String>,
+    /// Name of the release archive the binary was extracted from
+    pub archive: Option<String>,
+    /// BLAKE3 checksum of the download: the release archive, or the binary itself for standalone
+    /// binaries and sources serving binaries
+    #[serde(
+        default,
+        alias = "archive_blake3",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub download_blake3: Option<String>,
+    /// md5 checksum of the download, recorded by older versions of suiup
+    #[serde(
+        default,
+        alias = "archive_md5",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub download_md5: Option<String>,
+    /// BLAKE3 checksum of the installed binary, which `suiup doctor` checks it against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binary_blake3: Option<String>,
+    /// Branch the binary was built from (nightly only)
+    pub branch: Option<String>,
+    /// Commit the binary was built from (nightly only)
+    pub commit: Option<String>,
+    pub installed_at: DateTime<Utc>,
+    /// What installed the binary, recorded since suiup tracks it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub installed_by: Option<InstalledBy>,
+    /// Account that ran the install
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Command line of the install, with secrets redacted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+/// A Sui network, whose releases are published separately, e.g. `testnet-v1.40.1`. Binaries
+/// that are not released per network (mvr) and nightly builds have a release name instead, e.g.
+/// `standalone` or the branch, which is why installed binaries keep it as a string.
 #[derive(
     Copy, Deserialize, Serialize, Hash, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, ValueEnum,
 )]
@@ -101,43 +395,45 @@ pub enum Network {
     Mainnet,
 }
 
-impl InstalledBinaries {
-    pub fn create_file(path: &PathBuf) -> Result<(), Error> {
-        let binaries = InstalledBinaries { binaries: vec![] };
-        let s = serde_json::to_string_pretty(&binaries)
-            .map_err(|e| anyhow!("Cannot serialize the installed binaries to file: {e}"))?;
-        let mut file = std::fs::File::create(path)
-            .map_err(|e| anyhow!("Cannot create this file {}: {e}", path.display()))?;
-        file.write_all(s.as_bytes())
-            .map_err(|e| anyhow!("Cannot write to {}: {e}", path.display()))?;
-        Ok(())
+impl Default for InstalledBinaries {
+    fn default() -> Self {
+        InstalledBinaries {
+            schema_version: SCHEMA_VERSION,
+            binaries: vec![],
+            nightly_builds: vec![],
+        }
     }
+}
 
-    pub fn new() -> Result<Self, Error> {
-        Self::read_from_file()
+impl InstalledBinaries {
+    pub fn create_file(path: &Path) -> Result<(), Error> {
+        let s = serde_json::to_string_pretty(&InstalledBinaries::default())
+            .map_err(|e| anyhow!("Cannot serialize the installed binaries to file: {e}"))?;
+        create_file_atomically(path, s.as_bytes())
+            .map_err(|e| anyhow!("Cannot create this file {}: {e}", path.display()))
     }
 
     /// Save the installed binaries data to the installed binaries JSON file
     pub fn save_to_file(&self) -> Result<(), Error> {
         let s = serde_json::to_string_pretty(self)
-            .map_err(|e| anyhow!("Cannot read the installed binaries file: {e}"))?;
-        std::fs::write(installed_binaries_file()?, s)
-            .map_err(|e| anyhow!("Cannot serialize the installed binaries to file: {e}"))?;
+            .map_err(|e| anyhow!("Cannot serialize the installed binaries: {e}"))?;
+        write_file_atomically(&installed_binaries_file()?, s.as_bytes())
+            .map_err(|e| anyhow!("Cannot save the installed binaries to file: {e}"))?;
         Ok(())
     }
 
-    /// Read the installed binaries JSON file
+    /// Read the installed binaries JSON file, migrating it to the current schema if needed
     pub fn read_from_file() -> Result<Self, Error> {
-        let s = std::fs::read_to_string(installed_binaries_file()?)
-            .map_err(|e| anyhow!("Cannot read from the installed binaries file: {e}"))?;
-        let binaries: InstalledBinaries = serde_json::from_str(&s)
-            .map_err(|e| anyhow!("Cannot deserialize from installed binaries file: {e}"))?;
-        Ok(binaries)
+        read_state_file(&installed_binaries_file()?, migrations::INSTALLED_BINARIES)
     }
 
-    /// Add a binary to the installed binaries JSON file
-    pub fn add_binary(&mut self, binary: BinaryVersion) {
-        if !self.binaries.iter().any(|b| b == &binary) {
+    /// Add a binary to the installed binaries JSON file. If the binary is already registered,
+    /// its path and provenance are replaced, but its usage is kept.
+    pub fn add_binary(&mut self, mut binary: BinaryVersion) {
+        if let Some(existing) = self.binaries.iter_mut().find(|b| b.same_binary(&binary)) {
+            binary.last_used = binary.last_used.or(existing.last_used);
+            *existing = binary;
+        } else {
             self.binaries.push(binary);
         }
     }
@@ -145,12 +441,56 @@ impl InstalledBinaries {
     /// Remove a binary from the installed binaries JSON file
     pub fn remove_binary(&mut self, binary: &str) {
         self.binaries.retain(|b| b.binary_name != binary);
+        self.nightly_builds.retain(|b| b.binary_name != binary);
+    }
+
+    /// Remove a single version of a binary from the installed binaries JSON file
+    pub fn remove_version(&mut self, binary: &BinaryVersion) {
+        self.binaries.retain(|b| !b.same_binary(binary));
+    }
+
+    /// The kept nightly builds
+    pub fn nightly_builds(&self) -> &[NightlyBuild] {
+        &self.nightly_builds
+    }
+
+    /// Records a kept nightly build, replacing the one of the same commit if any. Returns the
+    /// builds of the same binary and branch beyond the `keep` most recent ones, which are no
+    /// longer recorded.
+    pub fn add_nightly_build(&mut self, build: NightlyBuild, keep: usize) -> Vec<NightlyBuild> {
+        let same = |a: &NightlyBuild, b: &NightlyBuild| {
+            a.binary_name == b.binary_name && a.branch == b.branch && a.debug == b.debug
+        };
+        self.nightly_builds
+            .retain(|b| !(same(b, &build) && b.commit == build.commit));
+        let mut kept = self
+            .nightly_builds
+            .iter()
+            .filter(|b| same(b, &build))
+            .map(|b| b.built_at)
+            .collect::<Vec<_>>();
+        kept.sort_unstable_by(|a, b| b.cmp(a));
+        // The new build takes one of the places
+        let cutoff = kept.get(keep.saturating_sub(1)).copied();
+        let (removed, nightly_builds) = std::mem::take(&mut self.nightly_builds)
+            .into_iter()
+            .partition(|b| same(b, &build) && cutoff.is_some_and(|cutoff| b.built_at <= cutoff));
+        self.nightly_builds = nightly_builds;
+        self.nightly_builds.push(build);
+        removed
     }
 
     /// List the binaries in the installed binaries JSON file
     pub fn binaries(&self) -> &[BinaryVersion] {
         &self.binaries
     }
+
+    /// Record that the binary was just run
+    pub fn mark_used(&mut self, binary: &BinaryVersion) {
+        if let Some(b) = self.binaries.iter_mut().find(|b| b.same_binary(binary)) {
+            b.last_used = Some(Utc::now());
+        }
+    }
 }
 
 impl DefaultBinaries {
@@ -200,26 +540,40 @@ impl Display for BinaryVersion {
     }
 }
 
-impl Display for Network {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+impl Network {
+    pub const ALL: [Network; 3] = [Network::Testnet, Network::Devnet, Network::Mainnet];
+
+    pub fn as_str(&self) -> &'static str {
         match self {
-            Network::Testnet => write!(f, "testnet"),
-            Network::Devnet => write!(f, "devnet"),
-            Network::Mainnet => write!(f, "mainnet"),
+            Network::Testnet => "testnet",
+            Network::Devnet => "devnet",
+            Network::Mainnet => "mainnet",
         }
     }
 }
 
-impl From<BTreeMap<String, (String, Version, bool)>> for Binaries {
-    fn from(map: BTreeMap<String, (String, Version, bool)>) -> Self {
+impl Display for Network {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl From<BTreeMap<String, (String, String, bool)>> for Binaries {
+    fn from(map: BTreeMap<String, (String, String, bool)>) -> Self {
         let binaries = map
             .iter()
             .map(|(k, v)| BinaryVersion {
-                binary_name: k.to_string(),
+                binary_name: if v.2 {
+                    k.strip_suffix("-debug").unwrap_or(k).to_string()
+                } else {
+                    k.to_string()
+                },
                 network_release: v.0.clone(),
                 version: v.1.to_string(),
                 debug: v.2,
                 path: None,
+                provenance: None,
+                last_used: None,
             })
             .collect();
         Binaries { binaries }
@@ -234,7 +588,66 @@ impl FromStr for Network {
             "testnet" => Ok(Network::Testnet),
             "devnet" => Ok(Network::Devnet),
             "mainnet" => Ok(Network::Mainnet),
-            _ => Err(anyhow!("Invalid network")),
+            _ => Err(anyhow!(
+                "Unknown network `{s}`, expected testnet, devnet or mainnet"
+            )),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(s: &str) -> Version {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_provenance_download_checksums() {
+        // State files written before the checksums were renamed
+        let provenance: Provenance = serde_json::from_str(
+            r#"{
+                "build_type": "release",
+                "source_url": null,
+                "archive": "sui-testnet-v1.40.1-ubuntu-x86_64.tgz",
+                "archive_blake3": "af13",
+                "archive_md5": "d41d",
+                "branch": null,
+                "commit": null,
+                "installed_at": "2025-07-01T00:00:00Z"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(provenance.download_blake3.as_deref(), Some("af13"));
+        assert_eq!(provenance.download_md5.as_deref(), Some("d41d"));
+        let json = serde_json::to_string(&provenance).unwrap();
+        assert!(json.contains(r#""download_blake3":"af13""#));
+        assert!(!json.contains("archive_md5"));
+    }
+
+    #[test]
+    fn test_version_order() {
+        assert!(version("v1.10.0") > version("v1.9.0"));
+        assert_eq!(version("1.40.1"), version("v1.40.1"));
+        assert!(version("v1.38.2") < version("1.39"));
+        assert!(version("v1.39.0") >= version("1.39"));
+        assert!(version("v1.40.0-rc1") < version("v1.40.0"));
+        assert_eq!(version("testnet-v1.40.1"), version("mainnet-1.40.1"));
+        assert!("nightly".parse::<Version>().is_err());
+        assert!("1.40.x".parse::<Version>().is_err());
+    }
+
+    #[test]
+    fn test_version_matches() {
+        let range = version("testnet-1.40");
+        assert!(!range.is_exact());
+        assert_eq!(range.to_string(), "testnet-v1.40");
+        assert!(range.matches(&version("v1.40.3")));
+        assert!(range.matches(&version("testnet-v1.40.0")));
+        assert!(!range.matches(&version("mainnet-v1.40.0")));
+        assert!(!range.matches(&version("v1.4.0")));
+        assert!(version("1.40.1").matches(&version("testnet-v1.40.1")));
+        assert!(!version("1.40.1").matches(&version("v1.40.10")));
+    }
+}
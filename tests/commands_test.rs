@@ -6,10 +6,15 @@ mod tests {
     use anyhow::Result;
     use std::fs;
     use std::time::{Duration, SystemTime};
-    use suiup::commands::{parse_component_with_version, BinaryName, CommandMetadata};
+    use suiup::commands::{
+        expand_release_shorthand, parse_component_with_version, BinaryName, CommandMetadata,
+        ErrorFormat,
+    };
     use suiup::handlers::cleanup::handle_cleanup;
-    use suiup::handlers::switch::parse_binary_spec;
+    use suiup::handlers::expand_group;
+    use suiup::handlers::switch::{find_matching_binary, parse_binary_spec};
     use suiup::paths;
+    use suiup::types::{BinaryVersion, InstalledBinaries, Network};
     use tempfile::TempDir;
 
     #[test]
@@ -17,35 +22,59 @@ mod tests {
         let result = parse_component_with_version("sui")?;
         let expected = CommandMetadata {
             name: BinaryName::Sui,
-            network: "testnet".to_string(),
+            network: Network::Testnet,
             version: None,
+            debug: false,
+            nightly: None,
         };
         assert_eq!(expected, result);
 
         let result = parse_component_with_version("sui@testnet-v1.39.3")?;
         let expected = CommandMetadata {
             name: BinaryName::Sui,
-            network: "testnet".to_string(),
+            network: Network::Testnet,
             version: Some("v1.39.3".to_string()),
+            debug: false,
+            nightly: None,
         };
         assert_eq!(expected, result,);
 
         let result = parse_component_with_version("walrus")?;
         let expected = CommandMetadata {
             name: BinaryName::Walrus,
-            network: "testnet".to_string(),
+            network: Network::Testnet,
             version: None,
+            debug: false,
+            nightly: None,
         };
         assert_eq!(expected, result);
 
         let result = parse_component_with_version("mvr")?;
         let expected = CommandMetadata {
             name: BinaryName::Mvr,
-            network: "testnet".to_string(),
+            network: Network::Testnet,
             version: None,
+            debug: false,
+            nightly: None,
         };
         assert_eq!(expected, result);
 
+        let result = parse_component_with_version("sui-node@testnet-1.40.1")?;
+        let expected = CommandMetadata {
+            name: BinaryName::SuiNode,
+            network: Network::Testnet,
+            version: Some("1.40.1".to_string()),
+            debug: false,
+            nightly: None,
+        };
+        assert_eq!(expected, result);
+
+        let result = parse_component_with_version("site-builder")?;
+        assert_eq!(result.network, Network::Mainnet);
+        let result = parse_component_with_version("site-builder@testnet-v1.2.0")?;
+        assert_eq!(result.network, Network::Testnet);
+        assert_eq!(result.version, Some("v1.2.0".to_string()));
+
         let result = parse_component_with_version("random");
         assert_eq!(
             result.unwrap_err().to_string(),
@@ -56,11 +85,202 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_component_spec_grammar() -> Result<()> {
+        let parse = |spec| {
+            parse_component_with_version(spec)
+                .map(|m| (m.name, m.network, m.version, m.debug, m.nightly))
+        };
+        let v = |v: &str| Some(v.to_string());
+
+        // Separators
+        for spec in [
+            "sui@testnet-1.39.3",
+            "sui==testnet-1.39.3",
+            "sui=testnet-1.39.3",
+            "sui testnet-1.39.3",
+        ] {
+            assert_eq!(
+                parse(spec)?,
+                (BinaryName::Sui, Network::Testnet, v("1.39.3"), false, None),
+                "{spec}"
+            );
+        }
+        // Versions without a network use the default network of the binary
+        assert_eq!(
+            parse("sui@1.39.3")?,
+            (BinaryName::Sui, Network::Testnet, v("1.39.3"), false, None)
+        );
+        assert_eq!(
+            parse("mvr@0.0.8")?,
+            (BinaryName::Mvr, Network::Testnet, v("0.0.8"), false, None)
+        );
+        assert_eq!(
+            parse("site-builder@v1.2.0")?,
+            (
+                BinaryName::WalrusSites,
+                Network::Mainnet,
+                v("v1.2.0"),
+                false,
+                None
+            )
+        );
+        assert_eq!(
+            parse("sui@devnet")?,
+            (BinaryName::Sui, Network::Devnet, None, false, None)
+        );
+
+        // Latest
+        assert_eq!(
+            parse("walrus@mainnet-latest")?,
+            (BinaryName::Walrus, Network::Mainnet, None, false, None)
+        );
+        assert_eq!(
+            parse("sui@latest")?,
+            (BinaryName::Sui, Network::Testnet, None, false, None)
+        );
+
+        // Build types
+        assert_eq!(
+            parse("sui@testnet-1.39.3+debug")?,
+            (BinaryName::Sui, Network::Testnet, v("1.39.3"), true, None)
+        );
+        assert_eq!(
+            parse("sui+debug")?,
+            (BinaryName::Sui, Network::Testnet, None, true, None)
+        );
+        assert_eq!(
+            parse("sui@mainnet+release")?,
+            (BinaryName::Sui, Network::Mainnet, None, false, None)
+        );
+
+        // Nightly builds
+        assert_eq!(
+            parse("sui@nightly")?,
+            (BinaryName::Sui, Network::Testnet, None, false, v("main"))
+        );
+        assert_eq!(
+            parse("sui@nightly:releases/sui-v1.40.0-release+debug")?,
+            (
+                BinaryName::Sui,
+                Network::Testnet,
+                None,
+                true,
+                v("releases/sui-v1.40.0-release")
+            )
+        );
+        assert_eq!(
+            parse("mvr@nightly:main")?,
+            (BinaryName::Mvr, Network::Testnet, None, false, v("main"))
+        );
+
+        // Invalid specs
+        for (spec, error) in [
+            ("sui@", "Invalid format"),
+            ("sui@testnet@1.39.3", "Invalid format"),
+            ("sui@testnet-", "Missing the version after `testnet-`"),
+            ("sui@nightly:", "Missing the branch"),
+            ("sui@testnet+fast", "Unknown build type `fast`"),
+            ("@testnet", "Invalid binary name"),
+            ("suii@testnet", "Invalid binary name"),
+            ("sui@testnett-1.39.3", "Unknown network `testnett`"),
+        ] {
+            let err = parse(spec).unwrap_err().to_string();
+            assert!(err.contains(error), "{spec}: {err}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_spec_with_flags() -> Result<()> {
+        let metadata = parse_component_with_version("sui@nightly:dev+debug")?;
+        assert_eq!(
+            metadata.with_flags(None, false)?,
+            (Some("dev".to_string()), true)
+        );
+        assert_eq!(
+            metadata.with_flags(Some("dev"), false)?,
+            (Some("dev".to_string()), true)
+        );
+        assert!(metadata.with_flags(Some("main"), false).is_err());
+
+        let metadata = parse_component_with_version("sui@testnet")?;
+        assert_eq!(
+            metadata.with_flags(Some("main"), true)?,
+            (Some("main".to_string()), true)
+        );
+        assert_eq!(metadata.with_flags(None, false)?, (None, false));
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand_group() {
+        assert_eq!(
+            expand_group("walrus-suite@testnet"),
+            Some(vec![
+                "walrus@testnet".to_string(),
+                "site-builder@testnet".to_string()
+            ])
+        );
+        assert_eq!(
+            expand_group("sui-full").unwrap(),
+            ["sui", "sui-node", "sui-tool"]
+        );
+        assert_eq!(expand_group("sui@testnet"), None);
+    }
+
     #[test]
     fn test_sui_component_display() {
         assert_eq!(BinaryName::Sui.to_string(), "sui");
         assert_eq!(BinaryName::Mvr.to_string(), "mvr");
         assert_eq!(BinaryName::Walrus.to_string(), "walrus");
+        assert_eq!(BinaryName::SuiTool.to_string(), "sui-tool");
+        assert_eq!(BinaryName::MoveAnalyzer.to_string(), "move-analyzer");
+    }
+
+    #[test]
+    fn test_error_format_from_raw_args() {
+        let format = |args: &[&str]| {
+            ErrorFormat::from_raw_args(&args.iter().map(Into::into).collect::<Vec<_>>())
+        };
+        assert_eq!(
+            format(&["suiup", "--error-format", "json", "bogus"]),
+            ErrorFormat::Json
+        );
+        assert_eq!(
+            format(&["suiup", "install", "--error-format=json"]),
+            ErrorFormat::Json
+        );
+        assert_eq!(
+            format(&["suiup", "--error-format", "xml"]),
+            ErrorFormat::Human
+        );
+    }
+
+    #[test]
+    fn test_expand_release_shorthand() {
+        let expand = |args: &[&str]| {
+            expand_release_shorthand(args.iter().map(Into::into).collect())
+                .into_iter()
+                .map(|arg| arg.into_string().unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            expand(&["suiup", "+testnet-1.40.1", "sui", "client", "--help"]),
+            [
+                "suiup",
+                "run",
+                "sui@testnet-1.40.1",
+                "--",
+                "client",
+                "--help"
+            ]
+        );
+        assert_eq!(expand(&["suiup", "+main"]), ["suiup", "run"]);
+        assert_eq!(
+            expand(&["suiup", "install", "+x"]),
+            ["suiup", "install", "+x"]
+        );
     }
 
     #[test]
@@ -101,13 +321,53 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_find_matching_binary_by_version() -> Result<()> {
+        let mut installed = InstalledBinaries::default();
+        for (network, version) in [
+            ("testnet", "v1.39.3"),
+            ("testnet", "v1.40.1"),
+            ("mainnet", "v1.39.3"),
+            ("devnet", "v1.41.0"),
+        ] {
+            installed.add_binary(BinaryVersion {
+                binary_name: "sui".to_string(),
+                network_release: network.to_string(),
+                version: version.to_string(),
+                debug: false,
+                path: None,
+                provenance: None,
+                last_used: None,
+            });
+        }
+
+        let binary = find_matching_binary(&installed, "sui", "testnet")?;
+        assert_eq!(binary.version, "v1.40.1");
+        let binary = find_matching_binary(&installed, "sui", "testnet-1.39.3")?;
+        assert_eq!(
+            (binary.network_release.as_str(), binary.version.as_str()),
+            ("testnet", "v1.39.3")
+        );
+        let binary = find_matching_binary(&installed, "sui", "1.41.0")?;
+        assert_eq!(binary.network_release, "devnet");
+
+        let err = find_matching_binary(&installed, "sui", "1.39.3").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("sui@testnet-1.39.3, sui@mainnet-1.39.3"));
+        let err = find_matching_binary(&installed, "sui", "1.38.0").unwrap_err();
+        assert!(err.to_string().contains("Installed: sui@testnet-1.39.3"));
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_cleanup_empty_directory() -> Result<()> {
         let temp_dir = TempDir::new()?;
         std::env::set_var("XDG_CACHE_HOME", temp_dir.path());
 
-        // Test cleanup on empty directory
-        let result = handle_cleanup(false, 30, true).await;
+        // Test cleanup on empty directory. `force` so that the archives of the binaries
+        // installed on this machine are not looked up.
+        let result = handle_cleanup(false, 30, true, true, None).await;
         assert!(result.is_ok());
 
         Ok(())
@@ -133,7 +393,7 @@ mod tests {
         std::env::set_var("XDG_CACHE_HOME", temp_dir.path());
 
         // Dry run should not remove files
-        let result = handle_cleanup(false, 30, true).await;
+        let result = handle_cleanup(false, 30, true, true, None).await;
         assert!(result.is_ok());
         assert!(old_file.exists());
         assert!(new_file.exists());
@@ -164,7 +424,7 @@ mod tests {
         std::env::set_var("XDG_CACHE_HOME", temp_dir.path());
 
         // Actual cleanup should remove old file but keep new file
-        let result = handle_cleanup(false, 30, false).await;
+        let result = handle_cleanup(false, 30, false, true, None).await;
         assert!(result.is_ok());
         assert!(!old_file.exists());
         assert!(new_file.exists());
@@ -191,7 +451,7 @@ mod tests {
         std::env::set_var("XDG_CACHE_HOME", temp_dir.path());
 
         // Remove all should clear everything
-        let result = handle_cleanup(true, 30, false).await;
+        let result = handle_cleanup(true, 30, false, true, None).await;
         assert!(result.is_ok());
         assert!(!file1.exists());
         assert!(!file2.exists());
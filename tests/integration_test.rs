@@ -5,13 +5,23 @@ mod test_utils;
 
 #[cfg(test)]
 mod tests {
+    #[cfg(not(windows))]
+    use crate::test_utils::MockGithub;
     use crate::test_utils::TestEnv;
     use anyhow::Result;
     use assert_cmd::Command;
+    #[cfg(not(windows))]
+    use flate2::{write::GzEncoder, Compression};
     use predicates::prelude::*;
+    #[cfg(not(windows))]
+    use sha2::{Digest, Sha256};
+    #[cfg(not(windows))]
+    use std::collections::HashMap;
     use std::fs;
     use std::time::{Duration, SystemTime};
     use suiup::paths::installed_binaries_file;
+    #[cfg(not(windows))]
+    use suiup::platform::Target;
 
     #[cfg(not(windows))]
     const DATA_HOME: &str = "XDG_DATA_HOME";
@@ -20,6 +30,8 @@ mod tests {
     #[cfg(not(windows))]
     const CACHE_HOME: &str = "XDG_CACHE_HOME";
     #[cfg(not(windows))]
+    const STATE_HOME: &str = "XDG_STATE_HOME";
+    #[cfg(not(windows))]
     const HOME: &str = "HOME";
 
     #[cfg(windows)]
@@ -29,6 +41,8 @@ mod tests {
     #[cfg(windows)]
     const CACHE_HOME: &str = "TEMP";
     #[cfg(windows)]
+    const STATE_HOME: &str = "LOCALAPPDATA";
+    #[cfg(windows)]
     const HOME: &str = "HOME";
 
     fn suiup_command(args: Vec<&str>, test_env: &TestEnv) -> Command {
@@ -38,6 +52,7 @@ mod tests {
         cmd.env(DATA_HOME, &test_env.data_dir)
             .env(CONFIG_HOME, &test_env.config_dir)
             .env(CACHE_HOME, &test_env.cache_dir)
+            .env(STATE_HOME, &test_env.state_dir)
             .env(HOME, test_env.temp_dir.path());
         cmd
     }
@@ -365,6 +380,66 @@ mod tests {
         Ok(())
     }
 
+    /// Gzipped tarball with a `sui` script printing `version`
+    #[cfg(not(windows))]
+    fn sui_archive(version: &str) -> Result<Vec<u8>> {
+        let script = format!("#!/bin/sh\necho \"sui {version}-mock\"\n");
+        let mut header = tar::Header::new_gnu();
+        header.set_size(script.len() as u64);
+        header.set_mode(0o755);
+        header.set_cksum();
+        let mut archive = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        archive.append_data(&mut header, "sui", script.as_bytes())?;
+        Ok(archive.into_inner()?.finish()?)
+    }
+
+    #[cfg(not(windows))]
+    #[tokio::test]
+    async fn test_install_from_mock_github() -> Result<()> {
+        let test_env = TestEnv::new()?;
+        test_env.initialize_paths()?;
+
+        let platform = Target::detect()?.release_asset_platform();
+        let asset = format!("sui-testnet-v1.40.1-{platform}.tgz");
+        let archive = sui_archive("1.40.1")?;
+        let release = format!(
+            r#"{{"tag_name": "testnet-v1.40.1", "assets": [{{
+                "name": "{asset}",
+                "browser_download_url": "{{url}}/download/{asset}",
+                "size": {},
+                "digest": "sha256:{:x}"
+            }}]}}"#,
+            archive.len(),
+            Sha256::digest(&archive)
+        );
+        let mock = MockGithub::start(HashMap::from([
+            (
+                "/repos/MystenLabs/sui/releases".to_string(),
+                format!("[{release}]").into_bytes(),
+            ),
+            (
+                "/repos/MystenLabs/sui/releases/tags/testnet-v1.40.1".to_string(),
+                release.into_bytes(),
+            ),
+            (format!("/download/{asset}"), archive),
+        ]))?;
+
+        let mut cmd = suiup_command(vec!["install", "sui@testnet", "-y"], &test_env);
+        cmd.env("SUIUP_GITHUB_API_URL", &mock.url)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("'sui' extracted successfully!"));
+        assert!(mock.requests().contains(&format!("GET /download/{asset}")));
+
+        let mut cmd = Command::new(test_env.bin_dir.join("sui"));
+        cmd.arg("--version")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("sui 1.40.1-mock"));
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_show_default_flag() -> Result<()> {
         let test_env = TestEnv::new()?;
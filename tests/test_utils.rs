@@ -3,10 +3,15 @@
 
 use anyhow::Result;
 use lazy_static::lazy_static;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
 use std::{env, sync::Mutex};
 use suiup::paths::{
-    get_cache_home, get_config_home, get_data_home, get_default_bin_dir, initialize,
+    get_cache_home, get_config_home, get_data_home, get_default_bin_dir, get_state_home, initialize,
 };
 use tempfile::TempDir;
 
@@ -15,6 +20,7 @@ pub struct TestEnv {
     pub temp_dir: TempDir,
     pub data_dir: PathBuf,
     pub config_dir: PathBuf,
+    pub state_dir: PathBuf,
     pub cache_dir: PathBuf,
     pub bin_dir: PathBuf,
     original_env: Vec<(String, String)>,
@@ -33,41 +39,30 @@ impl TestEnv {
 
         let data_home = get_data_home();
         let config_home = get_config_home();
+        let state_home = get_state_home();
         let cache_home = get_cache_home();
         let bin_home = get_default_bin_dir();
 
-        let data_dir = if let Ok(path) = data_home.strip_prefix(&home_dir) {
-            base.join(path)
-        } else {
-            base.join(data_home)
-        };
-
-        let config_dir = if let Ok(path) = config_home.strip_prefix(&home_dir) {
-            base.join(path)
-        } else {
-            base.join(config_home)
-        };
-
-        let cache_dir = if let Ok(path) = cache_home.strip_prefix(&home_dir) {
-            base.join(path)
-        } else {
-            base.join(cache_home)
-        };
-
-        let bin_dir = if let Ok(path) = bin_home.strip_prefix(&home_dir) {
-            base.join(path)
-        } else {
-            base.join(bin_home)
-        };
+        let data_dir = under(base, &data_home, &home_dir);
+
+        let config_dir = under(base, &config_home, &home_dir);
+
+        let state_dir = under(base, &state_home, &home_dir);
+
+        let cache_dir = under(base, &cache_home, &home_dir);
+
+        let bin_dir = under(base, &bin_home, &home_dir);
 
         // Create directories
         std::fs::create_dir_all(&data_dir)?;
         std::fs::create_dir_all(&config_dir)?;
+        std::fs::create_dir_all(&state_dir)?;
         std::fs::create_dir_all(&cache_dir)?;
         std::fs::create_dir_all(&bin_dir)?;
 
         assert!(data_dir.exists());
         assert!(config_dir.exists());
+        assert!(state_dir.exists());
         assert!(cache_dir.exists());
         assert!(bin_dir.exists());
 
@@ -78,6 +73,7 @@ impl TestEnv {
             "XDG_DATA_HOME",
             "XDG_CONFIG_HOME",
             "XDG_CACHE_HOME",
+            "XDG_STATE_HOME",
             "PATH",
         ];
 
@@ -95,6 +91,8 @@ impl TestEnv {
         env::set_var("XDG_CONFIG_HOME", &config_dir);
         #[cfg(not(windows))]
         env::set_var("XDG_CACHE_HOME", &cache_dir);
+        #[cfg(not(windows))]
+        env::set_var("XDG_STATE_HOME", &state_dir);
 
         // Add bin dir to PATH
         let path = env::var("PATH").unwrap_or_default();
@@ -108,6 +106,7 @@ impl TestEnv {
             temp_dir,
             data_dir,
             config_dir,
+            state_dir,
             cache_dir,
             bin_dir,
             original_env,
@@ -167,6 +166,18 @@ impl TestEnv {
     }
 }
 
+/// `path` moved from the home folder, or from the root, to `base`. Another test may have set
+/// the XDG variables to its own folder already, which must not be shared.
+fn under(base: &Path, path: &Path, home_dir: &Path) -> PathBuf {
+    let relative = path.strip_prefix(home_dir).unwrap_or(path);
+    base.join(
+        relative
+            .components()
+            .filter(|c| matches!(c, Component::Normal(_)))
+            .collect::<PathBuf>(),
+    )
+}
+
 impl Drop for TestEnv {
     fn drop(&mut self) {
         // Restore original env vars
@@ -176,6 +187,80 @@ impl Drop for TestEnv {
     }
 }
 
+/// A local stand-in for the GitHub API, for tests that run the real commands without network:
+/// point suiup at it with `SUIUP_GITHUB_API_URL`. It serves fixed responses by path, ignoring
+/// the query, and 404 for the other paths.
+pub struct MockGithub {
+    pub url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockGithub {
+    /// Starts serving `routes`, keyed by path. `{url}` in a response is replaced with the URL of
+    /// the server, so that release assets can be downloaded from it too.
+    pub fn start(routes: HashMap<String, Vec<u8>>) -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let routes = routes
+            .into_iter()
+            .map(|(path, body)| {
+                let body = match String::from_utf8(body) {
+                    Ok(text) => text.replace("{url}", &url).into_bytes(),
+                    Err(e) => e.into_bytes(),
+                };
+                (path, body)
+            })
+            .collect::<HashMap<_, _>>();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let _ = serve(stream, &routes, &log);
+            }
+        });
+        Ok(MockGithub { url, requests })
+    }
+
+    /// Paths requested so far, e.g. `GET /repos/MystenLabs/sui/releases`
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn serve(
+    mut stream: TcpStream,
+    routes: &HashMap<String, Vec<u8>>,
+    log: &Mutex<Vec<String>>,
+) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // The headers are not used
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 2 {
+        line.clear();
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default();
+    let path = path.split('?').next().unwrap_or_default();
+    log.lock().unwrap().push(format!("{method} {path}"));
+
+    let (status, body) = match routes.get(path) {
+        Some(body) => ("200 OK", body.as_slice()),
+        None => ("404 Not Found", b"{\"message\": \"Not Found\"}".as_slice()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    if method != "HEAD" {
+        stream.write_all(body)?;
+    }
+    stream.flush()
+}
+
 // Mock HTTP client for testing
 #[cfg(test)]
 pub mod mock_http {